@@ -1,220 +1,1606 @@
+//! Bookmarks
+//!
+//! Bookmarks, folders and separators are rows of the `bookmarks` table with
+//! stable ids. Each row sits at a `position` within its parent folder
+//! (`parent_id`, `NULL` at the top level); removing a folder removes
+//! everything inside it. Folder paths (`Work/Rust`) are derived from the
+//! tree for display, search and import merging.
+//!
+//! Bookmarks carry any number of tags (`bookmark_tags`, case-insensitive
+//! names) and a free-text description. Smart folders are saved queries over
+//! tags, domain, text and age; they hold no rows of their own and are
+//! evaluated whenever folders are listed.
+//!
+//! The last link check of each bookmark (see [`crate::link_check`]) is kept
+//! alongside it and dropped when its URL changes.
+//!
+//! Entries imported from a bookmark file keep its icon, keyword and any
+//! other attributes (see [`crate::netscape`]) so exporting writes them back.
+
+use chrono::{DateTime, Duration, SubsecRound, Utc};
+use rusqlite::{Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use axiom_storage::{Database, Result as StorageResult};
+
+use crate::error::CoreError;
+use crate::link_check::{LinkCheck, LinkStatus};
+use crate::netscape::BookmarkNode;
+use crate::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookmarkKind {
+    Bookmark,
+    Folder,
+    Separator,
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl BookmarkKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BookmarkKind::Bookmark => "bookmark",
+            BookmarkKind::Folder => "folder",
+            BookmarkKind::Separator => "separator",
+        }
+    }
+}
+
+impl FromStr for BookmarkKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "bookmark" => Ok(BookmarkKind::Bookmark),
+            "folder" => Ok(BookmarkKind::Folder),
+            "separator" => Ok(BookmarkKind::Separator),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Bookmark {
+    pub id: i64,
+    pub kind: BookmarkKind,
+    /// Containing folder, `None` at the top level
+    pub parent_id: Option<i64>,
+    /// Order within the parent folder
+    pub position: i64,
     pub title: String,
+    /// Empty for folders and separators
     pub url: String,
-    #[serde(default)]
+    pub description: String,
+    /// Tag names, sorted
+    pub tags: Vec<String>,
+    /// Last link check, if the bookmark was checked since its URL last changed
+    pub link_check: Option<LinkCheck>,
+    /// Favicon as a `data:` URI, from an imported bookmark file
+    pub icon: Option<String>,
+    /// Where `icon` came from
+    pub icon_uri: Option<String>,
+    /// Address bar keyword (`SHORTCUTURL` in bookmark files), kept for export;
+    /// keyword searches are added as search keywords on import
+    pub shortcut: Option<String>,
+    /// Other attributes of an imported entry, written back on export
+    pub attributes: BTreeMap<String, String>,
+    /// `/`-joined path of the containing folder
     pub folder: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub modified_at: DateTime<Utc>,
 }
 
-pub fn normalize_folder(folder: Option<String>) -> Option<String> {
-    folder
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
+impl Bookmark {
+    pub fn is_bookmark(&self) -> bool {
+        self.kind == BookmarkKind::Bookmark
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookmarkFolder {
+    pub id: i64,
+    pub parent_id: Option<i64>,
+    pub title: String,
+    /// `/`-joined path including this folder
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookmarkTag {
+    pub name: String,
+    /// Bookmarks carrying the tag
+    pub count: usize,
+}
+
+/// Conditions of a smart folder; every condition that is set must match
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SmartFolderQuery {
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Bookmarks on this domain or its subdomains
+    #[serde(default)]
+    pub domain: Option<String>,
+    /// Matched against title, URL, description and tags
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub added_within_days: Option<u32>,
 }
 
-pub fn folders_from_bookmarks(bookmarks: &[Bookmark]) -> Vec<String> {
-    let mut set = BTreeSet::new();
-    for bookmark in bookmarks {
-        if let Some(folder) = bookmark.folder.as_deref() {
-            let folder = folder.trim();
-            if !folder.is_empty() {
-                set.insert(folder.to_string());
+impl SmartFolderQuery {
+    /// Trimmed conditions with empty ones dropped; fails when nothing is left
+    fn normalized(self) -> Result<Self> {
+        let non_empty = |value: Option<String>| {
+            value
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let tag = match non_empty(self.tag) {
+            Some(tag) => Some(single_tag(&tag)?),
+            None => None,
+        };
+        let domain = match non_empty(self.domain) {
+            Some(domain) => Some(
+                crate::forget::normalize_domain(&domain)
+                    .ok_or_else(|| invalid("Smart folder domain is not a valid host"))?,
+            ),
+            None => None,
+        };
+        let query = Self {
+            tag,
+            domain,
+            text: non_empty(self.text),
+            added_within_days: self.added_within_days.filter(|days| *days > 0),
+        };
+        if query == Self::default() {
+            return Err(invalid("A smart folder needs at least one condition"));
+        }
+        Ok(query)
+    }
+
+    pub fn matches(&self, bookmark: &Bookmark, now: DateTime<Utc>) -> bool {
+        if !bookmark.is_bookmark() {
+            return false;
+        }
+        if let Some(tag) = &self.tag {
+            if !bookmark.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                return false;
+            }
+        }
+        if let Some(domain) = &self.domain {
+            if !crate::forget::url_in_domain(&bookmark.url, domain) {
+                return false;
             }
         }
+        if let Some(text) = &self.text {
+            let text = text.to_lowercase();
+            let found = [&bookmark.title, &bookmark.url, &bookmark.description]
+                .into_iter()
+                .chain(&bookmark.tags)
+                .any(|field| field.to_lowercase().contains(&text));
+            if !found {
+                return false;
+            }
+        }
+        if let Some(days) = self.added_within_days {
+            if bookmark.created_at < now - Duration::days(i64::from(days)) {
+                return false;
+            }
+        }
+        true
     }
-    set.into_iter().collect()
 }
 
-fn escape_html(s: &str) -> String {
-    let mut out = String::with_capacity(s.len());
-    for ch in s.chars() {
-        match ch {
-            '&' => out.push_str("&amp;"),
-            '<' => out.push_str("&lt;"),
-            '>' => out.push_str("&gt;"),
-            '"' => out.push_str("&quot;"),
-            '\'' => out.push_str("&#39;"),
-            _ => out.push(ch),
+/// A saved query, listed with the bookmarks it currently matches
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SmartFolder {
+    pub id: i64,
+    pub title: String,
+    pub position: i64,
+    pub query: SmartFolderQuery,
+    /// Matching bookmarks, in display order
+    pub bookmark_ids: Vec<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Regular folders and smart folders, as listed by the bookmarks manager
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookmarkFolders {
+    pub folders: Vec<BookmarkFolder>,
+    pub smart_folders: Vec<SmartFolder>,
+}
+
+/// A bookmark read from an HTML export or another browser, placed by folder path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportedBookmark {
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub folder: Option<String>,
+}
+
+/// Nesting limit when resolving folder paths (guards against parent cycles)
+pub(crate) const MAX_FOLDER_DEPTH: usize = 64;
+
+const COLUMNS: &str = "id, kind, parent_id, position, title, url, created_at, modified_at, \
+                       description, icon, icon_uri, shortcut, attributes";
+
+fn parse_time(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+fn bookmark_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Bookmark> {
+    let kind: String = row.get(1)?;
+    let created: String = row.get(6)?;
+    let modified: String = row.get(7)?;
+    let attributes: String = row.get(12)?;
+    Ok(Bookmark {
+        id: row.get(0)?,
+        kind: kind.parse().unwrap_or(BookmarkKind::Bookmark),
+        parent_id: row.get(2)?,
+        position: row.get(3)?,
+        title: row.get(4)?,
+        url: row.get(5)?,
+        description: row.get(8)?,
+        tags: Vec::new(),
+        link_check: None,
+        icon: row.get(9)?,
+        icon_uri: row.get(10)?,
+        shortcut: row.get(11)?,
+        attributes: serde_json::from_str(&attributes).unwrap_or_default(),
+        folder: None,
+        created_at: parse_time(&created),
+        modified_at: parse_time(&modified),
+    })
+}
+
+fn folder_segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn invalid(message: &str) -> CoreError {
+    CoreError::InvalidBookmark(message.to_string())
+}
+
+/// Tag names with whitespace collapsed, split on commas, without duplicates (ignoring case)
+pub(crate) fn normalize_tags<S: AsRef<str>>(tags: &[S]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for name in tags.iter().flat_map(|tag| tag.as_ref().split(',')) {
+        let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !name.is_empty() && !out.iter().any(|t| t.eq_ignore_ascii_case(&name)) {
+            out.push(name);
         }
     }
     out
 }
 
-#[derive(Default)]
-struct FolderNode {
-    folders: BTreeMap<String, FolderNode>,
-    bookmarks: Vec<Bookmark>,
+/// Exactly one valid tag name
+fn single_tag(name: &str) -> Result<String> {
+    match normalize_tags(&[name]).as_slice() {
+        [tag] if !name.contains(',') => Ok(tag.clone()),
+        _ => Err(invalid("A tag name cannot be empty or contain commas")),
+    }
+}
+
+/// Id of the tag called `name` (ignoring case), created if missing
+fn tag_id(conn: &Connection, name: &str) -> StorageResult<i64> {
+    conn.execute(
+        "INSERT OR IGNORE INTO bookmark_tags (name) VALUES (?1)",
+        [name],
+    )?;
+    Ok(conn.query_row(
+        "SELECT id FROM bookmark_tags WHERE name = ?1",
+        [name],
+        |row| row.get(0),
+    )?)
 }
 
-fn insert_bookmark(root: &mut FolderNode, bookmark: Bookmark) {
-    let mut node = root;
-    if let Some(folder) = bookmark.folder.as_deref() {
-        for part in folder.split('/').map(str::trim).filter(|s| !s.is_empty()) {
-            node = node.folders.entry(part.to_string()).or_default();
+/// Drop tags no bookmark carries any more
+/// Replace a bookmark's tags with already normalized names
+fn write_tags(conn: &Connection, id: i64, tags: &[String]) -> StorageResult<()> {
+    conn.execute("DELETE FROM bookmark_tag_map WHERE bookmark_id = ?1", [id])?;
+    for tag in tags {
+        let tag_id = tag_id(conn, tag)?;
+        conn.execute(
+            "INSERT OR IGNORE INTO bookmark_tag_map (bookmark_id, tag_id) VALUES (?1, ?2)",
+            [id, tag_id],
+        )?;
+    }
+    prune_tags(conn)
+}
+
+fn prune_tags(conn: &Connection) -> StorageResult<()> {
+    conn.execute(
+        "DELETE FROM bookmark_tags WHERE id NOT IN (SELECT tag_id FROM bookmark_tag_map)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn smart_folder_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SmartFolder> {
+    let days: Option<i64> = row.get(6)?;
+    let created: String = row.get(7)?;
+    Ok(SmartFolder {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        position: row.get(2)?,
+        query: SmartFolderQuery {
+            tag: row.get(3)?,
+            domain: row.get(4)?,
+            text: row.get(5)?,
+            added_within_days: days.and_then(|d| u32::try_from(d).ok()),
+        },
+        bookmark_ids: Vec::new(),
+        created_at: parse_time(&created),
+    })
+}
+
+/// Append a row at the end of `parent_id`; returns its id
+fn insert_row(
+    conn: &Connection,
+    kind: BookmarkKind,
+    parent_id: Option<i64>,
+    title: &str,
+    url: &str,
+) -> StorageResult<i64> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO bookmarks (kind, parent_id, position, title, url, created_at, modified_at)
+         VALUES (?1, ?2,
+                 (SELECT COALESCE(MAX(position) + 1, 0) FROM bookmarks WHERE parent_id IS ?2),
+                 ?3, ?4, ?5, ?5)",
+        rusqlite::params![kind.as_str(), parent_id, title, url, now],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Rewrite the positions of a folder's children as 0, 1, 2, … in their current order
+fn renumber(conn: &Connection, parent_id: Option<i64>) -> StorageResult<()> {
+    let ids: Vec<i64> = {
+        let mut stmt =
+            conn.prepare("SELECT id FROM bookmarks WHERE parent_id IS ?1 ORDER BY position, id")?;
+        let ids = stmt
+            .query_map([parent_id], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+        ids
+    };
+    for (position, id) in ids.iter().enumerate() {
+        conn.execute(
+            "UPDATE bookmarks SET position = ?1 WHERE id = ?2 AND position != ?1",
+            rusqlite::params![position as i64, id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Fail unless `parent_id` is the top level or an existing folder
+fn check_parent(db: &Database, parent_id: Option<i64>) -> Result<()> {
+    let Some(parent_id) = parent_id else {
+        return Ok(());
+    };
+    let kind: Option<String> = db.with_connection(|conn| {
+        Ok(conn
+            .query_row(
+                "SELECT kind FROM bookmarks WHERE id = ?1",
+                [parent_id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    })?;
+    match kind.as_deref() {
+        Some(kind) if kind == BookmarkKind::Folder.as_str() => Ok(()),
+        Some(_) => Err(invalid("Bookmarks can only be placed in folders")),
+        None => Err(CoreError::BookmarkNotFound(parent_id)),
+    }
+}
+
+/// `parent_id` of an item; `None` when the item does not exist
+fn parent_of(conn: &Connection, id: i64) -> rusqlite::Result<Option<Option<i64>>> {
+    conn.prepare_cached("SELECT parent_id FROM bookmarks WHERE id = ?1")?
+        .query_row([id], |row| row.get(0))
+        .optional()
+}
+
+/// Id of the folder at `path`, creating missing folders; `None` for the top level
+fn ensure_folder_path(conn: &Connection, path: &str) -> StorageResult<Option<i64>> {
+    let mut parent_id = None;
+    for name in folder_segments(path) {
+        let existing: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM bookmarks
+                 WHERE kind = 'folder' AND parent_id IS ?1 AND title = ?2
+                 ORDER BY position LIMIT 1",
+                rusqlite::params![parent_id, name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        parent_id = Some(match existing {
+            Some(id) => id,
+            None => insert_row(conn, BookmarkKind::Folder, parent_id, name, "")?,
+        });
+    }
+    Ok(parent_id)
+}
+
+/// Append a parsed entry at the end of `parent_id`, tags included; returns its id
+fn insert_node(
+    conn: &Connection,
+    node: &BookmarkNode,
+    parent_id: Option<i64>,
+) -> StorageResult<i64> {
+    // Files only hold whole seconds; entries without dates get the import time
+    let created = node
+        .created_at
+        .unwrap_or_else(|| Utc::now().trunc_subsecs(0));
+    let modified = node.modified_at.unwrap_or(created);
+    let attributes = serde_json::to_string(&node.attributes).unwrap_or_else(|_| "{}".to_string());
+    conn.execute(
+        "INSERT INTO bookmarks (kind, parent_id, position, title, url, created_at, modified_at,
+                                description, icon, icon_uri, shortcut, attributes)
+         VALUES (?1, ?2,
+                 (SELECT COALESCE(MAX(position) + 1, 0) FROM bookmarks WHERE parent_id IS ?2),
+                 ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        rusqlite::params![
+            node.kind.as_str(),
+            parent_id,
+            node.title,
+            node.url,
+            created.to_rfc3339(),
+            modified.to_rfc3339(),
+            node.description,
+            node.icon,
+            node.icon_uri,
+            node.shortcut,
+            attributes
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    if node.kind == BookmarkKind::Bookmark {
+        for tag in normalize_tags(&node.tags) {
+            let tag_id = tag_id(conn, &tag)?;
+            conn.execute(
+                "INSERT OR IGNORE INTO bookmark_tag_map (bookmark_id, tag_id) VALUES (?1, ?2)",
+                [id, tag_id],
+            )?;
         }
     }
-    node.bookmarks.push(bookmark);
+    Ok(id)
 }
 
-pub fn export_bookmarks_html(bookmarks: &[Bookmark]) -> String {
-    let mut root = FolderNode::default();
-    for bookmark in bookmarks.iter().cloned() {
-        insert_bookmark(&mut root, bookmark);
+/// Insert `nodes` into `parent_id`, merging with what the folder already holds;
+/// returns how many bookmarks were added
+fn insert_nodes(
+    conn: &Connection,
+    nodes: &[BookmarkNode],
+    parent_id: Option<i64>,
+) -> StorageResult<usize> {
+    // Only a folder that had entries before this call has anything to merge with
+    let merge: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM bookmarks WHERE parent_id IS ?1)",
+        [parent_id],
+        |row| row.get(0),
+    )?;
+
+    let mut added = 0;
+    for node in nodes {
+        match node.kind {
+            BookmarkKind::Folder => {
+                let existing: Option<i64> = if merge {
+                    conn.query_row(
+                        "SELECT id FROM bookmarks
+                         WHERE kind = 'folder' AND parent_id IS ?1 AND title = ?2
+                         ORDER BY position LIMIT 1",
+                        rusqlite::params![parent_id, node.title],
+                        |row| row.get(0),
+                    )
+                    .optional()?
+                } else {
+                    None
+                };
+                let id = match existing {
+                    Some(id) => id,
+                    None => insert_node(conn, node, parent_id)?,
+                };
+                added += insert_nodes(conn, &node.children, Some(id))?;
+            }
+            BookmarkKind::Bookmark => {
+                let exists: bool = merge
+                    && conn.query_row(
+                        "SELECT EXISTS(SELECT 1 FROM bookmarks
+                                       WHERE kind = 'bookmark' AND parent_id IS ?1 AND url = ?2)",
+                        rusqlite::params![parent_id, node.url],
+                        |row| row.get(0),
+                    )?;
+                if !exists {
+                    insert_node(conn, node, parent_id)?;
+                    added += 1;
+                }
+            }
+            BookmarkKind::Separator => {
+                if !merge {
+                    insert_node(conn, node, parent_id)?;
+                }
+            }
+        }
+    }
+    Ok(added)
+}
+
+/// Order rows depth-first (children by position) and fill in folder paths
+fn into_tree_order(rows: Vec<Bookmark>) -> Vec<Bookmark> {
+    let mut children: HashMap<Option<i64>, Vec<Bookmark>> = HashMap::new();
+    for row in rows {
+        children.entry(row.parent_id).or_default().push(row);
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|b| (b.position, b.id));
     }
 
-    fn render_node(node: &FolderNode, out: &mut String, indent: usize) {
-        let pad = |out: &mut String, n: usize| {
-            for _ in 0..n {
-                out.push(' ');
+    fn walk(
+        parent_id: Option<i64>,
+        path: Option<&str>,
+        children: &mut HashMap<Option<i64>, Vec<Bookmark>>,
+        out: &mut Vec<Bookmark>,
+    ) {
+        for mut item in children.remove(&parent_id).unwrap_or_default() {
+            item.folder = path.map(str::to_string);
+            let id = item.id;
+            let child_path = (item.kind == BookmarkKind::Folder).then(|| match path {
+                Some(path) => format!("{path}/{}", item.title),
+                None => item.title.clone(),
+            });
+            out.push(item);
+            if let Some(child_path) = child_path {
+                walk(Some(id), Some(&child_path), children, out);
             }
-        };
+        }
+    }
 
-        for (name, child) in &node.folders {
-            pad(out, indent);
-            out.push_str("<DT><H3>");
-            out.push_str(&escape_html(name));
-            out.push_str("</H3>\n");
-
-            pad(out, indent);
-            out.push_str("<DL><p>\n");
-            render_node(child, out, indent + 2);
-            pad(out, indent);
-            out.push_str("</DL><p>\n");
-        }
-
-        let mut items = node.bookmarks.clone();
-        items.sort_by(|a, b| a.title.cmp(&b.title).then_with(|| a.url.cmp(&b.url)));
-        for bookmark in items {
-            pad(out, indent);
-            out.push_str("<DT><A HREF=\"");
-            out.push_str(&escape_html(&bookmark.url));
-            out.push_str("\">");
-            out.push_str(&escape_html(&bookmark.title));
-            out.push_str("</A>\n");
-        }
-    }
-
-    let mut out = String::new();
-    out.push_str("<!DOCTYPE NETSCAPE-Bookmark-file-1>\n");
-    out.push_str("<META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n");
-    out.push_str("<TITLE>Bookmarks</TITLE>\n");
-    out.push_str("<H1>Bookmarks</H1>\n");
-    out.push_str("<DL><p>\n");
-    render_node(&root, &mut out, 2);
-    out.push_str("</DL><p>\n");
+    let mut out = Vec::new();
+    walk(None, None, &mut children, &mut out);
     out
 }
 
-fn find_from(haystack: &str, needle: &str, start: usize) -> Option<usize> {
-    haystack.get(start..)?.find(needle).map(|i| start + i)
+pub struct BookmarkManager {
+    db: Database,
 }
 
-fn extract_attr(tag_lower: &str, tag_raw: &str, attr: &str) -> Option<String> {
-    let attr = format!("{attr}=");
-    let idx = tag_lower.find(&attr)?;
-    let mut i = idx + attr.len();
-    let bytes = tag_lower.as_bytes();
-    if i >= bytes.len() {
-        return None;
+impl BookmarkManager {
+    pub fn new(db: Database) -> Self {
+        Self { db }
     }
 
-    let quote = bytes[i] as char;
-    if quote == '"' || quote == '\'' {
-        i += 1;
-        let end = tag_lower.get(i..)?.find(quote).map(|j| i + j)?;
-        return Some(tag_raw.get(i..end)?.to_string());
+    /// Every bookmark, folder and separator, depth-first in display order
+    pub fn list(&self) -> Result<Vec<Bookmark>> {
+        let (mut rows, mut tags, mut checks) = self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(&format!("SELECT {COLUMNS} FROM bookmarks"))?;
+            let rows: Vec<Bookmark> = stmt
+                .query_map([], bookmark_from_row)?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            let mut stmt = conn.prepare(
+                "SELECT m.bookmark_id, t.name FROM bookmark_tag_map m
+                 JOIN bookmark_tags t ON t.id = m.tag_id
+                 ORDER BY t.name",
+            )?;
+            let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+            for (id, name) in stmt
+                .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?)))?
+                .filter_map(|r| r.ok())
+            {
+                tags.entry(id).or_default().push(name);
+            }
+
+            let mut stmt = conn.prepare(
+                "SELECT bookmark_id, status, detail, checked_at, dns_failures
+                 FROM bookmark_link_checks",
+            )?;
+            let checks: HashMap<i64, LinkCheck> = stmt
+                .query_map([], |row| {
+                    let status: String = row.get(1)?;
+                    let checked: String = row.get(3)?;
+                    Ok((
+                        row.get(0)?,
+                        LinkCheck::new(
+                            LinkStatus::from_columns(&status, row.get(2)?),
+                            parse_time(&checked),
+                            row.get(4)?,
+                        ),
+                    ))
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok((rows, tags, checks))
+        })?;
+        for row in &mut rows {
+            row.tags = tags.remove(&row.id).unwrap_or_default();
+            row.link_check = checks.remove(&row.id);
+        }
+        Ok(into_tree_order(rows))
     }
 
-    let end = tag_lower
-        .get(i..)?
-        .find(|c: char| c.is_whitespace() || c == '>')
-        .map(|j| i + j)
-        .unwrap_or(tag_lower.len());
-    Some(tag_raw.get(i..end)?.to_string())
-}
+    /// Bookmarks only (no folders or separators), in display order
+    pub fn bookmarks(&self) -> Result<Vec<Bookmark>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(Bookmark::is_bookmark)
+            .collect())
+    }
 
-pub fn import_bookmarks_html(html: &str) -> Vec<Bookmark> {
-    let lower = html.to_ascii_lowercase();
-    let mut pos = 0usize;
-    let mut stack: Vec<String> = Vec::new();
-    let mut pending_folder: Option<String> = None;
-    let mut bookmarks = Vec::new();
+    /// (title, URL) of every bookmark, without tags, checks or folder paths
+    pub fn titles_and_urls(&self) -> Result<Vec<(String, String)>> {
+        Ok(self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT title, url FROM bookmarks WHERE kind = ?1 ORDER BY parent_id, position",
+            )?;
+            let rows = stmt
+                .query_map([BookmarkKind::Bookmark.as_str()], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(rows)
+        })?)
+    }
 
-    while pos < lower.len() {
-        if let Some(h3) = find_from(&lower, "<h3", pos) {
-            let gt = match find_from(&lower, ">", h3) {
-                Some(i) => i,
-                None => break,
-            };
-            let start = gt + 1;
-            let end = match find_from(&lower, "</h3", start) {
-                Some(i) => i,
-                None => break,
+    pub fn get(&self, id: i64) -> Result<Bookmark> {
+        let bookmark = self.db.with_connection(|conn| {
+            let Some(mut bookmark) = conn
+                .query_row(
+                    &format!("SELECT {COLUMNS} FROM bookmarks WHERE id = ?1"),
+                    [id],
+                    bookmark_from_row,
+                )
+                .optional()?
+            else {
+                return Ok(None);
             };
-            let name = html.get(start..end).unwrap_or("").trim();
-            pending_folder = (!name.is_empty()).then(|| name.to_string());
-            pos = end;
-            continue;
+
+            let mut stmt = conn.prepare(
+                "SELECT t.name FROM bookmark_tag_map m
+                 JOIN bookmark_tags t ON t.id = m.tag_id
+                 WHERE m.bookmark_id = ?1
+                 ORDER BY t.name",
+            )?;
+            bookmark.tags = stmt
+                .query_map([id], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            bookmark.link_check = conn
+                .query_row(
+                    "SELECT status, detail, checked_at, dns_failures
+                     FROM bookmark_link_checks WHERE bookmark_id = ?1",
+                    [id],
+                    |row| {
+                        let status: String = row.get(0)?;
+                        let checked: String = row.get(2)?;
+                        Ok(LinkCheck::new(
+                            LinkStatus::from_columns(&status, row.get(1)?),
+                            parse_time(&checked),
+                            row.get(3)?,
+                        ))
+                    },
+                )
+                .optional()?;
+
+            // Folder path, walking up from the parent
+            let mut names = Vec::new();
+            let mut parent = bookmark.parent_id;
+            while let Some(parent_id) = parent.filter(|_| names.len() <= MAX_FOLDER_DEPTH) {
+                let (title, next): (String, Option<i64>) = conn.query_row(
+                    "SELECT title, parent_id FROM bookmarks WHERE id = ?1",
+                    [parent_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )?;
+                names.push(title);
+                parent = next;
+            }
+            names.reverse();
+            bookmark.folder = (!names.is_empty()).then(|| names.join("/"));
+
+            Ok(Some(bookmark))
+        })?;
+        bookmark.ok_or(CoreError::BookmarkNotFound(id))
+    }
+
+    /// All folders, depth-first, with their paths
+    pub fn folders(&self) -> Result<Vec<BookmarkFolder>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|b| b.kind == BookmarkKind::Folder)
+            .map(|b| BookmarkFolder {
+                path: match &b.folder {
+                    Some(parent) => format!("{parent}/{}", b.title),
+                    None => b.title.clone(),
+                },
+                id: b.id,
+                parent_id: b.parent_id,
+                title: b.title,
+            })
+            .collect())
+    }
+
+    /// Add a bookmark at the end of a folder (the same URL may be saved more than once)
+    pub fn add_bookmark(&self, title: &str, url: &str, parent_id: Option<i64>) -> Result<Bookmark> {
+        let url = url.trim();
+        if url.is_empty() {
+            return Err(invalid("Bookmark URL cannot be empty"));
+        }
+        let title = match title.trim() {
+            "" => url,
+            title => title,
+        };
+        self.insert(BookmarkKind::Bookmark, parent_id, title, url)
+    }
+
+    pub fn add_folder(&self, title: &str, parent_id: Option<i64>) -> Result<Bookmark> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(invalid("Folder name cannot be empty"));
         }
+        self.insert(BookmarkKind::Folder, parent_id, title, "")
+    }
+
+    pub fn add_separator(&self, parent_id: Option<i64>) -> Result<Bookmark> {
+        self.insert(BookmarkKind::Separator, parent_id, "", "")
+    }
+
+    fn insert(
+        &self,
+        kind: BookmarkKind,
+        parent_id: Option<i64>,
+        title: &str,
+        url: &str,
+    ) -> Result<Bookmark> {
+        check_parent(&self.db, parent_id)?;
+        let id = self
+            .db
+            .with_connection(|conn| insert_row(conn, kind, parent_id, title, url))?;
+        self.get(id)
+    }
 
-        if let Some(dl) = find_from(&lower, "<dl", pos) {
-            if let Some(folder) = pending_folder.take() {
-                stack.push(folder);
+    /// Change a bookmark's title, URL and description, or a folder's name
+    ///
+    /// With `tags` the bookmark's tags are replaced in the same transaction.
+    pub fn update(
+        &self,
+        id: i64,
+        title: &str,
+        url: &str,
+        description: &str,
+        tags: Option<&[String]>,
+    ) -> Result<Bookmark> {
+        let current = self.get(id)?;
+        let (title, url) = match current.kind {
+            BookmarkKind::Bookmark => {
+                let url = url.trim();
+                if url.is_empty() {
+                    return Err(invalid("Bookmark URL cannot be empty"));
+                }
+                match title.trim() {
+                    "" => (url, url),
+                    title => (title, url),
+                }
             }
-            pos = dl + 3;
-            continue;
+            BookmarkKind::Folder => match title.trim() {
+                "" => return Err(invalid("Folder name cannot be empty")),
+                title => (title, ""),
+            },
+            BookmarkKind::Separator => return Err(invalid("Separators cannot be edited")),
+        };
+        if tags.is_some() && !current.is_bookmark() {
+            return Err(invalid("Only bookmarks can be tagged"));
         }
+        let tags = tags.map(normalize_tags);
 
-        if let Some(end_dl) = find_from(&lower, "</dl", pos) {
-            if !stack.is_empty() {
-                stack.pop();
+        self.db.transaction(|conn| {
+            conn.execute(
+                "UPDATE bookmarks SET title = ?1, url = ?2, description = ?3, modified_at = ?4
+                 WHERE id = ?5",
+                rusqlite::params![
+                    title,
+                    url,
+                    // Folders only get a description from imported files
+                    if current.is_bookmark() {
+                        description.trim()
+                    } else {
+                        current.description.as_str()
+                    },
+                    Utc::now().to_rfc3339(),
+                    id
+                ],
+            )?;
+            if url != current.url {
+                conn.execute(
+                    "DELETE FROM bookmark_link_checks WHERE bookmark_id = ?1",
+                    [id],
+                )?;
             }
-            pos = end_dl + 4;
-            continue;
+            if let Some(tags) = &tags {
+                write_tags(conn, id, tags)?;
+            }
+            Ok(())
+        })?;
+        self.get(id)
+    }
+
+    /// Move an item into `parent_id` at `position` (clamped; the end when `None`)
+    pub fn move_to(
+        &self,
+        id: i64,
+        parent_id: Option<i64>,
+        position: Option<usize>,
+    ) -> Result<Bookmark> {
+        check_parent(&self.db, parent_id)?;
+        let (old_parent, into_itself, mut siblings) = self
+            .db
+            .with_connection(|conn| {
+                let Some(old_parent) = parent_of(conn, id)? else {
+                    return Ok(None);
+                };
+
+                // A folder cannot move into itself or one of its descendants
+                let mut ancestor = parent_id;
+                while let Some(ancestor_id) = ancestor {
+                    if ancestor_id == id {
+                        return Ok(Some((old_parent, true, Vec::new())));
+                    }
+                    ancestor = parent_of(conn, ancestor_id)?.flatten();
+                }
+
+                let mut stmt = conn.prepare(
+                    "SELECT id FROM bookmarks WHERE parent_id IS ?1 AND id != ?2
+                 ORDER BY position, id",
+                )?;
+                let siblings: Vec<i64> = stmt
+                    .query_map(rusqlite::params![parent_id, id], |row| row.get(0))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                Ok(Some((old_parent, false, siblings)))
+            })?
+            .ok_or(CoreError::BookmarkNotFound(id))?;
+        if into_itself {
+            return Err(invalid("A folder cannot be moved into itself"));
         }
+        let index = position.unwrap_or(siblings.len()).min(siblings.len());
+        siblings.insert(index, id);
 
-        if let Some(a) = find_from(&lower, "<a", pos) {
-            let gt = match find_from(&lower, ">", a) {
-                Some(i) => i,
-                None => break,
-            };
-            let tag_lower = lower.get(a..gt).unwrap_or("");
-            let tag_raw = html.get(a..gt).unwrap_or("");
-            let url = extract_attr(tag_lower, tag_raw, "href").unwrap_or_default();
-
-            let text_start = gt + 1;
-            let text_end = match find_from(&lower, "</a", text_start) {
-                Some(i) => i,
-                None => break,
-            };
-            let title = html
-                .get(text_start..text_end)
-                .unwrap_or("")
-                .trim()
-                .to_string();
-            let folder = if stack.is_empty() {
-                None
-            } else {
-                Some(stack.join("/"))
-            };
+        self.db.transaction(|conn| {
+            conn.execute(
+                "UPDATE bookmarks SET parent_id = ?1, modified_at = ?2 WHERE id = ?3",
+                rusqlite::params![parent_id, Utc::now().to_rfc3339(), id],
+            )?;
+            for (position, sibling) in siblings.iter().enumerate() {
+                conn.execute(
+                    "UPDATE bookmarks SET position = ?1 WHERE id = ?2",
+                    rusqlite::params![position as i64, sibling],
+                )?;
+            }
+            if old_parent != parent_id {
+                renumber(conn, old_parent)?;
+            }
+            Ok(())
+        })?;
+        self.get(id)
+    }
 
-            if !url.trim().is_empty() {
-                bookmarks.push(Bookmark {
-                    title: if title.is_empty() { url.clone() } else { title },
-                    url,
-                    folder,
-                });
+    /// Remove an item (a folder with everything inside it); returns how many rows went
+    pub fn remove(&self, id: i64) -> Result<usize> {
+        let items = self.list()?;
+        let item = items
+            .iter()
+            .find(|b| b.id == id)
+            .ok_or(CoreError::BookmarkNotFound(id))?;
+        let before = items.len();
+
+        let remaining: i64 = self.db.transaction(|conn| {
+            conn.execute("DELETE FROM bookmarks WHERE id = ?1", [id])?;
+            renumber(conn, item.parent_id)?;
+            prune_tags(conn)?;
+            Ok(conn.query_row("SELECT COUNT(*) FROM bookmarks", [], |row| row.get(0))?)
+        })?;
+        Ok(before - remaining as usize)
+    }
+
+    /// Remove every bookmark (not folder) matching `predicate`; returns how many
+    pub fn remove_where<F>(&self, mut predicate: F) -> Result<usize>
+    where
+        F: FnMut(&Bookmark) -> bool,
+    {
+        let matching: Vec<Bookmark> = self
+            .bookmarks()?
+            .into_iter()
+            .filter(|b| predicate(b))
+            .collect();
+        if matching.is_empty() {
+            return Ok(0);
+        }
+
+        self.db.transaction(|conn| {
+            for bookmark in &matching {
+                conn.execute("DELETE FROM bookmarks WHERE id = ?1", [bookmark.id])?;
+            }
+            let mut parents: Vec<Option<i64>> = matching.iter().map(|b| b.parent_id).collect();
+            parents.sort();
+            parents.dedup();
+            for parent_id in parents {
+                renumber(conn, parent_id)?;
+            }
+            prune_tags(conn)
+        })?;
+        Ok(matching.len())
+    }
+
+    /// Append imported bookmarks, creating their folders by path; returns how many were added
+    pub fn import(&self, entries: &[ImportedBookmark]) -> Result<usize> {
+        self.db.transaction(|conn| {
+            for entry in entries {
+                let parent_id = ensure_folder_path(conn, entry.folder.as_deref().unwrap_or(""))?;
+                insert_row(
+                    conn,
+                    BookmarkKind::Bookmark,
+                    parent_id,
+                    &entry.title,
+                    &entry.url,
+                )?;
             }
+            Ok(())
+        })?;
+        Ok(entries.len())
+    }
+
+    /// Add the entries of a bookmark file (see [`crate::netscape`]) with their
+    /// dates, icons, tags and attributes; returns how many bookmarks were added
+    ///
+    /// Where the file meets existing bookmarks it is merged: a folder with the
+    /// name of one already in the same place is filled in rather than repeated,
+    /// and bookmarks (or separators) already there are skipped, so importing a
+    /// file twice adds nothing.
+    pub fn import_tree(&self, nodes: &[BookmarkNode]) -> Result<usize> {
+        Ok(self
+            .db
+            .transaction(|conn| insert_nodes(conn, nodes, None))?)
+    }
+
+    // === Tags ===
 
-            pos = text_end + 4;
-            continue;
+    /// Replace a bookmark's tags
+    pub fn set_tags<S: AsRef<str>>(&self, id: i64, tags: &[S]) -> Result<Bookmark> {
+        if !self.get(id)?.is_bookmark() {
+            return Err(invalid("Only bookmarks can be tagged"));
         }
+        let tags = normalize_tags(tags);
+
+        self.db.transaction(|conn| {
+            write_tags(conn, id, &tags)?;
+            conn.execute(
+                "UPDATE bookmarks SET modified_at = ?1 WHERE id = ?2",
+                rusqlite::params![Utc::now().to_rfc3339(), id],
+            )?;
+            Ok(())
+        })?;
+        self.get(id)
+    }
 
-        pos += 1;
+    /// Every tag in use with its bookmark count, by name
+    pub fn tags(&self) -> Result<Vec<BookmarkTag>> {
+        Ok(self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT t.name, COUNT(*) FROM bookmark_tags t
+                 JOIN bookmark_tag_map m ON m.tag_id = t.id
+                 GROUP BY t.id ORDER BY t.name",
+            )?;
+            let tags = stmt
+                .query_map([], |row| {
+                    Ok(BookmarkTag {
+                        name: row.get(0)?,
+                        count: row.get::<_, i64>(1)? as usize,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(tags)
+        })?)
     }
 
-    bookmarks
+    /// Rename a tag; renaming onto another existing tag merges the two
+    pub fn rename_tag(&self, from: &str, to: &str) -> Result<usize> {
+        self.merge_tags(&[from], to)
+    }
+
+    /// Move every bookmark tagged with one of `sources` to `into` and drop the
+    /// sources; smart folders filtering on a source follow. Returns how many
+    /// bookmarks carry `into` afterwards.
+    pub fn merge_tags<S: AsRef<str>>(&self, sources: &[S], into: &str) -> Result<usize> {
+        let into = single_tag(into)?;
+        let known = self.tags()?;
+        let mut names = Vec::new();
+        for source in sources {
+            let source = source.as_ref().trim();
+            let tag = known
+                .iter()
+                .find(|t| t.name.eq_ignore_ascii_case(source))
+                .ok_or_else(|| CoreError::BookmarkTagNotFound(source.to_string()))?;
+            // `rust` and `Rust` name the same tag
+            if !names.contains(&tag.name) {
+                names.push(tag.name.clone());
+            }
+        }
+
+        let count: i64 = self.db.transaction(|conn| {
+            let target = tag_id(conn, &into)?;
+            for name in &names {
+                let source: i64 = conn.query_row(
+                    "SELECT id FROM bookmark_tags WHERE name = ?1",
+                    [name],
+                    |row| row.get(0),
+                )?;
+                if source == target {
+                    // Only the spelling changes
+                    conn.execute(
+                        "UPDATE bookmark_tags SET name = ?1 WHERE id = ?2",
+                        rusqlite::params![into, target],
+                    )?;
+                } else {
+                    conn.execute(
+                        "INSERT OR IGNORE INTO bookmark_tag_map (bookmark_id, tag_id)
+                         SELECT bookmark_id, ?1 FROM bookmark_tag_map WHERE tag_id = ?2",
+                        [target, source],
+                    )?;
+                    conn.execute("DELETE FROM bookmark_tags WHERE id = ?1", [source])?;
+                }
+                conn.execute(
+                    "UPDATE bookmark_smart_folders SET tag = ?1 WHERE tag = ?2 COLLATE NOCASE",
+                    [&into, name],
+                )?;
+            }
+            prune_tags(conn)?;
+            Ok(conn.query_row(
+                "SELECT COUNT(*) FROM bookmark_tag_map WHERE tag_id = ?1",
+                [target],
+                |row| row.get(0),
+            )?)
+        })?;
+        Ok(count as usize)
+    }
+
+    // === Link checks ===
+
+    /// Store the result of checking a bookmark (ignored if it was removed meanwhile)
+    ///
+    /// DNS failures are counted across checks; any other result resets the count.
+    pub fn record_link_check(&self, id: i64, status: &LinkStatus) -> Result<()> {
+        let (kind, detail) = status.to_columns();
+        self.db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO bookmark_link_checks
+                 (bookmark_id, status, detail, checked_at, dns_failures)
+                 SELECT id, ?2, ?3, ?4, ?2 = 'dns' FROM bookmarks
+                 WHERE id = ?1 AND kind = 'bookmark'
+                 ON CONFLICT(bookmark_id) DO UPDATE SET
+                    status = excluded.status,
+                    detail = excluded.detail,
+                    checked_at = excluded.checked_at,
+                    dns_failures = CASE WHEN excluded.status = 'dns'
+                        THEN bookmark_link_checks.dns_failures + 1 ELSE 0 END",
+                rusqlite::params![id, kind, detail, Utc::now().to_rfc3339()],
+            )?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    /// Point permanently redirected bookmarks at their new URL; `ids` limits
+    /// which bookmarks are considered. Returns how many were changed.
+    pub fn apply_redirects(&self, ids: Option<&[i64]>) -> Result<usize> {
+        let redirects: Vec<(i64, String, DateTime<Utc>)> = self
+            .bookmarks()?
+            .into_iter()
+            .filter(|b| ids.is_none_or(|ids| ids.contains(&b.id)))
+            .filter_map(|b| match b.link_check {
+                Some(LinkCheck {
+                    status: LinkStatus::Redirected { to },
+                    checked_at,
+                    ..
+                }) => Some((b.id, to, checked_at)),
+                _ => None,
+            })
+            .collect();
+
+        self.db.transaction(|conn| {
+            let now = Utc::now().to_rfc3339();
+            for (id, to, checked_at) in &redirects {
+                conn.execute(
+                    "UPDATE bookmarks SET url = ?1, modified_at = ?2 WHERE id = ?3",
+                    rusqlite::params![to, now, id],
+                )?;
+                // The new URL answered when the old one was checked
+                conn.execute(
+                    "UPDATE bookmark_link_checks SET status = 'ok', detail = NULL, checked_at = ?1
+                     WHERE bookmark_id = ?2",
+                    rusqlite::params![checked_at.to_rfc3339(), id],
+                )?;
+            }
+            Ok(())
+        })?;
+        Ok(redirects.len())
+    }
+
+    /// Remove bookmarks whose last check found them dead (see
+    /// [`LinkCheck::dead`]); `ids` limits which are considered
+    pub fn remove_dead(&self, ids: Option<&[i64]>) -> Result<usize> {
+        self.remove_where(|b| {
+            ids.is_none_or(|ids| ids.contains(&b.id))
+                && b.link_check.as_ref().is_some_and(|c| c.dead)
+        })
+    }
+
+    // === Smart folders ===
+
+    /// Smart folders in order, each with the bookmarks it matches right now
+    pub fn smart_folders(&self) -> Result<Vec<SmartFolder>> {
+        let mut folders: Vec<SmartFolder> = self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, title, position, tag, domain, text, added_within_days, created_at
+                 FROM bookmark_smart_folders ORDER BY position, id",
+            )?;
+            let folders = stmt
+                .query_map([], smart_folder_from_row)?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(folders)
+        })?;
+        if folders.is_empty() {
+            return Ok(folders);
+        }
+
+        let bookmarks = self.bookmarks()?;
+        let now = Utc::now();
+        for folder in &mut folders {
+            folder.bookmark_ids = bookmarks
+                .iter()
+                .filter(|b| folder.query.matches(b, now))
+                .map(|b| b.id)
+                .collect();
+        }
+        Ok(folders)
+    }
+
+    fn smart_folder(&self, id: i64) -> Result<SmartFolder> {
+        self.smart_folders()?
+            .into_iter()
+            .find(|f| f.id == id)
+            .ok_or(CoreError::SmartFolderNotFound(id))
+    }
+
+    pub fn add_smart_folder(&self, title: &str, query: SmartFolderQuery) -> Result<SmartFolder> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(invalid("Folder name cannot be empty"));
+        }
+        let query = query.normalized()?;
+
+        let id = self.db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO bookmark_smart_folders
+                     (title, position, tag, domain, text, added_within_days, created_at)
+                 VALUES (?1, (SELECT COALESCE(MAX(position) + 1, 0) FROM bookmark_smart_folders),
+                         ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![
+                    title,
+                    query.tag,
+                    query.domain,
+                    query.text,
+                    query.added_within_days,
+                    Utc::now().to_rfc3339()
+                ],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })?;
+        self.smart_folder(id)
+    }
+
+    pub fn update_smart_folder(
+        &self,
+        id: i64,
+        title: &str,
+        query: SmartFolderQuery,
+    ) -> Result<SmartFolder> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(invalid("Folder name cannot be empty"));
+        }
+        let query = query.normalized()?;
+
+        let updated = self.db.with_connection(|conn| {
+            Ok(conn.execute(
+                "UPDATE bookmark_smart_folders
+                 SET title = ?1, tag = ?2, domain = ?3, text = ?4, added_within_days = ?5
+                 WHERE id = ?6",
+                rusqlite::params![
+                    title,
+                    query.tag,
+                    query.domain,
+                    query.text,
+                    query.added_within_days,
+                    id
+                ],
+            )?)
+        })?;
+        if updated == 0 {
+            return Err(CoreError::SmartFolderNotFound(id));
+        }
+        self.smart_folder(id)
+    }
+
+    pub fn remove_smart_folder(&self, id: i64) -> Result<()> {
+        let removed = self.db.with_connection(|conn| {
+            Ok(conn.execute("DELETE FROM bookmark_smart_folders WHERE id = ?1", [id])?)
+        })?;
+        if removed == 0 {
+            return Err(CoreError::SmartFolderNotFound(id));
+        }
+        Ok(())
+    }
+}
+
+impl Clone for BookmarkManager {
+    fn clone(&self) -> Self {
+        Self {
+            db: self.db.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bookmark_tree() {
+        let manager = BookmarkManager::new(Database::open_in_memory().unwrap());
+
+        let work = manager.add_folder("Work", None).unwrap();
+        let rust = manager.add_folder("Rust", Some(work.id)).unwrap();
+        let docs = manager
+            .add_bookmark("Docs", "https://doc.rust-lang.org/", Some(rust.id))
+            .unwrap();
+        let example = manager
+            .add_bookmark("", "https://example.com/", None)
+            .unwrap();
+        manager.add_separator(Some(work.id)).unwrap();
+        // The same URL may be saved twice
+        let again = manager
+            .add_bookmark("Example again", "https://example.com/", Some(work.id))
+            .unwrap();
+
+        assert_eq!(example.title, "https://example.com/");
+        assert_eq!(docs.folder.as_deref(), Some("Work/Rust"));
+        assert_eq!(manager.bookmarks().unwrap().len(), 3);
+        let titles: Vec<String> = manager
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|b| b.title)
+            .collect();
+        assert_eq!(
+            titles,
+            [
+                "Work",
+                "Rust",
+                "Docs",
+                "",
+                "Example again",
+                "https://example.com/"
+            ]
+        );
+        let folders = manager.folders().unwrap();
+        assert_eq!(folders[1].path, "Work/Rust");
+
+        // Bookmarks only go into folders, and folders never into themselves
+        assert!(manager
+            .add_bookmark("x", "https://x.test/", Some(docs.id))
+            .is_err());
+        assert!(manager.move_to(work.id, Some(rust.id), None).is_err());
+        assert!(manager.add_bookmark("x", "  ", None).is_err());
+        assert!(manager.move_to(work.id, Some(work.id), None).is_err());
+        assert!(matches!(
+            manager.add_bookmark("x", "https://x.test/", Some(999)),
+            Err(CoreError::BookmarkNotFound(999))
+        ));
+        assert!(matches!(
+            manager.get(999),
+            Err(CoreError::BookmarkNotFound(999))
+        ));
+        assert_eq!(manager.titles_and_urls().unwrap().len(), 3);
+
+        // Move to the front of the top level; the old folder is renumbered
+        let moved = manager.move_to(again.id, None, Some(0)).unwrap();
+        assert_eq!((moved.parent_id, moved.position), (None, 0));
+        assert_eq!(manager.get(work.id).unwrap().position, 1);
+        let separator = manager
+            .list()
+            .unwrap()
+            .into_iter()
+            .find(|b| b.kind == BookmarkKind::Separator)
+            .unwrap();
+        assert_eq!(separator.position, 1);
+
+        let updated = manager
+            .update(
+                docs.id,
+                "Std",
+                "https://doc.rust-lang.org/std/",
+                " The standard library ",
+                None,
+            )
+            .unwrap();
+        assert_eq!(updated.title, "Std");
+        assert_eq!(updated.description, "The standard library");
+        assert!(updated.modified_at >= updated.created_at);
+
+        // Removing a folder removes its contents
+        assert_eq!(manager.remove(work.id).unwrap(), 4);
+        assert_eq!(manager.list().unwrap().len(), 2);
+        assert!(matches!(
+            manager.remove(work.id),
+            Err(CoreError::BookmarkNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_tags() {
+        let manager = BookmarkManager::new(Database::open_in_memory().unwrap());
+        let rust = manager
+            .add_bookmark("Rust", "https://www.rust-lang.org/", None)
+            .unwrap();
+        let tokio = manager
+            .add_bookmark("Tokio", "https://tokio.rs/", None)
+            .unwrap();
+        let folder = manager.add_folder("Dev", None).unwrap();
+
+        let rust = manager
+            .set_tags(rust.id, &["lang,  Rust ", "rust", ""])
+            .unwrap();
+        assert_eq!(rust.tags, ["lang", "Rust"]);
+        manager.set_tags(tokio.id, &["async", "RUST"]).unwrap();
+        assert!(manager.set_tags(folder.id, &["x"]).is_err());
+        assert!(manager
+            .update(folder.id, "Dev", "", "", Some(&["x".to_string()]))
+            .is_err());
+        assert_eq!(manager.get(folder.id).unwrap().title, "Dev");
+
+        // Names are case-insensitive and keep their first spelling
+        let tags = manager.tags().unwrap();
+        let counts: Vec<(&str, usize)> = tags.iter().map(|t| (t.name.as_str(), t.count)).collect();
+        assert_eq!(counts, [("async", 1), ("lang", 1), ("Rust", 2)]);
+
+        // Renaming onto an existing tag merges
+        assert_eq!(manager.rename_tag("async", "rust").unwrap(), 2);
+        assert_eq!(manager.get(tokio.id).unwrap().tags, ["Rust"]);
+        assert_eq!(manager.rename_tag("RUST", "rust").unwrap(), 2);
+        assert_eq!(manager.get(rust.id).unwrap().tags, ["lang", "rust"]);
+        // Sources naming the same tag twice are merged once
+        assert_eq!(manager.merge_tags(&["rust", "Rust"], "rust").unwrap(), 2);
+        assert_eq!(manager.merge_tags(&["lang", "rust"], "dev").unwrap(), 2);
+        assert_eq!(manager.tags().unwrap().len(), 1);
+        assert!(matches!(
+            manager.rename_tag("missing", "x"),
+            Err(CoreError::BookmarkTagNotFound(_))
+        ));
+        assert!(manager.rename_tag("dev", "a,b").is_err());
+
+        // Tags nobody carries disappear
+        manager.remove(rust.id).unwrap();
+        manager.set_tags(tokio.id, &[] as &[&str]).unwrap();
+        assert!(manager.tags().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_smart_folders() {
+        let manager = BookmarkManager::new(Database::open_in_memory().unwrap());
+        let docs = manager
+            .add_bookmark("Docs", "https://doc.rust-lang.org/std/", None)
+            .unwrap();
+        let blog = manager
+            .add_bookmark("Blog", "https://blog.rust-lang.org/", None)
+            .unwrap();
+        let news = manager
+            .add_bookmark("News", "https://news.example.com/", None)
+            .unwrap();
+        manager.set_tags(docs.id, &["reference"]).unwrap();
+        manager
+            .update(
+                news.id,
+                "News",
+                "https://news.example.com/",
+                "Mentions Rust",
+                None,
+            )
+            .unwrap();
+
+        let query =
+            |tag: Option<&str>, domain: Option<&str>, text: Option<&str>| SmartFolderQuery {
+                tag: tag.map(str::to_string),
+                domain: domain.map(str::to_string),
+                text: text.map(str::to_string),
+                added_within_days: None,
+            };
+        let site = manager
+            .add_smart_folder(
+                "Rust site",
+                query(None, Some("https://rust-lang.org/"), None),
+            )
+            .unwrap();
+        assert_eq!(site.query.domain.as_deref(), Some("rust-lang.org"));
+        assert_eq!(site.bookmark_ids, [docs.id, blog.id]);
+
+        let text = manager
+            .add_smart_folder("About Rust", query(None, None, Some("rust")))
+            .unwrap();
+        assert_eq!(text.bookmark_ids, [docs.id, blog.id, news.id]);
+
+        let tagged = manager
+            .add_smart_folder(
+                "Reference",
+                query(Some("Reference"), Some("rust-lang.org"), None),
+            )
+            .unwrap();
+        assert_eq!(tagged.bookmark_ids, [docs.id]);
+
+        // Renaming a tag keeps the smart folder pointing at it
+        manager.rename_tag("reference", "docs").unwrap();
+        let folders = manager.smart_folders().unwrap();
+        assert_eq!(folders[2].query.tag.as_deref(), Some("docs"));
+        assert_eq!(folders[2].bookmark_ids, [docs.id]);
+
+        let mut recent = query(None, None, None);
+        recent.added_within_days = Some(7);
+        let recent = manager
+            .update_smart_folder(text.id, "Recent", recent)
+            .unwrap();
+        assert_eq!(recent.bookmark_ids.len(), 3);
+        let old = Bookmark {
+            created_at: Utc::now() - Duration::days(8),
+            ..manager.get(docs.id).unwrap()
+        };
+        assert!(!recent.query.matches(&old, Utc::now()));
+
+        assert!(manager
+            .add_smart_folder("Empty", query(Some(" "), None, None))
+            .is_err());
+        manager.remove_smart_folder(site.id).unwrap();
+        assert!(matches!(
+            manager.remove_smart_folder(site.id),
+            Err(CoreError::SmartFolderNotFound(_))
+        ));
+        assert_eq!(manager.smart_folders().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_link_checks() {
+        let manager = BookmarkManager::new(Database::open_in_memory().unwrap());
+        let moved = manager
+            .add_bookmark("Moved", "http://example.com/old", None)
+            .unwrap();
+        let gone = manager
+            .add_bookmark("Gone", "https://gone.example/", None)
+            .unwrap();
+        let slow = manager
+            .add_bookmark("Slow", "https://slow.example/", None)
+            .unwrap();
+        let flaky = manager
+            .add_bookmark("Flaky", "https://flaky.example/", None)
+            .unwrap();
+        let folder = manager.add_folder("Folder", None).unwrap();
+
+        manager
+            .record_link_check(
+                moved.id,
+                &LinkStatus::Redirected {
+                    to: "https://example.com/new".to_string(),
+                },
+            )
+            .unwrap();
+        for _ in 0..crate::link_check::DNS_FAILURES_BEFORE_DEAD {
+            manager
+                .record_link_check(gone.id, &LinkStatus::Dns)
+                .unwrap();
+        }
+        manager
+            .record_link_check(flaky.id, &LinkStatus::Dns)
+            .unwrap();
+        manager
+            .record_link_check(slow.id, &LinkStatus::Timeout)
+            .unwrap();
+        manager
+            .record_link_check(folder.id, &LinkStatus::Ok)
+            .unwrap();
+        manager.record_link_check(9999, &LinkStatus::Ok).unwrap();
+        assert!(manager.get(folder.id).unwrap().link_check.is_none());
+        assert_eq!(
+            manager.get(gone.id).unwrap().link_check.unwrap().status,
+            LinkStatus::Dns
+        );
+
+        assert_eq!(manager.apply_redirects(Some(&[gone.id])).unwrap(), 0);
+        assert_eq!(manager.apply_redirects(None).unwrap(), 1);
+        let moved = manager.get(moved.id).unwrap();
+        assert_eq!(moved.url, "https://example.com/new");
+        assert_eq!(moved.link_check.unwrap().status, LinkStatus::Ok);
+
+        // A host that failed to resolve once is not dead yet
+        let flaky_check = manager.get(flaky.id).unwrap().link_check.unwrap();
+        assert_eq!(flaky_check.dns_failures, 1);
+        assert!(!flaky_check.dead);
+        assert!(manager.get(gone.id).unwrap().link_check.unwrap().dead);
+        manager
+            .record_link_check(flaky.id, &LinkStatus::Ok)
+            .unwrap();
+        manager
+            .record_link_check(flaky.id, &LinkStatus::Dns)
+            .unwrap();
+        assert_eq!(
+            manager
+                .get(flaky.id)
+                .unwrap()
+                .link_check
+                .unwrap()
+                .dns_failures,
+            1
+        );
+
+        // Timeouts are reported but not treated as dead
+        assert_eq!(manager.remove_dead(None).unwrap(), 1);
+        assert_eq!(manager.bookmarks().unwrap().len(), 3);
+
+        // Editing the URL drops the stale result
+        manager
+            .update(slow.id, "Slow", "https://fast.example/", "", None)
+            .unwrap();
+        assert!(manager.get(slow.id).unwrap().link_check.is_none());
+    }
+
+    #[test]
+    fn test_import_creates_folders() {
+        let manager = BookmarkManager::new(Database::open_in_memory().unwrap());
+        let html = r#"<DL><p>
+            <DT><H3>Dev</H3>
+            <DL><p>
+                <DT><A HREF="https://www.rust-lang.org/">Rust</A>
+                <DT><A HREF="https://tokio.rs/">Tokio</A>
+            </DL><p>
+            <DT><A HREF="https://example.com/">Example</A>
+        </DL><p>"#;
+
+        let nodes = crate::netscape::parse_bookmarks_html(html);
+        assert_eq!(manager.import_tree(&nodes).unwrap(), 3);
+        // Importing again merges into the existing folder and adds nothing
+        assert_eq!(manager.import_tree(&nodes).unwrap(), 0);
+        let entries = [ImportedBookmark {
+            title: "Axum".to_string(),
+            url: "https://github.com/tokio-rs/axum".to_string(),
+            folder: Some("Dev".to_string()),
+        }];
+        assert_eq!(manager.import(&entries).unwrap(), 1);
+
+        let folders = manager.folders().unwrap();
+        assert_eq!(folders.len(), 1);
+        let titles: Vec<String> = manager
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|b| b.title)
+            .collect();
+        assert_eq!(titles, ["Dev", "Rust", "Tokio", "Axum", "Example"]);
+    }
 }
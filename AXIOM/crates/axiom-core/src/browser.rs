@@ -4,21 +4,43 @@
 
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use axiom_download::DownloadManager;
-use axiom_navigation::{HistoryManager, InputResolver};
-use axiom_privacy::{PermissionManager, TrackingProtection};
+use axiom_navigation::{
+    AnswerProvider, BookmarkCandidate, BookmarksProvider, CancellationToken, Command,
+    CommandProvider, CommandType, HistoryManager, HistoryProvider, HistorySort, HttpsOnlyException,
+    HttpsOnlyManager, InputResolver, KeywordManager, NewSearchEngine, OpenTabsProvider,
+    SearchEngine, SearchEngineManager, SearchKeyword, SearchProvider, SuggestionPipeline,
+    SuggestionResult, TabCandidate, TypedUrlProvider, VisitSource, VisitTransition,
+};
+use axiom_privacy::{PermissionManager, PublicSuffixList, TrackingProtection};
 use axiom_session::SessionManager;
 use axiom_storage::Database;
 
-use crate::bookmarks::Bookmark;
+use crate::bookmarks::{
+    Bookmark, BookmarkFolders, BookmarkManager, BookmarkTag, SmartFolder, SmartFolderQuery,
+};
+use crate::commands::{
+    folder_path_display, matches_terms, query_terms, CommandMatch, CommandTarget, OpenWindow,
+    SETTINGS,
+};
 use crate::config::Config;
 use crate::error::CoreError;
+use crate::forget::ForgetSiteSummary;
+use crate::import::{ImportReport, ImportSource};
+use crate::link_check::{LinkCheckRun, LinkProbe, LinkStatus};
+use crate::routing::{RoutePattern, RoutingRule, UrlRouter};
+use crate::session_settings::SessionSettings;
 use crate::Result;
 
+/// Page-load URL changes within this long of the tab's previous visit are logged as redirects
+const REDIRECT_WINDOW: chrono::Duration = chrono::Duration::seconds(3);
+
 #[derive(Debug, Clone)]
 struct ClosedTab {
+    id: String,
     session_id: String,
     url: String,
     title: String,
@@ -41,12 +63,24 @@ pub struct Browser {
     history_manager: HistoryManager,
     /// Input resolver for address bar
     input_resolver: Arc<RwLock<InputResolver>>,
+    /// Search keyword engines (`w rust`, `!gh tokio`)
+    keyword_manager: KeywordManager,
+    /// Selectable search engines (built-in and added)
+    search_engines: SearchEngineManager,
+    /// HTTPS-Only mode setting and per-host exceptions
+    https_only: HttpsOnlyManager,
     /// Download manager
     download_manager: DownloadManager,
+    /// Bookmarks, folders and separators
+    bookmark_manager: BookmarkManager,
     /// Permission manager
     permission_manager: Arc<RwLock<PermissionManager>>,
     /// Tracking protection
     tracking_protection: Arc<RwLock<TrackingProtection>>,
+    /// Site boundaries (eTLD+1) for partitions, URL detection and privacy checks
+    public_suffix_list: Arc<RwLock<Arc<PublicSuffixList>>>,
+    /// URL routing rules (URL pattern -> target session)
+    url_router: UrlRouter,
     /// Current active tab ID
     active_tab_id: Arc<RwLock<Option<String>>>,
     recently_closed_tabs: Arc<RwLock<Vec<ClosedTab>>>,
@@ -69,7 +103,12 @@ impl Browser {
         let input_resolver = Arc::new(RwLock::new(InputResolver::with_search_engine(
             config.search_engine.clone(),
         )));
+        let keyword_manager = KeywordManager::new(db.clone());
+        let search_engines = SearchEngineManager::new(db.clone());
+        let https_only = HttpsOnlyManager::new(db.clone());
         let download_manager = DownloadManager::new(db.clone(), config.download_dir.clone());
+        let bookmark_manager = BookmarkManager::new(db.clone());
+        let url_router = UrlRouter::new(db.clone());
 
         let mut tracking_protection = TrackingProtection::new();
         tracking_protection.set_enabled(config.tracking_protection);
@@ -80,9 +119,15 @@ impl Browser {
             session_manager,
             history_manager,
             input_resolver,
+            keyword_manager,
+            search_engines,
+            https_only,
             download_manager,
+            bookmark_manager,
             permission_manager: Arc::new(RwLock::new(PermissionManager::new())),
             tracking_protection: Arc::new(RwLock::new(tracking_protection)),
+            public_suffix_list: Arc::new(RwLock::new(PublicSuffixList::embedded_shared())),
+            url_router,
             active_tab_id: Arc::new(RwLock::new(None)),
             recently_closed_tabs: Arc::new(RwLock::new(Vec::new())),
         })
@@ -96,11 +141,39 @@ impl Browser {
         // Load downloads
         self.download_manager.load_downloads()?;
 
-        // Apply persisted search engine preference if available
-        if let Some(template) = self.db.get_setting("search_engine")? {
-            self.input_resolver.write().set_search_engine(template);
+        // Load URL routing rules
+        self.url_router.load()?;
+
+        // Private sessions from a run that did not shut down cleanly
+        let wiped =
+            crate::partition::remove_private_partitions(&self.config.webview_partitions_dir);
+        if wiped > 0 {
+            tracing::info!(wiped, "Removed leftover private session storage");
+        }
+
+        // Prefer a downloaded public suffix list over the embedded one
+        if let Some(path) = self.public_suffix_list_path().filter(|p| p.exists()) {
+            match PublicSuffixList::load_file(&path) {
+                Ok(list) if !list.is_empty() => self.install_public_suffix_list(list),
+                Ok(_) => tracing::warn!(?path, "Ignoring empty public suffix list"),
+                Err(e) => tracing::warn!(?path, "Failed to read public suffix list: {}", e),
+            }
         }
 
+        // Let aged visits fall into lower frecency buckets
+        let rescored = self.history_manager.recalculate_frecency()?;
+        tracing::debug!(rescored, "Recalculated history frecency");
+
+        // Apply the default search engine (adopting a legacy template preference)
+        let engine = self
+            .search_engines
+            .default_engine(&self.config.search_engine)?;
+        self.input_resolver
+            .write()
+            .set_search_engine(engine.template);
+        let keywords = self.keyword_manager.load()?;
+        self.input_resolver.write().set_keywords(keywords);
+
         if let Some(domains_json) = self.db.get_setting("blocked_domains")? {
             if let Ok(domains) = serde_json::from_str::<Vec<String>>(&domains_json) {
                 self.tracking_protection
@@ -140,15 +213,34 @@ impl Browser {
         Ok(self.session_manager.create_session(name)?)
     }
 
+    /// Create a session that leaves no history, tabs or downloads behind
+    pub fn create_private_session(&self, name: String) -> Result<axiom_session::Session> {
+        Ok(self.session_manager.create_private_session(name)?)
+    }
+
+    /// True if the session exists and is private
+    fn is_private_session(&self, session_id: &str) -> bool {
+        self.session_manager
+            .get_session(session_id)
+            .is_ok_and(|session| session.private)
+    }
+
     pub fn switch_session(&self, session_id: &str) -> Result<axiom_session::Session> {
         let session = self.session_manager.switch_session(session_id)?;
 
-        // Update active tab
-        if let Some(first_tab_id) = session.tab_order.first() {
+        // Update active tab (restore kept it materialized; fall back to the first tab)
+        let ordered_tabs = self
+            .session_manager
+            .get_ordered_tabs_for_session(&session.id)?;
+        let active = ordered_tabs
+            .iter()
+            .find(|tab| tab.state == axiom_tabs::TabState::Active)
+            .or_else(|| ordered_tabs.first());
+        if let Some(active) = active {
             let tab = self
                 .session_manager
                 .tab_manager()
-                .activate_tab(first_tab_id)?;
+                .activate_tab(&active.id)?;
             *self.active_tab_id.write() = Some(tab.id);
         } else {
             *self.active_tab_id.write() = None;
@@ -157,10 +249,231 @@ impl Browser {
         Ok(session)
     }
 
+    /// Delete a session and its isolated storage partitions
+    ///
+    /// Closing a private session also forgets its recently closed tabs.
+    pub fn delete_session(&self, session_id: &str) -> Result<()> {
+        let private = self.is_private_session(session_id);
+        self.session_manager.delete_session(session_id)?;
+        self.url_router.forget_session(session_id);
+        self.https_only.forget_session(session_id);
+
+        let dir = if private {
+            self.recently_closed_tabs
+                .write()
+                .retain(|closed| closed.session_id != session_id);
+            crate::partition::private_partition_root(
+                &self.config.webview_partitions_dir,
+                session_id,
+            )
+        } else {
+            crate::partition::session_partition_root(
+                &self.config.webview_partitions_dir,
+                session_id,
+            )
+        };
+        if dir.exists() {
+            match std::fs::remove_dir_all(&dir) {
+                Ok(()) => {
+                    tracing::info!(session_id = %session_id, "Removed session storage partitions")
+                }
+                // Webviews may still hold files open; the next start sweeps private storage
+                Err(e) if private => tracing::warn!(
+                    session_id = %session_id,
+                    "Failed to remove private session storage: {}",
+                    e
+                ),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn set_session_isolated(
+        &self,
+        session_id: &str,
+        isolated: bool,
+    ) -> Result<axiom_session::Session> {
+        Ok(self
+            .session_manager
+            .set_session_isolated(session_id, isolated)?)
+    }
+
+    /// Bytes used by a session's isolated storage partitions
+    pub fn session_storage_size(&self, session_id: &str) -> Result<u64> {
+        self.session_manager.get_session(session_id)?;
+        let dir = crate::partition::session_partition_root(
+            &self.config.webview_partitions_dir,
+            session_id,
+        );
+        Ok(crate::partition::directory_size(&dir))
+    }
+
+    /// Location of a user-installed public suffix list, next to the database
+    fn public_suffix_list_path(&self) -> Option<PathBuf> {
+        self.config
+            .database_path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| dir.join("public_suffix_list.dat"))
+    }
+
+    /// Install a newer public suffix list (`public_suffix_list.dat` format)
+    ///
+    /// The list is validated, saved next to the database so it survives
+    /// restarts, and used immediately. Returns the number of rules.
+    pub fn update_public_suffix_list(&self, contents: &str) -> Result<usize> {
+        let list = PublicSuffixList::parse(contents);
+        if list.is_empty() || !list.has_known_suffix("example.com") {
+            return Err(CoreError::Config(
+                "Not a valid public suffix list".to_string(),
+            ));
+        }
+
+        if let Some(path) = self.public_suffix_list_path() {
+            std::fs::write(&path, contents)?;
+        }
+        let rules = list.len();
+        self.install_public_suffix_list(list);
+        Ok(rules)
+    }
+
+    /// The public suffix list in use
+    pub fn public_suffix_list(&self) -> Arc<PublicSuffixList> {
+        Arc::clone(&self.public_suffix_list.read())
+    }
+
+    fn install_public_suffix_list(&self, list: PublicSuffixList) {
+        tracing::info!(rules = list.len(), "Installed public suffix list");
+        let list = Arc::new(list);
+        self.input_resolver
+            .write()
+            .set_public_suffix_list(Arc::clone(&list));
+        self.tracking_protection
+            .write()
+            .set_public_suffix_list(Arc::clone(&list));
+        *self.public_suffix_list.write() = list;
+    }
+
+    /// Data directory for a tab's webview: per site, and per session when it is isolated
+    ///
+    /// Private sessions use a directory of their own that is removed when the
+    /// session is closed or the browser exits.
+    pub fn webview_partition_dir(&self, tab_id: &str, url: &str) -> PathBuf {
+        let session = self
+            .session_manager
+            .tab_manager()
+            .get_tab(tab_id)
+            .ok()
+            .and_then(|tab| self.session_manager.get_session(&tab.session_id).ok());
+        if let Some(session) = session.as_ref().filter(|session| session.private) {
+            let root = crate::partition::private_partition_root(
+                &self.config.webview_partitions_dir,
+                &session.id,
+            );
+            return crate::partition::partition_dir(&root, None, url, &self.public_suffix_list());
+        }
+        let isolated_session = session
+            .filter(|session| session.isolated)
+            .map(|session| session.id);
+
+        let psl = self.public_suffix_list();
+        crate::partition::adopt_host_partitions(
+            &self.config.webview_partitions_dir,
+            isolated_session.as_deref(),
+            url,
+            &psl,
+        );
+        crate::partition::partition_dir(
+            &self.config.webview_partitions_dir,
+            isolated_session.as_deref(),
+            url,
+            &psl,
+        )
+    }
+
+    /// Crash recovery offer, present when the previous run did not shut down cleanly
+    pub fn recovery_info(&self) -> Option<axiom_session::RecoveryInfo> {
+        self.session_manager.recovery_info()
+    }
+
+    pub fn dismiss_recovery(&self) {
+        self.session_manager.dismiss_recovery();
+    }
+
+    /// Re-open the sessions saved at the last clean shutdown as new sessions
+    pub fn restore_previous_session(&self) -> Result<Vec<axiom_session::Session>> {
+        Ok(self.session_manager.restore_last_known_good()?)
+    }
+
+    /// Record a clean shutdown (called once when the app exits normally)
+    pub fn shutdown(&self) -> Result<()> {
+        self.session_manager.record_clean_shutdown()?;
+        crate::partition::remove_private_partitions(&self.config.webview_partitions_dir);
+        tracing::info!("Browser shut down cleanly");
+        Ok(())
+    }
+
+    /// Load a session's tabs lazily: only the active tab (plus the eager restore
+    /// count of recent tabs) stays loaded, the rest are `Discarded` until clicked
+    pub fn restore_session_tabs(&self, session_id: &str) -> Result<Vec<axiom_tabs::Tab>> {
+        Ok(self.session_manager.restore_session_tabs(session_id)?)
+    }
+
+    /// Show a session in a window without changing the global active session:
+    /// fails for archived sessions, otherwise restores its tabs lazily
+    pub fn open_session(&self, session_id: &str) -> Result<axiom_session::Session> {
+        let session = self.session_manager.get_session(session_id)?;
+        if session.is_archived() {
+            return Err(axiom_session::SessionError::Archived(session.id).into());
+        }
+        self.restore_session_tabs(session_id)?;
+        Ok(self.session_manager.get_session(session_id)?)
+    }
+
     pub fn list_sessions(&self) -> Vec<axiom_session::Session> {
         self.session_manager.list_sessions()
     }
 
+    pub fn list_archived_sessions(&self) -> Vec<axiom_session::Session> {
+        self.session_manager.list_archived_sessions()
+    }
+
+    /// Archive a session: hidden from `list_sessions`, tabs unloaded, still searchable
+    pub fn archive_session(&self, session_id: &str) -> Result<axiom_session::Session> {
+        Ok(self.session_manager.archive_session(session_id)?)
+    }
+
+    pub fn unarchive_session(&self, session_id: &str) -> Result<axiom_session::Session> {
+        Ok(self.session_manager.unarchive_session(session_id)?)
+    }
+
+    pub fn search_archived_sessions(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<axiom_session::ArchivedTab>> {
+        Ok(self.session_manager.search_archive(query, limit)?)
+    }
+
+    pub fn export_session(
+        &self,
+        session_id: &str,
+        format: axiom_session::SessionFormat,
+    ) -> Result<String> {
+        Ok(self.session_manager.export_session(session_id, format)?)
+    }
+
+    pub fn import_session(
+        &self,
+        format: axiom_session::SessionFormat,
+        input: &str,
+        name: Option<String>,
+    ) -> Result<axiom_session::SessionImport> {
+        Ok(self.session_manager.import_session(format, input, name)?)
+    }
+
     // === Tab operations ===
 
     pub fn create_tab(&self, url: String) -> Result<axiom_tabs::Tab> {
@@ -184,6 +497,7 @@ impl Browser {
 
                 let mut stack = self.recently_closed_tabs.write();
                 stack.push(ClosedTab {
+                    id: uuid::Uuid::new_v4().to_string(),
                     session_id: tab.session_id.clone(),
                     url: tab.url.clone(),
                     title: tab.title.clone(),
@@ -328,6 +642,7 @@ impl Browser {
         {
             let mut stack = self.recently_closed_tabs.write();
             stack.push(ClosedTab {
+                id: uuid::Uuid::new_v4().to_string(),
                 session_id: tab.session_id.clone(),
                 url: tab.url.clone(),
                 title: tab.title.clone(),
@@ -384,6 +699,28 @@ impl Browser {
             stack.remove(idx)
         };
 
+        self.reopen_closed_tab(session_id, closed)
+    }
+
+    /// Reopen a specific recently closed tab (see `command_matches` for ids)
+    pub fn restore_closed_tab_in_session(
+        &self,
+        session_id: &str,
+        closed_id: &str,
+    ) -> Result<axiom_tabs::Tab> {
+        let closed = {
+            let mut stack = self.recently_closed_tabs.write();
+            let idx = stack
+                .iter()
+                .position(|t| t.id == closed_id && t.session_id == session_id)
+                .ok_or_else(|| CoreError::Config("Closed tab not found".to_string()))?;
+            stack.remove(idx)
+        };
+
+        self.reopen_closed_tab(session_id, closed)
+    }
+
+    fn reopen_closed_tab(&self, session_id: &str, closed: ClosedTab) -> Result<axiom_tabs::Tab> {
         let tab = self.create_tab_in_session(session_id, closed.url)?;
         let _ = self
             .session_manager
@@ -406,14 +743,23 @@ impl Browser {
         Ok(tab)
     }
 
-    pub fn navigate_tab(&self, tab_id: &str, url: String) -> Result<axiom_tabs::Tab> {
+    pub fn navigate_tab(
+        &self,
+        tab_id: &str,
+        url: String,
+        transition: VisitTransition,
+    ) -> Result<axiom_tabs::Tab> {
         let tab = self
             .session_manager
             .tab_manager()
             .navigate_tab(tab_id, url.clone())?;
 
         // Record in history
-        let _ = self.history_manager.record_visit(&url, "");
+        if !self.is_private_session(&tab.session_id) {
+            let _ = self
+                .history_manager
+                .record_visit(&url, "", transition, &visit_source(&tab));
+        }
 
         Ok(tab)
     }
@@ -424,16 +770,47 @@ impl Browser {
             return Ok(());
         }
 
-        let _ = self
+        let tab = self
             .session_manager
             .tab_manager()
             .navigate_tab(tab_id, url.to_string())?;
+        if self.is_private_session(&tab.session_id) {
+            return Ok(());
+        }
 
-        let _ = self.history_manager.record_visit(url, "");
+        // A URL change right after the previous visit started is a redirect
+        let transition = match self.history_manager.last_visit_in_tab(tab_id) {
+            Some(last) if Utc::now() - last.visited_at <= REDIRECT_WINDOW => {
+                VisitTransition::Redirect
+            }
+            _ => VisitTransition::Link,
+        };
+        let _ = self
+            .history_manager
+            .record_visit(url, "", transition, &visit_source(&tab));
 
         Ok(())
     }
 
+    /// Log a reload of the tab's current page
+    pub fn record_tab_reload(&self, tab_id: &str) -> Result<()> {
+        let tab = self.session_manager.tab_manager().get_tab(tab_id)?;
+        if tab.url.is_empty()
+            || tab.url == "about:blank"
+            || self.is_private_session(&tab.session_id)
+        {
+            return Ok(());
+        }
+
+        self.history_manager.record_visit(
+            &tab.url,
+            "",
+            VisitTransition::Reload,
+            &visit_source(&tab),
+        )?;
+        Ok(())
+    }
+
     pub fn restore_last_closed_tab(&self) -> Result<axiom_tabs::Tab> {
         let session = self.session_manager.active_session()?;
 
@@ -472,7 +849,10 @@ impl Browser {
             .tab_manager()
             .set_tab_title(tab_id, title.clone())?;
 
-        if !tab.url.is_empty() && tab.url != "about:blank" {
+        if !tab.url.is_empty()
+            && tab.url != "about:blank"
+            && !self.is_private_session(&tab.session_id)
+        {
             let _ = self.history_manager.update_title(&tab.url, &title);
         }
 
@@ -489,12 +869,39 @@ impl Browser {
         self.input_resolver.read().resolve(input)
     }
 
-    pub fn search_history(&self, query: &str) -> Result<Vec<axiom_navigation::HistoryEntry>> {
-        Ok(self.history_manager.search(query, 20)?)
+    pub fn search_history(
+        &self,
+        query: &str,
+        sort: HistorySort,
+    ) -> Result<Vec<axiom_navigation::HistoryEntry>> {
+        Ok(self.history_manager.search(query, 20, sort)?)
     }
 
-    pub fn recent_history(&self) -> Result<Vec<axiom_navigation::HistoryEntry>> {
-        Ok(self.history_manager.recent(20)?)
+    pub fn recent_history(&self, sort: HistorySort) -> Result<Vec<axiom_navigation::HistoryEntry>> {
+        Ok(self.history_manager.recent(20, sort)?)
+    }
+
+    /// Individual visits in a time range, newest first
+    pub fn history_visits(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<axiom_navigation::Visit>> {
+        Ok(self.history_manager.visits_in_range(start, end, limit)?)
+    }
+
+    /// Visits grouped into journeys (bursts of related browsing), newest first
+    pub fn history_journeys(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        query: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<axiom_navigation::Journey>> {
+        Ok(self
+            .history_manager
+            .journeys(start, end, query, limit, &self.public_suffix_list())?)
     }
 
     pub fn clear_history_range(
@@ -511,14 +918,133 @@ impl Browser {
         self.input_resolver.read().search_template().to_string()
     }
 
-    pub fn set_search_engine(&self, template: String) -> Result<()> {
+    pub fn list_search_engines(&self) -> Result<Vec<SearchEngine>> {
+        Ok(self.search_engines.list()?)
+    }
+
+    pub fn default_search_engine(&self) -> Result<SearchEngine> {
+        Ok(self
+            .search_engines
+            .default_engine(&self.config.search_engine)?)
+    }
+
+    pub fn set_default_search_engine(&self, engine_id: &str) -> Result<SearchEngine> {
+        let engine = self.search_engines.set_default(engine_id)?;
+        self.input_resolver
+            .write()
+            .set_search_engine(engine.template.clone());
+        Ok(engine)
+    }
+
+    pub fn add_search_engine(&self, engine: NewSearchEngine) -> Result<SearchEngine> {
+        Ok(self.search_engines.add(engine)?)
+    }
+
+    /// Whether an engine with this results template is already in the list
+    pub fn has_search_engine(&self, template: &str) -> Result<bool> {
+        Ok(self.search_engines.find_by_template(template)?.is_some())
+    }
+
+    pub fn remove_search_engine(&self, engine_id: &str) -> Result<()> {
+        self.search_engines.remove(engine_id)?;
+        // Removing the default engine falls back to the built-in default
+        let engine = self.default_search_engine()?;
         self.input_resolver
             .write()
-            .set_search_engine(template.clone());
-        self.db.set_setting("search_engine", &template)?;
+            .set_search_engine(engine.template);
         Ok(())
     }
 
+    pub fn list_search_keywords(&self) -> Vec<SearchKeyword> {
+        self.input_resolver.read().keywords().to_vec()
+    }
+
+    pub fn add_search_keyword(
+        &self,
+        keyword: &str,
+        name: &str,
+        template: &str,
+    ) -> Result<SearchKeyword> {
+        let added = self.keyword_manager.add(keyword, name, template)?;
+        self.reload_search_keywords()?;
+        Ok(added)
+    }
+
+    pub fn update_search_keyword(
+        &self,
+        keyword: &str,
+        name: &str,
+        template: &str,
+    ) -> Result<SearchKeyword> {
+        let updated = self.keyword_manager.update(keyword, name, template)?;
+        self.reload_search_keywords()?;
+        Ok(updated)
+    }
+
+    pub fn remove_search_keyword(&self, keyword: &str) -> Result<()> {
+        self.keyword_manager.remove(keyword)?;
+        self.reload_search_keywords()
+    }
+
+    fn reload_search_keywords(&self) -> Result<()> {
+        let keywords = self.keyword_manager.list()?;
+        self.input_resolver.write().set_keywords(keywords);
+        Ok(())
+    }
+
+    // === HTTPS-Only mode ===
+
+    pub fn get_https_only_enabled(&self) -> Result<bool> {
+        Ok(self.https_only.is_enabled()?)
+    }
+
+    pub fn set_https_only_enabled(&self, enabled: bool) -> Result<()> {
+        Ok(self.https_only.set_enabled(enabled)?)
+    }
+
+    /// The `https://` URL to load instead, when HTTPS-Only applies to `url`
+    pub fn https_only_upgrade(&self, url: &str) -> Result<Option<String>> {
+        Ok(self.https_only.upgrade(url)?)
+    }
+
+    pub fn list_https_only_exceptions(&self) -> Result<Vec<HttpsOnlyException>> {
+        Ok(self.https_only.list_exceptions()?)
+    }
+
+    /// Like [`Self::https_only_upgrade`], for a navigation in a tab
+    pub fn https_only_upgrade_for_tab(&self, tab_id: &str, url: &str) -> Result<Option<String>> {
+        match self.session_id_for_tab(tab_id) {
+            Some(session_id) => Ok(self.https_only.upgrade_in_session(url, &session_id)?),
+            None => self.https_only_upgrade(url),
+        }
+    }
+
+    /// Allow a host to load over HTTP; returns the normalized host
+    pub fn add_https_only_exception(&self, host_or_url: &str) -> Result<String> {
+        Ok(self.https_only.add_exception(host_or_url)?)
+    }
+
+    /// Allow a host to load over HTTP from a tab; private sessions keep it unsaved
+    pub fn add_https_only_exception_for_tab(
+        &self,
+        tab_id: &str,
+        host_or_url: &str,
+    ) -> Result<String> {
+        match self
+            .session_id_for_tab(tab_id)
+            .filter(|session_id| self.is_private_session(session_id))
+        {
+            Some(session_id) => Ok(self
+                .https_only
+                .add_session_exception(&session_id, host_or_url)?),
+            None => self.add_https_only_exception(host_or_url),
+        }
+    }
+
+    pub fn remove_https_only_exception(&self, host: &str) -> Result<()> {
+        Ok(self.https_only.remove_exception(host)?)
+    }
+
     pub fn get_theme(&self) -> Result<Option<String>> {
         Ok(self.db.get_setting("theme")?)
     }
@@ -586,6 +1112,14 @@ impl Browser {
         Ok(())
     }
 
+    pub fn get_restore_eager_tab_count(&self) -> Result<usize> {
+        Ok(self.session_manager.eager_restore_count()?)
+    }
+
+    pub fn set_restore_eager_tab_count(&self, count: usize) -> Result<()> {
+        Ok(self.session_manager.set_eager_restore_count(count)?)
+    }
+
     pub fn get_password_save_prompt_enabled(&self) -> Result<bool> {
         Ok(self
             .db
@@ -602,118 +1136,884 @@ impl Browser {
         Ok(())
     }
 
-    pub fn get_bookmarks(&self) -> Result<Vec<Bookmark>> {
-        match self.db.get_setting("bookmarks")? {
-            Some(value) => Ok(serde_json::from_str(&value).unwrap_or_default()),
-            None => Ok(Vec::new()),
+    /// Global download folder, or the configured default when none was chosen
+    pub fn get_download_dir(&self) -> Result<PathBuf> {
+        Ok(self
+            .db
+            .get_setting("download_dir")?
+            .filter(|v| !v.trim().is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.config.download_dir.clone()))
+    }
+
+    /// Choose the global download folder; `None` or empty goes back to the default
+    pub fn set_download_dir(&self, dir: Option<&str>) -> Result<()> {
+        let dir = dir.map(str::trim).unwrap_or("");
+        if !dir.is_empty() && !Path::new(dir).is_absolute() {
+            return Err(CoreError::Config(
+                "Download folder must be an absolute path".to_string(),
+            ));
         }
+        self.db.set_setting("download_dir", dir)?;
+        Ok(())
+    }
+
+    // === URL routing ===
+
+    pub fn list_routing_rules(&self) -> Vec<RoutingRule> {
+        self.url_router.list()
     }
 
-    pub fn add_bookmark(
+    pub fn add_routing_rule(
         &self,
-        title: String,
-        url: String,
-        folder: Option<String>,
-    ) -> Result<Vec<Bookmark>> {
-        if url.trim().is_empty() {
-            return Err(CoreError::Config(
-                "Bookmark URL cannot be empty".to_string(),
+        pattern: RoutePattern,
+        target_session_id: String,
+    ) -> Result<RoutingRule> {
+        self.check_routing_target(&target_session_id)?;
+        self.url_router.add(pattern, target_session_id)
+    }
+
+    pub fn update_routing_rule(
+        &self,
+        rule_id: &str,
+        pattern: RoutePattern,
+        target_session_id: String,
+        enabled: bool,
+    ) -> Result<RoutingRule> {
+        self.check_routing_target(&target_session_id)?;
+        self.url_router
+            .update(rule_id, pattern, target_session_id, enabled)
+    }
+
+    pub fn remove_routing_rule(&self, rule_id: &str) -> Result<()> {
+        self.url_router.remove(rule_id)
+    }
+
+    /// Rules may only send URLs to live, non-private sessions
+    fn check_routing_target(&self, session_id: &str) -> Result<()> {
+        let session = self.session_manager.get_session(session_id)?;
+        if session.private {
+            return Err(CoreError::InvalidRoutingRule(
+                "URLs cannot be routed into a private session".to_string(),
+            ));
+        }
+        if session.is_archived() {
+            return Err(CoreError::InvalidRoutingRule(
+                "URLs cannot be routed into an archived session".to_string(),
             ));
         }
+        Ok(())
+    }
 
-        let folder = crate::bookmarks::normalize_folder(folder);
-        let mut bookmarks = self.get_bookmarks()?;
-        if let Some(existing) = bookmarks.iter_mut().find(|b| b.url == url) {
-            existing.title = title;
-            if folder.is_some() {
-                existing.folder = folder;
+    /// Session a URL should open in, when a rule routes it away from `current_session_id`
+    pub fn route_url(&self, current_session_id: &str, url: &str) -> Option<String> {
+        // Private browsing never hands its URLs to another session
+        if self.is_private_session(current_session_id) {
+            return None;
+        }
+        let rule = self.url_router.route(url)?;
+        if rule.target_session_id == current_session_id {
+            return None;
+        }
+        // Rules for deleted sessions are removed by cascade; ignore stale cache entries
+        self.session_manager
+            .get_session(&rule.target_session_id)
+            .ok()
+            .filter(|session| !session.is_archived())
+            .map(|session| session.id)
+    }
+
+    // === Per-session settings (session override, then global, then default) ===
+
+    pub fn get_session_settings(&self, session_id: &str) -> Result<SessionSettings> {
+        SessionSettings::load(&self.db, session_id)
+    }
+
+    pub fn set_session_settings(
+        &self,
+        session_id: &str,
+        settings: SessionSettings,
+    ) -> Result<SessionSettings> {
+        self.session_manager.get_session(session_id)?;
+        if let Some(engine_id) = settings.search_engine.as_deref().map(str::trim) {
+            if !engine_id.is_empty() && self.search_engines.get(engine_id)?.is_none() {
+                return Err(axiom_navigation::NavigationError::SearchEngineNotFound(
+                    engine_id.to_string(),
+                )
+                .into());
+            }
+        }
+        if let Some(dir) = settings.download_dir.as_deref().map(str::trim) {
+            if !dir.is_empty() && !Path::new(dir).is_absolute() {
+                return Err(CoreError::Config(
+                    "Download folder must be an absolute path".to_string(),
+                ));
             }
-        } else {
-            bookmarks.push(Bookmark { title, url, folder });
         }
+        if let Some(theme) = settings.theme.as_deref().map(str::trim) {
+            if !theme.is_empty() && theme != "light" && theme != "dark" {
+                return Err(CoreError::Config(format!("Unknown theme: {theme}")));
+            }
+        }
+        settings.save(&self.db, session_id)?;
+        SessionSettings::load(&self.db, session_id)
+    }
 
-        let serialized = serde_json::to_string(&bookmarks)?;
-        self.db.set_setting("bookmarks", &serialized)?;
+    /// True when the tab belongs to a private session
+    pub fn is_private_tab(&self, tab_id: &str) -> bool {
+        self.session_id_for_tab(tab_id)
+            .is_some_and(|session_id| self.is_private_session(&session_id))
+    }
 
-        Ok(bookmarks)
+    pub fn session_id_for_tab(&self, tab_id: &str) -> Option<String> {
+        self.session_manager
+            .tab_manager()
+            .get_tab(tab_id)
+            .ok()
+            .map(|tab| tab.session_id)
     }
 
-    pub fn remove_bookmark(&self, url: &str) -> Result<Vec<Bookmark>> {
-        let mut bookmarks = self.get_bookmarks()?;
-        bookmarks.retain(|bookmark| bookmark.url != url);
+    /// The session's chosen search engine, or the default engine
+    pub fn effective_search_engine(&self, session_id: &str) -> Result<SearchEngine> {
+        let Some(choice) = self.get_session_settings(session_id)?.search_engine else {
+            return self.default_search_engine();
+        };
 
-        let serialized = serde_json::to_string(&bookmarks)?;
-        self.db.set_setting("bookmarks", &serialized)?;
+        if let Some(engine) = self.search_engines.get(&choice)? {
+            return Ok(engine);
+        }
+        // Overrides saved before engines had ids hold a raw template
+        if choice.contains("%s") {
+            if let Some(engine) = self.search_engines.find_by_template(&choice)? {
+                return Ok(engine);
+            }
+            return Ok(SearchEngine {
+                id: String::new(),
+                name: "Custom".to_string(),
+                template: choice,
+                suggest_url: None,
+                icon_url: None,
+                builtin: false,
+            });
+        }
+        self.default_search_engine()
+    }
 
-        Ok(bookmarks)
+    pub fn resolve_input_in_session(
+        &self,
+        session_id: &str,
+        input: &str,
+    ) -> Result<axiom_navigation::InputResolution> {
+        Ok(self.session_input_resolver(session_id)?.resolve(input))
     }
 
-    pub fn update_bookmark(
+    /// The shared resolver (with its keywords) using the session's search engine
+    fn session_input_resolver(&self, session_id: &str) -> Result<InputResolver> {
+        let mut resolver = self.input_resolver.read().clone();
+        if self
+            .get_session_settings(session_id)?
+            .search_engine
+            .is_some()
+        {
+            resolver.set_search_engine(self.effective_search_engine(session_id)?.template);
+        }
+        Ok(resolver)
+    }
+
+    /// Ranked omnibox suggestions for the session's address bar input
+    pub fn omnibox_suggestions(
         &self,
-        old_url: &str,
-        title: String,
-        url: String,
-        folder: Option<String>,
-    ) -> Result<Vec<Bookmark>> {
-        if url.trim().is_empty() {
-            return Err(CoreError::Config(
-                "Bookmark URL cannot be empty".to_string(),
-            ));
+        session_id: &str,
+        input: &str,
+        limit: usize,
+        cancel: &CancellationToken,
+    ) -> Result<SuggestionResult> {
+        let resolver = self.session_input_resolver(session_id)?;
+        let tabs = self
+            .get_ordered_tabs_in_session(session_id)?
+            .into_iter()
+            .map(|tab| TabCandidate {
+                tab_id: tab.id,
+                title: tab.title,
+                url: tab.url,
+            })
+            .collect();
+        // Runs on every keystroke: only the columns the provider matches on
+        let bookmarks = self
+            .bookmark_manager
+            .titles_and_urls()?
+            .into_iter()
+            .map(|(title, url)| BookmarkCandidate { title, url })
+            .collect();
+
+        let pipeline = SuggestionPipeline::new(limit)
+            .with_provider(CommandProvider)
+            .with_provider(AnswerProvider::new(resolver.clone()))
+            .with_provider(TypedUrlProvider::new(resolver.clone()))
+            .with_provider(OpenTabsProvider::new(tabs))
+            .with_provider(BookmarksProvider::new(bookmarks))
+            .with_provider(HistoryProvider::new(self.history_manager.clone()))
+            .with_provider(SearchProvider::new(resolver));
+
+        Ok(pipeline.run(input, cancel)?)
+    }
+
+    /// Results for an address bar `@` command, best first
+    ///
+    /// `windows` lists the open windows, which only the shell knows about.
+    pub fn command_matches(
+        &self,
+        session_id: &str,
+        command: &Command,
+        windows: &[OpenWindow],
+        limit: usize,
+    ) -> Result<Vec<CommandMatch>> {
+        let kind = command.command_type;
+        let terms = query_terms(command.query.as_deref());
+        let query = command.query.as_deref().unwrap_or_default();
+
+        let mut matches: Vec<CommandMatch> = match kind {
+            CommandType::Tabs => self
+                .get_ordered_tabs_in_session(session_id)?
+                .into_iter()
+                .filter(|tab| matches_terms(&terms, &[&tab.title, &tab.url]))
+                .map(|tab| CommandMatch::new(kind, tab.title, tab.url, CommandTarget::Tab(tab.id)))
+                .collect(),
+            CommandType::History => {
+                let entries = if query.is_empty() {
+                    self.recent_history(HistorySort::Frecency)?
+                } else {
+                    self.search_history(query, HistorySort::Frecency)?
+                };
+                entries
+                    .into_iter()
+                    .map(|entry| {
+                        CommandMatch::new(
+                            kind,
+                            entry.title,
+                            entry.url.clone(),
+                            CommandTarget::Url(entry.url),
+                        )
+                    })
+                    .collect()
+            }
+            CommandType::Sessions => self
+                .list_sessions()
+                .into_iter()
+                .filter(|session| matches_terms(&terms, &[&session.name]))
+                .map(|session| {
+                    let detail = format!("{} tabs", session.tab_order.len());
+                    CommandMatch::new(
+                        kind,
+                        session.name,
+                        detail,
+                        CommandTarget::Session(session.id),
+                    )
+                })
+                .collect(),
+            CommandType::Bookmarks => self
+                .bookmark_manager
+                .bookmarks()?
+                .into_iter()
+                .filter(|bookmark| {
+                    let folder = bookmark.folder.as_deref().unwrap_or_default();
+                    let tags = bookmark.tags.join(" ");
+                    matches_terms(
+                        &terms,
+                        &[
+                            &bookmark.title,
+                            &bookmark.url,
+                            folder,
+                            &tags,
+                            &bookmark.description,
+                        ],
+                    )
+                })
+                .map(|bookmark| {
+                    let detail = match bookmark.folder.as_deref().map(folder_path_display) {
+                        Some(path) if !path.is_empty() => format!("{path} · {}", bookmark.url),
+                        _ => bookmark.url.clone(),
+                    };
+                    CommandMatch::new(
+                        kind,
+                        bookmark.title,
+                        detail,
+                        CommandTarget::Url(bookmark.url),
+                    )
+                })
+                .collect(),
+            CommandType::Downloads => {
+                let mut downloads = self.download_manager.list_downloads();
+                downloads.sort_by_key(|d| std::cmp::Reverse(d.created_at));
+                downloads
+                    .into_iter()
+                    .filter(|download| {
+                        matches_terms(&terms, &[&download.file_name, download.state.as_str()])
+                    })
+                    .map(|download| {
+                        let detail = format!("{} · {}", download.state.as_str(), download.url);
+                        CommandMatch::new(
+                            kind,
+                            download.file_name,
+                            detail,
+                            CommandTarget::Download(download.id),
+                        )
+                    })
+                    .collect()
+            }
+            CommandType::Closed => self
+                .recently_closed_tabs
+                .read()
+                .iter()
+                .rev()
+                .filter(|closed| closed.session_id == session_id)
+                .filter(|closed| matches_terms(&terms, &[&closed.title, &closed.url]))
+                .map(|closed| {
+                    let title = if closed.title.trim().is_empty() {
+                        closed.url.clone()
+                    } else {
+                        closed.title.clone()
+                    };
+                    CommandMatch::new(
+                        kind,
+                        title,
+                        closed.url.clone(),
+                        CommandTarget::ClosedTab(closed.id.clone()),
+                    )
+                })
+                .collect(),
+            CommandType::Settings => {
+                let mut matches = Vec::new();
+                for (key, label, keywords) in SETTINGS {
+                    if matches_terms(&terms, &[key, label, keywords]) {
+                        let value = self.setting_display_value(key)?;
+                        matches.push(CommandMatch::new(
+                            kind,
+                            label,
+                            value,
+                            CommandTarget::Setting(key.to_string()),
+                        ));
+                    }
+                }
+                matches
+            }
+            CommandType::Windows => {
+                let mut matches = Vec::new();
+                for window in windows {
+                    let Ok(session) = self.session_manager.get_session(&window.session_id) else {
+                        continue;
+                    };
+                    let title = self
+                        .get_active_tab_in_session(&window.session_id)
+                        .ok()
+                        .flatten()
+                        .map(|tab| tab.title)
+                        .filter(|title| !title.trim().is_empty())
+                        .unwrap_or_else(|| session.name.clone());
+                    if matches_terms(&terms, &[&title, &session.name]) {
+                        let detail = format!("{} · {} tabs", session.name, session.tab_order.len());
+                        matches.push(CommandMatch::new(
+                            kind,
+                            title,
+                            detail,
+                            CommandTarget::Window(window.label.clone()),
+                        ));
+                    }
+                }
+                matches
+            }
+        };
+
+        matches.truncate(limit);
+        Ok(matches)
+    }
+
+    /// Current value of a `SETTINGS` key, for display
+    fn setting_display_value(&self, key: &str) -> Result<String> {
+        let on_off = |enabled: bool| if enabled { "On" } else { "Off" }.to_string();
+        Ok(match key {
+            "search_engine" => self.default_search_engine()?.name,
+            "theme" => self.get_theme()?.unwrap_or_else(|| "System".to_string()),
+            "bookmarks_bar" => on_off(self.get_bookmarks_bar_visible()?),
+            "autofill" => on_off(self.get_autofill_enabled()?),
+            "autofill_name" => self.get_autofill_name()?.unwrap_or_default(),
+            "autofill_email" => self.get_autofill_email()?.unwrap_or_default(),
+            "password_save_prompt" => on_off(self.get_password_save_prompt_enabled()?),
+            "https_only" => on_off(self.get_https_only_enabled()?),
+            "download_dir" => self.get_download_dir()?.display().to_string(),
+            "restore_eager_tabs" => match self.get_restore_eager_tab_count()? {
+                0 => "Active tab only".to_string(),
+                n => format!("Active tab + {n} recent"),
+            },
+            _ => String::new(),
+        })
+    }
+
+    pub fn effective_theme(&self, session_id: &str) -> Result<Option<String>> {
+        match self.get_session_settings(session_id)?.theme {
+            Some(theme) => Ok(Some(theme)),
+            None => self.get_theme(),
         }
+    }
 
-        let folder = crate::bookmarks::normalize_folder(folder);
-        let mut bookmarks = self.get_bookmarks()?;
+    pub fn tracking_protection_enabled(&self, session_id: &str) -> Result<bool> {
+        Ok(self
+            .get_session_settings(session_id)?
+            .tracking_protection
+            .unwrap_or_else(|| self.tracking_protection.read().is_enabled()))
+    }
 
-        let idx = bookmarks
-            .iter()
-            .position(|b| b.url == old_url)
-            .ok_or_else(|| CoreError::Config("Bookmark not found".to_string()))?;
+    pub fn should_block_url_in_session(&self, session_id: &str, url: &str) -> Result<bool> {
+        if !self.tracking_protection_enabled(session_id)? {
+            return Ok(false);
+        }
+        Ok(self.tracking_protection.read().matches_blocklist(url))
+    }
 
-        if old_url != url && bookmarks.iter().any(|b| b.url == url) {
-            return Err(CoreError::Config("Bookmark URL already exists".to_string()));
+    pub fn clean_url_in_session(&self, session_id: &str, url: &str) -> Result<String> {
+        if !self.tracking_protection_enabled(session_id)? {
+            return Ok(url.to_string());
         }
+        Ok(self.tracking_protection.read().strip_tracking_params(url))
+    }
 
-        bookmarks[idx].title = title;
-        bookmarks[idx].url = url;
-        bookmarks[idx].folder = folder;
+    pub fn effective_download_dir(&self, session_id: &str) -> Result<PathBuf> {
+        // Relative overrides predate validation; they would resolve against the cwd
+        match self.get_session_settings(session_id)?.download_dir {
+            Some(dir) if Path::new(&dir).is_absolute() => Ok(PathBuf::from(dir)),
+            _ => self.get_download_dir(),
+        }
+    }
 
-        let serialized = serde_json::to_string(&bookmarks)?;
-        self.db.set_setting("bookmarks", &serialized)?;
+    /// Autofill (name, email), each overridable per session
+    pub fn effective_autofill_profile(
+        &self,
+        session_id: &str,
+    ) -> Result<(Option<String>, Option<String>)> {
+        let overrides = self.get_session_settings(session_id)?;
+        let name = match overrides.autofill_name {
+            Some(name) => Some(name),
+            None => self.get_autofill_name()?,
+        };
+        let email = match overrides.autofill_email {
+            Some(email) => Some(email),
+            None => self.get_autofill_email()?,
+        };
+        Ok((name, email))
+    }
 
-        Ok(bookmarks)
+    /// Bookmarks, folders and separators, depth-first in display order
+    pub fn get_bookmarks(&self) -> Result<Vec<Bookmark>> {
+        self.bookmark_manager.list()
     }
 
-    pub fn get_bookmark_folders(&self) -> Result<Vec<String>> {
-        Ok(crate::bookmarks::folders_from_bookmarks(
-            &self.get_bookmarks()?,
-        ))
+    pub fn add_bookmark(&self, title: &str, url: &str, parent_id: Option<i64>) -> Result<Bookmark> {
+        self.bookmark_manager.add_bookmark(title, url, parent_id)
+    }
+
+    pub fn add_bookmark_folder(&self, title: &str, parent_id: Option<i64>) -> Result<Bookmark> {
+        self.bookmark_manager.add_folder(title, parent_id)
+    }
+
+    pub fn add_bookmark_separator(&self, parent_id: Option<i64>) -> Result<Bookmark> {
+        self.bookmark_manager.add_separator(parent_id)
+    }
+
+    /// Rename a bookmark or folder, or change a bookmark's URL, description and tags
+    pub fn update_bookmark(
+        &self,
+        id: i64,
+        title: &str,
+        url: &str,
+        description: &str,
+        tags: Option<&[String]>,
+    ) -> Result<Bookmark> {
+        self.bookmark_manager
+            .update(id, title, url, description, tags)
+    }
+
+    pub fn set_bookmark_tags(&self, id: i64, tags: &[String]) -> Result<Bookmark> {
+        self.bookmark_manager.set_tags(id, tags)
+    }
+
+    /// Move a bookmark, folder or separator; `position` defaults to the end of the folder
+    pub fn move_bookmark(
+        &self,
+        id: i64,
+        parent_id: Option<i64>,
+        position: Option<usize>,
+    ) -> Result<Bookmark> {
+        self.bookmark_manager.move_to(id, parent_id, position)
+    }
+
+    /// Remove a bookmark, separator or folder (with its contents); returns the rows removed
+    pub fn remove_bookmark(&self, id: i64) -> Result<usize> {
+        self.bookmark_manager.remove(id)
+    }
+
+    /// Regular folders and smart folders (evaluated against the current bookmarks)
+    pub fn get_bookmark_folders(&self) -> Result<BookmarkFolders> {
+        Ok(BookmarkFolders {
+            folders: self.bookmark_manager.folders()?,
+            smart_folders: self.bookmark_manager.smart_folders()?,
+        })
+    }
+
+    pub fn get_bookmark_tags(&self) -> Result<Vec<BookmarkTag>> {
+        self.bookmark_manager.tags()
+    }
+
+    /// Rename a tag (merging it into another tag of that name); returns its bookmark count
+    pub fn rename_bookmark_tag(&self, from: &str, to: &str) -> Result<usize> {
+        self.bookmark_manager.rename_tag(from, to)
+    }
+
+    /// Fold several tags into one; returns how many bookmarks carry it
+    pub fn merge_bookmark_tags(&self, sources: &[String], into: &str) -> Result<usize> {
+        self.bookmark_manager.merge_tags(sources, into)
+    }
+
+    /// Bookmarks a link check should probe (all, or those in `ids`), hosts interleaved
+    pub fn bookmarks_to_check(&self, ids: Option<&[i64]>) -> Result<Vec<Bookmark>> {
+        let bookmarks = self
+            .bookmark_manager
+            .bookmarks()?
+            .into_iter()
+            .filter(|b| ids.is_none_or(|ids| ids.contains(&b.id)))
+            .collect();
+        Ok(crate::link_check::check_order(bookmarks))
+    }
+
+    /// Classify and store what probing a bookmark's URL found
+    ///
+    /// DNS failures are held in `run` until [`Browser::finish_bookmark_link_check`].
+    pub fn record_bookmark_link_check(
+        &self,
+        run: &mut LinkCheckRun,
+        id: i64,
+        probe: &LinkProbe,
+    ) -> Result<LinkStatus> {
+        let url = self.bookmark_manager.get(id)?.url;
+        let status = LinkStatus::classify(&url, probe);
+        if run.note(id, &url, &status) {
+            self.bookmark_manager.record_link_check(id, &status)?;
+        }
+        Ok(status)
+    }
+
+    /// Store the DNS failures of a finished run unless most hosts failed to
+    /// resolve; returns whether the run looked offline
+    pub fn finish_bookmark_link_check(&self, run: &LinkCheckRun) -> Result<bool> {
+        if run.is_offline() {
+            tracing::info!(
+                unresolved = run.unresolved().len(),
+                "Link check looked offline; DNS failures not counted"
+            );
+            return Ok(true);
+        }
+        for id in run.unresolved() {
+            self.bookmark_manager
+                .record_link_check(*id, &LinkStatus::Dns)?;
+        }
+        Ok(false)
+    }
+
+    /// Move permanently redirected bookmarks to their new URLs; returns how many changed
+    pub fn apply_bookmark_redirects(&self, ids: Option<&[i64]>) -> Result<usize> {
+        self.bookmark_manager.apply_redirects(ids)
+    }
+
+    /// Remove bookmarks the last link check found dead; returns how many went
+    pub fn remove_dead_bookmarks(&self, ids: Option<&[i64]>) -> Result<usize> {
+        self.bookmark_manager.remove_dead(ids)
+    }
+
+    pub fn add_smart_folder(&self, title: &str, query: SmartFolderQuery) -> Result<SmartFolder> {
+        self.bookmark_manager.add_smart_folder(title, query)
+    }
+
+    pub fn update_smart_folder(
+        &self,
+        id: i64,
+        title: &str,
+        query: SmartFolderQuery,
+    ) -> Result<SmartFolder> {
+        self.bookmark_manager.update_smart_folder(id, title, query)
     }
 
+    pub fn remove_smart_folder(&self, id: i64) -> Result<()> {
+        self.bookmark_manager.remove_smart_folder(id)
+    }
+
+    /// All bookmarks as a Netscape bookmark file, with dates, icons, tags and keywords
     pub fn export_bookmarks_html(&self) -> Result<String> {
-        Ok(crate::bookmarks::export_bookmarks_html(
-            &self.get_bookmarks()?,
+        Ok(crate::netscape::export_bookmarks_html(
+            &self.bookmark_manager.list()?,
         ))
     }
 
+    /// Add the bookmarks of a Netscape bookmark file
+    ///
+    /// Folders merge with existing ones of the same name and a URL already
+    /// saved in the same folder is skipped, so importing the same file twice
+    /// changes nothing. Keyword searches (a `SHORTCUTURL` on a `%s` URL)
+    /// become search keywords unless the keyword is taken.
     pub fn import_bookmarks_html(&self, html: &str) -> Result<Vec<Bookmark>> {
-        let mut bookmarks = self.get_bookmarks()?;
-        let imported = crate::bookmarks::import_bookmarks_html(html);
-
-        for bookmark in imported {
-            if let Some(existing) = bookmarks.iter_mut().find(|b| b.url == bookmark.url) {
-                existing.title = bookmark.title;
-                if bookmark.folder.is_some() {
-                    existing.folder = bookmark.folder;
+        let nodes = crate::netscape::parse_bookmarks_html(html);
+        let added = self.bookmark_manager.import_tree(&nodes)?;
+        let keywords = self.register_bookmark_keywords(&nodes);
+        if keywords > 0 {
+            self.reload_search_keywords()?;
+        }
+        tracing::info!(added, keywords, "Imported bookmarks file");
+        self.bookmark_manager.list()
+    }
+
+    /// Add the keyword searches among `nodes` as search keywords; returns how many were added
+    ///
+    /// Keywords of plain bookmarks are only kept on the bookmark for export.
+    fn register_bookmark_keywords(&self, nodes: &[crate::BookmarkNode]) -> usize {
+        let mut added = 0;
+        for node in nodes {
+            added += self.register_bookmark_keywords(&node.children);
+            let Some(keyword) = node.shortcut.as_deref() else {
+                continue;
+            };
+            if !node.url.contains("%s") && !node.url.contains("%S") {
+                continue;
+            }
+            match self.keyword_manager.add(keyword, &node.title, &node.url) {
+                Ok(_) => added += 1,
+                Err(e) => {
+                    tracing::debug!(keyword, error = %e, "Bookmark keyword not registered")
                 }
+            }
+        }
+        added
+    }
+
+    /// Report what importing another browser's profile file would change, without changing it
+    pub fn preview_profile_import(
+        &self,
+        source: ImportSource,
+        path: &Path,
+    ) -> Result<ImportReport> {
+        self.profile_import(source, path, true)
+    }
+
+    /// Merge bookmarks and history from another browser's profile file
+    ///
+    /// Existing bookmarks are kept when the import disagrees with them (see
+    /// `ImportReport::bookmark_conflicts`).
+    pub fn import_profile(&self, source: ImportSource, path: &Path) -> Result<ImportReport> {
+        self.profile_import(source, path, false)
+    }
+
+    fn profile_import(
+        &self,
+        source: ImportSource,
+        path: &Path,
+        dry_run: bool,
+    ) -> Result<ImportReport> {
+        let data = crate::import::read_profile(source, path)?;
+        let mut report = ImportReport::new(source, dry_run);
+
+        let existing = self.bookmark_manager.bookmarks()?;
+        let new_bookmarks = crate::import::merge_bookmarks(&existing, data.bookmarks, &mut report);
+
+        let known = self.history_manager.known_urls()?;
+        report.history_urls_found = data.history.len();
+        report.history_urls_merged = data
+            .history
+            .iter()
+            .filter(|entry| known.contains(&entry.url))
+            .count();
+        report.history_urls_added = report.history_urls_found - report.history_urls_merged;
+        report.visits_found = data.history.iter().map(|entry| entry.visits.len()).sum();
+        if dry_run {
+            return Ok(report);
+        }
+
+        self.bookmark_manager.import(&new_bookmarks)?;
+        report.visits_added = self.history_manager.import(&data.history)?.added_visits;
+
+        tracing::info!(
+            source = ?source,
+            bookmarks = report.bookmarks_added,
+            conflicts = report.bookmark_conflicts.len(),
+            history_urls = report.history_urls_found,
+            visits = report.visits_added,
+            "Imported browser profile"
+        );
+
+        Ok(report)
+    }
+
+    // === Site data ===
+
+    /// Tabs in every session, archived ones included, showing `domain` or one of its subdomains
+    pub fn tabs_on_domain(&self, domain: &str) -> Result<Vec<axiom_tabs::Tab>> {
+        let Some(domain) = crate::forget::normalize_domain(domain) else {
+            return Ok(Vec::new());
+        };
+        let mut tabs = Vec::new();
+        for session in self.all_sessions() {
+            self.session_manager.load_tabs_for_session(&session.id)?;
+            tabs.extend(
+                self.get_ordered_tabs_in_session(&session.id)?
+                    .into_iter()
+                    .filter(|tab| crate::forget::url_in_domain(&tab.url, &domain)),
+            );
+            if session.is_archived() {
+                self.session_manager
+                    .tab_manager()
+                    .unload_session_tabs(&session.id);
+            }
+        }
+        Ok(tabs)
+    }
+
+    /// Open and archived sessions
+    fn all_sessions(&self) -> Vec<axiom_session::Session> {
+        let mut sessions = self.session_manager.list_sessions();
+        sessions.extend(self.session_manager.list_archived_sessions());
+        sessions
+    }
+
+    /// Remove every trace of `domain` and its subdomains
+    ///
+    /// Deletes history, permissions, download entries and storage partitions,
+    /// closes the site's tabs in open and archived sessions (removing their
+    /// snapshots and archive index entries), drops its recently closed tabs,
+    /// its tabs in the last-known-good session snapshot and its favicon URLs
+    /// on other tabs, and with `include_bookmarks` its bookmarks too. Storage
+    /// is per site, so the partitions of the whole site containing `domain`
+    /// are removed. Public suffixes such as `com` or `co.uk` are refused.
+    pub fn forget_site(&self, domain: &str, include_bookmarks: bool) -> Result<ForgetSiteSummary> {
+        let domain = crate::forget::normalize_domain(domain)
+            .ok_or_else(|| CoreError::Config(format!("Not a valid domain: {}", domain.trim())))?;
+        // `com` or `co.uk` would take every site under it along
+        let is_ip = domain
+            .trim_matches(['[', ']'])
+            .parse::<std::net::IpAddr>()
+            .is_ok();
+        if !is_ip
+            && self
+                .public_suffix_list()
+                .registrable_domain(&domain)
+                .is_none()
+        {
+            return Err(CoreError::Config(format!(
+                "Not a site that can be forgotten: {}",
+                domain
+            )));
+        }
+        let in_domain = |url: &str| crate::forget::url_in_domain(url, &domain);
+        let mut summary = ForgetSiteSummary {
+            domain: domain.clone(),
+            ..Default::default()
+        };
+
+        let archived: Vec<String> = self
+            .session_manager
+            .list_archived_sessions()
+            .into_iter()
+            .map(|session| session.id)
+            .collect();
+
+        for tab in self.tabs_on_domain(&domain)? {
+            if let Some(path) = tab.snapshot_path.as_deref() {
+                if std::fs::remove_file(path).is_ok() {
+                    summary.snapshots += 1;
+                }
+            }
+            if archived.contains(&tab.session_id) {
+                // Not offered for reopening: nothing of the site may stay behind
+                self.session_manager.tab_manager().close_tab(&tab.id)?;
+                self.session_manager
+                    .remove_tab_from_session(&tab.session_id, &tab.id)?;
             } else {
-                bookmarks.push(bookmark);
+                self.close_tab_in_session(&tab.session_id, &tab.id)?;
+            }
+            if self.active_tab_id.read().as_deref() == Some(tab.id.as_str()) {
+                *self.active_tab_id.write() = None;
             }
+            summary.closed_tab_ids.push(tab.id);
         }
 
-        let serialized = serde_json::to_string(&bookmarks)?;
-        self.db.set_setting("bookmarks", &serialized)?;
+        // Other sites' tabs showing one of its icons
+        for session in self.all_sessions() {
+            self.session_manager.load_tabs_for_session(&session.id)?;
+            let cleared = || -> Result<usize> {
+                let mut cleared = 0;
+                for tab in self.get_ordered_tabs_in_session(&session.id)? {
+                    if tab.favicon_url.as_deref().is_some_and(in_domain) {
+                        self.session_manager
+                            .tab_manager()
+                            .set_tab_favicon(&tab.id, None)?;
+                        cleared += 1;
+                    }
+                }
+                Ok(cleared)
+            }();
+            if session.is_archived() {
+                // Back to cold storage, with an index matching the edited tabs
+                self.session_manager.reindex_archived_session(&session.id)?;
+            }
+            summary.favicons += cleared?;
+        }
+        summary.last_known_good_tabs = self.session_manager.forget_in_last_known_good(in_domain)?;
+
+        {
+            let mut closed_tabs = self.recently_closed_tabs.write();
+            let before = closed_tabs.len();
+            closed_tabs.retain(|closed| !in_domain(&closed.url));
+            summary.recently_closed_tabs = before - closed_tabs.len();
+            for closed in closed_tabs.iter_mut() {
+                if closed.favicon_url.as_deref().is_some_and(in_domain) {
+                    closed.favicon_url = None;
+                    summary.favicons += 1;
+                }
+            }
+        }
 
-        Ok(bookmarks)
+        summary.history_entries = self.history_manager.delete_domain(&domain)?;
+
+        if include_bookmarks {
+            summary.bookmarks = self
+                .bookmark_manager
+                .remove_where(|bookmark| in_domain(&bookmark.url))?;
+        }
+
+        summary.permissions = self
+            .permission_manager
+            .write()
+            .clear_site_permissions_where(in_domain);
+        if summary.permissions > 0 {
+            self.save_permissions()?;
+        }
+
+        for download in self.download_manager.list_downloads() {
+            if in_domain(&download.url) {
+                self.download_manager.remove_download(&download.id)?;
+                summary.download_ids.push(download.id);
+            }
+        }
+
+        summary.partitions = crate::partition::remove_site_partitions(
+            &self.config.webview_partitions_dir,
+            &domain,
+            &self.public_suffix_list(),
+        );
+
+        tracing::info!(
+            domain = %domain,
+            history = summary.history_entries,
+            tabs = summary.closed_tab_ids.len(),
+            partitions = summary.partitions,
+            "Forgot site"
+        );
+        Ok(summary)
     }
 
     // === Privacy operations ===
@@ -737,7 +2037,10 @@ impl Browser {
         self.permission_manager
             .write()
             .set_site_permission(origin, permission_type, state);
+        self.save_permissions()
+    }
 
+    fn save_permissions(&self) -> Result<()> {
         let serialized =
             serde_json::to_string(&self.permission_manager.read().export_permissions())?;
         self.db.set_setting("permissions", &serialized)?;
@@ -780,6 +2083,29 @@ impl Browser {
         Ok(self.download_manager.create_download(url, file_name)?)
     }
 
+    /// Create a download in the session's effective download directory
+    ///
+    /// Downloads from private sessions are not recorded and drop out of the
+    /// list once they finish; the file itself is kept.
+    pub fn create_download_in_session(
+        &self,
+        session_id: &str,
+        url: String,
+        file_name: String,
+    ) -> Result<axiom_download::Download> {
+        let download_dir = self.effective_download_dir(session_id)?;
+        if self.is_private_session(session_id) {
+            return Ok(self.download_manager.create_private_download_in(
+                url,
+                file_name,
+                &download_dir,
+            )?);
+        }
+        Ok(self
+            .download_manager
+            .create_download_in(url, file_name, &download_dir)?)
+    }
+
     // === Config ===
 
     pub fn config(&self) -> &Config {
@@ -791,6 +2117,13 @@ impl Browser {
     }
 }
 
+fn visit_source(tab: &axiom_tabs::Tab) -> VisitSource {
+    VisitSource {
+        tab_id: Some(tab.id.clone()),
+        session_id: Some(tab.session_id.clone()),
+    }
+}
+
 impl Clone for Browser {
     fn clone(&self) -> Self {
         Self {
@@ -799,9 +2132,15 @@ impl Clone for Browser {
             session_manager: self.session_manager.clone(),
             history_manager: self.history_manager.clone(),
             input_resolver: Arc::clone(&self.input_resolver),
+            keyword_manager: self.keyword_manager.clone(),
+            search_engines: self.search_engines.clone(),
+            https_only: self.https_only.clone(),
             download_manager: self.download_manager.clone(),
+            bookmark_manager: self.bookmark_manager.clone(),
             permission_manager: Arc::clone(&self.permission_manager),
             tracking_protection: Arc::clone(&self.tracking_protection),
+            public_suffix_list: Arc::clone(&self.public_suffix_list),
+            url_router: self.url_router.clone(),
             active_tab_id: Arc::clone(&self.active_tab_id),
             recently_closed_tabs: Arc::clone(&self.recently_closed_tabs),
         }
@@ -818,7 +2157,6 @@ impl From<std::io::Error> for CoreError {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
 
     fn test_config() -> Config {
         Config {
@@ -827,6 +2165,7 @@ mod tests {
             search_engine: "https://duckduckgo.com/?q=%s".to_string(),
             homepage: "about:blank".to_string(),
             tracking_protection: true,
+            webview_partitions_dir: PathBuf::from("/tmp/webview-partitions"),
         }
     }
 
@@ -841,7 +2180,12 @@ mod tests {
         let input_resolver = Arc::new(RwLock::new(InputResolver::with_search_engine(
             config.search_engine.clone(),
         )));
+        let keyword_manager = KeywordManager::new(db.clone());
+        let search_engines = SearchEngineManager::new(db.clone());
+        let https_only = HttpsOnlyManager::new(db.clone());
         let download_manager = DownloadManager::new(db.clone(), config.download_dir.clone());
+        let bookmark_manager = BookmarkManager::new(db.clone());
+        let url_router = UrlRouter::new(db.clone());
 
         let browser = Browser {
             config,
@@ -849,9 +2193,15 @@ mod tests {
             session_manager,
             history_manager,
             input_resolver,
+            keyword_manager,
+            search_engines,
+            https_only,
             download_manager,
+            bookmark_manager,
             permission_manager: Arc::new(RwLock::new(PermissionManager::new())),
             tracking_protection: Arc::new(RwLock::new(TrackingProtection::new())),
+            public_suffix_list: Arc::new(RwLock::new(PublicSuffixList::embedded_shared())),
+            url_router,
             active_tab_id: Arc::new(RwLock::new(None)),
             recently_closed_tabs: Arc::new(RwLock::new(Vec::new())),
         };
@@ -868,4 +2218,557 @@ mod tests {
         let active = browser.get_active_tab().unwrap().unwrap();
         assert_eq!(active.id, tab.id);
     }
+
+    #[test]
+    fn test_isolated_session_storage() {
+        let root = std::env::temp_dir().join(format!("axiom-isolation-{}", uuid::Uuid::new_v4()));
+        let browser = Browser::new(Config {
+            webview_partitions_dir: root.clone(),
+            ..test_config()
+        })
+        .unwrap();
+        browser.initialize().unwrap();
+        let shared = browser.session_manager.active_session().unwrap();
+        let client = browser.create_session("Client".to_string()).unwrap();
+        browser.set_session_isolated(&client.id, true).unwrap();
+
+        let url = "https://github.com/login";
+        let shared_tab = browser
+            .create_tab_in_session(&shared.id, url.to_string())
+            .unwrap();
+        let client_tab = browser
+            .create_tab_in_session(&client.id, url.to_string())
+            .unwrap();
+
+        // Same site, different cookie jars
+        let shared_dir = browser.webview_partition_dir(&shared_tab.id, url);
+        let client_dir = browser.webview_partition_dir(&client_tab.id, url);
+        assert_ne!(shared_dir, client_dir);
+        assert!(client_dir.starts_with(root.join(format!("session-{}", client.id))));
+
+        std::fs::create_dir_all(&client_dir).unwrap();
+        std::fs::write(client_dir.join("Cookies"), [0u8; 512]).unwrap();
+        std::fs::create_dir_all(&shared_dir).unwrap();
+        std::fs::write(shared_dir.join("Cookies"), [0u8; 64]).unwrap();
+        assert_eq!(browser.session_storage_size(&client.id).unwrap(), 512);
+
+        browser.delete_session(&client.id).unwrap();
+        assert!(!client_dir.exists());
+        assert!(shared_dir.join("Cookies").is_file());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_open_session() {
+        let browser = Browser::new(test_config()).unwrap();
+        browser.initialize().unwrap();
+        let work = browser.create_session("Work".to_string()).unwrap();
+        for url in ["https://a.test/", "https://b.test/", "https://c.test/"] {
+            browser
+                .create_tab_in_session(&work.id, url.to_string())
+                .unwrap();
+        }
+
+        let opened = browser.open_session(&work.id).unwrap();
+        assert_eq!(opened.tab_count(), 3);
+        let tabs = browser
+            .session_manager
+            .get_ordered_tabs_for_session(&work.id)
+            .unwrap();
+        assert_eq!(tabs.iter().filter(|t| t.state.is_discarded()).count(), 2);
+
+        browser.archive_session(&work.id).unwrap();
+        assert!(browser.open_session(&work.id).is_err());
+    }
+
+    #[test]
+    fn test_public_suffix_list_is_per_browser() {
+        let dir = std::env::temp_dir().join(format!("axiom-psl-{}", uuid::Uuid::new_v4()));
+        let browser = Browser::new(Config {
+            database_path: dir.join("axiom.db"),
+            ..test_config()
+        })
+        .unwrap();
+        let other = Browser::new(test_config()).unwrap();
+
+        assert!(browser.update_public_suffix_list("not a list").is_err());
+        assert_eq!(
+            browser
+                .update_public_suffix_list("com\nexample.com\n")
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            browser.public_suffix_list().site_for_host("a.example.com"),
+            "a.example.com"
+        );
+        assert_eq!(
+            other.public_suffix_list().site_for_host("a.example.com"),
+            "example.com"
+        );
+
+        // The saved list is picked up again on the next start
+        let reopened = Browser::new(Config {
+            database_path: dir.join("axiom.db"),
+            ..test_config()
+        })
+        .unwrap();
+        drop(browser);
+        reopened.initialize().unwrap();
+        assert_eq!(reopened.public_suffix_list().len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_download_dir_setting() {
+        let browser = Browser::new(test_config()).unwrap();
+        browser.initialize().unwrap();
+        let session = browser.session_manager.active_session().unwrap();
+
+        browser.set_download_dir(Some("/srv/downloads")).unwrap();
+        assert_eq!(
+            browser.effective_download_dir(&session.id).unwrap(),
+            PathBuf::from("/srv/downloads")
+        );
+
+        let overrides = SessionSettings {
+            download_dir: Some("/srv/work".to_string()),
+            ..Default::default()
+        };
+        browser
+            .set_session_settings(&session.id, overrides)
+            .unwrap();
+        assert_eq!(
+            browser.effective_download_dir(&session.id).unwrap(),
+            PathBuf::from("/srv/work")
+        );
+
+        // Per-session overrides are held to the same rules
+        let relative = SessionSettings {
+            download_dir: Some("relative/dir".to_string()),
+            ..Default::default()
+        };
+        assert!(browser.set_session_settings(&session.id, relative).is_err());
+        let unknown_theme = SessionSettings {
+            theme: Some("solarized".to_string()),
+            ..Default::default()
+        };
+        assert!(browser
+            .set_session_settings(&session.id, unknown_theme)
+            .is_err());
+        let dark = SessionSettings {
+            download_dir: Some("/srv/work".to_string()),
+            theme: Some("dark".to_string()),
+            ..Default::default()
+        };
+        browser.set_session_settings(&session.id, dark).unwrap();
+        assert_eq!(
+            browser.effective_theme(&session.id).unwrap().as_deref(),
+            Some("dark")
+        );
+        assert_eq!(
+            browser.effective_download_dir(&session.id).unwrap(),
+            PathBuf::from("/srv/work")
+        );
+
+        assert!(browser.set_download_dir(Some("relative/dir")).is_err());
+        browser.set_download_dir(None).unwrap();
+        assert_eq!(
+            browser.get_download_dir().unwrap(),
+            PathBuf::from("/tmp/downloads")
+        );
+    }
+
+    #[test]
+    fn test_routing_targets() {
+        let browser = Browser::new(test_config()).unwrap();
+        browser.initialize().unwrap();
+        let home = browser.session_manager.active_session().unwrap();
+        let work = browser.create_session("Work".to_string()).unwrap();
+        let private = browser
+            .create_private_session("Private".to_string())
+            .unwrap();
+        let old = browser.create_session("Old".to_string()).unwrap();
+        browser.archive_session(&old.id).unwrap();
+
+        let host = || RoutePattern::Host("github.com".to_string());
+        for target in [&private.id, &old.id] {
+            assert!(matches!(
+                browser.add_routing_rule(host(), target.clone()),
+                Err(CoreError::InvalidRoutingRule(_))
+            ));
+        }
+        let rule = browser.add_routing_rule(host(), work.id.clone()).unwrap();
+        assert!(matches!(
+            browser.update_routing_rule(&rule.id, host(), private.id.clone(), true),
+            Err(CoreError::InvalidRoutingRule(_))
+        ));
+
+        let url = "https://github.com/rust-lang";
+        assert_eq!(browser.route_url(&home.id, url), Some(work.id.clone()));
+        // Private sessions keep their URLs to themselves
+        assert_eq!(browser.route_url(&private.id, url), None);
+    }
+
+    #[test]
+    fn test_command_matches() {
+        let browser = Browser::new(test_config()).unwrap();
+        browser.initialize().unwrap();
+        let session = browser.session_manager.active_session().unwrap();
+
+        let keep = browser
+            .create_tab_in_session(&session.id, "https://keep.test/".to_string())
+            .unwrap();
+        let closed = browser
+            .create_tab_in_session(&session.id, "https://closed.test/page".to_string())
+            .unwrap();
+        browser
+            .close_tab_in_session(&session.id, &closed.id)
+            .unwrap();
+        let dev = browser.add_bookmark_folder("Dev", None).unwrap();
+        let rust = browser.add_bookmark_folder("Rust", Some(dev.id)).unwrap();
+        let book = browser
+            .add_bookmark(
+                "Rust Book",
+                "https://doc.rust-lang.org/book/",
+                Some(rust.id),
+            )
+            .unwrap();
+        browser
+            .set_bookmark_tags(book.id, &["reading".to_string()])
+            .unwrap();
+
+        let run = |input: &str| {
+            let command = Command::parse(input).unwrap();
+            let windows = [OpenWindow {
+                label: "main".to_string(),
+                session_id: session.id.clone(),
+            }];
+            browser
+                .command_matches(&session.id, &command, &windows, 10)
+                .unwrap()
+        };
+
+        let bookmarks = run("@b rust");
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(
+            bookmarks[0].detail,
+            "Dev / Rust · https://doc.rust-lang.org/book/"
+        );
+        assert!(run("@b python").is_empty());
+        assert_eq!(run("@b reading").len(), 1);
+
+        assert!(matches!(&run("@t keep")[0].target, CommandTarget::Tab(id) if *id == keep.id));
+        assert_eq!(run("@settings engine")[0].detail, "DuckDuckGo");
+        assert_eq!(run("@w").len(), 1);
+
+        let closed_matches = run("@closed");
+        assert_eq!(closed_matches.len(), 1);
+        let CommandTarget::ClosedTab(closed_id) = &closed_matches[0].target else {
+            panic!("expected a closed tab target");
+        };
+        let reopened = browser
+            .restore_closed_tab_in_session(&session.id, closed_id)
+            .unwrap();
+        assert_eq!(reopened.url, "https://closed.test/page");
+        assert!(run("@closed").is_empty());
+    }
+
+    #[test]
+    fn test_private_session_leaves_no_trace() {
+        let mut config = test_config();
+        config.webview_partitions_dir =
+            std::env::temp_dir().join(format!("axiom-partitions-{}", uuid::Uuid::new_v4()));
+        let root = config.webview_partitions_dir.clone();
+        let browser = Browser::new(config).unwrap();
+        browser.initialize().unwrap();
+
+        let private = browser
+            .create_private_session("Private".to_string())
+            .unwrap();
+        let tab = browser
+            .create_tab_in_session(&private.id, "about:blank".to_string())
+            .unwrap();
+        browser
+            .navigate_tab(
+                &tab.id,
+                "https://secret.test/".to_string(),
+                VisitTransition::Typed,
+            )
+            .unwrap();
+        browser
+            .set_tab_title(&tab.id, "Secret".to_string())
+            .unwrap();
+        assert!(browser
+            .recent_history(HistorySort::Recency)
+            .unwrap()
+            .is_empty());
+
+        let download = browser
+            .create_download_in_session(
+                &private.id,
+                "https://secret.test/file.zip".to_string(),
+                "file.zip".to_string(),
+            )
+            .unwrap();
+        browser
+            .download_manager()
+            .complete_download(&download.id, None)
+            .unwrap();
+        assert!(browser.download_manager().list_downloads().is_empty());
+
+        browser.set_https_only_enabled(true).unwrap();
+        browser
+            .add_https_only_exception_for_tab(&tab.id, "http://secret.test/")
+            .unwrap();
+        assert!(browser
+            .https_only_upgrade_for_tab(&tab.id, "http://secret.test/")
+            .unwrap()
+            .is_none());
+        assert!(browser.list_https_only_exceptions().unwrap().is_empty());
+
+        let dir = browser.webview_partition_dir(&tab.id, "https://secret.test/");
+        assert_eq!(
+            dir,
+            root.join(format!("private-{}", private.id))
+                .join("secret.test")
+        );
+        std::fs::create_dir_all(&dir).unwrap();
+
+        browser.close_tab_in_session(&private.id, &tab.id).unwrap();
+        browser.delete_session(&private.id).unwrap();
+        assert!(!dir.exists());
+        assert!(browser
+            .restore_last_closed_tab_in_session(&private.id)
+            .is_err());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_forget_site() {
+        let mut config = test_config();
+        config.webview_partitions_dir =
+            std::env::temp_dir().join(format!("axiom-partitions-{}", uuid::Uuid::new_v4()));
+        let root = config.webview_partitions_dir.clone();
+        let browser = Browser::new(config).unwrap();
+        browser.initialize().unwrap();
+
+        let forgotten = browser
+            .create_tab("https://mail.example.com/inbox".to_string())
+            .unwrap();
+        let kept = browser
+            .create_tab("https://rust-lang.org/".to_string())
+            .unwrap();
+        browser
+            .session_manager()
+            .tab_manager()
+            .set_tab_favicon(
+                &kept.id,
+                Some("https://example.com/favicon.ico".to_string()),
+            )
+            .unwrap();
+        let closed = browser
+            .create_tab("https://www.example.com/old".to_string())
+            .unwrap();
+        browser.close_tab(&closed.id).unwrap();
+
+        for url in ["https://mail.example.com/inbox", "https://rust-lang.org/"] {
+            browser
+                .navigate_tab(&kept.id, url.to_string(), VisitTransition::Link)
+                .unwrap();
+        }
+        browser
+            .add_bookmark("Example", "https://example.com/", None)
+            .unwrap();
+        browser
+            .set_permission(
+                "https://mail.example.com",
+                axiom_privacy::PermissionType::Camera,
+                axiom_privacy::PermissionState::Allow,
+            )
+            .unwrap();
+        browser
+            .create_download(
+                "https://cdn.example.com/file.zip".to_string(),
+                "file.zip".to_string(),
+            )
+            .unwrap();
+        std::fs::create_dir_all(root.join("example.com")).unwrap();
+
+        let summary = browser.forget_site("Example.com", false).unwrap();
+        assert_eq!(summary.domain, "example.com");
+        assert_eq!(summary.history_entries, 1);
+        assert_eq!(summary.bookmarks, 0);
+        assert_eq!(summary.permissions, 1);
+        assert_eq!(summary.download_ids.len(), 1);
+        assert_eq!(summary.closed_tab_ids, vec![forgotten.id.clone()]);
+        assert_eq!(summary.recently_closed_tabs, 2);
+        assert_eq!(summary.favicons, 1);
+        assert_eq!(summary.partitions, 1);
+
+        let history = browser.recent_history(HistorySort::Recency).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].url, "https://rust-lang.org/");
+        assert_eq!(browser.get_bookmarks().unwrap().len(), 1);
+        assert!(browser
+            .session_manager()
+            .tab_manager()
+            .get_tab(&kept.id)
+            .unwrap()
+            .favicon_url
+            .is_none());
+        assert!(browser.restore_last_closed_tab().is_err());
+        assert!(!root.join("example.com").exists());
+
+        let summary = browser.forget_site("example.com", true).unwrap();
+        assert_eq!(summary.bookmarks, 1);
+        assert!(browser.get_bookmarks().unwrap().is_empty());
+        assert!(browser.forget_site("  ", false).is_err());
+
+        // Public suffixes are refused and touch nothing
+        for suffix in ["com", "co.uk", "org."] {
+            assert!(browser.forget_site(suffix, true).is_err());
+        }
+        assert_eq!(
+            browser.recent_history(HistorySort::Recency).unwrap().len(),
+            1
+        );
+        assert_eq!(
+            browser.forget_site("127.0.0.1", false).unwrap().domain,
+            "127.0.0.1"
+        );
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_forget_site_in_archived_sessions() {
+        let browser = Browser::new(test_config()).unwrap();
+        browser.initialize().unwrap();
+
+        let work = browser.create_session("Work".to_string()).unwrap();
+        let forgotten = browser
+            .create_tab_in_session(&work.id, "https://docs.example.com/".to_string())
+            .unwrap();
+        let kept = browser
+            .create_tab_in_session(&work.id, "https://rust-lang.org/".to_string())
+            .unwrap();
+        browser
+            .session_manager()
+            .tab_manager()
+            .set_tab_favicon(
+                &kept.id,
+                Some("https://example.com/favicon.ico".to_string()),
+            )
+            .unwrap();
+        browser.shutdown().unwrap();
+        browser.archive_session(&work.id).unwrap();
+
+        let summary = browser.forget_site("example.com", false).unwrap();
+        assert_eq!(summary.closed_tab_ids, vec![forgotten.id.clone()]);
+        assert_eq!(summary.recently_closed_tabs, 0);
+        assert_eq!(summary.last_known_good_tabs, 1);
+        assert_eq!(summary.favicons, 1);
+
+        assert!(browser
+            .search_archived_sessions("example", 10)
+            .unwrap()
+            .is_empty());
+        let found = browser.search_archived_sessions("rust", 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].tab_id, kept.id);
+        assert!(browser
+            .restore_last_closed_tab_in_session(&work.id)
+            .is_err());
+
+        let recovered = browser.restore_previous_session().unwrap();
+        assert_eq!(recovered.len(), 1);
+        let tabs = browser
+            .get_ordered_tabs_in_session(&recovered[0].id)
+            .unwrap();
+        assert_eq!(tabs.len(), 1);
+        assert_eq!(tabs[0].url, "https://rust-lang.org/");
+        assert!(tabs[0].favicon_url.is_none());
+
+        // The archived session stays out of memory
+        assert!(browser
+            .session_manager()
+            .tab_manager()
+            .get_session_tabs(&work.id)
+            .is_empty());
+
+        let session = browser.unarchive_session(&work.id).unwrap();
+        assert_eq!(session.tab_order, vec![kept.id.clone()]);
+        let tabs = browser.restore_session_tabs(&work.id).unwrap();
+        assert!(tabs[0].favicon_url.is_none());
+    }
+
+    #[test]
+    fn test_import_bookmark_keywords() {
+        let browser = Browser::new(test_config()).unwrap();
+        browser.initialize().unwrap();
+        let html = r#"<DL><p>
+            <DT><A HREF="https://docs.rs/releases/search?query=%s" SHORTCUTURL="docs">Docs.rs</A>
+            <DT><A HREF="https://developer.mozilla.org/search?q=%s" SHORTCUTURL="mdn">MDN</A>
+            <DT><A HREF="https://news.example.com/" SHORTCUTURL="news">News</A>
+        </DL><p>"#;
+
+        browser.import_bookmarks_html(html).unwrap();
+        let keywords = browser.list_search_keywords();
+        let docs = keywords.iter().find(|k| k.keyword == "docs").unwrap();
+        assert_eq!(docs.name, "Docs.rs");
+        assert_eq!(
+            docs.search_url("serde json"),
+            "https://docs.rs/releases/search?query=serde%20json"
+        );
+        // Taken keywords stay as they were; plain bookmarks keep theirs for export
+        let mdn = keywords.iter().find(|k| k.keyword == "mdn").unwrap();
+        assert_eq!(mdn.name, "MDN Web Docs");
+        assert!(keywords.iter().all(|k| k.keyword != "news"));
+        assert!(browser
+            .export_bookmarks_html()
+            .unwrap()
+            .contains("SHORTCUTURL=\"news\""));
+    }
+
+    #[test]
+    fn test_profile_import_preview_writes_nothing() {
+        let browser = Browser::new(test_config()).unwrap();
+        browser.initialize().unwrap();
+        browser
+            .add_bookmark("Mine", "https://example.com/", None)
+            .unwrap();
+
+        let path = std::env::temp_dir().join(format!("axiom-bookmarks-{}", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"{"roots": {"bookmark_bar": {"type": "folder", "name": "Bookmarks bar", "children": [
+                {"type": "url", "name": "Example", "url": "https://example.com/"},
+                {"type": "url", "name": "Rust", "url": "https://www.rust-lang.org/"}
+            ]}}}"#,
+        )
+        .unwrap();
+
+        let preview = browser
+            .preview_profile_import(ImportSource::ChromiumBookmarks, &path)
+            .unwrap();
+        assert!(preview.dry_run);
+        assert_eq!(preview.bookmarks_added, 1);
+        assert_eq!(preview.bookmark_conflicts.len(), 1);
+        assert_eq!(browser.get_bookmarks().unwrap().len(), 1);
+
+        let report = browser
+            .import_profile(ImportSource::ChromiumBookmarks, &path)
+            .unwrap();
+        assert!(!report.dry_run);
+        let bookmarks = browser.get_bookmarks().unwrap();
+        assert_eq!(bookmarks.len(), 3);
+        assert_eq!(bookmarks[0].title, "Mine");
+        assert_eq!(bookmarks[2].folder.as_deref(), Some("Bookmarks bar"));
+
+        std::fs::remove_file(&path).ok();
+    }
 }
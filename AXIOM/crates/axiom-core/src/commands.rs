@@ -0,0 +1,133 @@
+//! Results for address bar `@` commands
+//!
+//! `Browser::command_matches` answers a parsed `Command` with a list of
+//! `CommandMatch`es; the target says what activating one does.
+
+use serde::{Deserialize, Serialize};
+
+use axiom_navigation::CommandType;
+
+/// Settings that `@settings` can jump to: (key, label, extra search terms)
+pub const SETTINGS: [(&str, &str, &str); 10] = [
+    (
+        "search_engine",
+        "Default search engine",
+        "search engine google bing duckduckgo",
+    ),
+    ("theme", "Theme", "appearance dark light"),
+    ("bookmarks_bar", "Show bookmarks bar", "toolbar"),
+    ("autofill", "Form autofill", "forms"),
+    ("autofill_name", "Autofill name", "forms profile"),
+    ("autofill_email", "Autofill email", "forms profile mail"),
+    (
+        "password_save_prompt",
+        "Password save prompts",
+        "passwords credentials",
+    ),
+    ("https_only", "HTTPS-Only mode", "secure http upgrade"),
+    (
+        "download_dir",
+        "Download folder",
+        "downloads save location directory",
+    ),
+    ("restore_eager_tabs", "Session restore", "startup tabs load"),
+];
+
+/// A browser window and the session it shows
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenWindow {
+    pub label: String,
+    pub session_id: String,
+}
+
+/// What activating a command match does
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum CommandTarget {
+    /// Switch to an open tab
+    Tab(String),
+    /// Open a URL (history entries and bookmarks)
+    Url(String),
+    /// Switch to a session
+    Session(String),
+    /// Show a download
+    Download(String),
+    /// Reopen a recently closed tab by its closed-tab id
+    ClosedTab(String),
+    /// Open settings at a key from `SETTINGS`
+    Setting(String),
+    /// Focus a window by label
+    Window(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandMatch {
+    pub command_type: CommandType,
+    pub title: String,
+    /// Secondary line: URL, folder path, download state, current value...
+    pub detail: String,
+    pub target: CommandTarget,
+}
+
+impl CommandMatch {
+    pub(crate) fn new(
+        command_type: CommandType,
+        title: impl Into<String>,
+        detail: impl Into<String>,
+        target: CommandTarget,
+    ) -> Self {
+        Self {
+            command_type,
+            title: title.into(),
+            detail: detail.into(),
+            target,
+        }
+    }
+}
+
+/// Lowercased query terms; an absent query matches everything
+pub(crate) fn query_terms(query: Option<&str>) -> Vec<String> {
+    query
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// True if every term occurs in one of the fields
+pub(crate) fn matches_terms(terms: &[String], fields: &[&str]) -> bool {
+    let fields: Vec<String> = fields.iter().map(|f| f.to_lowercase()).collect();
+    terms
+        .iter()
+        .all(|term| fields.iter().any(|field| field.contains(term.as_str())))
+}
+
+/// `Work / Rust` for a `Work/Rust` bookmark folder
+pub(crate) fn folder_path_display(folder: &str) -> String {
+    folder
+        .split('/')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_terms() {
+        let terms = query_terms(Some("Rust  book"));
+        assert!(matches_terms(
+            &terms,
+            &[
+                "The Rust Programming Language",
+                "https://doc.rust-lang.org/book/"
+            ]
+        ));
+        assert!(!matches_terms(&terms, &["Rust", "https://rust-lang.org/"]));
+        assert!(matches_terms(&query_terms(None), &["anything"]));
+        assert_eq!(folder_path_display("Work/ Rust/"), "Work / Rust");
+    }
+}
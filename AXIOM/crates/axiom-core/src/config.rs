@@ -15,6 +15,8 @@ pub struct Config {
     pub homepage: String,
     /// Enable tracking protection
     pub tracking_protection: bool,
+    /// Root directory for per-host webview storage partitions
+    pub webview_partitions_dir: PathBuf,
 }
 
 impl Config {
@@ -27,6 +29,7 @@ impl Config {
             search_engine: "https://duckduckgo.com/?q=%s".to_string(),
             homepage: "about:blank".to_string(),
             tracking_protection: true,
+            webview_partitions_dir: data_dir.join("webview-partitions"),
         }
     }
 
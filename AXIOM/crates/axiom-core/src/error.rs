@@ -22,6 +22,27 @@ pub enum CoreError {
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    #[error("Invalid routing rule: {0}")]
+    InvalidRoutingRule(String),
+
+    #[error("Routing rule not found: {0}")]
+    RoutingRuleNotFound(String),
+
+    #[error("Invalid bookmark: {0}")]
+    InvalidBookmark(String),
+
+    #[error("Bookmark not found: {0}")]
+    BookmarkNotFound(i64),
+
+    #[error("Bookmark tag not found: {0}")]
+    BookmarkTagNotFound(String),
+
+    #[error("Smart folder not found: {0}")]
+    SmartFolderNotFound(i64),
+
+    #[error("Import error: {0}")]
+    Import(String),
+
     #[error("Configuration error: {0}")]
     Config(String),
 
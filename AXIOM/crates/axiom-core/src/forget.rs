@@ -0,0 +1,91 @@
+//! "Forget about this site"
+//!
+//! [`crate::Browser::forget_site`] removes what the browser keeps about a
+//! domain and its subdomains: history, bookmarks (optionally), permissions,
+//! download entries, open, archived and recently closed tabs with their
+//! snapshots, its favicon URLs on other tabs, and webview storage partitions.
+
+use serde::{Deserialize, Serialize};
+
+/// What [`crate::Browser::forget_site`] removed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForgetSiteSummary {
+    /// Normalized domain that was forgotten
+    pub domain: String,
+    pub history_entries: usize,
+    pub bookmarks: usize,
+    pub permissions: usize,
+    /// Removed download entries (a running transfer should be stopped)
+    pub download_ids: Vec<String>,
+    /// Tabs that were closed (their webviews should be destroyed)
+    pub closed_tab_ids: Vec<String>,
+    pub recently_closed_tabs: usize,
+    /// Tabs dropped from the last-known-good session snapshot
+    pub last_known_good_tabs: usize,
+    /// Favicon URLs of the site cleared from tabs of other sites
+    pub favicons: usize,
+    pub snapshots: usize,
+    pub partitions: usize,
+}
+
+/// Lower-case ASCII host from a domain or URL typed by the user
+///
+/// `Example.com`, `https://www.example.com/path` and `bücher.de` are all
+/// accepted; anything without a host is rejected.
+pub(crate) fn normalize_domain(input: &str) -> Option<String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+    let url = if input.contains("://") {
+        url::Url::parse(input).ok()?
+    } else {
+        url::Url::parse(&format!("http://{}", input)).ok()?
+    };
+    let host = url.host_str()?.trim_end_matches('.').to_lowercase();
+    (!host.is_empty()).then_some(host)
+}
+
+/// Whether a URL (or a bare origin/host, as stored for permissions) is on `domain`
+pub(crate) fn url_in_domain(url: &str, domain: &str) -> bool {
+    // `example.com:8080` parses as a URL with scheme `example.com` and no host
+    let host = url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .or_else(|| normalize_domain(url));
+    host.is_some_and(|host| axiom_privacy::host_in_domain(&host, domain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_domain() {
+        assert_eq!(
+            normalize_domain(" Example.COM ").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            normalize_domain("https://www.example.com/path?q=1").as_deref(),
+            Some("www.example.com")
+        );
+        assert_eq!(
+            normalize_domain("bücher.de").as_deref(),
+            Some("xn--bcher-kva.de")
+        );
+        assert_eq!(normalize_domain(""), None);
+        assert_eq!(normalize_domain("about:blank"), None);
+
+        assert!(url_in_domain(
+            "https://mail.example.com/inbox",
+            "example.com"
+        ));
+        assert!(url_in_domain("example.com:8080", "example.com"));
+        assert!(!url_in_domain(
+            "https://example.com.evil.test/",
+            "example.com"
+        ));
+        assert!(!url_in_domain("about:blank", "example.com"));
+    }
+}
@@ -0,0 +1,721 @@
+//! Bookmark and history import from other browsers' profiles
+//!
+//! Reads a Firefox `places.sqlite` (bookmarks and history), a Chromium
+//! `Bookmarks` JSON file and a Chromium `History` database. Databases are
+//! copied to a temporary directory before they are opened, since the other
+//! browser keeps them locked while it runs.
+//!
+//! Merging never overwrites: a bookmark whose URL is already saved with a
+//! different title or folder is reported as a conflict and the existing one
+//! is kept. History visits are merged by `HistoryManager::import`.
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use axiom_navigation::{ImportedHistory, VisitTransition};
+
+use crate::bookmarks::{Bookmark, ImportedBookmark, MAX_FOLDER_DEPTH};
+use crate::error::CoreError;
+use crate::Result;
+
+/// Microseconds between 1601-01-01 (Chromium's epoch) and 1970-01-01
+const CHROMIUM_EPOCH_OFFSET_MICROS: i64 = 11_644_473_600_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    /// Firefox `places.sqlite`: bookmarks and history
+    FirefoxPlaces,
+    /// Chromium `Bookmarks` JSON file
+    ChromiumBookmarks,
+    /// Chromium `History` database
+    ChromiumHistory,
+}
+
+/// Bookmarks and history read from a profile file
+#[derive(Debug, Clone, Default)]
+pub struct ProfileData {
+    pub bookmarks: Vec<ImportedBookmark>,
+    pub history: Vec<ImportedHistory>,
+}
+
+/// A bookmark whose URL is already saved with a different title or folder
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookmarkConflict {
+    pub url: String,
+    pub existing_title: String,
+    pub existing_folder: Option<String>,
+    pub imported_title: String,
+    pub imported_folder: Option<String>,
+}
+
+/// Counts for an import; with `dry_run` nothing was written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub source: ImportSource,
+    pub dry_run: bool,
+    pub bookmarks_found: usize,
+    pub bookmarks_added: usize,
+    /// Already saved with the same title and folder
+    pub bookmarks_unchanged: usize,
+    /// Already saved differently; the existing bookmark is kept
+    pub bookmark_conflicts: Vec<BookmarkConflict>,
+    pub history_urls_found: usize,
+    pub history_urls_added: usize,
+    /// URLs already in history; their visits are merged
+    pub history_urls_merged: usize,
+    pub visits_found: usize,
+    /// Visits written (0 on a dry run)
+    pub visits_added: usize,
+}
+
+impl ImportReport {
+    pub(crate) fn new(source: ImportSource, dry_run: bool) -> Self {
+        Self {
+            source,
+            dry_run,
+            bookmarks_found: 0,
+            bookmarks_added: 0,
+            bookmarks_unchanged: 0,
+            bookmark_conflicts: Vec::new(),
+            history_urls_found: 0,
+            history_urls_added: 0,
+            history_urls_merged: 0,
+            visits_found: 0,
+            visits_added: 0,
+        }
+    }
+}
+
+/// Read everything a profile file offers for `source`
+pub fn read_profile(source: ImportSource, path: &Path) -> Result<ProfileData> {
+    match source {
+        ImportSource::FirefoxPlaces => with_database_copy(path, |conn| {
+            require_tables(conn, &["moz_places", "moz_bookmarks"], "Firefox places")?;
+            Ok(ProfileData {
+                bookmarks: read_firefox_bookmarks(conn)?,
+                history: read_firefox_history(conn)?,
+            })
+        }),
+        ImportSource::ChromiumBookmarks => {
+            let json = std::fs::read_to_string(path)?;
+            Ok(ProfileData {
+                bookmarks: parse_chromium_bookmarks(&json)?,
+                history: Vec::new(),
+            })
+        }
+        ImportSource::ChromiumHistory => with_database_copy(path, |conn| {
+            require_tables(conn, &["urls", "visits"], "Chromium history")?;
+            Ok(ProfileData {
+                bookmarks: Vec::new(),
+                history: read_chromium_history(conn)?,
+            })
+        }),
+    }
+}
+
+/// Imported bookmarks that are not saved yet, filling in the bookmark counts of `report`
+pub(crate) fn merge_bookmarks(
+    existing: &[Bookmark],
+    imported: Vec<ImportedBookmark>,
+    report: &mut ImportReport,
+) -> Vec<ImportedBookmark> {
+    report.bookmarks_found = imported.len();
+    let mut new = Vec::new();
+    for bookmark in imported {
+        // The same URL may be saved in several folders; prefer the copy in this one
+        let current = existing
+            .iter()
+            .find(|b| b.url == bookmark.url && b.folder == bookmark.folder)
+            .or_else(|| existing.iter().find(|b| b.url == bookmark.url));
+        match current {
+            Some(current)
+                if current.title == bookmark.title && current.folder == bookmark.folder =>
+            {
+                report.bookmarks_unchanged += 1;
+            }
+            Some(current) => report.bookmark_conflicts.push(BookmarkConflict {
+                url: bookmark.url,
+                existing_title: current.title.clone(),
+                existing_folder: current.folder.clone(),
+                imported_title: bookmark.title,
+                imported_folder: bookmark.folder,
+            }),
+            None => {
+                report.bookmarks_added += 1;
+                new.push(bookmark);
+            }
+        }
+    }
+    new
+}
+
+fn import_error(message: impl std::fmt::Display) -> CoreError {
+    CoreError::Import(message.to_string())
+}
+
+/// Run `read` against a private copy of an SQLite database (plus its WAL)
+fn with_database_copy<T>(path: &Path, read: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+    if !path.is_file() {
+        return Err(import_error(format!("File not found: {}", path.display())));
+    }
+
+    let dir = std::env::temp_dir().join(format!("axiom-import-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir)?;
+    let result = (|| {
+        let copy = dir.join("profile.sqlite");
+        std::fs::copy(path, &copy)?;
+        for suffix in ["-wal", "-shm"] {
+            let mut side = path.as_os_str().to_owned();
+            side.push(suffix);
+            let side = std::path::PathBuf::from(side);
+            if side.is_file() {
+                let mut target = copy.as_os_str().to_owned();
+                target.push(suffix);
+                std::fs::copy(&side, std::path::PathBuf::from(target))?;
+            }
+        }
+
+        let conn = Connection::open_with_flags(
+            &copy,
+            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(import_error)?;
+        read(&conn)
+    })();
+
+    if let Err(e) = std::fs::remove_dir_all(&dir) {
+        tracing::warn!(?dir, "Failed to remove import copy: {}", e);
+    }
+    result
+}
+
+fn require_tables(conn: &Connection, tables: &[&str], kind: &str) -> Result<()> {
+    for table in tables {
+        let found = conn
+            .query_row(
+                "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                [table],
+                |_| Ok(()),
+            )
+            .is_ok();
+        if !found {
+            return Err(import_error(format!("Not a {kind} database")));
+        }
+    }
+    Ok(())
+}
+
+/// Web and file URLs; internal pages and `place:` queries are skipped
+fn importable_url(url: &str) -> bool {
+    url::Url::parse(url).is_ok_and(|u| matches!(u.scheme(), "http" | "https" | "ftp" | "file"))
+}
+
+/// Folder names are joined with `/`, so a slash inside a name becomes a dash
+fn folder_name(name: &str) -> String {
+    name.trim().replace('/', "-")
+}
+
+fn join_folder(parent: Option<&str>, name: &str) -> Option<String> {
+    let name = folder_name(name);
+    match (parent, name.is_empty()) {
+        (parent, true) => parent.map(str::to_string),
+        (Some(parent), false) => Some(format!("{parent}/{name}")),
+        (None, false) => Some(name),
+    }
+}
+
+fn from_unix_micros(micros: i64) -> Option<DateTime<Utc>> {
+    if micros <= 0 {
+        return None;
+    }
+    DateTime::from_timestamp_micros(micros)
+}
+
+fn from_chromium_micros(micros: i64) -> Option<DateTime<Utc>> {
+    from_unix_micros(micros.checked_sub(CHROMIUM_EPOCH_OFFSET_MICROS)?)
+}
+
+/// Keep the first bookmark per URL within each folder
+fn dedupe_bookmarks(bookmarks: Vec<ImportedBookmark>) -> Vec<ImportedBookmark> {
+    let mut seen = HashSet::new();
+    bookmarks
+        .into_iter()
+        .filter(|b| seen.insert((b.folder.clone(), b.url.clone())))
+        .collect()
+}
+
+// === Firefox ===
+
+/// Display names of the Firefox root folders, by guid (`None`: the invisible root)
+fn firefox_root_name(guid: &str) -> Option<Option<&'static str>> {
+    match guid {
+        "root________" => Some(None),
+        "menu________" => Some(Some("Bookmarks Menu")),
+        "toolbar_____" => Some(Some("Bookmarks Toolbar")),
+        "unfiled_____" => Some(Some("Other Bookmarks")),
+        "mobile______" => Some(Some("Mobile Bookmarks")),
+        _ => None,
+    }
+}
+
+const FIREFOX_TAGS_ROOT: &str = "tags________";
+
+struct FirefoxItem {
+    kind: i64,
+    parent: i64,
+    position: i64,
+    title: String,
+    guid: String,
+    url: Option<String>,
+}
+
+fn read_firefox_bookmarks(conn: &Connection) -> Result<Vec<ImportedBookmark>> {
+    let items: HashMap<i64, FirefoxItem> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT b.id, b.type, b.parent, COALESCE(b.position, 0), COALESCE(b.title, ''),
+                        COALESCE(b.guid, ''), p.url
+                 FROM moz_bookmarks b LEFT JOIN moz_places p ON p.id = b.fk",
+            )
+            .map_err(import_error)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    FirefoxItem {
+                        kind: row.get(1)?,
+                        parent: row.get(2)?,
+                        position: row.get(3)?,
+                        title: row.get(4)?,
+                        guid: row.get(5)?,
+                        url: row.get(6)?,
+                    },
+                ))
+            })
+            .map_err(import_error)?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    };
+
+    // Folder path of an item's parent; `Err(())` for items under the tags root
+    fn folder_path(
+        items: &HashMap<i64, FirefoxItem>,
+        id: i64,
+        depth: usize,
+    ) -> std::result::Result<Option<String>, ()> {
+        let Some(item) = items.get(&id) else {
+            return Ok(None);
+        };
+        if item.guid == FIREFOX_TAGS_ROOT || depth > MAX_FOLDER_DEPTH {
+            return Err(());
+        }
+        if let Some(root) = firefox_root_name(&item.guid) {
+            return Ok(root.map(str::to_string));
+        }
+        let parent = folder_path(items, item.parent, depth + 1)?;
+        Ok(join_folder(parent.as_deref(), &item.title))
+    }
+
+    let mut ordered: Vec<(&i64, &FirefoxItem)> = items.iter().collect();
+    // Folder by folder, in the order Firefox shows them
+    ordered.sort_by_key(|(_, item)| (item.parent, item.position));
+
+    let mut bookmarks = Vec::new();
+    for (_, item) in ordered {
+        let Some(url) = item.url.as_deref().filter(|_| item.kind == 1) else {
+            continue;
+        };
+        if !importable_url(url) {
+            continue;
+        }
+        let Ok(folder) = folder_path(&items, item.parent, 0) else {
+            continue;
+        };
+        let title = item.title.trim();
+        bookmarks.push(ImportedBookmark {
+            title: if title.is_empty() { url } else { title }.to_string(),
+            url: url.to_string(),
+            folder,
+        });
+    }
+
+    Ok(dedupe_bookmarks(bookmarks))
+}
+
+/// Firefox `visit_type`; embedded, framed and download visits are skipped
+fn firefox_transition(visit_type: i64) -> Option<VisitTransition> {
+    match visit_type {
+        1 => Some(VisitTransition::Link),
+        2 => Some(VisitTransition::Typed),
+        3 => Some(VisitTransition::Bookmark),
+        5 | 6 => Some(VisitTransition::Redirect),
+        9 => Some(VisitTransition::Reload),
+        _ => None,
+    }
+}
+
+fn read_firefox_history(conn: &Connection) -> Result<Vec<ImportedHistory>> {
+    let visits = collect_visits(
+        conn,
+        "SELECT place_id, visit_date, visit_type FROM moz_historyvisits",
+        from_unix_micros,
+        firefox_transition,
+    )?;
+    collect_history(
+        conn,
+        "SELECT id, url, COALESCE(title, ''), visit_count, last_visit_date
+         FROM moz_places WHERE hidden = 0 AND last_visit_date IS NOT NULL",
+        from_unix_micros,
+        visits,
+    )
+}
+
+// === Chromium ===
+
+/// Chromium page transition: the core type is the low byte, redirects are qualifier bits
+fn chromium_transition(transition: i64) -> Option<VisitTransition> {
+    const REDIRECT_MASK: i64 = 0xC000_0000;
+    if transition & REDIRECT_MASK != 0 {
+        return Some(VisitTransition::Redirect);
+    }
+    match transition & 0xFF {
+        0 | 6 | 7 => Some(VisitTransition::Link),
+        1 | 5 | 9 => Some(VisitTransition::Typed),
+        2 => Some(VisitTransition::Bookmark),
+        8 => Some(VisitTransition::Reload),
+        // Subframes and keyword-generated visits
+        _ => None,
+    }
+}
+
+fn read_chromium_history(conn: &Connection) -> Result<Vec<ImportedHistory>> {
+    let visits = collect_visits(
+        conn,
+        "SELECT url, visit_time, transition FROM visits",
+        from_chromium_micros,
+        chromium_transition,
+    )?;
+    collect_history(
+        conn,
+        "SELECT id, url, COALESCE(title, ''), visit_count, last_visit_time
+         FROM urls WHERE hidden = 0 AND last_visit_time > 0",
+        from_chromium_micros,
+        visits,
+    )
+}
+
+/// Parse a Chromium `Bookmarks` file; each root becomes a top-level folder
+pub fn parse_chromium_bookmarks(json: &str) -> Result<Vec<ImportedBookmark>> {
+    let value: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| import_error(format!("Not a Chromium bookmarks file: {e}")))?;
+    let roots = value
+        .get("roots")
+        .and_then(|r| r.as_object())
+        .ok_or_else(|| import_error("Not a Chromium bookmarks file: no roots"))?;
+
+    fn walk(
+        node: &serde_json::Value,
+        folder: Option<&str>,
+        depth: usize,
+        out: &mut Vec<ImportedBookmark>,
+    ) {
+        let name = node.get("name").and_then(|n| n.as_str()).unwrap_or("");
+        match node.get("type").and_then(|t| t.as_str()) {
+            Some("url") => {
+                let Some(url) = node.get("url").and_then(|u| u.as_str()) else {
+                    return;
+                };
+                if importable_url(url) {
+                    let title = name.trim();
+                    out.push(ImportedBookmark {
+                        title: if title.is_empty() { url } else { title }.to_string(),
+                        url: url.to_string(),
+                        folder: folder.map(str::to_string),
+                    });
+                }
+            }
+            Some("folder") if depth <= MAX_FOLDER_DEPTH => {
+                let path = join_folder(folder, name);
+                for child in node
+                    .get("children")
+                    .and_then(|c| c.as_array())
+                    .into_iter()
+                    .flatten()
+                {
+                    walk(child, path.as_deref(), depth + 1, out);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut bookmarks = Vec::new();
+    for key in ["bookmark_bar", "other", "synced"] {
+        if let Some(root) = roots.get(key) {
+            walk(root, None, 0, &mut bookmarks);
+        }
+    }
+    for (key, root) in roots {
+        if !matches!(key.as_str(), "bookmark_bar" | "other" | "synced") && root.is_object() {
+            walk(root, None, 0, &mut bookmarks);
+        }
+    }
+
+    Ok(dedupe_bookmarks(bookmarks))
+}
+
+// === Shared history reading ===
+
+type VisitsByUrl = HashMap<i64, Vec<(DateTime<Utc>, VisitTransition)>>;
+
+/// `sql` selects (url id, time, transition)
+fn collect_visits(
+    conn: &Connection,
+    sql: &str,
+    time: fn(i64) -> Option<DateTime<Utc>>,
+    transition: fn(i64) -> Option<VisitTransition>,
+) -> Result<VisitsByUrl> {
+    let mut stmt = conn.prepare(sql).map_err(import_error)?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })
+        .map_err(import_error)?;
+
+    let mut visits: VisitsByUrl = HashMap::new();
+    for (url_id, at, kind) in rows.filter_map(|r| r.ok()) {
+        if let (Some(at), Some(kind)) = (time(at), transition(kind)) {
+            visits.entry(url_id).or_default().push((at, kind));
+        }
+    }
+    Ok(visits)
+}
+
+/// `sql` selects (url id, url, title, visit count, last visit time)
+fn collect_history(
+    conn: &Connection,
+    sql: &str,
+    time: fn(i64) -> Option<DateTime<Utc>>,
+    mut visits: VisitsByUrl,
+) -> Result<Vec<ImportedHistory>> {
+    let mut stmt = conn.prepare(sql).map_err(import_error)?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, i64>(4)?,
+            ))
+        })
+        .map_err(import_error)?;
+
+    let mut history = Vec::new();
+    for (id, url, title, visit_count, last_visit) in rows.filter_map(|r| r.ok()) {
+        let Some(last_visited_at) = time(last_visit) else {
+            continue;
+        };
+        if !importable_url(&url) {
+            continue;
+        }
+        let mut url_visits = visits.remove(&id).unwrap_or_default();
+        url_visits.sort_by_key(|(at, _)| *at);
+        history.push(ImportedHistory {
+            url,
+            title,
+            visit_count: visit_count.max(0),
+            last_visited_at,
+            visits: url_visits,
+        });
+    }
+    Ok(history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bookmarks::BookmarkManager;
+
+    fn temp_db(name: &str, schema: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("axiom-import-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(schema)
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_read_firefox_places() {
+        let path = temp_db(
+            "places.sqlite",
+            r#"
+            CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url TEXT, title TEXT,
+                visit_count INTEGER, hidden INTEGER, last_visit_date INTEGER);
+            CREATE TABLE moz_bookmarks (id INTEGER PRIMARY KEY, type INTEGER, fk INTEGER,
+                parent INTEGER, position INTEGER, title TEXT, guid TEXT, dateAdded INTEGER);
+            CREATE TABLE moz_historyvisits (id INTEGER PRIMARY KEY, place_id INTEGER,
+                visit_date INTEGER, visit_type INTEGER);
+
+            INSERT INTO moz_places VALUES
+                (1, 'https://www.rust-lang.org/', 'Rust', 3, 0, 1700000000000000),
+                (2, 'https://doc.rust-lang.org/book/', 'The Book', 0, 0, NULL),
+                (3, 'place:sort=8', NULL, 0, 0, NULL),
+                (4, 'https://redirect.example/', 'Hidden', 1, 1, 1700000000000000);
+            INSERT INTO moz_bookmarks VALUES
+                (1, 2, NULL, 0, 0, '', 'root________', 0),
+                (2, 2, NULL, 1, 0, 'menu', 'menu________', 0),
+                (3, 2, NULL, 1, 1, 'toolbar', 'toolbar_____', 0),
+                (4, 2, NULL, 1, 2, 'tags', 'tags________', 0),
+                (10, 2, NULL, 3, 0, 'Dev/Docs', 'folder000001', 0),
+                (11, 1, 2, 10, 0, 'The Book', 'bookmark0001', 0),
+                (12, 1, 1, 2, 1, '', 'bookmark0002', 0),
+                (13, 1, 3, 2, 2, 'Most Visited', 'bookmark0003', 0),
+                (14, 2, NULL, 4, 0, 'rust', 'tagfolder001', 0),
+                (15, 1, 1, 14, 0, NULL, 'tagentry0001', 0),
+                (16, 1, 2, 2, 0, 'Book', 'bookmark0004', 0),
+                (17, 1, 1, 2, 3, 'Rust again', 'bookmark0005', 0);
+            INSERT INTO moz_historyvisits VALUES
+                (1, 1, 1699999000000000, 2),
+                (2, 1, 1700000000000000, 1),
+                (3, 1, 1700000000000001, 8);
+            "#,
+        );
+
+        let data = read_profile(ImportSource::FirefoxPlaces, &path).unwrap();
+        let summary: Vec<(&str, &str, Option<&str>)> = data
+            .bookmarks
+            .iter()
+            .map(|b| (b.url.as_str(), b.title.as_str(), b.folder.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                // By position, not id; the duplicate in the same folder is dropped
+                (
+                    "https://doc.rust-lang.org/book/",
+                    "Book",
+                    Some("Bookmarks Menu")
+                ),
+                (
+                    "https://www.rust-lang.org/",
+                    "https://www.rust-lang.org/",
+                    Some("Bookmarks Menu")
+                ),
+                (
+                    "https://doc.rust-lang.org/book/",
+                    "The Book",
+                    Some("Bookmarks Toolbar/Dev-Docs")
+                ),
+            ]
+        );
+
+        assert_eq!(data.history.len(), 1);
+        let rust = &data.history[0];
+        assert_eq!(rust.visit_count, 3);
+        assert_eq!(
+            rust.visits.iter().map(|(_, t)| *t).collect::<Vec<_>>(),
+            vec![VisitTransition::Typed, VisitTransition::Link]
+        );
+
+        assert!(read_profile(ImportSource::ChromiumHistory, &path).is_err());
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_read_chromium_history() {
+        let path = temp_db(
+            "History",
+            r#"
+            CREATE TABLE urls (id INTEGER PRIMARY KEY, url TEXT, title TEXT, visit_count INTEGER,
+                typed_count INTEGER, last_visit_time INTEGER, hidden INTEGER);
+            CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER,
+                transition INTEGER);
+
+            INSERT INTO urls VALUES
+                (1, 'https://example.com/', 'Example', 2, 1, 13345000000000000, 0),
+                (2, 'chrome://settings/', 'Settings', 1, 0, 13345000000000000, 0);
+            INSERT INTO visits VALUES
+                (1, 1, 13344000000000000, 805306369),
+                (2, 1, 13345000000000000, 2147483648),
+                (3, 1, 13345000000000001, 3),
+                (4, 2, 13345000000000000, 1);
+            "#,
+        );
+
+        let data = read_profile(ImportSource::ChromiumHistory, &path).unwrap();
+        assert_eq!(data.history.len(), 1);
+        let example = &data.history[0];
+        assert_eq!(example.url, "https://example.com/");
+        assert_eq!(
+            example.visits.iter().map(|(_, t)| *t).collect::<Vec<_>>(),
+            vec![VisitTransition::Typed, VisitTransition::Redirect]
+        );
+        // 13345000000000000 µs since 1601 is 2023-11-21
+        assert_eq!(
+            example.last_visited_at.date_naive().to_string(),
+            "2023-11-21"
+        );
+
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_parse_chromium_bookmarks_and_merge() {
+        let json = r#"{
+            "checksum": "0",
+            "roots": {
+                "bookmark_bar": {"type": "folder", "name": "Bookmarks bar", "children": [
+                    {"type": "url", "name": "Rust", "url": "https://www.rust-lang.org/"},
+                    {"type": "folder", "name": "News", "children": [
+                        {"type": "url", "name": "HN", "url": "https://news.ycombinator.com/"}
+                    ]}
+                ]},
+                "other": {"type": "folder", "name": "Other bookmarks", "children": [
+                    {"type": "url", "name": "JS", "url": "javascript:void(0)"},
+                    {"type": "url", "name": "Example", "url": "https://example.com/"}
+                ]},
+                "synced": {"type": "folder", "name": "Mobile bookmarks", "children": []}
+            },
+            "version": 1
+        }"#;
+
+        let imported = parse_chromium_bookmarks(json).unwrap();
+        assert_eq!(imported.len(), 3);
+        assert_eq!(imported[1].folder.as_deref(), Some("Bookmarks bar/News"));
+
+        let manager = BookmarkManager::new(axiom_storage::Database::open_in_memory().unwrap());
+        let bar = manager.add_folder("Bookmarks bar", None).unwrap();
+        manager
+            .add_bookmark("Rust", "https://www.rust-lang.org/", Some(bar.id))
+            .unwrap();
+        manager
+            .add_bookmark("My example", "https://example.com/", None)
+            .unwrap();
+        let existing = manager.bookmarks().unwrap();
+        let mut report = ImportReport::new(ImportSource::ChromiumBookmarks, true);
+        let new = merge_bookmarks(&existing, imported, &mut report);
+        assert_eq!(report.bookmarks_found, 3);
+        assert_eq!(report.bookmarks_added, 1);
+        assert_eq!(report.bookmarks_unchanged, 1);
+        assert_eq!(report.bookmark_conflicts.len(), 1);
+        assert_eq!(report.bookmark_conflicts[0].existing_title, "My example");
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].title, "HN");
+
+        assert!(parse_chromium_bookmarks("{}").is_err());
+        assert!(parse_chromium_bookmarks("not json").is_err());
+    }
+}
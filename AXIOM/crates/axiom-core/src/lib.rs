@@ -5,24 +5,53 @@
 
 mod bookmarks;
 mod browser;
+mod commands;
 mod config;
 mod error;
+mod forget;
+pub mod import;
+mod link_check;
+mod netscape;
+pub mod partition;
+mod probe;
+mod routing;
+mod session_settings;
 
-pub use bookmarks::Bookmark;
+pub use bookmarks::{
+    Bookmark, BookmarkFolder, BookmarkFolders, BookmarkKind, BookmarkManager, BookmarkTag,
+    ImportedBookmark, SmartFolder, SmartFolderQuery,
+};
 pub use browser::Browser;
+pub use commands::{CommandMatch, CommandTarget, OpenWindow, SETTINGS};
 pub use config::Config;
 pub use error::CoreError;
+pub use forget::ForgetSiteSummary;
+pub use import::{BookmarkConflict, ImportReport, ImportSource};
+pub use link_check::{
+    check_order, HostRateLimiter, LinkCheck, LinkCheckRun, LinkProbe, LinkStatus,
+    DNS_FAILURES_BEFORE_DEAD,
+};
+pub use netscape::BookmarkNode;
+pub use probe::{probe_url, ProbeInfo};
+pub use routing::{RoutePattern, RoutingRule, UrlRouter};
+pub use session_settings::SessionSettings;
 
 // Re-export core components
 pub use axiom_download::{Download, DownloadError, DownloadManager, DownloadState, RiskLevel};
 pub use axiom_navigation::{
-    Command, CommandType, HistoryEntry, HistoryManager, InputResolution, InputResolver,
-    NavigationError,
+    compute_answer, discover_opensearch_links, display_host, parse_opensearch, url_display, Answer,
+    AnswerKind, CancellationToken, Command, CommandType, HistoryEntry, HistoryManager, HistorySort,
+    IdnRisk, IdnWarning, InlineAutocomplete, InputResolution, InputResolver, Journey, JourneyPage,
+    KeywordManager, NavigationError, NewSearchEngine, OpenSearchLink, SearchEngine, SearchKeyword,
+    Suggestion, SuggestionKind, SuggestionResult, UrlDisplay, Visit, VisitSource, VisitTransition,
 };
 pub use axiom_privacy::{
     Permission, PermissionManager, PermissionState, PermissionType, TrackingProtection,
 };
-pub use axiom_session::{Session, SessionError, SessionManager};
+pub use axiom_session::{
+    ArchivedTab, RecoveryInfo, Session, SessionError, SessionExport, SessionFormat, SessionImport,
+    SessionManager, SkippedTab, TabGroup,
+};
 pub use axiom_storage::{Database, StorageError};
 pub use axiom_tabs::{Tab, TabError, TabManager, TabState};
 
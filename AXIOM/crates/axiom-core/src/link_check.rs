@@ -0,0 +1,402 @@
+//! Bookmark link checking
+//!
+//! The app probes each bookmark (HEAD, then a ranged GET, see
+//! [`crate::probe_url`]) and records a [`LinkStatus`] per bookmark. This
+//! module turns probe results into statuses, orders the work so hosts are
+//! interleaved, and spaces out requests to the same host.
+//!
+//! A host that fails to resolve once may only be unreachable for a moment,
+//! so DNS failures count as dead only after [`DNS_FAILURES_BEFORE_DEAD`]
+//! runs in a row, and a run in which most hosts fail to resolve is taken as
+//! offline and does not count at all (see [`LinkCheckRun`]).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::bookmarks::Bookmark;
+
+/// What a bookmark's URL answered when it was last checked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum LinkStatus {
+    Ok,
+    /// Permanently moved (301/308 all the way) to another URL that answers
+    Redirected {
+        to: String,
+    },
+    /// 404 Not Found or 410 Gone
+    NotFound,
+    /// Any other 4xx/5xx answer
+    HttpError {
+        code: u16,
+    },
+    /// The host name does not resolve
+    Dns,
+    Tls,
+    Timeout,
+    /// Connection refused, invalid URL and other failures
+    Unreachable {
+        kind: String,
+    },
+}
+
+impl LinkStatus {
+    /// Status of `url` from what probing it found
+    pub fn classify(url: &str, probe: &LinkProbe) -> Self {
+        if let Some(kind) = probe.error_kind.as_deref() {
+            return match kind {
+                "dns" => LinkStatus::Dns,
+                "tls" => LinkStatus::Tls,
+                "timeout" => LinkStatus::Timeout,
+                kind => LinkStatus::Unreachable {
+                    kind: kind.to_string(),
+                },
+            };
+        }
+
+        match probe.status {
+            Some(404 | 410) => LinkStatus::NotFound,
+            Some(code) if code >= 400 => LinkStatus::HttpError { code },
+            _ => match probe.final_url.as_deref() {
+                Some(to) if probe.permanent_redirect && !same_url(url, to) => {
+                    LinkStatus::Redirected { to: to.to_string() }
+                }
+                _ => LinkStatus::Ok,
+            },
+        }
+    }
+
+    /// `(status, detail)` columns of `bookmark_link_checks`
+    pub(crate) fn to_columns(&self) -> (&'static str, Option<String>) {
+        match self {
+            LinkStatus::Ok => ("ok", None),
+            LinkStatus::Redirected { to } => ("redirected", Some(to.clone())),
+            LinkStatus::NotFound => ("not_found", None),
+            LinkStatus::HttpError { code } => ("http_error", Some(code.to_string())),
+            LinkStatus::Dns => ("dns", None),
+            LinkStatus::Tls => ("tls", None),
+            LinkStatus::Timeout => ("timeout", None),
+            LinkStatus::Unreachable { kind } => ("unreachable", Some(kind.clone())),
+        }
+    }
+
+    pub(crate) fn from_columns(status: &str, detail: Option<String>) -> Self {
+        match (status, detail) {
+            ("ok", _) => LinkStatus::Ok,
+            ("redirected", Some(to)) => LinkStatus::Redirected { to },
+            ("not_found", _) => LinkStatus::NotFound,
+            ("http_error", Some(code)) => match code.parse() {
+                Ok(code) => LinkStatus::HttpError { code },
+                Err(_) => LinkStatus::Unreachable { kind: code },
+            },
+            ("dns", _) => LinkStatus::Dns,
+            ("tls", _) => LinkStatus::Tls,
+            ("timeout", _) => LinkStatus::Timeout,
+            (_, detail) => LinkStatus::Unreachable {
+                kind: detail.unwrap_or_else(|| "unknown".to_string()),
+            },
+        }
+    }
+}
+
+/// Consecutive runs a host must fail to resolve in before its bookmarks count as dead
+pub const DNS_FAILURES_BEFORE_DEAD: u32 = 3;
+
+/// Fewest hosts a run must probe before it can be judged offline
+const OFFLINE_MIN_HOSTS: usize = 3;
+
+/// Result of the last check of a bookmark
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkCheck {
+    #[serde(flatten)]
+    pub status: LinkStatus,
+    pub checked_at: DateTime<Utc>,
+    /// Runs in a row the host failed to resolve in
+    pub dns_failures: u32,
+    /// Whether "remove dead" takes the bookmark away
+    pub dead: bool,
+}
+
+impl LinkCheck {
+    pub fn new(status: LinkStatus, checked_at: DateTime<Utc>, dns_failures: u32) -> Self {
+        let dead = match status {
+            LinkStatus::NotFound => true,
+            LinkStatus::Dns => dns_failures >= DNS_FAILURES_BEFORE_DEAD,
+            // Timeouts, TLS and server errors are often temporary and are only reported
+            _ => false,
+        };
+        Self {
+            status,
+            checked_at,
+            dns_failures,
+            dead,
+        }
+    }
+}
+
+/// DNS failures of one link check run, held back until it ends
+///
+/// A machine that lost its network fails to resolve every host, so when most
+/// hosts of a run fail to resolve the run is taken as offline and its DNS
+/// failures are dropped instead of counting against the bookmarks.
+#[derive(Debug, Default)]
+pub struct LinkCheckRun {
+    hosts: HashSet<String>,
+    unresolved_hosts: HashSet<String>,
+    unresolved: Vec<i64>,
+}
+
+impl LinkCheckRun {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note a checked bookmark; returns false for a DNS failure, which is held back
+    pub(crate) fn note(&mut self, id: i64, url: &str, status: &LinkStatus) -> bool {
+        let host = checkable_host(url).unwrap_or_default();
+        if *status == LinkStatus::Dns {
+            self.unresolved_hosts.insert(host.clone());
+            self.unresolved.push(id);
+        }
+        self.hosts.insert(host);
+        *status != LinkStatus::Dns
+    }
+
+    /// Bookmarks whose host failed to resolve in this run
+    pub(crate) fn unresolved(&self) -> &[i64] {
+        &self.unresolved
+    }
+
+    /// Whether most hosts of the run failed to resolve
+    pub fn is_offline(&self) -> bool {
+        self.hosts.len() >= OFFLINE_MIN_HOSTS && self.unresolved_hosts.len() * 2 > self.hosts.len()
+    }
+}
+
+/// What probing a URL found
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkProbe {
+    /// HTTP status of the final answer
+    pub status: Option<u16>,
+    pub final_url: Option<String>,
+    /// At least one redirect was followed and every one was 301 or 308
+    pub permanent_redirect: bool,
+    /// `dns`, `tls`, `timeout`, `connect`, ... when no answer came
+    pub error_kind: Option<String>,
+}
+
+fn same_url(a: &str, b: &str) -> bool {
+    match (url::Url::parse(a), url::Url::parse(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn checkable_host(url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    url.host_str().map(str::to_ascii_lowercase)
+}
+
+/// Bookmarks worth probing (http and https only), interleaved by host so a
+/// run does not queue up many requests to one site in a row
+pub fn check_order(bookmarks: Vec<Bookmark>) -> Vec<Bookmark> {
+    let mut hosts: Vec<String> = Vec::new();
+    let mut queues: HashMap<String, std::collections::VecDeque<Bookmark>> = HashMap::new();
+    for bookmark in bookmarks.into_iter().filter(Bookmark::is_bookmark) {
+        let Some(host) = checkable_host(&bookmark.url) else {
+            continue;
+        };
+        if !queues.contains_key(&host) {
+            hosts.push(host.clone());
+        }
+        queues.entry(host).or_default().push_back(bookmark);
+    }
+
+    let mut ordered = Vec::new();
+    while !queues.is_empty() {
+        for host in &hosts {
+            if let Some(queue) = queues.get_mut(host) {
+                if let Some(bookmark) = queue.pop_front() {
+                    ordered.push(bookmark);
+                }
+                if queue.is_empty() {
+                    queues.remove(host);
+                }
+            }
+        }
+    }
+    ordered
+}
+
+/// Keeps requests to the same host at least `interval` apart
+#[derive(Debug)]
+pub struct HostRateLimiter {
+    interval: Duration,
+    next_slot: HashMap<String, Instant>,
+}
+
+impl HostRateLimiter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_slot: HashMap::new(),
+        }
+    }
+
+    /// Book the next request to the host of `url`; returns how long to wait before sending it
+    pub fn reserve(&mut self, url: &str, now: Instant) -> Duration {
+        let host = checkable_host(url).unwrap_or_default();
+        let slot = match self.next_slot.get(&host) {
+            Some(slot) if *slot > now => *slot,
+            _ => now,
+        };
+        self.next_slot.insert(host, slot + self.interval);
+        slot - now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(status: u16, final_url: &str, permanent_redirect: bool) -> LinkProbe {
+        LinkProbe {
+            status: Some(status),
+            final_url: Some(final_url.to_string()),
+            permanent_redirect,
+            error_kind: None,
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        let url = "https://example.com/old";
+        assert_eq!(
+            LinkStatus::classify(url, &probe(200, url, false)),
+            LinkStatus::Ok
+        );
+        assert_eq!(
+            LinkStatus::classify(url, &probe(200, "https://example.com/new", true)),
+            LinkStatus::Redirected {
+                to: "https://example.com/new".to_string()
+            }
+        );
+        // Temporary redirects (logins, geo pages) are not worth rewriting
+        assert_eq!(
+            LinkStatus::classify(url, &probe(200, "https://example.com/login", false)),
+            LinkStatus::Ok
+        );
+        assert_eq!(
+            LinkStatus::classify(url, &probe(410, url, false)),
+            LinkStatus::NotFound
+        );
+        assert_eq!(
+            LinkStatus::classify(url, &probe(503, url, false)),
+            LinkStatus::HttpError { code: 503 }
+        );
+        let failed = |kind: &str| LinkProbe {
+            error_kind: Some(kind.to_string()),
+            ..LinkProbe::default()
+        };
+        assert_eq!(LinkStatus::classify(url, &failed("dns")), LinkStatus::Dns);
+        assert_eq!(
+            LinkStatus::classify(url, &failed("connect")),
+            LinkStatus::Unreachable {
+                kind: "connect".to_string()
+            }
+        );
+
+        for status in [
+            LinkStatus::Ok,
+            LinkStatus::Redirected {
+                to: "https://example.com/".to_string(),
+            },
+            LinkStatus::HttpError { code: 500 },
+            LinkStatus::Tls,
+            LinkStatus::Unreachable {
+                kind: "connect".to_string(),
+            },
+        ] {
+            let (kind, detail) = status.to_columns();
+            assert_eq!(LinkStatus::from_columns(kind, detail), status);
+        }
+    }
+
+    #[test]
+    fn test_dead_links() {
+        let now = Utc::now();
+        assert!(LinkCheck::new(LinkStatus::NotFound, now, 0).dead);
+        assert!(!LinkCheck::new(LinkStatus::Timeout, now, 0).dead);
+        // A host that stopped resolving only counts once it kept failing
+        assert!(!LinkCheck::new(LinkStatus::Dns, now, 1).dead);
+        assert!(LinkCheck::new(LinkStatus::Dns, now, DNS_FAILURES_BEFORE_DEAD).dead);
+
+        let mut run = LinkCheckRun::new();
+        assert!(run.note(1, "https://a.test/", &LinkStatus::Ok));
+        assert!(!run.note(2, "https://b.test/", &LinkStatus::Dns));
+        assert!(run.note(3, "https://c.test/", &LinkStatus::NotFound));
+        assert!(!run.is_offline());
+        assert_eq!(run.unresolved(), [2]);
+
+        let mut offline = LinkCheckRun::new();
+        for (id, url) in [(1, "https://a.test/"), (2, "https://b.test/")] {
+            offline.note(id, url, &LinkStatus::Dns);
+        }
+        // Too few hosts to tell
+        assert!(!offline.is_offline());
+        offline.note(3, "https://c.test/", &LinkStatus::Dns);
+        offline.note(4, "https://d.test/", &LinkStatus::Ok);
+        assert!(offline.is_offline());
+    }
+
+    #[test]
+    fn test_rate_limit_and_order() {
+        let mut limiter = HostRateLimiter::new(Duration::from_secs(1));
+        let now = Instant::now();
+        assert_eq!(limiter.reserve("https://a.test/1", now), Duration::ZERO);
+        assert_eq!(
+            limiter.reserve("https://a.test/2", now),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            limiter.reserve("https://A.test/3", now),
+            Duration::from_secs(2)
+        );
+        assert_eq!(limiter.reserve("https://b.test/", now), Duration::ZERO);
+        let later = now + Duration::from_secs(5);
+        assert_eq!(limiter.reserve("https://a.test/4", later), Duration::ZERO);
+
+        let manager =
+            crate::BookmarkManager::new(axiom_storage::Database::open_in_memory().unwrap());
+        for url in [
+            "https://a.test/1",
+            "https://a.test/2",
+            "https://a.test/3",
+            "https://b.test/1",
+            "javascript:alert(1)",
+            "https://c.test/1",
+        ] {
+            manager.add_bookmark("", url, None).unwrap();
+        }
+        manager.add_folder("Folder", None).unwrap();
+        let urls: Vec<String> = check_order(manager.list().unwrap())
+            .into_iter()
+            .map(|b| b.url)
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                "https://a.test/1",
+                "https://b.test/1",
+                "https://c.test/1",
+                "https://a.test/2",
+                "https://a.test/3"
+            ]
+        );
+    }
+}
@@ -0,0 +1,727 @@
+//! Netscape bookmark files
+//!
+//! The `<!DOCTYPE NETSCAPE-Bookmark-file-1>` format every browser imports and
+//! exports. Reading keeps everything a file says about an entry: dates
+//! (`ADD_DATE`, `LAST_MODIFIED`), icons (`ICON`, `ICON_URI`), `TAGS`,
+//! `SHORTCUTURL`, `<DD>` descriptions, separators and any other attribute
+//! (`PERSONAL_TOOLBAR_FOLDER`, Safari's `FOLDED`), which is written back on
+//! export. Files are not well-formed HTML: tags are matched without regard
+//! to case, closing tags may be missing and stray ones are ignored.
+
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use crate::bookmarks::{normalize_tags, Bookmark, BookmarkKind};
+
+/// One entry of a bookmark file, with the entries of a folder as children
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookmarkNode {
+    pub kind: BookmarkKind,
+    pub title: String,
+    /// Empty for folders and separators
+    pub url: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub modified_at: Option<DateTime<Utc>>,
+    /// Favicon as a `data:` URI
+    pub icon: Option<String>,
+    /// Where the favicon came from
+    pub icon_uri: Option<String>,
+    /// Address bar keyword; on a `%s` URL it names a keyword search
+    pub shortcut: Option<String>,
+    /// Attributes with no field of their own, by their name in the file
+    pub attributes: BTreeMap<String, String>,
+    pub children: Vec<BookmarkNode>,
+}
+
+impl BookmarkNode {
+    fn new(kind: BookmarkKind) -> Self {
+        Self {
+            kind,
+            title: String::new(),
+            url: String::new(),
+            description: String::new(),
+            tags: Vec::new(),
+            created_at: None,
+            modified_at: None,
+            icon: None,
+            icon_uri: None,
+            shortcut: None,
+            attributes: BTreeMap::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Fill fields from a tag's attributes; anything unknown is kept as is
+    fn apply_attributes(&mut self, attributes: Vec<(String, String)>) {
+        for (name, value) in attributes {
+            match name.to_ascii_uppercase().as_str() {
+                "HREF" if self.kind == BookmarkKind::Bookmark => {
+                    self.url = value.trim().to_string()
+                }
+                "ADD_DATE" => self.created_at = parse_timestamp(&value),
+                "LAST_MODIFIED" => self.modified_at = parse_timestamp(&value),
+                "ICON" if !value.is_empty() => self.icon = Some(value),
+                "ICON_URI" if !value.is_empty() => self.icon_uri = Some(value),
+                "SHORTCUTURL" if !value.trim().is_empty() => {
+                    self.shortcut = Some(value.trim().to_string())
+                }
+                "TAGS" => self.tags = normalize_tags(&[value]),
+                _ => {
+                    self.attributes.insert(name, value);
+                }
+            }
+        }
+    }
+}
+
+/// Seconds since the epoch; milliseconds and microseconds (written by some
+/// tools) are recognised by their size. `0` and garbage mean "unknown".
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value: i64 = value.trim().parse().ok()?;
+    let seconds = match value {
+        v if v <= 0 => return None,
+        v if v >= 100_000_000_000_000 => v / 1_000_000,
+        v if v >= 100_000_000_000 => v / 1_000,
+        v => v,
+    };
+    DateTime::from_timestamp(seconds, 0)
+}
+
+const NAMED_ENTITIES: &[(&str, char)] = &[
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", '\u{a0}'),
+    ("copy", '©'),
+    ("reg", '®'),
+    ("trade", '™'),
+    ("hellip", '…'),
+    ("mdash", '—'),
+    ("ndash", '–'),
+    ("lsquo", '‘'),
+    ("rsquo", '’'),
+    ("ldquo", '“'),
+    ("rdquo", '”'),
+    ("laquo", '«'),
+    ("raquo", '»'),
+    ("middot", '·'),
+    ("bull", '•'),
+    ("euro", '€'),
+];
+
+/// Entities that old files write without the closing `;`
+const LEGACY_ENTITIES: &[&str] = &["amp", "lt", "gt", "quot"];
+
+/// Decode character references; anything that is not one is left alone
+pub(crate) fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        match decode_entity(rest) {
+            Some((ch, len)) => {
+                out.push(ch);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The character an entity at the start of `s` stands for, and its length
+fn decode_entity(s: &str) -> Option<(char, usize)> {
+    let body = &s[1..];
+    if let Some(number) = body.strip_prefix('#') {
+        let (digits, radix, prefix) = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => (hex, 16, 2),
+            None => (number, 10, 1),
+        };
+        let len = digits
+            .find(|c: char| !c.is_digit(radix))
+            .unwrap_or(digits.len());
+        if len == 0 {
+            return None;
+        }
+        let code = u32::from_str_radix(&digits[..len], radix).unwrap_or(u32::MAX);
+        let ch = match char::from_u32(code) {
+            Some('\0') | None => char::REPLACEMENT_CHARACTER,
+            Some(ch) => ch,
+        };
+        let semicolon = usize::from(digits[len..].starts_with(';'));
+        return Some((ch, 1 + prefix + len + semicolon));
+    }
+
+    let len = body
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(body.len());
+    let name = &body[..len];
+    let (_, ch) = NAMED_ENTITIES.iter().find(|(n, _)| *n == name)?;
+    if body[len..].starts_with(';') {
+        Some((*ch, 1 + len + 1))
+    } else if LEGACY_ENTITIES.contains(&name) {
+        Some((*ch, 1 + len))
+    } else {
+        None
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Text content: tags dropped, entities decoded, whitespace runs collapsed
+fn clean_text(raw: &str) -> String {
+    let mut text = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(lt) = rest.find('<') {
+        text.push_str(&rest[..lt]);
+        rest = match rest[lt..].find('>') {
+            Some(gt) => &rest[lt + gt + 1..],
+            None => "",
+        };
+    }
+    text.push_str(rest);
+    decode_entities(&text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A start or end tag
+struct Tag {
+    /// Lower-case name
+    name: String,
+    closing: bool,
+    /// Names as written, values decoded; a bare attribute has an empty value
+    attributes: Vec<(String, String)>,
+    /// Byte offset just past the `>`
+    end: usize,
+}
+
+/// Read the tag starting at `start` (a `<`); `None` if it is not one
+fn read_tag(html: &str, start: usize) -> Option<Tag> {
+    let bytes = html.as_bytes();
+    let mut i = start + 1;
+    let closing = bytes.get(i) == Some(&b'/');
+    if closing {
+        i += 1;
+    }
+    let name_start = i;
+    while i < bytes.len() && bytes[i].is_ascii_alphanumeric() {
+        i += 1;
+    }
+    if i == name_start {
+        return None;
+    }
+    let name = html[name_start..i].to_ascii_lowercase();
+
+    let mut attributes = Vec::new();
+    loop {
+        while i < bytes.len() && (bytes[i].is_ascii_whitespace() || bytes[i] == b'/') {
+            i += 1;
+        }
+        match bytes.get(i) {
+            None => break,
+            Some(b'>') => {
+                i += 1;
+                break;
+            }
+            // A new tag before this one closed: stop here and let it be read next
+            Some(b'<') => break,
+            _ => {}
+        }
+
+        let attr_start = i;
+        while i < bytes.len()
+            && !bytes[i].is_ascii_whitespace()
+            && !matches!(bytes[i], b'=' | b'>' | b'<')
+        {
+            i += 1;
+        }
+        let attr_name = html[attr_start..i].to_string();
+        if attr_name.is_empty() {
+            i += 1;
+            continue;
+        }
+
+        let mut j = i;
+        while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+            j += 1;
+        }
+        if bytes.get(j) != Some(&b'=') {
+            attributes.push((attr_name, String::new()));
+            continue;
+        }
+        j += 1;
+        while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+            j += 1;
+        }
+
+        let value = match bytes.get(j) {
+            Some(&quote @ (b'"' | b'\'')) => {
+                let value_start = j + 1;
+                let value_end = html[value_start..]
+                    .find(quote as char)
+                    .map(|k| value_start + k)
+                    .unwrap_or(html.len());
+                i = (value_end + 1).min(html.len());
+                &html[value_start..value_end]
+            }
+            _ => {
+                let value_start = j;
+                let mut k = j;
+                while k < bytes.len() && !bytes[k].is_ascii_whitespace() && bytes[k] != b'>' {
+                    k += 1;
+                }
+                i = k;
+                &html[value_start..k]
+            }
+        };
+        attributes.push((attr_name, decode_entities(value)));
+    }
+
+    Some(Tag {
+        name,
+        closing,
+        attributes,
+        end: i,
+    })
+}
+
+/// Tags that end an entry's text even when its own end tag is missing
+fn is_structural(lower: &str) -> bool {
+    ["<dt", "<dl", "</dl", "<dd", "<hr", "<h3", "<a "]
+        .iter()
+        .any(|tag| lower.starts_with(tag))
+}
+
+/// Text from `start` up to `</{end_tag}` (or the next structural tag if that
+/// is missing); returns the raw text and where parsing resumes
+fn read_until<'a>(
+    html: &'a str,
+    lower: &str,
+    start: usize,
+    end_tag: Option<&str>,
+) -> (&'a str, usize) {
+    let mut search = start;
+    while let Some(offset) = lower[search..].find('<') {
+        let lt = search + offset;
+        let rest = &lower[lt..];
+        if let Some(end_tag) = end_tag {
+            // `</a>` or `</a >`, but not `</abbr>`
+            let name_ends = rest
+                .as_bytes()
+                .get(2 + end_tag.len())
+                .is_none_or(|b| *b == b'>' || b.is_ascii_whitespace());
+            if rest.starts_with("</") && rest[2..].starts_with(end_tag) && name_ends {
+                let resume = lower[lt..]
+                    .find('>')
+                    .map(|gt| lt + gt + 1)
+                    .unwrap_or(lower.len());
+                return (&html[start..lt], resume);
+            }
+        }
+        if is_structural(rest) {
+            return (&html[start..lt], lt);
+        }
+        search = lt + 1;
+    }
+    (&html[start..], html.len())
+}
+
+/// One open `<DL>`; `folder` is false for lists that are not a folder's (the root)
+struct Level {
+    folder: bool,
+    items: Vec<BookmarkNode>,
+}
+
+fn close_level(stack: &mut Vec<Level>) {
+    if stack.len() < 2 {
+        return;
+    }
+    let level = stack.pop().unwrap_or(Level {
+        folder: false,
+        items: Vec::new(),
+    });
+    let Some(parent) = stack.last_mut() else {
+        return;
+    };
+    match parent.items.last_mut() {
+        Some(folder) if level.folder => folder.children.extend(level.items),
+        _ => parent.items.extend(level.items),
+    }
+}
+
+/// Entries of a Netscape bookmark file, as a tree in file order
+pub fn parse_bookmarks_html(html: &str) -> Vec<BookmarkNode> {
+    let lower = html.to_ascii_lowercase();
+    let mut stack = vec![Level {
+        folder: false,
+        items: Vec::new(),
+    }];
+    // The last <H3> has not been followed by anything but its <DD> yet
+    let mut folder_pending = false;
+    // The last entry may still receive a <DD> description
+    let mut describable = false;
+    let mut pos = 0;
+
+    while let Some(offset) = lower[pos..].find('<') {
+        let lt = pos + offset;
+        let rest = &lower[lt..];
+
+        if rest.starts_with("<!--") {
+            pos = lower[lt..]
+                .find("-->")
+                .map(|end| lt + end + 3)
+                .unwrap_or(lower.len());
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            pos = lower[lt..]
+                .find('>')
+                .map(|gt| lt + gt + 1)
+                .unwrap_or(lower.len());
+            continue;
+        }
+        let Some(tag) = read_tag(html, lt) else {
+            pos = lt + 1;
+            continue;
+        };
+        pos = tag.end.max(lt + 1);
+        let level = stack.len() - 1;
+
+        match (tag.name.as_str(), tag.closing) {
+            ("h3", false) => {
+                let (raw, resume) = read_until(html, &lower, pos, Some("h3"));
+                let mut folder = BookmarkNode::new(BookmarkKind::Folder);
+                folder.apply_attributes(tag.attributes);
+                folder.title = clean_text(raw);
+                stack[level].items.push(folder);
+                folder_pending = true;
+                describable = true;
+                pos = resume;
+            }
+            ("a", false) => {
+                let (raw, resume) = read_until(html, &lower, pos, Some("a"));
+                let mut bookmark = BookmarkNode::new(BookmarkKind::Bookmark);
+                bookmark.apply_attributes(tag.attributes);
+                bookmark.title = clean_text(raw);
+                folder_pending = false;
+                describable = !bookmark.url.is_empty();
+                if describable {
+                    if bookmark.title.is_empty() {
+                        bookmark.title = bookmark.url.clone();
+                    }
+                    stack[level].items.push(bookmark);
+                }
+                pos = resume;
+            }
+            ("dd", false) => {
+                let (raw, resume) = read_until(html, &lower, pos, Some("dd"));
+                if describable {
+                    if let Some(item) = stack[level].items.last_mut() {
+                        item.description = clean_text(raw);
+                    }
+                }
+                describable = false;
+                pos = resume;
+            }
+            ("hr", false) => {
+                stack[level]
+                    .items
+                    .push(BookmarkNode::new(BookmarkKind::Separator));
+                folder_pending = false;
+                describable = false;
+            }
+            ("dl", false) => {
+                stack.push(Level {
+                    folder: folder_pending,
+                    items: Vec::new(),
+                });
+                folder_pending = false;
+                describable = false;
+            }
+            ("dl", true) => {
+                close_level(&mut stack);
+                folder_pending = false;
+                describable = false;
+            }
+            _ => {}
+        }
+    }
+
+    while stack.len() > 1 {
+        close_level(&mut stack);
+    }
+    stack.pop().map(|level| level.items).unwrap_or_default()
+}
+
+/// Netscape bookmark file of `items` (as returned by [`crate::BookmarkManager::list`])
+pub fn export_bookmarks_html(items: &[Bookmark]) -> String {
+    fn attributes(item: &Bookmark) -> String {
+        let mut out = format!(
+            " ADD_DATE=\"{}\" LAST_MODIFIED=\"{}\"",
+            item.created_at.timestamp(),
+            item.modified_at.timestamp()
+        );
+        let optional = [
+            ("ICON_URI", item.icon_uri.as_deref()),
+            ("ICON", item.icon.as_deref()),
+            ("SHORTCUTURL", item.shortcut.as_deref()),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                out.push_str(&format!(" {name}=\"{}\"", escape_html(value)));
+            }
+        }
+        if !item.tags.is_empty() {
+            out.push_str(&format!(" TAGS=\"{}\"", escape_html(&item.tags.join(","))));
+        }
+        for (name, value) in &item.attributes {
+            if value.is_empty() {
+                out.push_str(&format!(" {name}"));
+            } else {
+                out.push_str(&format!(" {name}=\"{}\"", escape_html(value)));
+            }
+        }
+        out
+    }
+
+    fn render(items: &[Bookmark], parent_id: Option<i64>, out: &mut String, indent: usize) {
+        let pad = " ".repeat(indent);
+        for item in items.iter().filter(|b| b.parent_id == parent_id) {
+            match item.kind {
+                BookmarkKind::Folder => out.push_str(&format!(
+                    "{pad}<DT><H3{}>{}</H3>\n",
+                    attributes(item),
+                    escape_html(&item.title)
+                )),
+                BookmarkKind::Bookmark => out.push_str(&format!(
+                    "{pad}<DT><A HREF=\"{}\"{}>{}</A>\n",
+                    escape_html(&item.url),
+                    attributes(item),
+                    escape_html(&item.title)
+                )),
+                BookmarkKind::Separator => out.push_str(&format!("{pad}<HR>\n")),
+            }
+            if !item.description.is_empty() {
+                out.push_str(&format!("{pad}<DD>{}\n", escape_html(&item.description)));
+            }
+            if item.kind == BookmarkKind::Folder {
+                out.push_str(&format!("{pad}<DL><p>\n"));
+                render(items, Some(item.id), out, indent + 4);
+                out.push_str(&format!("{pad}</DL><p>\n"));
+            }
+        }
+    }
+
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE NETSCAPE-Bookmark-file-1>\n");
+    out.push_str("<!-- This is an automatically generated file.\n");
+    out.push_str("     It will be read and overwritten.\n");
+    out.push_str("     DO NOT EDIT! -->\n");
+    out.push_str("<META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n");
+    out.push_str("<TITLE>Bookmarks</TITLE>\n");
+    out.push_str("<H1>Bookmarks</H1>\n");
+    out.push_str("<DL><p>\n");
+    render(items, None, &mut out, 4);
+    out.push_str("</DL><p>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BookmarkManager;
+    use axiom_storage::Database;
+
+    const FIREFOX: &str = include_str!("../testdata/netscape/firefox.html");
+    const CHROME: &str = include_str!("../testdata/netscape/chrome.html");
+    const SAFARI: &str = include_str!("../testdata/netscape/safari.html");
+
+    fn titles(nodes: &[BookmarkNode]) -> Vec<&str> {
+        nodes.iter().map(|n| n.title.as_str()).collect()
+    }
+
+    fn at(seconds: i64) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(seconds, 0)
+    }
+
+    #[test]
+    fn test_firefox_export() {
+        let nodes = parse_bookmarks_html(FIREFOX);
+        assert_eq!(
+            titles(&nodes),
+            [
+                "Mozilla Firefox",
+                "",
+                "MDN: HTML",
+                "Bookmarks Toolbar",
+                "Other Bookmarks"
+            ]
+        );
+        assert_eq!(nodes[1].kind, BookmarkKind::Separator);
+
+        let help = &nodes[0].children[0];
+        assert_eq!(help.title, "Get Help");
+        assert_eq!(help.created_at, at(1_700_000_001));
+        assert_eq!(help.modified_at, at(1_700_000_002));
+        assert_eq!(
+            help.icon_uri.as_deref(),
+            Some("https://support.mozilla.org/favicon.ico")
+        );
+        assert_eq!(
+            help.icon.as_deref(),
+            Some("data:image/png;base64,iVBORw0KGgo=")
+        );
+
+        let mdn = &nodes[2];
+        assert_eq!(mdn.shortcut.as_deref(), Some("mdn"));
+        assert_eq!(mdn.tags, ["docs", "web"]);
+        assert_eq!(mdn.description, "The <html> element & its friends");
+
+        let toolbar = &nodes[3];
+        assert_eq!(toolbar.attributes["PERSONAL_TOOLBAR_FOLDER"], "true");
+        assert!(toolbar
+            .description
+            .starts_with("Add bookmarks to this folder"));
+        assert_eq!(
+            toolbar.children[1].url,
+            "https://news.ycombinator.com/item?id=1&p=2"
+        );
+        assert_eq!(toolbar.children[1].title, "Hacker News – Item");
+        assert_eq!(nodes[4].attributes["UNFILED_BOOKMARKS_FOLDER"], "true");
+        assert_eq!(nodes[4].children[0].url, "place:sort=8&maxResults=10");
+    }
+
+    #[test]
+    fn test_chrome_export() {
+        let nodes = parse_bookmarks_html(CHROME);
+        assert_eq!(titles(&nodes), ["Bookmarks bar", "Wikipedia"]);
+
+        let bar = &nodes[0];
+        assert_eq!(titles(&bar.children), ["GitHub", "Work", "Empty"]);
+        assert!(bar.children[2].children.is_empty());
+        assert_eq!(bar.children[2].modified_at, None);
+
+        let work = &bar.children[1];
+        assert_eq!(work.children[1].url, "https://example.com/?q=a&b=c");
+        assert_eq!(work.children[1].title, "Tom & Jerry's \"Page\"");
+        // Chrome writes no LAST_MODIFIED for links
+        assert_eq!(work.children[0].created_at, at(1_699_999_250));
+        assert_eq!(work.children[0].modified_at, None);
+    }
+
+    #[test]
+    fn test_safari_export() {
+        // Safari has no list around the top level and bare attributes
+        let nodes = parse_bookmarks_html(SAFARI);
+        assert_eq!(
+            titles(&nodes),
+            ["Favorites", "Bookmarks Menu", "Reading List", "Swift.org"]
+        );
+        assert_eq!(nodes[0].attributes["FOLDED"], "");
+        assert_eq!(titles(&nodes[0].children), ["Apple", "iCloud"]);
+        assert!(nodes[1].children.is_empty());
+        assert_eq!(nodes[2].attributes["id"], "com.apple.ReadingList");
+        assert_eq!(nodes[2].children[0].description, "Preview of the article…");
+        assert_eq!(nodes[3].created_at, None);
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        let html = "<dl><P>\r\n\
+            <dt><h3 add_date=1700000000>Unclosed folder\r\n\
+            <dl>\r\n\
+              <DT><A href = 'https://a.test/?x=1&y=2' TAGS=\"one, two,one\">A &amp B &#x1F600; &bogus; &</A>\r\n\
+              <DT><A HREF=\"https://b.test/\">B without end tag\r\n\
+              <DT><a HREF=\"https://c.test/\"><b>Bold</b>   title</a>\r\n\
+              <DT><A HREF=\"https://f.test/\">Use <abbr>HTML</abbr> here</a >\r\n\
+              <DT><A>no href</A>\r\n\
+              <!-- <DT><A HREF=\"https://commented.test/\">Hidden</A> -->\r\n\
+            </DL></DL></DL></DL>\r\n\
+            <DT><A HREF=\"https://d.test/\" ADD_DATE=\"1700000000000\" LAST_MODIFIED=\"junk\">D</A>\r\n\
+            <DT><A HREF=\"https://e.test/\">E";
+
+        let nodes = parse_bookmarks_html(html);
+        assert_eq!(titles(&nodes), ["Unclosed folder", "D", "E"]);
+        assert_eq!(nodes[0].created_at, at(1_700_000_000));
+
+        let children = &nodes[0].children;
+        assert_eq!(
+            titles(children),
+            [
+                "A & B 😀 &bogus; &",
+                "B without end tag",
+                "Bold title",
+                "Use HTML here"
+            ]
+        );
+        assert_eq!(children[0].url, "https://a.test/?x=1&y=2");
+        assert_eq!(children[0].tags, ["one", "two"]);
+        // Milliseconds are recognised, garbage is ignored
+        assert_eq!(nodes[1].created_at, at(1_700_000_000));
+        assert_eq!(nodes[1].modified_at, None);
+
+        assert!(parse_bookmarks_html("").is_empty());
+        assert!(parse_bookmarks_html("<<<>>> </a> <dl").is_empty());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for corpus in [FIREFOX, CHROME, SAFARI] {
+            let first = BookmarkManager::new(Database::open_in_memory().unwrap());
+            first.import_tree(&parse_bookmarks_html(corpus)).unwrap();
+            let exported = export_bookmarks_html(&first.list().unwrap());
+
+            let second = BookmarkManager::new(Database::open_in_memory().unwrap());
+            second
+                .import_tree(&parse_bookmarks_html(&exported))
+                .unwrap();
+            assert_eq!(export_bookmarks_html(&second.list().unwrap()), exported);
+
+            // Ids differ between profiles and separators carry no dates in the file
+            let strip = |items: Vec<Bookmark>| -> Vec<Bookmark> {
+                items
+                    .into_iter()
+                    .filter(|b| b.kind != BookmarkKind::Separator)
+                    .map(|b| Bookmark {
+                        id: 0,
+                        parent_id: None,
+                        ..b
+                    })
+                    .collect()
+            };
+            assert_eq!(strip(first.list().unwrap()), strip(second.list().unwrap()));
+        }
+
+        let manager = BookmarkManager::new(Database::open_in_memory().unwrap());
+        manager.import_tree(&parse_bookmarks_html(FIREFOX)).unwrap();
+        let exported = export_bookmarks_html(&manager.list().unwrap());
+        assert!(exported.contains("SHORTCUTURL=\"mdn\""));
+        assert!(exported.contains("TAGS=\"docs,web\""));
+        assert!(exported.contains("PERSONAL_TOOLBAR_FOLDER=\"true\""));
+        assert!(exported.contains("<DD>The &lt;html&gt; element &amp; its friends"));
+    }
+}
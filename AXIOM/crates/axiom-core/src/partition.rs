@@ -0,0 +1,332 @@
+//! Webview storage partitions
+//!
+//! Web content storage (cookies, localStorage, cache) lives in one directory
+//! per site (registrable domain, so `mail.example.co.uk` and
+//! `www.example.co.uk` share `example.co.uk`) under
+//! `Config::webview_partitions_dir`. Sessions flagged as isolated get their
+//! own subtree, so accounts are not shared across sessions:
+//!
+//! ```text
+//! webview-partitions/<site>                      shared
+//! webview-partitions/session-<session id>/<site> isolated
+//! webview-partitions/private-<session id>/<site> private, wiped on close
+//! ```
+//!
+//! Partitions used to be keyed by full host; [`adopt_host_partitions`] folds
+//! such directories into their site key the first time it is needed.
+//!
+//! Site keys come from the caller's public suffix list, so a browser with an
+//! updated list partitions by it.
+
+use std::path::{Path, PathBuf};
+
+use axiom_privacy::PublicSuffixList;
+
+const SESSION_DIR_PREFIX: &str = "session-";
+const PRIVATE_DIR_PREFIX: &str = "private-";
+
+fn url_host(url: &str) -> Option<String> {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
+}
+
+/// Filesystem-safe partition key for a URL (its site: eTLD+1, or the host for IPs)
+pub fn partition_key(url: &str, psl: &PublicSuffixList) -> String {
+    match url_host(url) {
+        Some(host) => sanitize(&psl.site_for_host(&host)),
+        None => "blank".to_string(),
+    }
+}
+
+/// Root of a session's isolated partitions
+pub fn session_partition_root(root: &Path, session_id: &str) -> PathBuf {
+    root.join(format!("{}{}", SESSION_DIR_PREFIX, sanitize(session_id)))
+}
+
+/// Root of a private session's partitions
+pub fn private_partition_root(root: &Path, session_id: &str) -> PathBuf {
+    root.join(format!("{}{}", PRIVATE_DIR_PREFIX, sanitize(session_id)))
+}
+
+/// Remove every private session's partitions; returns how many were removed
+pub fn remove_private_partitions(root: &Path) -> usize {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(PRIVATE_DIR_PREFIX)
+                && entry.file_type().is_ok_and(|t| t.is_dir())
+        })
+        .filter(|entry| match std::fs::remove_dir_all(entry.path()) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!(path = ?entry.path(), "Failed to remove private partition: {}", e);
+                false
+            }
+        })
+        .count()
+}
+
+/// Remove the partitions of `domain` from the shared root and every session's subtree
+///
+/// Storage is per site, so this removes the whole site containing `domain`,
+/// plus any host-keyed directories left from before site keys. Returns how
+/// many directories were removed.
+pub fn remove_site_partitions(root: &Path, domain: &str, psl: &PublicSuffixList) -> usize {
+    let site = sanitize(&psl.site_for_host(domain));
+    let is_partition_of = |name: &str| name == site || axiom_privacy::host_in_domain(name, domain);
+
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return 0;
+    };
+    let mut bases = vec![root.to_path_buf()];
+    bases.extend(
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                (name.starts_with(SESSION_DIR_PREFIX) || name.starts_with(PRIVATE_DIR_PREFIX))
+                    && entry.file_type().is_ok_and(|t| t.is_dir())
+            })
+            .map(|entry| entry.path()),
+    );
+
+    let mut removed = 0;
+    for base in bases {
+        let Ok(entries) = std::fs::read_dir(&base) else {
+            continue;
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !is_partition_of(&name) || !entry.file_type().is_ok_and(|t| t.is_dir()) {
+                continue;
+            }
+            match std::fs::remove_dir_all(entry.path()) {
+                Ok(()) => removed += 1,
+                Err(e) => {
+                    tracing::warn!(path = ?entry.path(), "Failed to remove site partition: {}", e)
+                }
+            }
+        }
+    }
+    removed
+}
+
+/// Data directory for a webview showing `url`
+pub fn partition_dir(
+    root: &Path,
+    isolated_session: Option<&str>,
+    url: &str,
+    psl: &PublicSuffixList,
+) -> PathBuf {
+    let base = match isolated_session {
+        Some(session_id) => session_partition_root(root, session_id),
+        None => root.to_path_buf(),
+    };
+    base.join(partition_key(url, psl))
+}
+
+/// Fold host-keyed partitions from before site keys into the site's directory
+///
+/// Every old directory of a host on the same site is folded in: the first
+/// becomes the site directory when there is none yet, later ones contribute
+/// the entries the site directory lacks. Entries present in both stay in the
+/// host directory, which is then kept; failures leave it in place too.
+pub fn adopt_host_partitions(
+    root: &Path,
+    isolated_session: Option<&str>,
+    url: &str,
+    psl: &PublicSuffixList,
+) {
+    if url_host(url).is_none() {
+        return;
+    }
+    let site_dir = partition_dir(root, isolated_session, url, psl);
+    let (Some(base), Some(site_key)) = (
+        site_dir.parent(),
+        site_dir.file_name().and_then(|name| name.to_str()),
+    ) else {
+        return;
+    };
+    let Ok(entries) = std::fs::read_dir(base) else {
+        return;
+    };
+
+    let host_dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name != site_key
+                && !name.starts_with(SESSION_DIR_PREFIX)
+                && !name.starts_with(PRIVATE_DIR_PREFIX)
+                && sanitize(&psl.site_for_host(&name)) == site_key
+                && entry.file_type().is_ok_and(|t| t.is_dir())
+        })
+        .map(|entry| entry.path())
+        .collect();
+    for host_dir in host_dirs {
+        merge_host_partition(&host_dir, &site_dir);
+    }
+}
+
+fn merge_host_partition(host_dir: &Path, site_dir: &Path) {
+    if !site_dir.exists() {
+        match std::fs::rename(host_dir, site_dir) {
+            Ok(()) => tracing::info!(path = ?host_dir, "Moved webview partition to its site key"),
+            Err(e) => tracing::warn!(path = ?host_dir, "Failed to move webview partition: {}", e),
+        }
+        return;
+    }
+
+    let Ok(entries) = std::fs::read_dir(host_dir) else {
+        return;
+    };
+    let mut kept = 0;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let target = site_dir.join(entry.file_name());
+        if target.exists() {
+            kept += 1;
+        } else if let Err(e) = std::fs::rename(entry.path(), &target) {
+            tracing::warn!(path = ?entry.path(), "Failed to merge webview partition: {}", e);
+            kept += 1;
+        }
+    }
+
+    if kept == 0 {
+        let _ = std::fs::remove_dir(host_dir);
+        tracing::info!(path = ?host_dir, "Merged webview partition into its site key");
+    } else {
+        tracing::debug!(path = ?host_dir, kept, "Kept conflicting webview partition entries");
+    }
+}
+
+/// Total size in bytes of all files below `path` (0 if it does not exist)
+pub fn directory_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => directory_size(&entry.path()),
+            Ok(t) if t.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '.' || ch == '-' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_dirs() {
+        let root = Path::new("/data/webview-partitions");
+        let psl = PublicSuffixList::embedded();
+
+        assert_eq!(
+            partition_key("https://GitHub.com/login", &psl),
+            "github.com"
+        );
+        assert_eq!(
+            partition_key("https://mail.example.co.uk/inbox", &psl),
+            "example.co.uk"
+        );
+        assert_eq!(partition_key("http://127.0.0.1:8080/", &psl), "127.0.0.1");
+        assert_eq!(partition_key("about:blank", &psl), "blank");
+        assert_eq!(
+            partition_dir(root, None, "https://github.com/", &psl),
+            root.join("github.com")
+        );
+        assert_eq!(
+            partition_dir(root, Some("abc-123"), "https://github.com/", &psl),
+            root.join("session-abc-123").join("github.com")
+        );
+        assert_eq!(
+            private_partition_root(root, "abc-123"),
+            root.join("private-abc-123")
+        );
+        assert_eq!(
+            session_partition_root(root, "../escape"),
+            root.join("session-.._escape")
+        );
+
+        // Keys follow the list they are given
+        let custom = PublicSuffixList::parse("com\nexample.com\n");
+        assert_eq!(
+            partition_key("https://a.example.com/", &custom),
+            "a.example.com"
+        );
+    }
+
+    #[test]
+    fn test_adopt_host_partitions() {
+        let root = std::env::temp_dir().join(format!("axiom-partitions-{}", uuid::Uuid::new_v4()));
+        let psl = PublicSuffixList::embedded();
+        std::fs::create_dir_all(root.join("mail.example.com").join("Cookies")).unwrap();
+        std::fs::create_dir_all(root.join("www.example.com").join("Cookies")).unwrap();
+        std::fs::create_dir_all(root.join("www.example.com").join("Local Storage")).unwrap();
+        std::fs::create_dir_all(root.join("other.org")).unwrap();
+
+        adopt_host_partitions(&root, None, "https://mail.example.com/", &psl);
+        let site_dir = root.join("example.com");
+        assert!(site_dir.join("Cookies").is_dir());
+        assert!(site_dir.join("Local Storage").is_dir());
+        // Only the conflicting entry stays behind
+        let leftovers: Vec<PathBuf> = ["mail.example.com", "www.example.com"]
+            .iter()
+            .map(|host| root.join(host))
+            .filter(|dir| dir.exists())
+            .collect();
+        assert_eq!(leftovers.len(), 1);
+        assert!(leftovers[0].join("Cookies").is_dir());
+        assert!(root.join("other.org").is_dir());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_remove_site_partitions() {
+        let root = std::env::temp_dir().join(format!("axiom-partitions-{}", uuid::Uuid::new_v4()));
+        let psl = PublicSuffixList::embedded();
+        for dir in [
+            root.join("example.com"),
+            root.join("www.example.com"),
+            root.join("notexample.com"),
+            root.join("session-abc").join("example.com"),
+            root.join("private-def").join("example.com"),
+            root.join("session-abc").join("rust-lang.org"),
+        ] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+
+        assert_eq!(remove_site_partitions(&root, "mail.example.com", &psl), 3);
+        assert!(root.join("www.example.com").is_dir());
+        assert_eq!(remove_site_partitions(&root, "example.com", &psl), 1);
+        assert!(root.join("notexample.com").is_dir());
+        assert!(root.join("session-abc").join("rust-lang.org").is_dir());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
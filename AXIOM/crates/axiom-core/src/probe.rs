@@ -0,0 +1,253 @@
+//! URL reachability probes
+//!
+//! [`probe_url`] checks that a URL answers (HEAD, then a ranged GET for
+//! servers that refuse HEAD) and classifies failures. Error pages, the
+//! HTTPS-Only fallback and the bookmark link checker all use it.
+
+use parking_lot::Mutex;
+use reqwest::redirect::Policy;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::link_check::LinkProbe;
+
+/// What probing a URL found, with the error message for display
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProbeInfo {
+    pub ok: bool,
+    pub status: Option<u16>,
+    pub final_url: Option<String>,
+    pub error_kind: Option<String>,
+    pub error_message: Option<String>,
+    /// Redirects were followed and all of them were permanent (301/308)
+    pub permanent_redirect: bool,
+}
+
+impl ProbeInfo {
+    fn failed(kind: &str, message: String) -> Self {
+        Self {
+            ok: false,
+            status: None,
+            final_url: None,
+            error_kind: Some(kind.to_string()),
+            error_message: Some(message),
+            permanent_redirect: false,
+        }
+    }
+
+    /// Not an http(s) URL: nothing to probe
+    fn skipped(url: &str) -> Self {
+        Self {
+            ok: true,
+            status: None,
+            final_url: Some(url.to_string()),
+            error_kind: None,
+            error_message: None,
+            permanent_redirect: false,
+        }
+    }
+}
+
+impl From<ProbeInfo> for LinkProbe {
+    fn from(probe: ProbeInfo) -> Self {
+        Self {
+            status: probe.status,
+            final_url: probe.final_url,
+            permanent_redirect: probe.permanent_redirect,
+            error_kind: probe.error_kind,
+        }
+    }
+}
+
+/// Check that a URL is reachable, classifying failures (`dns`, `tls`,
+/// `timeout`, `connect`, ...)
+pub async fn probe_url(url: &str) -> ProbeInfo {
+    let trimmed = url.trim();
+    if trimmed.is_empty() {
+        return ProbeInfo::failed("invalid_url", "URL is empty".to_string());
+    }
+    if trimmed.starts_with("about:") || trimmed.starts_with("axiom:") {
+        return ProbeInfo::skipped(trimmed);
+    }
+
+    let parsed = match url::Url::parse(trimmed) {
+        Ok(u) => u,
+        Err(e) => return ProbeInfo::failed("invalid_url", e.to_string()),
+    };
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return ProbeInfo::skipped(trimmed);
+    }
+
+    // Status of each redirect followed, to tell permanent moves from temporary ones
+    let hops: Arc<Mutex<Vec<u16>>> = Arc::default();
+    let policy_hops = hops.clone();
+    let client = match reqwest::Client::builder()
+        .redirect(Policy::custom(move |attempt| {
+            if attempt.previous().len() > 5 {
+                return attempt.error("too many redirects");
+            }
+            policy_hops.lock().push(attempt.status().as_u16());
+            attempt.follow()
+        }))
+        .timeout(Duration::from_secs(6))
+        .build()
+    {
+        Ok(c) => c,
+        Err(e) => return ProbeInfo::failed("client", e.to_string()),
+    };
+
+    let response = match client.head(parsed.clone()).send().await {
+        // Some servers refuse HEAD; ask them for the first byte instead
+        Ok(resp) if !matches!(resp.status().as_u16(), 405 | 501) => Ok(resp),
+        _ => {
+            hops.lock().clear();
+            client
+                .get(parsed.clone())
+                .header(reqwest::header::RANGE, "bytes=0-0")
+                .send()
+                .await
+        }
+    };
+
+    match response {
+        Ok(resp) => {
+            let hops = hops.lock();
+            ProbeInfo {
+                ok: true,
+                status: Some(resp.status().as_u16()),
+                final_url: Some(resp.url().to_string()),
+                error_kind: None,
+                error_message: None,
+                permanent_redirect: !hops.is_empty()
+                    && hops.iter().all(|status| matches!(status, 301 | 308)),
+            }
+        }
+        Err(e) => {
+            // The cause (`dns error`, `invalid peer certificate`) is only in the sources
+            let msg = error_chain(&e);
+            let kind = if e.is_timeout() {
+                "timeout"
+            } else if e.is_connect() {
+                let lowered = msg.to_lowercase();
+                if lowered.contains("tls")
+                    || lowered.contains("certificate")
+                    || lowered.contains("handshake")
+                {
+                    "tls"
+                } else if !resolves(e.url().unwrap_or(&parsed)).await {
+                    // Asked directly: the message alone cannot tell a lookup
+                    // failure from a refused connection
+                    "dns"
+                } else {
+                    "connect"
+                }
+            } else if e.is_request() {
+                "request"
+            } else {
+                "unknown"
+            };
+            ProbeInfo::failed(kind, msg)
+        }
+    }
+}
+
+/// Whether the host of `url` resolves to at least one address
+async fn resolves(url: &url::Url) -> bool {
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        return false;
+    };
+    tokio::net::lookup_host((host.trim_matches(['[', ']']), port))
+        .await
+        .is_ok_and(|mut addrs| addrs.next().is_some())
+}
+
+/// `error: source: source ...`
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(inner) = source {
+        message.push_str(": ");
+        message.push_str(&inner.to_string());
+        source = inner.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link_check::LinkStatus;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Answers each request by path: `/moved` (301) and `/login` (302)
+    /// redirect to `/new`, `/gone` is a 404 and everything else a 200
+    fn serve() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().filter_map(|s| s.ok()) {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let request = String::from_utf8_lossy(&request);
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+                let head = match path {
+                    "/moved" => "301 Moved Permanently\r\nLocation: /new",
+                    "/login" => "302 Found\r\nLocation: /new",
+                    "/gone" => "404 Not Found",
+                    _ => "200 OK",
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {head}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+            }
+        });
+        base
+    }
+
+    async fn status_of(url: &str) -> LinkStatus {
+        LinkStatus::classify(url, &LinkProbe::from(probe_url(url).await))
+    }
+
+    #[tokio::test]
+    async fn test_probe_statuses() {
+        let base = serve();
+
+        assert_eq!(status_of(&format!("{base}/")).await, LinkStatus::Ok);
+        assert_eq!(
+            status_of(&format!("{base}/moved")).await,
+            LinkStatus::Redirected {
+                to: format!("{base}/new")
+            }
+        );
+        // Temporary redirects leave the bookmark alone
+        assert_eq!(status_of(&format!("{base}/login")).await, LinkStatus::Ok);
+        assert_eq!(
+            status_of(&format!("{base}/gone")).await,
+            LinkStatus::NotFound
+        );
+
+        // A closed port resolves fine and is not a DNS failure
+        let closed = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}/", listener.local_addr().unwrap())
+        };
+        let probe = probe_url(&closed).await;
+        assert!(!probe.ok);
+        assert_eq!(probe.error_kind.as_deref(), Some("connect"));
+
+        assert_eq!(probe_url("ftp://example.com/").await.error_kind, None);
+        assert_eq!(
+            probe_url("not a url").await.error_kind.as_deref(),
+            Some("invalid_url")
+        );
+    }
+}
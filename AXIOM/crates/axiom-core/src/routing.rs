@@ -0,0 +1,393 @@
+//! URL routing rules
+//!
+//! Rules map URL patterns to a target session so that, for example, every
+//! `*.atlassian.net` link opens in the "Work" session. Rules are evaluated in
+//! position order and the first enabled match wins.
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use axiom_storage::Database;
+
+use crate::error::CoreError;
+use crate::Result;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum RoutePattern {
+    /// Exact host match (`github.com` does not match `gist.github.com`)
+    Host(String),
+    /// Host or any subdomain of it (`github.com` matches `gist.github.com`)
+    HostSuffix(String),
+    /// Literal prefix of the full URL
+    UrlPrefix(String),
+    /// Regular expression tested against the full URL
+    Regex(String),
+}
+
+impl RoutePattern {
+    fn kind(&self) -> &'static str {
+        match self {
+            RoutePattern::Host(_) => "host",
+            RoutePattern::HostSuffix(_) => "host_suffix",
+            RoutePattern::UrlPrefix(_) => "url_prefix",
+            RoutePattern::Regex(_) => "regex",
+        }
+    }
+
+    fn value(&self) -> &str {
+        match self {
+            RoutePattern::Host(v)
+            | RoutePattern::HostSuffix(v)
+            | RoutePattern::UrlPrefix(v)
+            | RoutePattern::Regex(v) => v,
+        }
+    }
+
+    fn from_parts(kind: &str, value: String) -> Option<Self> {
+        match kind {
+            "host" => Some(RoutePattern::Host(value)),
+            "host_suffix" => Some(RoutePattern::HostSuffix(value)),
+            "url_prefix" => Some(RoutePattern::UrlPrefix(value)),
+            "regex" => Some(RoutePattern::Regex(value)),
+            _ => None,
+        }
+    }
+
+    /// Trim and lowercase host patterns; reject empty or invalid patterns
+    fn normalized(self) -> Result<Self> {
+        let pattern = match self {
+            RoutePattern::Host(v) => RoutePattern::Host(normalize_host(&v)),
+            RoutePattern::HostSuffix(v) => RoutePattern::HostSuffix(normalize_host(&v)),
+            RoutePattern::UrlPrefix(v) => RoutePattern::UrlPrefix(v.trim().to_string()),
+            RoutePattern::Regex(v) => RoutePattern::Regex(v.trim().to_string()),
+        };
+
+        if pattern.value().is_empty() {
+            return Err(CoreError::InvalidRoutingRule(
+                "Pattern cannot be empty".to_string(),
+            ));
+        }
+        if let RoutePattern::Regex(source) = &pattern {
+            Regex::new(source).map_err(|e| CoreError::InvalidRoutingRule(e.to_string()))?;
+        }
+
+        Ok(pattern)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutingRule {
+    pub id: String,
+    pub pattern: RoutePattern,
+    pub target_session_id: String,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+struct CompiledRule {
+    rule: RoutingRule,
+    regex: Option<Regex>,
+}
+
+impl CompiledRule {
+    fn new(rule: RoutingRule) -> Self {
+        let regex = match &rule.pattern {
+            RoutePattern::Regex(source) => Regex::new(source).ok(),
+            _ => None,
+        };
+        Self { rule, regex }
+    }
+
+    fn matches(&self, url: &str, host: Option<&str>) -> bool {
+        match &self.rule.pattern {
+            RoutePattern::Host(pattern) => host == Some(pattern.as_str()),
+            RoutePattern::HostSuffix(pattern) => host.is_some_and(|h| {
+                h == pattern
+                    || h.strip_suffix(pattern.as_str())
+                        .is_some_and(|rest| rest.ends_with('.'))
+            }),
+            RoutePattern::UrlPrefix(prefix) => url.starts_with(prefix.as_str()),
+            RoutePattern::Regex(_) => self.regex.as_ref().is_some_and(|re| re.is_match(url)),
+        }
+    }
+}
+
+pub struct UrlRouter {
+    /// Rules in evaluation order
+    rules: Arc<RwLock<Vec<CompiledRule>>>,
+    /// Database for persistence
+    db: Database,
+}
+
+impl UrlRouter {
+    pub fn new(db: Database) -> Self {
+        Self {
+            rules: Arc::new(RwLock::new(Vec::new())),
+            db,
+        }
+    }
+
+    /// Load rules from database
+    pub fn load(&self) -> Result<()> {
+        let rules: Vec<RoutingRule> = self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, kind, pattern, session_id, enabled, created_at
+                 FROM routing_rules ORDER BY position ASC, created_at ASC",
+            )?;
+
+            let rules = stmt
+                .query_map([], |row| {
+                    let kind: String = row.get(1)?;
+                    let created_str: String = row.get(5)?;
+                    let created_at = DateTime::parse_from_rfc3339(&created_str)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now());
+
+                    Ok(
+                        RoutePattern::from_parts(&kind, row.get(2)?).map(|pattern| RoutingRule {
+                            id: row.get(0).unwrap_or_default(),
+                            pattern,
+                            target_session_id: row.get(3).unwrap_or_default(),
+                            enabled: row.get::<_, i32>(4).unwrap_or(1) != 0,
+                            created_at,
+                        }),
+                    )
+                })?
+                .filter_map(|r| r.ok().flatten())
+                .collect();
+
+            Ok(rules)
+        })?;
+
+        *self.rules.write() = rules.into_iter().map(CompiledRule::new).collect();
+        Ok(())
+    }
+
+    pub fn list(&self) -> Vec<RoutingRule> {
+        self.rules.read().iter().map(|c| c.rule.clone()).collect()
+    }
+
+    /// Add a rule at the end of the evaluation order
+    pub fn add(&self, pattern: RoutePattern, target_session_id: String) -> Result<RoutingRule> {
+        let rule = RoutingRule {
+            id: uuid::Uuid::new_v4().to_string(),
+            pattern: pattern.normalized()?,
+            target_session_id,
+            enabled: true,
+            created_at: Utc::now(),
+        };
+
+        let position = self.rules.read().len();
+        self.save_rule(&rule, position)?;
+        self.rules.write().push(CompiledRule::new(rule.clone()));
+
+        tracing::info!(rule_id = %rule.id, session_id = %rule.target_session_id, "Added routing rule");
+
+        Ok(rule)
+    }
+
+    pub fn update(
+        &self,
+        id: &str,
+        pattern: RoutePattern,
+        target_session_id: String,
+        enabled: bool,
+    ) -> Result<RoutingRule> {
+        let pattern = pattern.normalized()?;
+        let mut rules = self.rules.write();
+        let position = rules
+            .iter()
+            .position(|c| c.rule.id == id)
+            .ok_or_else(|| CoreError::RoutingRuleNotFound(id.to_string()))?;
+
+        let mut rule = rules[position].rule.clone();
+        rule.pattern = pattern;
+        rule.target_session_id = target_session_id;
+        rule.enabled = enabled;
+
+        self.save_rule(&rule, position)?;
+        rules[position] = CompiledRule::new(rule.clone());
+
+        Ok(rule)
+    }
+
+    pub fn remove(&self, id: &str) -> Result<()> {
+        self.db.with_connection(|conn| {
+            conn.execute("DELETE FROM routing_rules WHERE id = ?1", [id])?;
+            Ok(())
+        })?;
+        self.rules.write().retain(|c| c.rule.id != id);
+        Ok(())
+    }
+
+    /// Drop cached rules targeting a deleted session (the database cascades)
+    pub fn forget_session(&self, session_id: &str) {
+        self.rules
+            .write()
+            .retain(|c| c.rule.target_session_id != session_id);
+    }
+
+    /// First enabled rule matching the URL
+    pub fn route(&self, url: &str) -> Option<RoutingRule> {
+        let host = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.to_lowercase()));
+
+        self.rules
+            .read()
+            .iter()
+            .find(|c| c.rule.enabled && c.matches(url, host.as_deref()))
+            .map(|c| c.rule.clone())
+    }
+
+    fn save_rule(&self, rule: &RoutingRule, position: usize) -> Result<()> {
+        self.db.with_connection(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO routing_rules
+                 (id, kind, pattern, session_id, enabled, position, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                rusqlite::params![
+                    rule.id,
+                    rule.pattern.kind(),
+                    rule.pattern.value(),
+                    rule.target_session_id,
+                    rule.enabled as i32,
+                    position as i64,
+                    rule.created_at.to_rfc3339(),
+                ],
+            )?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+impl Clone for UrlRouter {
+    fn clone(&self) -> Self {
+        Self {
+            rules: Arc::clone(&self.rules),
+            db: self.db.clone(),
+        }
+    }
+}
+
+fn normalize_host(value: &str) -> String {
+    value
+        .trim()
+        .trim_start_matches("*.")
+        .trim_start_matches('.')
+        .trim_end_matches('.')
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn router_with_session() -> UrlRouter {
+        let db = Database::open_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO sessions (id, name, created_at, updated_at) VALUES ('work', 'Work', '', '')",
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        UrlRouter::new(db)
+    }
+
+    #[test]
+    fn test_route_patterns() {
+        let router = router_with_session();
+        router
+            .add(
+                RoutePattern::Host("GitHub.com".to_string()),
+                "work".to_string(),
+            )
+            .unwrap();
+        router
+            .add(
+                RoutePattern::HostSuffix("*.atlassian.net".to_string()),
+                "work".to_string(),
+            )
+            .unwrap();
+        router
+            .add(
+                RoutePattern::UrlPrefix("https://docs.google.com/a/acme".to_string()),
+                "work".to_string(),
+            )
+            .unwrap();
+        router
+            .add(
+                RoutePattern::Regex(r"^https://[^/]+/acme-".to_string()),
+                "work".to_string(),
+            )
+            .unwrap();
+
+        assert!(router.route("https://github.com/rust-lang").is_some());
+        assert!(router.route("https://gist.github.com/").is_none());
+        assert!(router
+            .route("https://acme.atlassian.net/browse/X-1")
+            .is_some());
+        assert!(router.route("https://notatlassian.net/").is_none());
+        assert!(router.route("https://docs.google.com/a/acme/doc").is_some());
+        assert!(router.route("https://example.com/acme-report").is_some());
+        assert!(router.route("https://example.com/").is_none());
+    }
+
+    #[test]
+    fn test_rules_persist_and_validate() {
+        let router = router_with_session();
+        assert!(router
+            .add(RoutePattern::Regex("(".to_string()), "work".to_string())
+            .is_err());
+        assert!(router
+            .add(RoutePattern::Host("  ".to_string()), "work".to_string())
+            .is_err());
+
+        let rule = router
+            .add(
+                RoutePattern::Host("example.com".to_string()),
+                "work".to_string(),
+            )
+            .unwrap();
+        router
+            .update(&rule.id, rule.pattern.clone(), "work".to_string(), false)
+            .unwrap();
+
+        let reloaded = UrlRouter::new(router.db.clone());
+        reloaded.load().unwrap();
+        assert_eq!(reloaded.list().len(), 1);
+        assert!(reloaded.route("https://example.com/").is_none());
+
+        reloaded.remove(&rule.id).unwrap();
+        assert!(reloaded.list().is_empty());
+    }
+
+    #[test]
+    fn test_deleted_session_rules_are_dropped() {
+        let router = router_with_session();
+        router
+            .add(
+                RoutePattern::Host("example.com".to_string()),
+                "work".to_string(),
+            )
+            .unwrap();
+
+        router
+            .db
+            .with_connection(|conn| {
+                conn.execute("DELETE FROM sessions WHERE id = 'work'", [])?;
+                Ok(())
+            })
+            .unwrap();
+        router.forget_session("work");
+        assert!(router.route("https://example.com/").is_none());
+        assert!(router.list().is_empty());
+    }
+}
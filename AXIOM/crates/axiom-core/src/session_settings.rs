@@ -0,0 +1,117 @@
+//! Per-session settings overrides
+//!
+//! A session may override a subset of the global settings. Values are stored
+//! in the `session_settings` table under the same keys as their global
+//! counterparts; a missing row means "use the global value".
+
+use serde::{Deserialize, Serialize};
+
+use axiom_storage::Database;
+
+use crate::Result;
+
+const SEARCH_ENGINE: &str = "search_engine";
+const THEME: &str = "theme";
+const TRACKING_PROTECTION: &str = "tracking_protection";
+const DOWNLOAD_DIR: &str = "download_dir";
+const AUTOFILL_NAME: &str = "autofill_name";
+const AUTOFILL_EMAIL: &str = "autofill_email";
+
+/// Overrides for one session; `None` falls back to the global setting
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionSettings {
+    /// Search engine id (older overrides may hold a URL template)
+    pub search_engine: Option<String>,
+    /// "light" or "dark" (dark also forces dark page rendering)
+    pub theme: Option<String>,
+    pub tracking_protection: Option<bool>,
+    pub download_dir: Option<String>,
+    pub autofill_name: Option<String>,
+    pub autofill_email: Option<String>,
+}
+
+impl SessionSettings {
+    pub fn load(db: &Database, session_id: &str) -> Result<Self> {
+        let get = |key: &str| -> Result<Option<String>> {
+            Ok(db
+                .get_session_setting(session_id, key)?
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty()))
+        };
+
+        Ok(Self {
+            search_engine: get(SEARCH_ENGINE)?,
+            theme: get(THEME)?,
+            tracking_protection: get(TRACKING_PROTECTION)?.map(|v| v == "true"),
+            download_dir: get(DOWNLOAD_DIR)?,
+            autofill_name: get(AUTOFILL_NAME)?,
+            autofill_email: get(AUTOFILL_EMAIL)?,
+        })
+    }
+
+    /// Replace all overrides for a session (`None` clears the override)
+    pub fn save(&self, db: &Database, session_id: &str) -> Result<()> {
+        let tracking = self
+            .tracking_protection
+            .map(|enabled| if enabled { "true" } else { "false" }.to_string());
+        let entries = [
+            (SEARCH_ENGINE, self.search_engine.clone()),
+            (THEME, self.theme.clone()),
+            (TRACKING_PROTECTION, tracking),
+            (DOWNLOAD_DIR, self.download_dir.clone()),
+            (AUTOFILL_NAME, self.autofill_name.clone()),
+            (AUTOFILL_EMAIL, self.autofill_email.clone()),
+        ];
+
+        for (key, value) in entries {
+            match value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+                Some(value) => db.set_session_setting(session_id, key, value)?,
+                None => db.remove_session_setting(session_id, key)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_settings_round_trip() {
+        let db = Database::open_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO sessions (id, name, created_at, updated_at) VALUES ('s1', 'Work', '', '')",
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(
+            SessionSettings::load(&db, "s1").unwrap(),
+            SessionSettings::default()
+        );
+
+        let overrides = SessionSettings {
+            search_engine: Some("https://www.bing.com/search?q=%s".to_string()),
+            tracking_protection: Some(false),
+            autofill_name: Some("  ".to_string()),
+            ..Default::default()
+        };
+        overrides.save(&db, "s1").unwrap();
+
+        let loaded = SessionSettings::load(&db, "s1").unwrap();
+        assert_eq!(loaded.search_engine, overrides.search_engine);
+        assert_eq!(loaded.tracking_protection, Some(false));
+        assert_eq!(loaded.autofill_name, None);
+
+        SessionSettings::default().save(&db, "s1").unwrap();
+        assert_eq!(
+            SessionSettings::load(&db, "s1").unwrap(),
+            SessionSettings::default()
+        );
+    }
+}
@@ -1,7 +1,7 @@
 //! Download manager
 
 use parking_lot::RwLock;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -18,6 +18,9 @@ pub struct DownloadManager {
     db: Database,
     /// Default download directory
     download_dir: PathBuf,
+    /// Downloads from private sessions: never written to the database and
+    /// forgotten once they finish
+    private_ids: Arc<RwLock<HashSet<String>>>,
 }
 
 impl DownloadManager {
@@ -26,15 +29,50 @@ impl DownloadManager {
             downloads: Arc::new(RwLock::new(HashMap::new())),
             db,
             download_dir,
+            private_ids: Arc::new(RwLock::new(HashSet::new())),
         }
     }
 
     /// Create a new download (pending user consent)
     pub fn create_download(&self, url: String, file_name: String) -> Result<Download> {
+        self.create_download_in(url, file_name, &self.download_dir)
+    }
+
+    /// Create a new download saved to a specific directory instead of the default
+    pub fn create_download_in(
+        &self,
+        url: String,
+        file_name: String,
+        download_dir: &Path,
+    ) -> Result<Download> {
+        self.create(url, file_name, download_dir, false)
+    }
+
+    /// Create a download that is kept in memory only and dropped from the
+    /// list once it completes, fails or is cancelled
+    pub fn create_private_download_in(
+        &self,
+        url: String,
+        file_name: String,
+        download_dir: &Path,
+    ) -> Result<Download> {
+        self.create(url, file_name, download_dir, true)
+    }
+
+    fn create(
+        &self,
+        url: String,
+        file_name: String,
+        download_dir: &Path,
+        private: bool,
+    ) -> Result<Download> {
         let safe_file_name = sanitize_file_name(&file_name);
-        let file_path = self.download_dir.join(&safe_file_name);
+        let file_path = download_dir.join(&safe_file_name);
         let download = Download::new(url, file_path.to_string_lossy().to_string(), safe_file_name);
 
+        if private {
+            self.private_ids.write().insert(download.id.clone());
+        }
         self.save_download(&download)?;
         self.downloads
             .write()
@@ -173,6 +211,7 @@ impl DownloadManager {
             hash = ?download.hash,
             "Completed download"
         );
+        self.forget_if_private(id);
 
         Ok(download)
     }
@@ -188,6 +227,7 @@ impl DownloadManager {
             .insert(id.to_string(), download.clone());
 
         tracing::info!(download_id = %id, "Cancelled download");
+        self.forget_if_private(id);
 
         Ok(download)
     }
@@ -203,10 +243,41 @@ impl DownloadManager {
             .insert(id.to_string(), download.clone());
 
         tracing::warn!(download_id = %id, "Download failed");
+        self.forget_if_private(id);
+
+        Ok(download)
+    }
+
+    /// Remove a download from the list and the database (the file stays on disk)
+    pub fn remove_download(&self, id: &str) -> Result<Download> {
+        let download = self
+            .downloads
+            .write()
+            .remove(id)
+            .ok_or_else(|| DownloadError::NotFound(id.to_string()))?;
+
+        if !self.private_ids.write().remove(id) {
+            self.db.with_connection(|conn| {
+                conn.execute("DELETE FROM downloads WHERE id = ?1", [id])?;
+                Ok(())
+            })?;
+        }
 
+        tracing::info!(download_id = %id, "Removed download");
         Ok(download)
     }
 
+    pub fn is_private(&self, id: &str) -> bool {
+        self.private_ids.read().contains(id)
+    }
+
+    /// Drop a finished private download from memory
+    fn forget_if_private(&self, id: &str) {
+        if self.private_ids.write().remove(id) {
+            self.downloads.write().remove(id);
+        }
+    }
+
     /// Get all downloads
     pub fn list_downloads(&self) -> Vec<Download> {
         self.downloads.read().values().cloned().collect()
@@ -281,6 +352,9 @@ impl DownloadManager {
 
     /// Save download to database
     fn save_download(&self, download: &Download) -> Result<()> {
+        if self.is_private(&download.id) {
+            return Ok(());
+        }
         Ok(self.db.with_connection(|conn| {
             conn.execute(
                 "INSERT OR REPLACE INTO downloads
@@ -312,6 +386,7 @@ impl Clone for DownloadManager {
             downloads: Arc::clone(&self.downloads),
             db: self.db.clone(),
             download_dir: self.download_dir.clone(),
+            private_ids: Arc::clone(&self.private_ids),
         }
     }
 }
@@ -366,5 +441,38 @@ mod tests {
             .unwrap();
         assert_eq!(completed.state, DownloadState::Completed);
         assert_eq!(completed.hash, Some("abc123".to_string()));
+
+        // Removed from the list and the database
+        manager.remove_download(&download.id).unwrap();
+        assert!(manager.get_download(&download.id).is_err());
+        manager.load_downloads().unwrap();
+        assert!(manager.list_downloads().is_empty());
+    }
+
+    #[test]
+    fn test_private_download() {
+        let db = Database::open_in_memory().unwrap();
+        let manager = DownloadManager::new(db.clone(), PathBuf::from("/downloads"));
+
+        let download = manager
+            .create_private_download_in(
+                "https://example.com/private.pdf".to_string(),
+                "private.pdf".to_string(),
+                Path::new("/tmp"),
+            )
+            .unwrap();
+        assert!(manager.is_private(&download.id));
+        manager.start_download(&download.id).unwrap();
+        assert_eq!(manager.active_downloads().len(), 1);
+
+        // Never written to the database
+        let reloaded = DownloadManager::new(db, PathBuf::from("/downloads"));
+        reloaded.load_downloads().unwrap();
+        assert!(reloaded.list_downloads().is_empty());
+
+        // Not listed once finished
+        manager.complete_download(&download.id, None).unwrap();
+        assert!(manager.list_downloads().is_empty());
+        assert!(!manager.is_private(&download.id));
     }
 }
@@ -0,0 +1,90 @@
+//! Answers computed locally from address bar input
+//!
+//! Arithmetic (`2^10*3`) and unit conversions (`12 in to cm`) are answered
+//! without sending the query to a search engine.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::calculator::{evaluate, format_number};
+use crate::units::convert;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerKind {
+    Calculation,
+    Conversion,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Answer {
+    pub kind: AnswerKind,
+    /// The question as understood (`2^10*3`, `12 in`)
+    pub query: String,
+    /// The result for display (`3072`, `30.48 cm`)
+    pub result: String,
+    pub value: f64,
+}
+
+/// Dates like `2024-01-15` or `15/01/2024` are searches, not subtractions
+fn looks_like_date(input: &str) -> bool {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"^(\d{4}-\d{1,2}-\d{1,2}|\d{1,2}/\d{1,2}/\d{2,4}|\d{1,2}\.\d{1,2}\.\d{4})$")
+            .unwrap()
+    })
+    .is_match(input)
+}
+
+/// Answer the input if it is a calculation or a conversion
+pub fn compute_answer(input: &str) -> Option<Answer> {
+    let input = input.trim();
+    // `=2+2` and `2+2=` are both questions
+    let expression = input
+        .strip_prefix('=')
+        .unwrap_or(input)
+        .trim_end_matches('=')
+        .trim();
+    if expression.is_empty() || looks_like_date(expression) {
+        return None;
+    }
+
+    if let Some(conversion) = convert(expression) {
+        return Some(Answer {
+            kind: AnswerKind::Conversion,
+            query: format!("{} {}", format_number(conversion.amount), conversion.from),
+            result: format!("{} {}", format_number(conversion.value), conversion.to),
+            value: conversion.value,
+        });
+    }
+
+    let value = evaluate(expression)?;
+    Some(Answer {
+        kind: AnswerKind::Calculation,
+        query: expression.to_string(),
+        result: format_number(value),
+        value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_answer() {
+        let answer = compute_answer("2^10*3").unwrap();
+        assert_eq!(answer.kind, AnswerKind::Calculation);
+        assert_eq!(answer.result, "3072");
+
+        let answer = compute_answer("= 12 in to cm").unwrap();
+        assert_eq!(answer.kind, AnswerKind::Conversion);
+        assert_eq!(answer.query, "12 in");
+        assert_eq!(answer.result, "30.48 cm");
+
+        assert!(compute_answer("2024-01-15").is_none());
+        assert!(compute_answer("1/2/2024").is_none());
+        assert!(compute_answer("weather today").is_none());
+    }
+}
@@ -0,0 +1,378 @@
+//! Arithmetic for address bar answers
+//!
+//! A small recursive-descent evaluator. Precedence, loosest first:
+//! `+ -`, `* / %` (and implicit multiplication, `2pi`), unary `-`, `^`
+//! (right-associative, so `-2^2` is -4), postfix `!`. Functions take their
+//! arguments in parentheses: `sqrt(2)`, `max(1, 2, 3)`. Trigonometry is in
+//! radians. A hyphen joining a name to a word or number (`e-3`, `pi-1`)
+//! reads as a name rather than a subtraction, so it is no answer.
+
+/// Factorials above this overflow `f64`
+const MAX_FACTORIAL: f64 = 170.0;
+/// Nesting of parentheses, signs and exponents before giving up
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Caret,
+    Bang,
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(input: &str) -> Option<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '0'..='9' | '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                // Exponent only when digits follow, so `2e` stays 2 * e
+                if i < chars.len() && matches!(chars[i], 'e' | 'E') {
+                    let mut j = i + 1;
+                    if j < chars.len() && matches!(chars[j], '+' | '-') {
+                        j += 1;
+                    }
+                    if j < chars.len() && chars[j].is_ascii_digit() {
+                        i = j;
+                        while i < chars.len() && chars[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                    }
+                }
+                let text: String = chars[start..i].iter().collect();
+                tokens.push(Token::Number(text.parse().ok()?));
+                continue;
+            }
+            c if c.is_alphabetic() => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let ident: String = chars[start..i].iter().collect();
+                tokens.push(Token::Ident(ident.to_lowercase()));
+                continue;
+            }
+            '+' => tokens.push(Token::Plus),
+            '-' if i > 0
+                && chars[i - 1].is_alphabetic()
+                && chars.get(i + 1).is_some_and(|c| c.is_alphanumeric()) =>
+            {
+                return None;
+            }
+            '-' | '−' => tokens.push(Token::Minus),
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                tokens.push(Token::Caret);
+                i += 1;
+            }
+            '*' | '×' | '·' => tokens.push(Token::Star),
+            '/' | '÷' => tokens.push(Token::Slash),
+            '%' => tokens.push(Token::Percent),
+            '^' => tokens.push(Token::Caret),
+            '!' => tokens.push(Token::Bang),
+            '(' => tokens.push(Token::LParen),
+            ')' => tokens.push(Token::RParen),
+            ',' => tokens.push(Token::Comma),
+            _ => return None,
+        }
+        i += 1;
+    }
+
+    Some(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Set once an operator or function is seen; a bare number is no answer
+    operated: bool,
+    /// Current recursion depth, bounded by `MAX_DEPTH`
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expression(&mut self) -> Option<f64> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat(&Token::Plus) {
+                value += self.term()?;
+            } else if self.eat(&Token::Minus) {
+                value -= self.term()?;
+            } else {
+                return Some(value);
+            }
+            self.operated = true;
+        }
+    }
+
+    fn term(&mut self) -> Option<f64> {
+        let mut value = self.unary()?;
+        loop {
+            if self.eat(&Token::Star) {
+                value *= self.unary()?;
+            } else if self.eat(&Token::Slash) {
+                value /= self.unary()?;
+            } else if self.eat(&Token::Percent) {
+                value %= self.unary()?;
+            } else if matches!(self.peek(), Some(Token::Ident(_) | Token::LParen)) {
+                // Implicit multiplication: `2pi`, `3(4 + 1)`; never `2024 2025`
+                value *= self.power()?;
+            } else {
+                return Some(value);
+            }
+            self.operated = true;
+        }
+    }
+
+    /// Every nested parse passes through here, so the depth is counted here
+    fn unary(&mut self) -> Option<f64> {
+        if self.depth == MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let value = self.signed();
+        self.depth -= 1;
+        value
+    }
+
+    fn signed(&mut self) -> Option<f64> {
+        if self.eat(&Token::Minus) {
+            return Some(-self.unary()?);
+        }
+        if self.eat(&Token::Plus) {
+            return self.unary();
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Option<f64> {
+        let base = self.postfix()?;
+        if self.eat(&Token::Caret) {
+            self.operated = true;
+            // Right-associative; the exponent may carry its own sign
+            let exponent = self.unary()?;
+            return Some(base.powf(exponent));
+        }
+        Some(base)
+    }
+
+    fn postfix(&mut self) -> Option<f64> {
+        let mut value = self.primary()?;
+        while self.eat(&Token::Bang) {
+            self.operated = true;
+            value = factorial(value)?;
+        }
+        Some(value)
+    }
+
+    fn primary(&mut self) -> Option<f64> {
+        match self.peek()?.clone() {
+            Token::Number(n) => {
+                self.pos += 1;
+                Some(n)
+            }
+            Token::LParen => {
+                self.pos += 1;
+                let value = self.expression()?;
+                self.eat(&Token::RParen).then_some(value)
+            }
+            Token::Ident(name) => {
+                self.pos += 1;
+                if let Some(value) = constant(&name) {
+                    return Some(value);
+                }
+                let args = self.arguments()?;
+                self.operated = true;
+                function(&name, &args)
+            }
+            _ => None,
+        }
+    }
+
+    fn arguments(&mut self) -> Option<Vec<f64>> {
+        if !self.eat(&Token::LParen) {
+            return None;
+        }
+        let mut args = vec![self.expression()?];
+        while self.eat(&Token::Comma) {
+            args.push(self.expression()?);
+        }
+        self.eat(&Token::RParen).then_some(args)
+    }
+}
+
+fn constant(name: &str) -> Option<f64> {
+    match name {
+        "pi" | "π" => Some(std::f64::consts::PI),
+        "tau" | "τ" => Some(std::f64::consts::TAU),
+        "e" => Some(std::f64::consts::E),
+        _ => None,
+    }
+}
+
+fn function(name: &str, args: &[f64]) -> Option<f64> {
+    let one = || (args.len() == 1).then(|| args[0]);
+    Some(match name {
+        "sqrt" => one()?.sqrt(),
+        "cbrt" => one()?.cbrt(),
+        "abs" => one()?.abs(),
+        "ln" => one()?.ln(),
+        "log" | "log10" => one()?.log10(),
+        "log2" => one()?.log2(),
+        "exp" => one()?.exp(),
+        "sin" => one()?.sin(),
+        "cos" => one()?.cos(),
+        "tan" => one()?.tan(),
+        "asin" => one()?.asin(),
+        "acos" => one()?.acos(),
+        "atan" => one()?.atan(),
+        "floor" => one()?.floor(),
+        "ceil" => one()?.ceil(),
+        "round" => one()?.round(),
+        "pow" if args.len() == 2 => args[0].powf(args[1]),
+        "min" => args.iter().copied().fold(f64::INFINITY, f64::min),
+        "max" => args.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        _ => return None,
+    })
+}
+
+fn factorial(n: f64) -> Option<f64> {
+    if n < 0.0 || n.fract() != 0.0 || n > MAX_FACTORIAL {
+        return None;
+    }
+    Some((2..=n as u64).map(|k| k as f64).product())
+}
+
+/// Evaluate an arithmetic expression
+///
+/// `None` if it does not parse, is only a number, or has no finite value.
+pub(crate) fn evaluate(input: &str) -> Option<f64> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+        operated: false,
+        depth: 0,
+    };
+    let value = parser.expression()?;
+    if parser.pos != parser.tokens.len() || !parser.operated || !value.is_finite() {
+        return None;
+    }
+    Some(value)
+}
+
+/// Up to 10 significant digits, without trailing zeros
+pub(crate) fn format_number(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+
+    let magnitude = value.abs().log10().floor() as i32;
+    if !(-6..15).contains(&magnitude) {
+        let formatted = format!("{value:.9e}");
+        let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+        return format!("{}e{exponent}", trim_fraction(mantissa));
+    }
+
+    let decimals = (9 - magnitude).max(0) as usize;
+    trim_fraction(&format!("{value:.decimals$}")).to_string()
+}
+
+fn trim_fraction(number: &str) -> &str {
+    if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        number
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(input: &str) -> Option<String> {
+        evaluate(input).map(format_number)
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_eq!(eval("2^10*3").as_deref(), Some("3072"));
+        assert_eq!(eval("1 + 2 * 3").as_deref(), Some("7"));
+        assert_eq!(eval("(1 + 2) * 3").as_deref(), Some("9"));
+        assert_eq!(eval("-2^2").as_deref(), Some("-4"));
+        assert_eq!(eval("2^3^2").as_deref(), Some("512"));
+        assert_eq!(eval("2 ** -1").as_deref(), Some("0.5"));
+        assert_eq!(eval("10 % 4 + 5!").as_deref(), Some("122"));
+        assert_eq!(eval("2pi").as_deref(), Some("6.283185307"));
+        assert_eq!(eval("1/3").as_deref(), Some("0.3333333333"));
+        assert_eq!(eval("0.1 + 0.2").as_deref(), Some("0.3"));
+        assert_eq!(eval("1e3 * 2").as_deref(), Some("2000"));
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(eval("sqrt(16) + abs(-2)").as_deref(), Some("6"));
+        assert_eq!(eval("max(1, 7, 3) - min(4, 2)").as_deref(), Some("5"));
+        assert_eq!(eval("log(1000)").as_deref(), Some("3"));
+        assert_eq!(eval("sin(pi / 2)").as_deref(), Some("1"));
+        assert_eq!(eval("pow(2, 0.5)").as_deref(), Some("1.414213562"));
+        assert_eq!(eval("2^100").as_deref(), Some("1.2676506e30"));
+    }
+
+    #[test]
+    fn test_not_answers() {
+        assert!(eval("42").is_none());
+        assert!(eval("pi").is_none());
+        assert!(eval("1/0").is_none());
+        assert!(eval("2 +").is_none());
+        assert!(eval("foo(2)").is_none());
+        assert!(eval("rust programming").is_none());
+        assert!(eval("1.2.3").is_none());
+        assert!(eval("(1 + 2").is_none());
+        assert!(eval("2024 2025").is_none());
+        assert!(eval("e-3").is_none());
+        assert!(eval("pi-1").is_none());
+        assert_eq!(eval("e - 3").as_deref(), Some("-0.2817181715"));
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        assert_eq!(
+            eval(&format!("{}1+1{}", "(".repeat(20), ")".repeat(20))).as_deref(),
+            Some("2")
+        );
+        assert!(eval(&"(".repeat(20_000)).is_none());
+        assert!(eval(&format!("{}1", "-".repeat(20_000))).is_none());
+        assert!(eval(&"2^".repeat(20_000)).is_none());
+    }
+}
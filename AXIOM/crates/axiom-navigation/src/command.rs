@@ -4,10 +4,13 @@
 //! - `@tabs` — fuzzy search open tabs
 //! - `@history` — fuzzy search history
 //! - `@sessions` — switch session
+//!
+//! Also `@bookmarks`, `@downloads`, `@closed`, `@settings` and `@windows`.
+//! Every command has short aliases (`@b`, `@d`, ...).
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CommandType {
     /// Search open tabs
     Tabs,
@@ -15,6 +18,83 @@ pub enum CommandType {
     History,
     /// Switch sessions
     Sessions,
+    /// Search bookmarks
+    Bookmarks,
+    /// Search downloads by file name or state
+    Downloads,
+    /// Reopen recently closed tabs
+    Closed,
+    /// Jump to a setting
+    Settings,
+    /// Switch between open windows
+    Windows,
+}
+
+impl CommandType {
+    pub const ALL: [CommandType; 8] = [
+        CommandType::Tabs,
+        CommandType::History,
+        CommandType::Sessions,
+        CommandType::Bookmarks,
+        CommandType::Downloads,
+        CommandType::Closed,
+        CommandType::Settings,
+        CommandType::Windows,
+    ];
+
+    /// Lowercase name, as used over IPC
+    pub fn name(&self) -> &'static str {
+        self.aliases()[0]
+    }
+
+    /// Canonical `@` prefix
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            CommandType::Tabs => "@tabs",
+            CommandType::History => "@history",
+            CommandType::Sessions => "@sessions",
+            CommandType::Bookmarks => "@bookmarks",
+            CommandType::Downloads => "@downloads",
+            CommandType::Closed => "@closed",
+            CommandType::Settings => "@settings",
+            CommandType::Windows => "@windows",
+        }
+    }
+
+    /// Accepted spellings after the `@`, canonical name first
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            CommandType::Tabs => &["tabs", "tab", "t"],
+            CommandType::History => &["history", "hist", "h"],
+            CommandType::Sessions => &["sessions", "session", "s"],
+            CommandType::Bookmarks => &["bookmarks", "bookmark", "bm", "b"],
+            CommandType::Downloads => &["downloads", "download", "dl", "d"],
+            CommandType::Closed => &["closed", "c"],
+            CommandType::Settings => &["settings", "setting", "prefs"],
+            CommandType::Windows => &["windows", "window", "w"],
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            CommandType::Tabs => "Search open tabs",
+            CommandType::History => "Search history",
+            CommandType::Sessions => "Switch session",
+            CommandType::Bookmarks => "Search bookmarks",
+            CommandType::Downloads => "Search downloads",
+            CommandType::Closed => "Reopen a closed tab",
+            CommandType::Settings => "Find a setting",
+            CommandType::Windows => "Switch window",
+        }
+    }
+
+    /// Look up a command by any of its aliases (without the `@`)
+    pub fn from_alias(alias: &str) -> Option<Self> {
+        let alias = alias.to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|command| command.aliases().contains(&alias.as_str()))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,38 +108,51 @@ impl Command {
     /// Parse a command string (must start with @)
     pub fn parse(input: &str) -> Option<Self> {
         let input = input.trim();
-        if !input.starts_with('@') {
-            return None;
-        }
+        let without_prefix = input.strip_prefix('@')?;
 
-        let without_prefix = &input[1..];
         let mut parts = without_prefix.splitn(2, ' ');
-        let command = parts.next()?.to_lowercase();
+        let command_type = CommandType::from_alias(parts.next()?)?;
         let query = parts
             .next()
             .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty());
 
-        let command_type = match command.as_str() {
-            "tabs" | "tab" | "t" => CommandType::Tabs,
-            "history" | "hist" | "h" => CommandType::History,
-            "sessions" | "session" | "s" => CommandType::Sessions,
-            _ => return None,
-        };
-
         Some(Self {
             command_type,
             query,
         })
     }
 
+    /// Commands that the `@word` being typed could complete to
+    ///
+    /// Empty once a query follows the command (`@hi` completes to
+    /// `@history`, `@history foo` does not complete). An alias that already
+    /// matches exactly still lists its command, first.
+    pub fn completions(input: &str) -> Vec<CommandType> {
+        let input = input.trim_start();
+        let Some(typed) = input.strip_prefix('@') else {
+            return Vec::new();
+        };
+        if typed.contains(char::is_whitespace) {
+            return Vec::new();
+        }
+
+        let typed = typed.to_lowercase();
+        let exact = CommandType::from_alias(&typed);
+        let mut completions: Vec<CommandType> = exact.into_iter().collect();
+        completions.extend(CommandType::ALL.into_iter().filter(|command| {
+            Some(*command) != exact
+                && command
+                    .aliases()
+                    .iter()
+                    .any(|alias| alias.starts_with(typed.as_str()))
+        }));
+        completions
+    }
+
     /// Get the command prefix for display
     pub fn prefix(&self) -> &'static str {
-        match self.command_type {
-            CommandType::Tabs => "@tabs",
-            CommandType::History => "@history",
-            CommandType::Sessions => "@sessions",
-        }
+        self.command_type.prefix()
     }
 }
 
@@ -90,6 +183,43 @@ mod tests {
         assert_eq!(cmd.command_type, CommandType::Sessions);
     }
 
+    #[test]
+    fn test_parse_new_commands() {
+        let cmd = Command::parse("@b rust book").unwrap();
+        assert_eq!(cmd.command_type, CommandType::Bookmarks);
+        assert_eq!(cmd.query.as_deref(), Some("rust book"));
+
+        assert_eq!(
+            Command::parse("@D").unwrap().command_type,
+            CommandType::Downloads
+        );
+        assert_eq!(
+            Command::parse("@closed").unwrap().command_type,
+            CommandType::Closed
+        );
+        assert_eq!(
+            Command::parse("@settings theme").unwrap().command_type,
+            CommandType::Settings
+        );
+        assert_eq!(
+            Command::parse("@w").unwrap().command_type,
+            CommandType::Windows
+        );
+    }
+
+    #[test]
+    fn test_completions() {
+        assert_eq!(Command::completions("@hi"), vec![CommandType::History]);
+        assert_eq!(
+            Command::completions("@s"),
+            vec![CommandType::Sessions, CommandType::Settings]
+        );
+        assert_eq!(Command::completions("@").len(), CommandType::ALL.len());
+        assert!(Command::completions("@history foo").is_empty());
+        assert!(Command::completions("@zz").is_empty());
+        assert!(Command::completions("hi").is_empty());
+    }
+
     #[test]
     fn test_unknown_command() {
         assert!(Command::parse("@unknown").is_none());
@@ -10,6 +10,27 @@ pub enum NavigationError {
     #[error("Unknown command: {0}")]
     UnknownCommand(String),
 
+    #[error("Invalid search keyword: {0}")]
+    InvalidKeyword(String),
+
+    #[error("Invalid search template: {0}")]
+    InvalidTemplate(String),
+
+    #[error("Search keyword not found: {0}")]
+    KeywordNotFound(String),
+
+    #[error("Invalid search engine: {0}")]
+    InvalidSearchEngine(String),
+
+    #[error("Search engine not found: {0}")]
+    SearchEngineNotFound(String),
+
+    #[error("Invalid OpenSearch description: {0}")]
+    InvalidOpenSearch(String),
+
+    #[error("Suggestion request cancelled")]
+    Cancelled,
+
     #[error("Storage error: {0}")]
     Storage(#[from] axiom_storage::StorageError),
 }
@@ -0,0 +1,156 @@
+//! Frecency scoring
+//!
+//! A URL's frecency is its visit count multiplied by the average weight of
+//! its most recent visits. Each sampled visit is weighted by how long ago it
+//! happened (coarse time buckets) and by how it was initiated, so a site
+//! typed a dozen times last week outranks one followed once this morning.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Number of most recent visits sampled per URL
+pub(crate) const MAX_SAMPLES: usize = 10;
+
+/// (maximum age in days, weight) for each time-decay bucket, newest first
+const DECAY_BUCKETS: [(i64, u32); 4] = [(4, 100), (14, 70), (31, 50), (90, 30)];
+
+/// Age in days past which a visit stays in the oldest bucket for good
+pub(crate) const DECAY_WINDOW_DAYS: i64 = DECAY_BUCKETS[DECAY_BUCKETS.len() - 1].0;
+
+/// Weight for visits older than the last bucket
+const OLD_VISIT_WEIGHT: u32 = 10;
+
+/// How a visit was initiated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VisitTransition {
+    /// Followed a link (or any navigation not otherwise classified)
+    #[default]
+    Link,
+    /// Typed or pasted into the address bar
+    Typed,
+    /// Opened from a bookmark
+    Bookmark,
+    /// Reached by a redirect from the previous visit in the tab
+    Redirect,
+    /// The same page reloaded
+    Reload,
+}
+
+impl VisitTransition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VisitTransition::Link => "link",
+            VisitTransition::Typed => "typed",
+            VisitTransition::Bookmark => "bookmark",
+            VisitTransition::Redirect => "redirect",
+            VisitTransition::Reload => "reload",
+        }
+    }
+
+    /// Whether the visit adds to a URL's visit count and frecency samples
+    ///
+    /// Reloads are logged but do not make a page more "visited".
+    pub fn is_counted(&self) -> bool {
+        !matches!(self, VisitTransition::Reload)
+    }
+
+    /// Percentage applied to the bucket weight of a visit
+    fn bonus(&self) -> u32 {
+        match self {
+            VisitTransition::Link => 100,
+            VisitTransition::Typed => 200,
+            VisitTransition::Bookmark => 140,
+            VisitTransition::Redirect => 50,
+            VisitTransition::Reload => 0,
+        }
+    }
+}
+
+impl fmt::Display for VisitTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for VisitTransition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "link" => Ok(VisitTransition::Link),
+            "typed" => Ok(VisitTransition::Typed),
+            "bookmark" => Ok(VisitTransition::Bookmark),
+            "redirect" => Ok(VisitTransition::Redirect),
+            "reload" => Ok(VisitTransition::Reload),
+            other => Err(format!("Unknown visit transition: {other}")),
+        }
+    }
+}
+
+/// A sampled visit used for scoring
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct VisitSample {
+    pub at: DateTime<Utc>,
+    pub transition: VisitTransition,
+}
+
+fn bucket_weight(at: DateTime<Utc>, now: DateTime<Utc>) -> u32 {
+    let age_days = (now - at).num_days();
+    DECAY_BUCKETS
+        .iter()
+        .find(|(max_days, _)| age_days <= *max_days)
+        .map(|(_, weight)| *weight)
+        .unwrap_or(OLD_VISIT_WEIGHT)
+}
+
+/// Frecency for a URL with `visit_count` total visits and the given recent samples
+pub(crate) fn score(visit_count: i64, samples: &[VisitSample], now: DateTime<Utc>) -> i64 {
+    if visit_count <= 0 || samples.is_empty() {
+        return 0;
+    }
+
+    let points: f64 = samples
+        .iter()
+        .map(|s| bucket_weight(s.at, now) as f64 * s.transition.bonus() as f64 / 100.0)
+        .sum();
+
+    (visit_count as f64 * points / samples.len() as f64).ceil() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn sample(days_ago: i64, transition: VisitTransition, now: DateTime<Utc>) -> VisitSample {
+        VisitSample {
+            at: now - Duration::days(days_ago),
+            transition,
+        }
+    }
+
+    #[test]
+    fn test_decay_buckets() {
+        let now = Utc::now();
+        assert_eq!(bucket_weight(now, now), 100);
+        assert_eq!(bucket_weight(now - Duration::days(10), now), 70);
+        assert_eq!(bucket_weight(now - Duration::days(60), now), 30);
+        assert_eq!(bucket_weight(now - Duration::days(400), now), 10);
+    }
+
+    #[test]
+    fn test_frequent_typed_beats_single_recent_link() {
+        let now = Utc::now();
+        let frequent: Vec<_> = (0..10)
+            .map(|d| sample(d, VisitTransition::Typed, now))
+            .collect();
+        let once = [sample(0, VisitTransition::Link, now)];
+
+        assert!(score(12, &frequent, now) > score(1, &once, now));
+        assert_eq!(score(1, &once, now), 100);
+        assert_eq!(score(0, &once, now), 0);
+    }
+}
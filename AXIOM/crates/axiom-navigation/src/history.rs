@@ -1,11 +1,26 @@
 //! History management
+//!
+//! `history` holds one row per URL with aggregate counts and frecency;
+//! `visits` logs every individual visit with its transition, referrer and
+//! the tab and session it happened in.
 
 use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use rusqlite::{Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
+use crate::frecency::{self, VisitSample, VisitTransition, DECAY_WINDOW_DAYS, MAX_SAMPLES};
+use crate::journeys::{self, Journey, MAX_JOURNEY_VISITS};
 use crate::Result;
 use axiom_storage::Database;
 
+const ENTRY_COLUMNS: &str = "id, url, title, visited_at, visit_count, frecency";
+
+/// Setting holding when `recalculate_frecency` last ran
+const FRECENCY_RECALCULATED_AT: &str = "frecency_recalculated_at";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryEntry {
     pub id: i64,
@@ -13,46 +28,348 @@ pub struct HistoryEntry {
     pub title: String,
     pub visited_at: DateTime<Utc>,
     pub visit_count: i32,
+    pub frecency: i64,
+}
+
+/// Ordering for history listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistorySort {
+    /// Most recently visited first
+    #[default]
+    Recency,
+    /// Highest frecency (frequency weighted by recency and visit type) first
+    Frecency,
+}
+
+impl HistorySort {
+    fn order_by(&self) -> &'static str {
+        match self {
+            HistorySort::Recency => "visited_at DESC, visit_count DESC",
+            HistorySort::Frecency => "frecency DESC, visited_at DESC",
+        }
+    }
+}
+
+/// A single visit from the `visits` log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Visit {
+    pub id: i64,
+    /// Row id of the URL in `history`
+    pub url_id: i64,
+    pub url: String,
+    pub title: String,
+    pub visited_at: DateTime<Utc>,
+    pub transition: VisitTransition,
+    /// Visit this one was reached from (same tab), if known
+    pub from_visit_id: Option<i64>,
+    pub tab_id: Option<String>,
+    pub session_id: Option<String>,
+}
+
+/// Where a visit happened
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VisitSource {
+    pub tab_id: Option<String>,
+    pub session_id: Option<String>,
+}
+
+/// Most recent visit recorded in a tab, used as the referrer of the next one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TabVisit {
+    pub visit_id: i64,
+    pub visited_at: DateTime<Utc>,
+}
+
+/// A URL's history read from another browser's profile
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedHistory {
+    pub url: String,
+    pub title: String,
+    /// Visit count reported by the other browser (may exceed `visits`, which it expires)
+    pub visit_count: i64,
+    pub last_visited_at: DateTime<Utc>,
+    pub visits: Vec<(DateTime<Utc>, VisitTransition)>,
+}
+
+/// Outcome of `HistoryManager::import`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryImportStats {
+    /// URLs that were not in history yet
+    pub added_urls: usize,
+    /// URLs already in history that gained visits
+    pub merged_urls: usize,
+    pub added_visits: usize,
+}
+
+fn entry_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<HistoryEntry> {
+    let visited_str: String = row.get(3)?;
+    let visited_at = DateTime::parse_from_rfc3339(&visited_str)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now());
+
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        url: row.get(1)?,
+        title: row.get(2)?,
+        visited_at,
+        visit_count: row.get(4)?,
+        frecency: row.get(5)?,
+    })
 }
 
 pub struct HistoryManager {
     db: Database,
+    /// Last visit per tab id (referrer for the tab's next visit)
+    tab_visits: Arc<RwLock<HashMap<String, TabVisit>>>,
 }
 
 impl HistoryManager {
     pub fn new(db: Database) -> Self {
-        Self { db }
+        Self {
+            db,
+            tab_visits: Arc::new(RwLock::new(HashMap::new())),
+        }
     }
 
-    /// Record a visit to a URL
-    pub fn record_visit(&self, url: &str, title: &str) -> Result<()> {
-        Ok(self.db.with_connection(|conn| {
+    /// Record a visit to a URL and update its aggregates and frecency incrementally
+    ///
+    /// Returns the id of the new row in `visits`. Link, redirect and reload
+    /// visits are linked to the previous visit in the same tab.
+    pub fn record_visit(
+        &self,
+        url: &str,
+        title: &str,
+        transition: VisitTransition,
+        source: &VisitSource,
+    ) -> Result<i64> {
+        let now = Utc::now();
+        let from_visit_id = match transition {
+            VisitTransition::Typed | VisitTransition::Bookmark => None,
+            _ => source
+                .tab_id
+                .as_deref()
+                .and_then(|tab_id| self.last_visit_in_tab(tab_id))
+                .map(|v| v.visit_id),
+        };
+        let counted = transition.is_counted() as i64;
+        let typed = (transition == VisitTransition::Typed) as i64;
+
+        let visit_id = self.db.transaction(|conn| {
             // Check if URL exists
-            let existing: Option<i64> = conn.query_row(
-                "SELECT id FROM history WHERE url = ?1",
-                [url],
-                |row| row.get(0),
-            ).ok();
+            let existing: Option<i64> = conn
+                .query_row("SELECT id FROM history WHERE url = ?1", [url], |row| {
+                    row.get(0)
+                })
+                .optional()?;
 
-            if let Some(id) = existing {
+            let url_id = if let Some(id) = existing {
                 // Update existing entry
                 conn.execute(
                     "UPDATE history
                      SET title = CASE WHEN ?1 != '' THEN ?1 ELSE title END,
                          visited_at = ?2,
-                         visit_count = visit_count + 1
-                     WHERE id = ?3",
-                    rusqlite::params![title, Utc::now().to_rfc3339(), id],
+                         visit_count = visit_count + ?3,
+                         typed_count = typed_count + ?4
+                     WHERE id = ?5",
+                    rusqlite::params![title, now.to_rfc3339(), counted, typed, id],
                 )?;
+                id
             } else {
                 // Insert new entry
                 conn.execute(
-                    "INSERT INTO history (url, title, visited_at, visit_count) VALUES (?1, ?2, ?3, 1)",
-                    rusqlite::params![url, title, Utc::now().to_rfc3339()],
+                    "INSERT INTO history (url, title, visited_at, visit_count, typed_count)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![url, title, now.to_rfc3339(), counted, typed],
+                )?;
+                conn.last_insert_rowid()
+            };
+
+            conn.execute(
+                "INSERT INTO visits
+                 (history_id, visited_at, transition, from_visit_id, tab_id, session_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![
+                    url_id,
+                    now.to_rfc3339(),
+                    transition.as_str(),
+                    from_visit_id,
+                    source.tab_id,
+                    source.session_id
+                ],
+            )?;
+            let visit_id = conn.last_insert_rowid();
+
+            update_frecency(conn, url_id, now)?;
+
+            Ok(visit_id)
+        })?;
+
+        if let Some(tab_id) = &source.tab_id {
+            self.tab_visits.write().insert(
+                tab_id.clone(),
+                TabVisit {
+                    visit_id,
+                    visited_at: now,
+                },
+            );
+        }
+
+        Ok(visit_id)
+    }
+
+    /// Most recent visit recorded in a tab during this run
+    pub fn last_visit_in_tab(&self, tab_id: &str) -> Option<TabVisit> {
+        self.tab_visits.read().get(tab_id).copied()
+    }
+
+    /// Recompute stored frecency so older visits drop into lower decay buckets
+    ///
+    /// Scores are only updated when a URL is visited; this is meant to run
+    /// occasionally (e.g. at startup). Only URLs with a visit that was still
+    /// inside the decay window at the previous pass are rescored, since older
+    /// visits cannot change bucket again. Returns the number of rows changed.
+    pub fn recalculate_frecency(&self) -> Result<usize> {
+        let now = Utc::now();
+        let since = self
+            .db
+            .get_setting(FRECENCY_RECALCULATED_AT)?
+            .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+            .map(|at| (at - chrono::Duration::days(DECAY_WINDOW_DAYS + 1)).to_rfc3339());
+
+        let changed = self.db.transaction(|conn| {
+            let rows: Vec<(i64, i64)> = {
+                let mut stmt = conn.prepare(
+                    "SELECT id, frecency FROM history
+                     WHERE ?1 IS NULL
+                        OR id IN (SELECT history_id FROM visits WHERE visited_at >= ?1)",
                 )?;
+                let rows = stmt
+                    .query_map([&since], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                rows
+            };
+
+            let mut changed = 0;
+            for (id, current) in rows {
+                if update_frecency(conn, id, now)? != current {
+                    changed += 1;
+                }
             }
 
-            Ok(())
+            Ok(changed)
+        })?;
+        self.db
+            .set_setting(FRECENCY_RECALCULATED_AT, &now.to_rfc3339())?;
+
+        Ok(changed)
+    }
+
+    /// Every URL in history
+    pub fn known_urls(&self) -> Result<HashSet<String>> {
+        Ok(self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare("SELECT url FROM history")?;
+            let urls = stmt
+                .query_map([], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(urls)
+        })?)
+    }
+
+    /// Merge history from another browser in one transaction
+    ///
+    /// Visits already logged for a URL at the same instant are skipped, so
+    /// importing the same profile twice changes nothing. New URLs take the
+    /// other browser's visit count; known URLs add the visits imported.
+    pub fn import(&self, entries: &[ImportedHistory]) -> Result<HistoryImportStats> {
+        let now = Utc::now();
+
+        Ok(self.db.transaction(|conn| {
+            let mut stats = HistoryImportStats::default();
+            for entry in entries {
+                let existing: Option<i64> = conn
+                    .query_row(
+                        "SELECT id FROM history WHERE url = ?1",
+                        [&entry.url],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+                let url_id = match existing {
+                    Some(id) => id,
+                    None => {
+                        conn.execute(
+                            "INSERT INTO history (url, title, visited_at, visit_count, typed_count)
+                             VALUES (?1, ?2, ?3, 0, 0)",
+                            rusqlite::params![
+                                entry.url,
+                                entry.title,
+                                entry.last_visited_at.to_rfc3339()
+                            ],
+                        )?;
+                        conn.last_insert_rowid()
+                    }
+                };
+
+                let mut counted = 0i64;
+                let mut typed = 0i64;
+                let mut inserted = 0usize;
+                for (visited_at, transition) in &entry.visits {
+                    let at = visited_at.to_rfc3339();
+                    let duplicate = conn
+                        .query_row(
+                            "SELECT 1 FROM visits WHERE history_id = ?1 AND visited_at = ?2",
+                            rusqlite::params![url_id, at],
+                            |_| Ok(()),
+                        )
+                        .optional()?
+                        .is_some();
+                    if duplicate {
+                        continue;
+                    }
+                    conn.execute(
+                        "INSERT INTO visits (history_id, visited_at, transition)
+                         VALUES (?1, ?2, ?3)",
+                        rusqlite::params![url_id, at, transition.as_str()],
+                    )?;
+                    inserted += 1;
+                    counted += transition.is_counted() as i64;
+                    typed += (*transition == VisitTransition::Typed) as i64;
+                }
+
+                if existing.is_some() {
+                    if inserted == 0 {
+                        continue;
+                    }
+                    conn.execute(
+                        "UPDATE history
+                         SET title = CASE WHEN title = '' THEN ?1 ELSE title END,
+                             visited_at = MAX(visited_at, ?2),
+                             visit_count = visit_count + ?3,
+                             typed_count = typed_count + ?4
+                         WHERE id = ?5",
+                        rusqlite::params![
+                            entry.title,
+                            entry.last_visited_at.to_rfc3339(),
+                            counted,
+                            typed,
+                            url_id
+                        ],
+                    )?;
+                    stats.merged_urls += 1;
+                } else {
+                    conn.execute(
+                        "UPDATE history SET visit_count = ?1, typed_count = ?2 WHERE id = ?3",
+                        rusqlite::params![entry.visit_count.max(counted), typed, url_id],
+                    )?;
+                    stats.added_urls += 1;
+                }
+                stats.added_visits += inserted;
+                update_frecency(conn, url_id, now)?;
+            }
+
+            Ok(stats)
         })?)
     }
 
@@ -72,32 +389,25 @@ impl HistoryManager {
     }
 
     /// Search history by query
-    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<HistoryEntry>> {
+    pub fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        sort: HistorySort,
+    ) -> Result<Vec<HistoryEntry>> {
         Ok(self.db.with_connection(|conn| {
             let pattern = format!("%{}%", query.to_lowercase());
 
-            let mut stmt = conn.prepare(
-                "SELECT id, url, title, visited_at, visit_count FROM history    
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {ENTRY_COLUMNS} FROM history
                  WHERE LOWER(url) LIKE ?1 OR LOWER(title) LIKE ?1
-                 ORDER BY visited_at DESC, visit_count DESC
+                 ORDER BY {}
                  LIMIT ?2",
-            )?;
+                sort.order_by()
+            ))?;
 
             let entries: Vec<HistoryEntry> = stmt
-                .query_map(rusqlite::params![pattern, limit as i64], |row| {
-                    let visited_str: String = row.get(3)?;
-                    let visited_at = DateTime::parse_from_rfc3339(&visited_str)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now());
-
-                    Ok(HistoryEntry {
-                        id: row.get(0)?,
-                        url: row.get(1)?,
-                        title: row.get(2)?,
-                        visited_at,
-                        visit_count: row.get(4)?,
-                    })
-                })?
+                .query_map(rusqlite::params![pattern, limit as i64], entry_from_row)?
                 .filter_map(|r| r.ok())
                 .collect();
 
@@ -106,88 +416,259 @@ impl HistoryManager {
     }
 
     /// Get recent history entries
-    pub fn recent(&self, limit: usize) -> Result<Vec<HistoryEntry>> {
+    pub fn recent(&self, limit: usize, sort: HistorySort) -> Result<Vec<HistoryEntry>> {
         Ok(self.db.with_connection(|conn| {
-            let mut stmt = conn.prepare(
-                "SELECT id, url, title, visited_at, visit_count FROM history
-                 ORDER BY visited_at DESC
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {ENTRY_COLUMNS} FROM history
+                 ORDER BY {}
                  LIMIT ?1",
-            )?;
+                sort.order_by()
+            ))?;
 
             let entries: Vec<HistoryEntry> = stmt
-                .query_map([limit as i64], |row| {
-                    let visited_str: String = row.get(3)?;
+                .query_map([limit as i64], entry_from_row)?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            Ok(entries)
+        })?)
+    }
+
+    /// Individual visits within an optional time range (inclusive), newest first
+    pub fn visits_in_range(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<Visit>> {
+        let start = start.map(|t| t.to_rfc3339());
+        let end = end.map(|t| t.to_rfc3339());
+
+        Ok(self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT v.id, v.history_id, h.url, h.title, v.visited_at, v.transition,
+                        v.from_visit_id, v.tab_id, v.session_id
+                 FROM visits v JOIN history h ON h.id = v.history_id
+                 WHERE (?1 IS NULL OR v.visited_at >= ?1)
+                   AND (?2 IS NULL OR v.visited_at <= ?2)
+                 ORDER BY v.visited_at DESC, v.id DESC
+                 LIMIT ?3",
+            )?;
+
+            let visits: Vec<Visit> = stmt
+                .query_map(rusqlite::params![start, end, limit as i64], |row| {
+                    let visited_str: String = row.get(4)?;
                     let visited_at = DateTime::parse_from_rfc3339(&visited_str)
                         .map(|dt| dt.with_timezone(&Utc))
                         .unwrap_or_else(|_| Utc::now());
+                    let transition: String = row.get(5)?;
 
-                    Ok(HistoryEntry {
+                    Ok(Visit {
                         id: row.get(0)?,
-                        url: row.get(1)?,
-                        title: row.get(2)?,
+                        url_id: row.get(1)?,
+                        url: row.get(2)?,
+                        title: row.get(3)?,
                         visited_at,
-                        visit_count: row.get(4)?,
+                        transition: transition.parse().unwrap_or_default(),
+                        from_visit_id: row.get(6)?,
+                        tab_id: row.get(7)?,
+                        session_id: row.get(8)?,
                     })
                 })?
                 .filter_map(|r| r.ok())
                 .collect();
 
-            Ok(entries)
+            Ok(visits)
         })?)
     }
 
-    /// Delete a history entry
+    /// Journeys in an optional time range, newest first
+    ///
+    /// Only the newest `MAX_JOURNEY_VISITS` visits of the range are grouped.
+    /// With a `query`, only journeys whose label, titles or URLs contain it
+    /// are returned.
+    pub fn journeys(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        query: Option<&str>,
+        limit: usize,
+        public_suffix_list: &axiom_privacy::PublicSuffixList,
+    ) -> Result<Vec<Journey>> {
+        let visits = self.visits_in_range(start, end, MAX_JOURNEY_VISITS)?;
+        Ok(journeys::cluster_journeys(&visits, public_suffix_list)
+            .into_iter()
+            .filter(|journey| query.is_none_or(|q| journey.matches(q)))
+            .take(limit)
+            .collect())
+    }
+
+    /// Delete a history entry (and its visits)
     pub fn delete(&self, id: i64) -> Result<()> {
-        Ok(self.db.with_connection(|conn| {
+        self.db.with_connection(|conn| {
             conn.execute("DELETE FROM history WHERE id = ?1", [id])?;
             Ok(())
-        })?)
+        })?;
+        self.tab_visits.write().clear();
+        Ok(())
+    }
+
+    /// Delete every URL (and its visits) on `domain` or one of its subdomains
+    ///
+    /// Returns the number of URLs removed.
+    pub fn delete_domain(&self, domain: &str) -> Result<usize> {
+        let removed = self.db.transaction(|conn| {
+            let ids: Vec<i64> = {
+                let mut stmt =
+                    conn.prepare("SELECT id, url FROM history WHERE instr(lower(url), ?1) > 0")?;
+                let rows = stmt
+                    .query_map([domain.to_lowercase()], |row| {
+                        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                    })?
+                    .filter_map(|r| r.ok())
+                    .filter(|(_, url)| {
+                        url::Url::parse(url).ok().is_some_and(|url| {
+                            url.host_str()
+                                .is_some_and(|host| axiom_privacy::host_in_domain(host, domain))
+                        })
+                    })
+                    .map(|(id, _)| id)
+                    .collect();
+                rows
+            };
+
+            for id in &ids {
+                conn.execute("DELETE FROM visits WHERE history_id = ?1", [id])?;
+                conn.execute("DELETE FROM history WHERE id = ?1", [id])?;
+            }
+            Ok(ids.len())
+        })?;
+
+        self.tab_visits.write().clear();
+        Ok(removed)
     }
 
     /// Clear all history
     pub fn clear_all(&self) -> Result<()> {
-        Ok(self.db.with_connection(|conn| {
+        self.db.with_connection(|conn| {
+            conn.execute("DELETE FROM visits", [])?;
             conn.execute("DELETE FROM history", [])?;
             Ok(())
-        })?)
+        })?;
+        self.tab_visits.write().clear();
+        Ok(())
     }
 
-    /// Clear history within an optional time range (inclusive).
+    /// Clear visits within an optional time range (inclusive).
+    ///
+    /// Visit counts, last-visit times and frecency of the affected URLs are
+    /// adjusted; URLs left without any visits are removed.
     pub fn clear_range(
         &self,
         start: Option<DateTime<Utc>>,
         end: Option<DateTime<Utc>>,
     ) -> Result<()> {
+        let now = Utc::now();
         let start = start.map(|t| t.to_rfc3339());
         let end = end.map(|t| t.to_rfc3339());
 
-        Ok(self.db.with_connection(|conn| {
-            match (start, end) {
-                (Some(start), Some(end)) => {
-                    conn.execute(
-                        "DELETE FROM history WHERE visited_at >= ?1 AND visited_at <= ?2",
-                        rusqlite::params![start, end],
-                    )?;
-                }
-                (Some(start), None) => {
-                    conn.execute("DELETE FROM history WHERE visited_at >= ?1", [start])?;
-                }
-                (None, Some(end)) => {
-                    conn.execute("DELETE FROM history WHERE visited_at <= ?1", [end])?;
-                }
-                (None, None) => {
-                    conn.execute("DELETE FROM history", [])?;
-                }
+        self.db.transaction(|conn| {
+            let range = "(?1 IS NULL OR visited_at >= ?1) AND (?2 IS NULL OR visited_at <= ?2)";
+
+            // (url id, counted visits removed, typed visits removed)
+            let affected: Vec<(i64, i64, i64)> = {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT history_id,
+                            SUM(transition != 'reload'),
+                            SUM(transition = 'typed')
+                     FROM visits WHERE {range} GROUP BY history_id"
+                ))?;
+                let rows = stmt
+                    .query_map(rusqlite::params![start, end], |row| {
+                        Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                    })?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                rows
+            };
+
+            conn.execute(
+                &format!("DELETE FROM visits WHERE {range}"),
+                rusqlite::params![start, end],
+            )?;
+
+            for (url_id, counted, typed) in affected {
+                let last_visit: Option<String> = conn.query_row(
+                    "SELECT MAX(visited_at) FROM visits WHERE history_id = ?1",
+                    [url_id],
+                    |row| row.get(0),
+                )?;
+
+                let Some(last_visit) = last_visit else {
+                    conn.execute("DELETE FROM history WHERE id = ?1", [url_id])?;
+                    continue;
+                };
+
+                conn.execute(
+                    "UPDATE history
+                     SET visit_count = MAX(visit_count - ?1, 0),
+                         typed_count = MAX(typed_count - ?2, 0),
+                         visited_at = ?3
+                     WHERE id = ?4",
+                    rusqlite::params![counted, typed, last_visit, url_id],
+                )?;
+                update_frecency(conn, url_id, now)?;
             }
+
             Ok(())
-        })?)
+        })?;
+
+        // Referrers may point at deleted visits
+        self.tab_visits.write().clear();
+        Ok(())
     }
 }
 
+/// Recompute a URL's frecency from its most recent counted visits; returns the new score
+fn update_frecency(conn: &Connection, url_id: i64, now: DateTime<Utc>) -> rusqlite::Result<i64> {
+    let visit_count: i64 = conn
+        .prepare_cached("SELECT visit_count FROM history WHERE id = ?1")?
+        .query_row([url_id], |row| row.get(0))?;
+
+    let samples: Vec<VisitSample> = {
+        let mut stmt = conn.prepare_cached(
+            "SELECT visited_at, transition FROM visits
+             WHERE history_id = ?1 AND transition != 'reload'
+             ORDER BY visited_at DESC LIMIT ?2",
+        )?;
+        let samples = stmt
+            .query_map(rusqlite::params![url_id, MAX_SAMPLES as i64], |row| {
+                let at: String = row.get(0)?;
+                let transition: String = row.get(1)?;
+                Ok(DateTime::parse_from_rfc3339(&at)
+                    .ok()
+                    .map(|at| VisitSample {
+                        at: at.with_timezone(&Utc),
+                        transition: transition.parse().unwrap_or_default(),
+                    }))
+            })?
+            .filter_map(|r| r.ok().flatten())
+            .collect();
+        samples
+    };
+
+    let score = frecency::score(visit_count, &samples, now);
+    conn.prepare_cached("UPDATE history SET frecency = ?1 WHERE id = ?2")?
+        .execute(rusqlite::params![score, url_id])?;
+
+    Ok(score)
+}
+
 impl Clone for HistoryManager {
     fn clone(&self) -> Self {
         Self {
             db: self.db.clone(),
+            tab_visits: Arc::clone(&self.tab_visits),
         }
     }
 }
@@ -203,22 +684,221 @@ mod tests {
 
         // Record visits
         manager
-            .record_visit("https://example.com", "Example")
+            .record_visit(
+                "https://example.com",
+                "Example",
+                VisitTransition::Link,
+                &VisitSource::default(),
+            )
             .unwrap();
         manager
-            .record_visit("https://rust-lang.org", "Rust")
+            .record_visit(
+                "https://rust-lang.org",
+                "Rust",
+                VisitTransition::Link,
+                &VisitSource::default(),
+            )
             .unwrap();
         manager
-            .record_visit("https://example.com", "Example")
+            .record_visit(
+                "https://example.com",
+                "Example",
+                VisitTransition::Link,
+                &VisitSource::default(),
+            )
             .unwrap(); // Second visit
 
         // Search
-        let results = manager.search("example", 10).unwrap();
+        let results = manager.search("example", 10, HistorySort::Recency).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].visit_count, 2);
 
         // Recent
-        let recent = manager.recent(10).unwrap();
+        let recent = manager.recent(10, HistorySort::Recency).unwrap();
         assert_eq!(recent.len(), 2);
+
+        // Forget a domain
+        assert_eq!(manager.delete_domain("example.com").unwrap(), 1);
+        let recent = manager.recent(10, HistorySort::Recency).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].url, "https://rust-lang.org");
+    }
+
+    #[test]
+    fn test_frecency_ordering() {
+        let db = Database::open_in_memory().unwrap();
+        let manager = HistoryManager::new(db);
+
+        for _ in 0..5 {
+            manager
+                .record_visit(
+                    "https://docs.rs/",
+                    "Docs",
+                    VisitTransition::Typed,
+                    &VisitSource::default(),
+                )
+                .unwrap();
+        }
+        manager
+            .record_visit(
+                "https://news.example/once",
+                "News",
+                VisitTransition::Link,
+                &VisitSource::default(),
+            )
+            .unwrap();
+
+        let recent = manager.recent(10, HistorySort::Recency).unwrap();
+        assert_eq!(recent[0].url, "https://news.example/once");
+
+        let ranked = manager.recent(10, HistorySort::Frecency).unwrap();
+        assert_eq!(ranked[0].url, "https://docs.rs/");
+        assert!(ranked[0].frecency > ranked[1].frecency);
+        assert_eq!(ranked[1].frecency, 100);
+
+        // Nothing has aged, so recalculation leaves scores alone
+        assert_eq!(manager.recalculate_frecency().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_recalculate_frecency_skips_settled_visits() {
+        let manager = HistoryManager::new(Database::open_in_memory().unwrap());
+        let at = |days: i64| Utc::now() - chrono::Duration::days(days);
+        let entry = |url: &str, days: i64| ImportedHistory {
+            url: url.to_string(),
+            title: String::new(),
+            visit_count: 1,
+            last_visited_at: at(days),
+            visits: vec![(at(days), VisitTransition::Link)],
+        };
+        manager
+            .import(&[
+                entry("https://recent.example/", 10),
+                entry("https://settled.example/", 200),
+            ])
+            .unwrap();
+        assert_eq!(manager.recalculate_frecency().unwrap(), 0);
+
+        // Visits already past the last bucket at the previous pass are not rescored
+        manager
+            .db
+            .with_connection(|conn| {
+                conn.execute("UPDATE history SET frecency = 0", [])?;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(manager.recalculate_frecency().unwrap(), 1);
+        let settled = &manager.search("settled", 10, HistorySort::Recency).unwrap()[0];
+        assert_eq!(settled.frecency, 0);
+        let recent = &manager.search("recent", 10, HistorySort::Recency).unwrap()[0];
+        assert!(recent.frecency > 0);
+    }
+
+    #[test]
+    fn test_visit_log_and_clear_range() {
+        let db = Database::open_in_memory().unwrap();
+        let manager = HistoryManager::new(db);
+        let tab = VisitSource {
+            tab_id: Some("tab-1".to_string()),
+            session_id: None,
+        };
+
+        let typed = manager
+            .record_visit("https://a.example/", "A", VisitTransition::Typed, &tab)
+            .unwrap();
+        let link = manager
+            .record_visit("https://b.example/", "B", VisitTransition::Link, &tab)
+            .unwrap();
+        manager
+            .record_visit("https://b.example/", "", VisitTransition::Reload, &tab)
+            .unwrap();
+        let cutoff = Utc::now();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        manager
+            .record_visit("https://a.example/", "", VisitTransition::Link, &tab)
+            .unwrap();
+
+        let visits = manager.visits_in_range(None, None, 10).unwrap();
+        assert_eq!(visits.len(), 4);
+        assert_eq!(visits[1].transition, VisitTransition::Reload);
+        assert_eq!(visits[1].from_visit_id, Some(link));
+        assert_eq!(visits[3].id, typed);
+        assert_eq!(visits[3].from_visit_id, None);
+        assert_eq!(visits[3].tab_id.as_deref(), Some("tab-1"));
+
+        // Reloads are logged but not counted
+        let b = manager
+            .search("b.example", 10, HistorySort::Recency)
+            .unwrap();
+        assert_eq!(b[0].visit_count, 1);
+
+        // Clearing everything up to the cutoff removes b.example entirely and
+        // leaves a.example with only its later visit
+        manager.clear_range(None, Some(cutoff)).unwrap();
+        let remaining = manager.recent(10, HistorySort::Recency).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].url, "https://a.example/");
+        assert_eq!(remaining[0].visit_count, 1);
+        assert_eq!(remaining[0].frecency, 100);
+        assert_eq!(manager.visits_in_range(None, None, 10).unwrap().len(), 1);
+
+        // New visits no longer reference the cleared ones
+        manager
+            .record_visit("https://c.example/", "C", VisitTransition::Link, &tab)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_import_is_idempotent() {
+        let manager = HistoryManager::new(Database::open_in_memory().unwrap());
+        manager
+            .record_visit(
+                "https://example.com/",
+                "Example",
+                VisitTransition::Link,
+                &VisitSource::default(),
+            )
+            .unwrap();
+
+        let at = |days: i64| Utc::now() - chrono::Duration::days(days);
+        let entries = vec![
+            ImportedHistory {
+                url: "https://example.com/".to_string(),
+                title: "Other title".to_string(),
+                visit_count: 1,
+                last_visited_at: at(2),
+                visits: vec![(at(2), VisitTransition::Typed)],
+            },
+            ImportedHistory {
+                url: "https://rust-lang.org/".to_string(),
+                title: "Rust".to_string(),
+                visit_count: 7,
+                last_visited_at: at(1),
+                visits: vec![
+                    (at(3), VisitTransition::Link),
+                    (at(1), VisitTransition::Link),
+                ],
+            },
+        ];
+
+        let stats = manager.import(&entries).unwrap();
+        assert_eq!(
+            stats,
+            HistoryImportStats {
+                added_urls: 1,
+                merged_urls: 1,
+                added_visits: 3,
+            }
+        );
+        let example = &manager.search("example", 10, HistorySort::Recency).unwrap()[0];
+        assert_eq!(example.visit_count, 2);
+        assert_eq!(example.title, "Example");
+        let rust = &manager.search("rust", 10, HistorySort::Recency).unwrap()[0];
+        assert_eq!(rust.visit_count, 7);
+        assert!(rust.frecency > 0);
+
+        assert_eq!(manager.import(&entries).unwrap().added_visits, 0);
+        assert_eq!(manager.visits_in_range(None, None, 100).unwrap().len(), 4);
+        assert_eq!(manager.known_urls().unwrap().len(), 2);
     }
 }
@@ -0,0 +1,309 @@
+//! HTTPS-Only mode
+//!
+//! When enabled, `http://` navigations are upgraded to `https://` before they
+//! load. A host that cannot be reached securely can be exempted, after which
+//! it loads over plain HTTP. Exceptions are kept per host in the
+//! `https_only_exceptions` table; those added in a private session live in
+//! memory for that session only. Local names (`localhost`, loopback and
+//! private addresses, single-label intranet hosts, `.local`) are never
+//! upgraded, since they rarely have certificates.
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::Arc;
+use url::{Host, Url};
+
+use axiom_storage::Database;
+
+use crate::error::NavigationError;
+use crate::Result;
+
+const ENABLED_SETTING: &str = "https_only_enabled";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpsOnlyException {
+    pub host: String,
+    pub created_at: DateTime<Utc>,
+}
+
+fn is_local_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => v4.is_loopback() || v4.is_private() || v4.is_link_local(),
+        IpAddr::V6(v6) => {
+            // fc00::/7 (unique local) and fe80::/10 (link-local)
+            v6.is_loopback()
+                || (v6.segments()[0] & 0xfe00) == 0xfc00
+                || (v6.segments()[0] & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+fn is_local_host(host: &Host<&str>) -> bool {
+    match host {
+        Host::Ipv4(ip) => is_local_ip(IpAddr::V4(*ip)),
+        Host::Ipv6(ip) => is_local_ip(IpAddr::V6(*ip)),
+        Host::Domain(domain) => {
+            let domain = domain.trim_end_matches('.');
+            !domain.contains('.')
+                || domain.ends_with(".localhost")
+                || domain.ends_with(".local")
+                || domain.ends_with(".internal")
+        }
+    }
+}
+
+/// The `https://` form of an `http://` URL, unless the host is local
+pub fn upgrade_url(url: &str) -> Option<String> {
+    let mut parsed = Url::parse(url).ok()?;
+    if parsed.scheme() != "http" || is_local_host(&parsed.host()?) {
+        return None;
+    }
+    // An explicit :80 is dropped when parsing; other ports are kept
+    parsed.set_scheme("https").ok()?;
+    Some(parsed.to_string())
+}
+
+/// Lowercase host without a trailing dot, from a host or a URL
+fn normalize_host(input: &str) -> Result<String> {
+    let input = input.trim();
+    let parsed = if input.contains("://") {
+        Url::parse(input)
+    } else {
+        Url::parse(&format!("http://{input}/"))
+    };
+    parsed
+        .ok()
+        .and_then(|url| url.host_str().map(|h| h.trim_end_matches('.').to_string()))
+        .filter(|h| !h.is_empty())
+        .ok_or_else(|| NavigationError::InvalidUrl(input.to_string()))
+}
+
+pub struct HttpsOnlyManager {
+    db: Database,
+    /// Unsaved exceptions by session id (private sessions)
+    session_exceptions: Arc<RwLock<HashMap<String, HashSet<String>>>>,
+}
+
+impl HttpsOnlyManager {
+    pub fn new(db: Database) -> Self {
+        Self {
+            db,
+            session_exceptions: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Off unless the user turned it on
+    pub fn is_enabled(&self) -> Result<bool> {
+        Ok(self
+            .db
+            .get_setting(ENABLED_SETTING)?
+            .is_some_and(|v| v == "true"))
+    }
+
+    pub fn set_enabled(&self, enabled: bool) -> Result<()> {
+        self.db
+            .set_setting(ENABLED_SETTING, if enabled { "true" } else { "false" })?;
+        Ok(())
+    }
+
+    /// The URL to load instead of `url`, if HTTPS-Only applies to it
+    pub fn upgrade(&self, url: &str) -> Result<Option<String>> {
+        if !self.is_enabled()? {
+            return Ok(None);
+        }
+        let Some(upgraded) = upgrade_url(url) else {
+            return Ok(None);
+        };
+        if self.has_exception(url)? {
+            return Ok(None);
+        }
+        Ok(Some(upgraded))
+    }
+
+    /// Like [`Self::upgrade`], also honouring the session's unsaved exceptions
+    pub fn upgrade_in_session(&self, url: &str, session_id: &str) -> Result<Option<String>> {
+        let Some(upgraded) = self.upgrade(url)? else {
+            return Ok(None);
+        };
+        let host = normalize_host(url)?;
+        let exempt = self
+            .session_exceptions
+            .read()
+            .get(session_id)
+            .is_some_and(|hosts| hosts.contains(&host));
+        Ok((!exempt).then_some(upgraded))
+    }
+
+    /// True if the host (or the URL's host) may load over HTTP
+    pub fn has_exception(&self, host_or_url: &str) -> Result<bool> {
+        let host = normalize_host(host_or_url)?;
+        Ok(self.db.with_connection(|conn| {
+            Ok(conn
+                .query_row(
+                    "SELECT 1 FROM https_only_exceptions WHERE host = ?1",
+                    [&host],
+                    |_| Ok(()),
+                )
+                .is_ok())
+        })?)
+    }
+
+    pub fn list_exceptions(&self) -> Result<Vec<HttpsOnlyException>> {
+        Ok(self.db.with_connection(|conn| {
+            let mut stmt = conn
+                .prepare("SELECT host, created_at FROM https_only_exceptions ORDER BY host ASC")?;
+            let exceptions = stmt
+                .query_map([], |row| {
+                    let created_at: String = row.get(1)?;
+                    Ok(HttpsOnlyException {
+                        host: row.get(0)?,
+                        created_at: DateTime::parse_from_rfc3339(&created_at)
+                            .map(|d| d.with_timezone(&Utc))
+                            .unwrap_or_else(|_| Utc::now()),
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(exceptions)
+        })?)
+    }
+
+    /// Let a host load over HTTP; returns the normalized host
+    pub fn add_exception(&self, host_or_url: &str) -> Result<String> {
+        let host = normalize_host(host_or_url)?;
+        self.db.with_connection(|conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO https_only_exceptions (host, created_at) VALUES (?1, ?2)",
+                rusqlite::params![host, Utc::now().to_rfc3339()],
+            )?;
+            Ok(())
+        })?;
+
+        tracing::info!(host = %host, "Added HTTPS-Only exception");
+
+        Ok(host)
+    }
+
+    /// Let a host load over HTTP in one session only, without saving it
+    pub fn add_session_exception(&self, session_id: &str, host_or_url: &str) -> Result<String> {
+        let host = normalize_host(host_or_url)?;
+        self.session_exceptions
+            .write()
+            .entry(session_id.to_string())
+            .or_default()
+            .insert(host.clone());
+        Ok(host)
+    }
+
+    /// Drop a session's unsaved exceptions
+    pub fn forget_session(&self, session_id: &str) {
+        self.session_exceptions.write().remove(session_id);
+    }
+
+    pub fn remove_exception(&self, host_or_url: &str) -> Result<()> {
+        let host = normalize_host(host_or_url)?;
+        self.db.with_connection(|conn| {
+            conn.execute("DELETE FROM https_only_exceptions WHERE host = ?1", [&host])?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+impl Clone for HttpsOnlyManager {
+    fn clone(&self) -> Self {
+        Self {
+            db: self.db.clone(),
+            session_exceptions: Arc::clone(&self.session_exceptions),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade_url() {
+        assert_eq!(
+            upgrade_url("http://example.com/a?b=1#c").as_deref(),
+            Some("https://example.com/a?b=1#c")
+        );
+        assert_eq!(
+            upgrade_url("http://example.com:80/").as_deref(),
+            Some("https://example.com/")
+        );
+        assert_eq!(
+            upgrade_url("http://example.com:8080/").as_deref(),
+            Some("https://example.com:8080/")
+        );
+        assert!(upgrade_url("https://example.com/").is_none());
+        assert!(upgrade_url("http://localhost:3000/").is_none());
+        assert!(upgrade_url("http://app.localhost/").is_none());
+        assert!(upgrade_url("http://127.0.0.1/").is_none());
+        assert!(upgrade_url("http://192.168.1.1/").is_none());
+        assert!(upgrade_url("http://[::1]/").is_none());
+        assert!(upgrade_url("http://router/").is_none());
+        assert!(upgrade_url("http://printer.local/").is_none());
+        assert!(upgrade_url("http://93.184.216.34/").is_some());
+    }
+
+    #[test]
+    fn test_exceptions() {
+        let manager = HttpsOnlyManager::new(Database::open_in_memory().unwrap());
+        assert!(!manager.is_enabled().unwrap());
+        assert!(manager.upgrade("http://example.com/").unwrap().is_none());
+
+        manager.set_enabled(true).unwrap();
+        assert_eq!(
+            manager.upgrade("http://example.com/").unwrap().as_deref(),
+            Some("https://example.com/")
+        );
+
+        let host = manager.add_exception("http://Example.COM./page").unwrap();
+        assert_eq!(host, "example.com");
+        assert!(manager.has_exception("example.com").unwrap());
+        assert!(manager
+            .upgrade("http://example.com/other")
+            .unwrap()
+            .is_none());
+        // Exceptions are per host, not per site
+        assert!(manager
+            .upgrade("http://www.example.com/")
+            .unwrap()
+            .is_some());
+        assert_eq!(manager.list_exceptions().unwrap().len(), 1);
+
+        manager.remove_exception("example.com").unwrap();
+        assert!(manager.upgrade("http://example.com/").unwrap().is_some());
+        assert!(manager.add_exception("").is_err());
+    }
+
+    #[test]
+    fn test_session_exceptions() {
+        let manager = HttpsOnlyManager::new(Database::open_in_memory().unwrap());
+        manager.set_enabled(true).unwrap();
+
+        manager
+            .add_session_exception("private", "http://example.com/")
+            .unwrap();
+        assert!(manager
+            .upgrade_in_session("http://example.com/", "private")
+            .unwrap()
+            .is_none());
+        assert!(manager
+            .upgrade_in_session("http://example.com/", "other")
+            .unwrap()
+            .is_some());
+        assert!(manager.list_exceptions().unwrap().is_empty());
+
+        manager.forget_session("private");
+        assert!(manager
+            .upgrade_in_session("http://example.com/", "private")
+            .unwrap()
+            .is_some());
+    }
+}
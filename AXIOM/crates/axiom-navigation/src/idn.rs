@@ -0,0 +1,500 @@
+//! IDN homograph checks and the punycode display policy
+//!
+//! Internationalized hostnames are shown in Unicode only when they cannot be
+//! mistaken for another name, roughly following Chromium's rules:
+//!
+//! - no label mixes scripts, except the combinations CJK writing uses
+//!   (Latin with Han and Kana, Bopomofo or Hangul)
+//! - no label is made entirely of Cyrillic or Greek letters that look Latin
+//!   (`раураl`), unless the TLD belongs to that script
+//! - the site does not reduce to a well-known domain once lookalike letters
+//!   and diacritics are replaced (`аpple.com`, `gööglе.com`)
+//! - no label contains characters that pass for punctuation
+//!
+//! Anything else is displayed as punycode (`xn--pple-43d.com`) with a warning
+//! the UI can show.
+
+use serde::{Deserialize, Serialize};
+
+/// Why a hostname is displayed as punycode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdnRisk {
+    /// A label mixes scripts (`аpple`: Cyrillic `а` with Latin)
+    MixedScript,
+    /// A label is entirely in a script that imitates Latin (`сосо`)
+    WholeScriptConfusable,
+    /// The site looks like a well-known domain
+    Lookalike,
+    /// A character that looks like punctuation or a different letter
+    DisallowedCharacter,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdnWarning {
+    pub risk: IdnRisk,
+    /// The hostname in Unicode, as it would otherwise have been shown
+    pub unicode_host: String,
+    /// The well-known domain it imitates (for `Lookalike`)
+    pub lookalike_of: Option<String>,
+}
+
+/// How to show a URL's host
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UrlDisplay {
+    /// The URL with the host in its display form
+    pub display_url: String,
+    /// The host as displayed: Unicode when safe, punycode otherwise
+    pub display_host: String,
+    pub warning: Option<IdnWarning>,
+}
+
+/// Domains whose lookalikes are always shown as punycode
+const TOP_DOMAINS: &[&str] = &[
+    "google.com",
+    "youtube.com",
+    "facebook.com",
+    "instagram.com",
+    "whatsapp.com",
+    "twitter.com",
+    "x.com",
+    "linkedin.com",
+    "wikipedia.org",
+    "amazon.com",
+    "apple.com",
+    "icloud.com",
+    "microsoft.com",
+    "live.com",
+    "outlook.com",
+    "office.com",
+    "github.com",
+    "gitlab.com",
+    "paypal.com",
+    "ebay.com",
+    "netflix.com",
+    "yahoo.com",
+    "bing.com",
+    "reddit.com",
+    "dropbox.com",
+    "adobe.com",
+    "spotify.com",
+    "steampowered.com",
+    "binance.com",
+    "coinbase.com",
+    "blockchain.com",
+    "chase.com",
+    "bankofamerica.com",
+    "wellsfargo.com",
+    "citibank.com",
+    "americanexpress.com",
+    "visa.com",
+    "mastercard.com",
+    "stripe.com",
+    "shopify.com",
+    "cloudflare.com",
+    "yandex.ru",
+    "mail.ru",
+    "vk.com",
+    "baidu.com",
+    "qq.com",
+    "tiktok.com",
+    "telegram.org",
+    "discord.com",
+    "zoom.us",
+];
+
+/// TLDs under which an all-Cyrillic label is expected
+const CYRILLIC_TLDS: &[&str] = &[
+    "ru",
+    "su",
+    "рф",
+    "ua",
+    "укр",
+    "by",
+    "бел",
+    "kz",
+    "қаз",
+    "bg",
+    "бг",
+    "mk",
+    "мкд",
+    "rs",
+    "срб",
+    "mn",
+    "мон",
+    "kg",
+    "tj",
+    "uz",
+    "рус",
+    "москва",
+    "дети",
+    "сайт",
+    "онлайн",
+    "орг",
+    "ком",
+];
+
+/// TLDs under which an all-Greek label is expected
+const GREEK_TLDS: &[&str] = &["gr", "ελ", "cy", "ευ"];
+
+/// Characters that pass for punctuation or for other letters
+const DISALLOWED: &[char] = &[
+    '\u{0131}', // ı dotless i
+    '\u{0138}', // ĸ kra
+    '\u{01C0}', // ǀ dental click
+    '\u{01C3}', // ǃ retroflex click
+    '\u{02BB}', // ʻ turned comma
+    '\u{02BC}', // ʼ modifier apostrophe
+    '\u{02D0}', // ː triangular colon
+    '\u{0589}', // ։ Armenian full stop
+    '\u{05C3}', // ׃ Hebrew sof pasuq
+    '\u{05F4}', // ״ Hebrew gershayim
+    '\u{0F0B}', // ་ Tibetan tsheg
+    '\u{2010}', // ‐ hyphen
+    '\u{2019}', // ’ right single quote
+    '\u{2027}', // ‧ hyphenation point
+    '\u{30FB}', // ・ katakana middle dot
+    '\u{A789}', // ꞉ modifier colon
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    /// Digits and hyphens, which go with any script
+    Common,
+    /// Combining marks, which take the script of the base letter
+    Inherited,
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+    Hebrew,
+    Arabic,
+    Indic,
+    Thai,
+    Georgian,
+    Hangul,
+    Hiragana,
+    Katakana,
+    Bopomofo,
+    Han,
+    Other,
+}
+
+fn script_of(c: char) -> Script {
+    match c as u32 {
+        0x30..=0x39 | 0x2D => Script::Common,
+        0x61..=0x7A | 0x41..=0x5A => Script::Latin,
+        0x300..=0x36F | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF | 0x200C | 0x200D => Script::Inherited,
+        0xC0..=0x24F | 0x1E00..=0x1EFF | 0x250..=0x2AF | 0x2C60..=0x2C7F | 0xA720..=0xA7FF => {
+            Script::Latin
+        }
+        0x370..=0x3FF | 0x1F00..=0x1FFF => Script::Greek,
+        0x400..=0x52F | 0x1C80..=0x1C8F | 0x2DE0..=0x2DFF | 0xA640..=0xA69F => Script::Cyrillic,
+        0x530..=0x58F => Script::Armenian,
+        0x590..=0x5FF => Script::Hebrew,
+        0x600..=0x6FF | 0x750..=0x77F | 0x8A0..=0x8FF => Script::Arabic,
+        0x900..=0xDFF => Script::Indic,
+        0xE00..=0xE7F => Script::Thai,
+        0x10A0..=0x10FF | 0x2D00..=0x2D2F => Script::Georgian,
+        0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Script::Hangul,
+        0x3040..=0x309F => Script::Hiragana,
+        // The prolonged sound mark is shared by Hiragana and Katakana
+        0x30FC => Script::Common,
+        0x30A0..=0x30FF | 0x31F0..=0x31FF => Script::Katakana,
+        0x3100..=0x312F | 0x31A0..=0x31BF => Script::Bopomofo,
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x2FA1F => Script::Han,
+        _ => Script::Other,
+    }
+}
+
+/// The Latin letter a character imitates, if any
+fn latin_lookalike(c: char) -> Option<char> {
+    Some(match c {
+        // Cyrillic
+        'а' => 'a',
+        'в' | 'ь' => 'b',
+        'с' => 'c',
+        'ԁ' => 'd',
+        'е' | 'ё' | 'ҽ' => 'e',
+        'г' => 'r',
+        'һ' | 'н' => 'h',
+        'і' | 'ї' => 'i',
+        'ј' => 'j',
+        'к' => 'k',
+        'ӏ' => 'l',
+        'м' => 'm',
+        'п' => 'n',
+        'о' => 'o',
+        'р' => 'p',
+        'ԛ' => 'q',
+        'ѕ' => 's',
+        'т' => 't',
+        'у' => 'y',
+        'ѵ' => 'v',
+        'ԝ' | 'ѡ' => 'w',
+        'х' => 'x',
+        // Greek
+        'α' => 'a',
+        'β' => 'b',
+        'ϲ' => 'c',
+        'ε' => 'e',
+        'η' => 'n',
+        'ι' | 'ί' => 'i',
+        'ϳ' => 'j',
+        'κ' => 'k',
+        'ν' => 'v',
+        'ο' | 'ό' => 'o',
+        'ρ' => 'p',
+        'τ' => 't',
+        'υ' | 'ύ' => 'u',
+        'χ' => 'x',
+        'γ' => 'y',
+        // Armenian
+        'հ' => 'h',
+        'ո' => 'n',
+        'օ' => 'o',
+        'ս' => 'u',
+        'ց' => 'g',
+        'զ' => 'q',
+        // Latin letters with marks or unusual shapes
+        'à'..='å' | 'ā' | 'ă' | 'ą' | 'ǎ' | 'ạ' | 'ả' | 'ɑ' => 'a',
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+        'ď' | 'đ' | 'ɗ' => 'd',
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' | 'ẹ' | 'ẽ' => 'e',
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' | 'ɡ' => 'g',
+        'ĥ' | 'ħ' => 'h',
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' | 'ǐ' | 'ị' => 'i',
+        'ĵ' => 'j',
+        'ķ' | 'ĸ' => 'k',
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' | 'ǀ' => 'l',
+        'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+        'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' | 'ǒ' | 'ọ' | 'ơ' => 'o',
+        'ŕ' | 'ŗ' | 'ř' => 'r',
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ș' => 's',
+        'ţ' | 'ť' | 'ŧ' | 'ț' => 't',
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' | 'ǔ' | 'ụ' | 'ư' => 'u',
+        'ŵ' => 'w',
+        'ý' | 'ÿ' | 'ŷ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        _ => return None,
+    })
+}
+
+/// The Latin string a label would pass for, with digits that double as letters
+fn skeleton(label: &str) -> String {
+    label
+        .chars()
+        .filter(|c| script_of(*c) != Script::Inherited)
+        .map(|c| {
+            let c = latin_lookalike(c).unwrap_or(c);
+            match c {
+                '0' => 'o',
+                '1' => 'l',
+                _ => c,
+            }
+        })
+        .collect::<String>()
+        .replace("rn", "m")
+}
+
+/// Scripts in a label, ignoring digits, hyphens and combining marks
+fn label_scripts(label: &str) -> Vec<Script> {
+    let mut scripts = Vec::new();
+    for c in label.chars() {
+        let script = script_of(c);
+        if !matches!(script, Script::Common | Script::Inherited) && !scripts.contains(&script) {
+            scripts.push(script);
+        }
+    }
+    scripts
+}
+
+fn is_mixed_script(scripts: &[Script]) -> bool {
+    if scripts.len() <= 1 {
+        return false;
+    }
+    let within = |allowed: &[Script]| scripts.iter().all(|s| allowed.contains(s));
+    // Japanese, Chinese (with Bopomofo) and Korean text mix scripts by design
+    !(within(&[
+        Script::Latin,
+        Script::Han,
+        Script::Hiragana,
+        Script::Katakana,
+    ]) || within(&[Script::Latin, Script::Han, Script::Bopomofo])
+        || within(&[Script::Latin, Script::Han, Script::Hangul]))
+}
+
+/// All Cyrillic (or all Greek) letters that each look Latin
+fn is_whole_script_confusable(label: &str, scripts: &[Script], tld: &str) -> bool {
+    let allowed_tlds = match scripts {
+        [Script::Cyrillic] => CYRILLIC_TLDS,
+        [Script::Greek] => GREEK_TLDS,
+        _ => return false,
+    };
+    !allowed_tlds.contains(&tld)
+        && label
+            .chars()
+            .filter(|c| !matches!(script_of(*c), Script::Common | Script::Inherited))
+            .all(|c| latin_lookalike(c).is_some())
+}
+
+/// The well-known domain a Unicode site imitates
+fn lookalike_of(unicode_site: &str) -> Option<&'static str> {
+    let (name, _) = unicode_site.split_once('.')?;
+    if name.is_ascii() {
+        return None;
+    }
+    let name = skeleton(name);
+    TOP_DOMAINS.iter().copied().find(|top| {
+        top.split_once('.')
+            .is_some_and(|(top_name, _)| skeleton(top_name) == name)
+    })
+}
+
+fn to_unicode(ascii_host: &str) -> Option<String> {
+    let (unicode, result) = idna::domain_to_unicode(ascii_host);
+    result.ok().map(|_| unicode)
+}
+
+/// Decide how to display a hostname
+///
+/// Returns the display form and, when the host had to be shown as punycode,
+/// why. Hosts without punycode labels are returned unchanged.
+pub fn display_host(host: &str) -> (String, Option<IdnWarning>) {
+    let ascii_host = host.to_ascii_lowercase();
+    if !ascii_host.split('.').any(|label| label.starts_with("xn--")) {
+        return (host.to_string(), None);
+    }
+    let Some(unicode_host) = to_unicode(&ascii_host) else {
+        // Invalid punycode never decodes for display
+        return (ascii_host, None);
+    };
+
+    let warn = |risk: IdnRisk, lookalike_of: Option<&str>| {
+        Some(IdnWarning {
+            risk,
+            unicode_host: unicode_host.clone(),
+            lookalike_of: lookalike_of.map(str::to_string),
+        })
+    };
+
+    let labels: Vec<&str> = unicode_host.split('.').collect();
+    let tld = labels.last().copied().unwrap_or_default();
+    let mut warning = None;
+
+    for label in &labels {
+        if label.chars().any(|c| DISALLOWED.contains(&c)) {
+            warning = warn(IdnRisk::DisallowedCharacter, None);
+            break;
+        }
+        let scripts = label_scripts(label);
+        if is_mixed_script(&scripts) {
+            warning = warn(IdnRisk::MixedScript, None);
+            break;
+        }
+        if label != &tld && is_whole_script_confusable(label, &scripts, tld) {
+            warning = warn(IdnRisk::WholeScriptConfusable, None);
+            break;
+        }
+    }
+
+    // A lookalike is worth naming even when another rule caught it first.
+    // The sites it is compared with are well known, so the embedded suffix
+    // list is enough here.
+    let site = axiom_privacy::PublicSuffixList::embedded_shared()
+        .registrable_domain(&ascii_host)
+        .and_then(|site| to_unicode(&site))
+        .unwrap_or_else(|| unicode_host.clone());
+    if let Some(top) = lookalike_of(&site) {
+        warning = warn(IdnRisk::Lookalike, Some(top));
+    }
+
+    match warning {
+        Some(warning) => (ascii_host, Some(warning)),
+        None => (unicode_host, None),
+    }
+}
+
+/// Decide how to display a URL; `None` for URLs without a host
+pub fn url_display(url: &str) -> Option<UrlDisplay> {
+    let parsed = url::Url::parse(url).ok()?;
+    let host = parsed.host_str()?;
+    let (display_host, warning) = display_host(host);
+
+    let serialized = parsed.as_str();
+    let display_url = format!(
+        "{}{}{}",
+        &serialized[..parsed[..url::Position::BeforeHost].len()],
+        display_host,
+        &serialized[parsed[..url::Position::AfterHost].len()..]
+    );
+
+    Some(UrlDisplay {
+        display_url,
+        display_host,
+        warning,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn risk(url: &str) -> Option<IdnRisk> {
+        url_display(url).unwrap().warning.map(|w| w.risk)
+    }
+
+    #[test]
+    fn test_safe_hosts_display_unicode() {
+        let display = url_display("https://xn--mnchen-3ya.de/karte?x=1").unwrap();
+        assert_eq!(display.display_host, "münchen.de");
+        assert_eq!(display.display_url, "https://münchen.de/karte?x=1");
+        assert!(display.warning.is_none());
+
+        // Single-script labels in their own script
+        assert_eq!(risk("https://пример.рф/"), None);
+        assert_eq!(risk("https://παράδειγμα.ελ/"), None);
+        assert_eq!(risk("https://例え.テスト/"), None);
+        // Japanese mixes Latin, Han and Kana by design
+        assert_eq!(risk("https://abcひらがな漢字.jp/"), None);
+        // Cyrillic words that do not only look Latin
+        assert_eq!(risk("https://яндекс.com/"), None);
+
+        // ASCII is left alone, including its own lookalikes
+        let display = url_display("https://paypa1.com:8443/").unwrap();
+        assert_eq!(display.display_url, "https://paypa1.com:8443/");
+        assert!(display.warning.is_none());
+        assert!(url_display("about:blank").is_none());
+    }
+
+    #[test]
+    fn test_homographs_display_punycode() {
+        // Cyrillic `а` in a Latin label
+        let display = url_display("https://\u{0430}pple.com/").unwrap();
+        assert_eq!(display.display_host, "xn--pple-43d.com");
+        assert_eq!(display.display_url, "https://xn--pple-43d.com/");
+        let warning = display.warning.unwrap();
+        assert_eq!(warning.risk, IdnRisk::Lookalike);
+        assert_eq!(warning.lookalike_of.as_deref(), Some("apple.com"));
+        assert_eq!(warning.unicode_host, "\u{0430}pple.com");
+
+        assert_eq!(risk("https://\u{0430}bc.com/"), Some(IdnRisk::MixedScript));
+        // All-Cyrillic `раураl`-style labels outside Cyrillic TLDs
+        assert_eq!(
+            risk("https://\u{0441}\u{043e}\u{0441}\u{043e}.com/"),
+            Some(IdnRisk::WholeScriptConfusable)
+        );
+        assert_eq!(risk("https://\u{0441}\u{043e}\u{0441}\u{043e}.ru/"), None);
+        // Diacritics on a well-known name
+        assert_eq!(risk("https://gööglé.com/"), Some(IdnRisk::Lookalike));
+        // Subdomains do not hide the site
+        assert_eq!(
+            risk("https://login.p\u{0430}ypal.com/"),
+            Some(IdnRisk::Lookalike)
+        );
+        assert_eq!(
+            risk("https://ex\u{02BC}ample.com/"),
+            Some(IdnRisk::DisallowedCharacter)
+        );
+    }
+}
@@ -4,11 +4,23 @@
 //! 1. Valid URL → navigate
 //! 2. Invalid URL → search
 //! 3. `@command` → internal command mode
+//!
+//! Search keywords (`w rust`, `!gh tokio`) take precedence over URL parsing.
+//! Calculations and unit conversions that are not URLs are answered locally.
 
+use axiom_privacy::PublicSuffixList;
 use std::net::IpAddr;
+use std::sync::Arc;
 use url::Url;
 
+use crate::answer::{compute_answer, Answer};
 use crate::command::Command;
+use crate::keywords::{self, SearchKeyword};
+
+/// TLDs that are also common file extensions; `name.ext` alone is searched
+const FILE_EXTENSION_TLDS: [&str; 12] = [
+    "md", "rs", "py", "sh", "pl", "pm", "zip", "mov", "cc", "ps", "so", "tf",
+];
 
 /// Result of resolving address bar input
 #[derive(Debug, Clone)]
@@ -16,14 +28,26 @@ pub enum InputResolution {
     /// Navigate to a URL
     Navigate(String),
     /// Perform a search
-    Search(String),
+    Search {
+        url: String,
+        query: String,
+        /// Keyword whose engine was used; `None` for the default engine
+        keyword: Option<String>,
+    },
     /// Execute a command
     Command(Command),
+    /// A locally computed answer (calculation or unit conversion)
+    Answer(Answer),
 }
 
+#[derive(Debug, Clone)]
 pub struct InputResolver {
     /// Search engine URL template (%s replaced with query)
     search_template: String,
+    /// Keyword engines
+    keywords: Vec<SearchKeyword>,
+    /// Decides whether a bare `name.suffix` is a domain
+    public_suffix_list: Arc<PublicSuffixList>,
 }
 
 impl InputResolver {
@@ -31,15 +55,23 @@ impl InputResolver {
         Self {
             // Default to DuckDuckGo (privacy-focused per PRD philosophy)
             search_template: "https://duckduckgo.com/?q=%s".to_string(),
+            keywords: Vec::new(),
+            public_suffix_list: PublicSuffixList::embedded_shared(),
         }
     }
 
     pub fn with_search_engine(template: String) -> Self {
         Self {
             search_template: template,
+            keywords: Vec::new(),
+            public_suffix_list: PublicSuffixList::embedded_shared(),
         }
     }
 
+    pub fn set_public_suffix_list(&mut self, list: Arc<PublicSuffixList>) {
+        self.public_suffix_list = list;
+    }
+
     pub fn set_search_engine(&mut self, template: String) {
         self.search_template = template;
     }
@@ -48,6 +80,24 @@ impl InputResolver {
         &self.search_template
     }
 
+    pub fn set_keywords(&mut self, keywords: Vec<SearchKeyword>) {
+        self.keywords = keywords;
+    }
+
+    pub fn keywords(&self) -> &[SearchKeyword] {
+        &self.keywords
+    }
+
+    pub fn keyword(&self, keyword: &str) -> Option<&SearchKeyword> {
+        let keyword = keyword.trim_start_matches('!').to_lowercase();
+        self.keywords.iter().find(|k| k.keyword == keyword)
+    }
+
+    /// Search URL for a query, regardless of whether it looks like a URL
+    pub fn search_url(&self, query: &str) -> String {
+        self.build_search_url(query.trim())
+    }
+
     /// Resolve user input into an action
     pub fn resolve(&self, input: &str) -> InputResolution {
         let input = input.trim();
@@ -63,14 +113,58 @@ impl InputResolver {
             }
         }
 
+        if let Some(resolution) = self.resolve_keyword(input) {
+            return resolution;
+        }
+
         // Try to parse as URL
         if let Some(url) = self.try_parse_url(input) {
             return InputResolution::Navigate(url);
         }
 
+        if let Some(answer) = compute_answer(input) {
+            return InputResolution::Answer(answer);
+        }
+
         // Fall back to search
-        let search_url = self.build_search_url(input);
-        InputResolution::Search(search_url)
+        InputResolution::Search {
+            url: self.build_search_url(input),
+            query: input.to_string(),
+            keyword: None,
+        }
+    }
+
+    /// `!keyword` anywhere in the input, or `keyword query` at the start
+    fn resolve_keyword(&self, input: &str) -> Option<InputResolution> {
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+
+        let bang = tokens.iter().position(|token| {
+            token.len() > 1 && token.starts_with('!') && self.keyword(token).is_some()
+        });
+        let (engine, query) = match bang {
+            Some(idx) => {
+                let query = tokens
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != idx)
+                    .map(|(_, token)| *token)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                (self.keyword(tokens[idx])?, query)
+            }
+            None if tokens.len() > 1 => (self.keyword(tokens[0])?, tokens[1..].join(" ")),
+            None => return None,
+        };
+
+        if query.is_empty() {
+            return engine.homepage().map(InputResolution::Navigate);
+        }
+
+        Some(InputResolution::Search {
+            url: engine.search_url(&query),
+            query,
+            keyword: Some(engine.keyword.clone()),
+        })
     }
 
     /// Try to parse input as a valid URL
@@ -118,23 +212,58 @@ impl InputResolver {
         }
 
         // Domain-like pattern
-        if input.contains('.') {
-            let parts: Vec<&str> = input.split('.').collect();
-            if parts.len() >= 2 {
-                // Check for valid TLD
-                let tld = parts.last().unwrap();
-                // Split off port/path if present
-                let tld = tld.split(':').next().unwrap();
-                let tld = tld.split('/').next().unwrap();
-
-                // Basic TLD validation (2-6 chars)
-                if tld.len() >= 2 && tld.len() <= 6 && tld.chars().all(|c| c.is_alphabetic()) {
-                    return true;
-                }
+        let (host, rest) = Self::split_host_and_rest(input);
+        let (host, port) = match host.rsplit_once(':') {
+            Some((host, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => {
+                (host, Some(port))
             }
+            _ => (host, None),
+        };
+        let labels: Vec<&str> = host.split('.').collect();
+        let valid_labels = labels.len() >= 2
+            && labels
+                .iter()
+                .all(|l| !l.is_empty() && l.chars().all(|c| c.is_alphanumeric() || c == '-'));
+        if !valid_labels {
+            return false;
         }
 
-        false
+        // A port or path makes intent clear even for unlisted suffixes (`router.lan/admin`)
+        if port.is_some() || rest.len() > 1 {
+            return true;
+        }
+
+        // Otherwise the host must end in a real public suffix
+        if !self.public_suffix_list.has_known_suffix(host)
+            || self.public_suffix_list.registrable_domain(host).is_none()
+        {
+            return false;
+        }
+
+        // `readme.md`: a bare file name whose extension happens to be a TLD
+        !Self::is_bare_file_name(&labels, rest)
+    }
+
+    /// `https://docs.rs` for a bare `name.ext` that [`resolve`](Self::resolve)
+    /// searches but which is also a real domain, to offer as a suggestion
+    pub fn file_name_domain_url(&self, input: &str) -> Option<String> {
+        let input = input.trim();
+        let (host, rest) = Self::split_host_and_rest(input);
+        let labels: Vec<&str> = host.split('.').collect();
+        let is_domain = Self::is_bare_file_name(&labels, rest)
+            && labels
+                .iter()
+                .all(|l| !l.is_empty() && l.chars().all(|c| c.is_alphanumeric() || c == '-'))
+            && self.public_suffix_list.registrable_domain(host).is_some();
+        is_domain.then(|| format!("https://{host}{rest}"))
+    }
+
+    /// Two labels, no path or port, and a TLD from [`FILE_EXTENSION_TLDS`]
+    fn is_bare_file_name(labels: &[&str], rest: &str) -> bool {
+        let [_, tld] = labels else {
+            return false;
+        };
+        rest.is_empty() && FILE_EXTENSION_TLDS.contains(&tld.to_lowercase().as_str())
     }
 
     /// Check if input looks like an IP address
@@ -181,8 +310,7 @@ impl InputResolver {
 
     /// Build search URL from query
     fn build_search_url(&self, query: &str) -> String {
-        let encoded = urlencoding::encode(query);
-        self.search_template.replace("%s", &encoded)
+        keywords::expand_template(&self.search_template, query)
     }
 }
 
@@ -192,24 +320,6 @@ impl Default for InputResolver {
     }
 }
 
-// Add urlencoding as a simple inline implementation
-mod urlencoding {
-    pub fn encode(input: &str) -> String {
-        let mut result = String::with_capacity(input.len() * 3);
-        for byte in input.bytes() {
-            match byte {
-                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
-                    result.push(byte as char);
-                }
-                _ => {
-                    result.push_str(&format!("%{:02X}", byte));
-                }
-            }
-        }
-        result
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -237,17 +347,109 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_public_suffix_detection() {
+        let resolver = InputResolver::new();
+        let navigates =
+            |input: &str| matches!(resolver.resolve(input), InputResolution::Navigate(_));
+
+        // Long, multi-label and IDN suffixes
+        assert!(navigates("studio.photography"));
+        assert!(navigates("bbc.co.uk"));
+        assert!(navigates("пример.рф"));
+        assert!(navigates("docs.rs/tokio"));
+        assert!(navigates("www.readme.md"));
+        // Unlisted suffixes with a port or path
+        assert!(navigates("router.lan/admin"));
+        assert!(navigates("dev.internal:8443"));
+
+        // File names and bare suffixes search
+        assert!(!navigates("file.txt"));
+        assert!(!navigates("readme.md"));
+        assert!(!navigates("main.rs"));
+        assert!(!navigates("docs.rs"));
+        assert!(!navigates("co.uk"));
+        assert!(!navigates("user@example.com"));
+
+        // ...but the ones that are real domains can still be offered
+        assert_eq!(
+            resolver.file_name_domain_url("docs.rs").as_deref(),
+            Some("https://docs.rs")
+        );
+        assert!(resolver.file_name_domain_url("nic.so").is_some());
+        assert!(resolver.file_name_domain_url("google.cc").is_some());
+        assert_eq!(resolver.file_name_domain_url("file.txt"), None);
+        assert_eq!(resolver.file_name_domain_url("docs.rs/tokio"), None);
+    }
+
     #[test]
     fn test_resolve_search() {
         let resolver = InputResolver::new();
 
         match resolver.resolve("rust programming") {
-            InputResolution::Search(url) => {
+            InputResolution::Search { url, keyword, .. } => {
                 assert!(url.contains("duckduckgo.com"));
                 assert!(url.contains("rust%20programming"));
+                assert!(keyword.is_none());
+            }
+            _ => panic!("Expected Search"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_keywords() {
+        let mut resolver = InputResolver::new();
+        resolver.set_keywords(vec![
+            SearchKeyword {
+                keyword: "w".to_string(),
+                name: "Wikipedia".to_string(),
+                template: "https://en.wikipedia.org/w/index.php?search=%s".to_string(),
+            },
+            SearchKeyword {
+                keyword: "yt".to_string(),
+                name: "YouTube".to_string(),
+                template: "https://www.youtube.com/results?search_query=%s".to_string(),
+            },
+        ]);
+
+        match resolver.resolve("w rust & c++") {
+            InputResolution::Search {
+                url,
+                query,
+                keyword,
+            } => {
+                assert_eq!(
+                    url,
+                    "https://en.wikipedia.org/w/index.php?search=rust%20%26%20c%2B%2B"
+                );
+                assert_eq!(query, "rust & c++");
+                assert_eq!(keyword.as_deref(), Some("w"));
+            }
+            _ => panic!("Expected Search"),
+        }
+
+        match resolver.resolve("lofi beats !YT") {
+            InputResolution::Search { url, keyword, .. } => {
+                assert!(url.ends_with("search_query=lofi%20beats"));
+                assert_eq!(keyword.as_deref(), Some("yt"));
             }
             _ => panic!("Expected Search"),
         }
+
+        match resolver.resolve("!yt") {
+            InputResolution::Navigate(url) => assert_eq!(url, "https://www.youtube.com/"),
+            _ => panic!("Expected Navigate"),
+        }
+
+        // A bare keyword or an unknown bang is not a keyword search
+        assert!(matches!(
+            resolver.resolve("w"),
+            InputResolution::Search { keyword: None, .. }
+        ));
+        assert!(matches!(
+            resolver.resolve("!nope rust"),
+            InputResolution::Search { keyword: None, .. }
+        ));
     }
 
     #[test]
@@ -281,4 +483,35 @@ mod tests {
             _ => panic!("Expected Navigate"),
         }
     }
+
+    #[test]
+    fn test_resolve_answers() {
+        let resolver = InputResolver::new();
+
+        match resolver.resolve("2^10*3") {
+            InputResolution::Answer(answer) => assert_eq!(answer.result, "3072"),
+            _ => panic!("Expected Answer"),
+        }
+        match resolver.resolve("12 in to cm") {
+            InputResolution::Answer(answer) => assert_eq!(answer.result, "30.48 cm"),
+            _ => panic!("Expected Answer"),
+        }
+
+        // Hosts and IPs still navigate
+        for input in [
+            "192.168.1.1",
+            "10.0.0.1:8080",
+            "example.com",
+            "localhost:3000",
+        ] {
+            assert!(
+                matches!(resolver.resolve(input), InputResolution::Navigate(_)),
+                "{input} should navigate"
+            );
+        }
+        assert!(matches!(
+            resolver.resolve("1.2.3 release notes"),
+            InputResolution::Search { .. }
+        ));
+    }
 }
@@ -0,0 +1,429 @@
+//! History journeys
+//!
+//! Groups the `visits` log into journeys: visits are first split into
+//! bursts wherever the browser sat idle for `IDLE_GAP`, then the pages of a
+//! burst are clustered when they belong to the same site or their titles
+//! share enough significant terms. A research session that hops between a
+//! search engine, docs and a forum becomes one journey; checking mail in the
+//! middle of it becomes another.
+//!
+//! Everything is computed locally from the visit log.
+
+use axiom_privacy::PublicSuffixList;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::frecency::VisitTransition;
+use crate::history::Visit;
+
+/// Inactivity that ends a burst of browsing
+pub const IDLE_GAP: Duration = Duration::minutes(30);
+
+/// Visits considered per query (newest first)
+pub const MAX_JOURNEY_VISITS: usize = 5_000;
+
+/// Terms shorter than this never link pages on their own
+const MIN_LINKING_TERM_LEN: usize = 6;
+
+/// Words too common in page titles to say anything about a topic
+const STOPWORDS: &[&str] = &[
+    "about",
+    "and",
+    "are",
+    "bing",
+    "but",
+    "can",
+    "com",
+    "duckduckgo",
+    "for",
+    "from",
+    "google",
+    "has",
+    "have",
+    "home",
+    "how",
+    "into",
+    "its",
+    "login",
+    "new",
+    "not",
+    "official",
+    "one",
+    "online",
+    "our",
+    "page",
+    "results",
+    "search",
+    "sign",
+    "site",
+    "the",
+    "this",
+    "was",
+    "web",
+    "welcome",
+    "what",
+    "when",
+    "why",
+    "will",
+    "with",
+    "www",
+    "you",
+    "your",
+];
+
+/// A page within a journey
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JourneyPage {
+    pub url: String,
+    pub title: String,
+    /// Visits to the page within the journey
+    pub visit_count: usize,
+    pub first_visited_at: DateTime<Utc>,
+    pub last_visited_at: DateTime<Utc>,
+}
+
+/// A group of related visits made in one stretch of browsing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Journey {
+    /// Stable while the underlying visits are unchanged
+    pub id: String,
+    pub label: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// Sites (registrable domains) visited, most visited first
+    pub sites: Vec<String>,
+    /// Pages in order of first visit
+    pub pages: Vec<JourneyPage>,
+    pub visit_count: usize,
+}
+
+impl Journey {
+    /// True if the label, a page title or a URL contains `query` (case-insensitive)
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        query.is_empty()
+            || self.label.to_lowercase().contains(&query)
+            || self.pages.iter().any(|page| {
+                page.title.to_lowercase().contains(&query)
+                    || page.url.to_lowercase().contains(&query)
+            })
+    }
+}
+
+fn site_of(url: &str, public_suffix_list: &PublicSuffixList) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| {
+            u.host_str()
+                .map(|host| public_suffix_list.site_for_host(host))
+        })
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// Lowercase significant words of a title, in order, without duplicates
+fn title_terms(title: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|word| {
+            word.chars().count() >= 3
+                && !word.chars().all(|c| c.is_ascii_digit())
+                && !STOPWORDS.contains(&word.as_str())
+        })
+        .filter(|word| seen.insert(word.clone()))
+        .collect()
+}
+
+/// Whether two pages' titles are about the same thing
+fn titles_related(a: &[String], b: &[String]) -> bool {
+    let shared: Vec<&String> = a.iter().filter(|term| b.contains(term)).collect();
+    shared.len() >= 2
+        || shared
+            .iter()
+            .any(|term| term.chars().count() >= MIN_LINKING_TERM_LEN)
+}
+
+fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parents, a), find(parents, b));
+    if a != b {
+        parents[b.max(a)] = b.min(a);
+    }
+}
+
+/// Split visits (any order) into bursts separated by `IDLE_GAP`, oldest first
+fn bursts(visits: &[Visit]) -> Vec<Vec<&Visit>> {
+    let mut sorted: Vec<&Visit> = visits
+        .iter()
+        .filter(|visit| visit.transition != VisitTransition::Reload)
+        .collect();
+    sorted.sort_by_key(|visit| (visit.visited_at, visit.id));
+
+    let mut bursts: Vec<Vec<&Visit>> = Vec::new();
+    for visit in sorted {
+        match bursts.last_mut() {
+            Some(burst)
+                if burst
+                    .last()
+                    .is_some_and(|last| visit.visited_at - last.visited_at <= IDLE_GAP) =>
+            {
+                burst.push(visit)
+            }
+            _ => bursts.push(vec![visit]),
+        }
+    }
+    bursts
+}
+
+/// Most telling label for a cluster: shared title terms, else the main site
+fn label_for(pages: &[JourneyPage], sites: &[String]) -> String {
+    let site_names: HashSet<String> = sites
+        .iter()
+        .filter_map(|site| site.split('.').next())
+        .map(str::to_lowercase)
+        .collect();
+
+    // term -> (pages using it, first position)
+    let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut position = 0;
+    for page in pages {
+        for term in title_terms(&page.title) {
+            if site_names.contains(&term) {
+                continue;
+            }
+            let entry = counts.entry(term).or_insert((0, position));
+            entry.0 += 1;
+            position += 1;
+        }
+    }
+
+    let min_pages = if pages.len() > 1 { 2 } else { 1 };
+    let mut terms: Vec<(String, (usize, usize))> = counts
+        .into_iter()
+        .filter(|(_, (pages, _))| *pages >= min_pages)
+        .collect();
+    terms.sort_by(|(_, (a_pages, a_pos)), (_, (b_pages, b_pos))| {
+        b_pages.cmp(a_pages).then(a_pos.cmp(b_pos))
+    });
+    // Keep title order among the chosen terms so labels read naturally
+    let mut chosen: Vec<(String, usize)> = terms
+        .into_iter()
+        .take(3)
+        .map(|(term, (_, pos))| (term, pos))
+        .collect();
+    chosen.sort_by_key(|(_, pos)| *pos);
+
+    if chosen.is_empty() {
+        return sites
+            .first()
+            .cloned()
+            .or_else(|| pages.first().map(|page| page.title.clone()))
+            .unwrap_or_default();
+    }
+    chosen
+        .into_iter()
+        .map(|(term, _)| term)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn build_journey(visits: &[&Visit], public_suffix_list: &PublicSuffixList) -> Journey {
+    let mut pages: Vec<JourneyPage> = Vec::new();
+    let mut site_counts: Vec<(String, usize)> = Vec::new();
+    for visit in visits {
+        match pages.iter_mut().find(|page| page.url == visit.url) {
+            Some(page) => {
+                page.visit_count += 1;
+                page.last_visited_at = visit.visited_at;
+                if page.title.is_empty() {
+                    page.title = visit.title.clone();
+                }
+            }
+            None => pages.push(JourneyPage {
+                url: visit.url.clone(),
+                title: visit.title.clone(),
+                visit_count: 1,
+                first_visited_at: visit.visited_at,
+                last_visited_at: visit.visited_at,
+            }),
+        }
+
+        let site = site_of(&visit.url, public_suffix_list);
+        match site_counts.iter_mut().find(|(s, _)| *s == site) {
+            Some((_, count)) => *count += 1,
+            None => site_counts.push((site, 1)),
+        }
+    }
+    // Stable sort keeps first-visit order among equally visited sites
+    site_counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    let sites: Vec<String> = site_counts.into_iter().map(|(site, _)| site).collect();
+
+    let first = visits[0];
+    let last = visits[visits.len() - 1];
+    Journey {
+        id: format!("{}-{}", first.visited_at.timestamp_millis(), first.id),
+        label: label_for(&pages, &sites),
+        started_at: first.visited_at,
+        ended_at: last.visited_at,
+        sites,
+        pages,
+        visit_count: visits.len(),
+    }
+}
+
+/// Group visits into journeys, newest first
+pub fn cluster_journeys(visits: &[Visit], public_suffix_list: &PublicSuffixList) -> Vec<Journey> {
+    let mut journeys = Vec::new();
+
+    for burst in bursts(visits) {
+        // One node per distinct URL in the burst
+        let mut urls: Vec<&str> = Vec::new();
+        for visit in &burst {
+            if !urls.contains(&visit.url.as_str()) {
+                urls.push(&visit.url);
+            }
+        }
+        let url_index: HashMap<&str, usize> =
+            urls.iter().enumerate().map(|(i, url)| (*url, i)).collect();
+        let sites: Vec<String> = urls
+            .iter()
+            .map(|url| site_of(url, public_suffix_list))
+            .collect();
+        let terms: Vec<Vec<String>> = urls
+            .iter()
+            .map(|url| {
+                let title = burst
+                    .iter()
+                    .rev()
+                    .find(|visit| visit.url == *url && !visit.title.is_empty())
+                    .map_or("", |visit| visit.title.as_str());
+                title_terms(title)
+            })
+            .collect();
+
+        let mut parents: Vec<usize> = (0..urls.len()).collect();
+        for a in 0..urls.len() {
+            for b in (a + 1)..urls.len() {
+                if sites[a] == sites[b] || titles_related(&terms[a], &terms[b]) {
+                    union(&mut parents, a, b);
+                }
+            }
+        }
+
+        let mut clusters: Vec<(usize, Vec<&Visit>)> = Vec::new();
+        for visit in &burst {
+            let root = find(&mut parents, url_index[visit.url.as_str()]);
+            match clusters.iter_mut().find(|(r, _)| *r == root) {
+                Some((_, members)) => members.push(visit),
+                None => clusters.push((root, vec![visit])),
+            }
+        }
+        journeys.extend(
+            clusters
+                .into_iter()
+                .map(|(_, members)| build_journey(&members, public_suffix_list)),
+        );
+    }
+
+    journeys.sort_by(|a, b| b.ended_at.cmp(&a.ended_at).then(b.id.cmp(&a.id)));
+    journeys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visit(id: i64, minutes: i64, url: &str, title: &str) -> Visit {
+        Visit {
+            id,
+            url_id: id,
+            url: url.to_string(),
+            title: title.to_string(),
+            visited_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+                + Duration::minutes(minutes),
+            transition: VisitTransition::Link,
+            from_visit_id: None,
+            tab_id: None,
+            session_id: None,
+        }
+    }
+
+    #[test]
+    fn test_title_terms() {
+        assert_eq!(
+            title_terms("Rust Lifetimes Explained - Search - The Rust Book 2024"),
+            vec!["rust", "lifetimes", "explained", "book"]
+        );
+        assert!(titles_related(
+            &title_terms("Understanding lifetimes"),
+            &title_terms("Lifetimes - Rust By Example")
+        ));
+        assert!(!titles_related(
+            &title_terms("Rust news"),
+            &title_terms("Rust belt weather")
+        ));
+    }
+
+    #[test]
+    fn test_cluster_journeys() {
+        let visits = vec![
+            visit(
+                1,
+                0,
+                "https://duckduckgo.com/?q=rust+lifetimes",
+                "rust lifetimes at DuckDuckGo",
+            ),
+            visit(
+                2,
+                1,
+                "https://doc.rust-lang.org/book/ch10-03-lifetime-syntax.html",
+                "Validating References with Lifetimes - The Rust Programming Language",
+            ),
+            visit(3, 4, "https://mail.example.com/inbox", "Inbox (3)"),
+            visit(
+                4,
+                6,
+                "https://stackoverflow.com/questions/1/rust-lifetimes",
+                "Rust lifetimes in structs - Stack Overflow",
+            ),
+            visit(5, 8, "https://mail.example.com/message/7", "Re: lunch"),
+            // Idle for more than IDLE_GAP: a new burst
+            visit(6, 120, "https://doc.rust-lang.org/std/", "std - Rust"),
+        ];
+
+        let journeys = cluster_journeys(&visits, &PublicSuffixList::embedded());
+        assert_eq!(journeys.len(), 3);
+
+        // Newest first
+        assert_eq!(journeys[0].pages.len(), 1);
+        assert_eq!(journeys[0].sites, vec!["rust-lang.org"]);
+
+        let research = journeys
+            .iter()
+            .find(|j| j.pages.len() == 3)
+            .expect("research journey");
+        assert_eq!(research.label, "rust lifetimes");
+        assert_eq!(research.visit_count, 3);
+        assert_eq!(research.started_at, visits[0].visited_at);
+        assert_eq!(research.ended_at, visits[3].visited_at);
+
+        let mail = journeys
+            .iter()
+            .find(|j| j.sites == vec!["example.com"])
+            .expect("mail journey");
+        assert_eq!(mail.pages.len(), 2);
+        assert_eq!(mail.label, "example.com");
+
+        assert!(research.matches("stack overflow"));
+        assert!(!mail.matches("lifetimes"));
+    }
+}
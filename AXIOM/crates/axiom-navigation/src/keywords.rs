@@ -0,0 +1,292 @@
+//! Search keywords
+//!
+//! A keyword routes a search to its own engine: `w rust` searches Wikipedia,
+//! and the bang form `!w rust` (or `rust !w`) does the same from anywhere in
+//! the input. Templates use `%s` for the URL-encoded query and `%S` for the
+//! query as typed. A default set is written the first time keywords load;
+//! after that the table belongs to the user.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use axiom_storage::Database;
+
+use crate::error::NavigationError;
+use crate::Result;
+
+/// Marks that the defaults were written, so deleted defaults stay deleted
+const SEEDED_SETTING: &str = "search_keywords_seeded";
+
+/// (keyword, name, template)
+const DEFAULT_KEYWORDS: [(&str, &str, &str); 7] = [
+    (
+        "w",
+        "Wikipedia",
+        "https://en.wikipedia.org/w/index.php?search=%s",
+    ),
+    ("gh", "GitHub", "https://github.com/search?q=%s"),
+    (
+        "yt",
+        "YouTube",
+        "https://www.youtube.com/results?search_query=%s",
+    ),
+    ("ddg", "DuckDuckGo", "https://duckduckgo.com/?q=%s"),
+    ("g", "Google", "https://www.google.com/search?q=%s"),
+    (
+        "mdn",
+        "MDN Web Docs",
+        "https://developer.mozilla.org/search?q=%s",
+    ),
+    ("crates", "crates.io", "https://crates.io/search?q=%s"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchKeyword {
+    /// Lowercase, without the `!`
+    pub keyword: String,
+    pub name: String,
+    pub template: String,
+}
+
+impl SearchKeyword {
+    pub fn search_url(&self, query: &str) -> String {
+        expand_template(&self.template, query)
+    }
+
+    /// Origin of the template, opened for a bare `!keyword`
+    pub fn homepage(&self) -> Option<String> {
+        let url = Url::parse(&self.search_url("")).ok()?;
+        Some(format!("{}/", url.origin().ascii_serialization()))
+    }
+}
+
+/// Substitute a query into a search template
+pub(crate) fn expand_template(template: &str, query: &str) -> String {
+    template
+        .replace("%s", &encode_query(query))
+        .replace("%S", query)
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters
+pub(crate) fn encode_query(input: &str) -> String {
+    let mut result = String::with_capacity(input.len() * 3);
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                result.push(byte as char);
+            }
+            _ => {
+                result.push_str(&format!("%{:02X}", byte));
+            }
+        }
+    }
+    result
+}
+
+fn normalize_keyword(keyword: &str) -> Result<String> {
+    let keyword = keyword.trim().trim_start_matches('!').to_lowercase();
+    if keyword.is_empty() {
+        return Err(NavigationError::InvalidKeyword(
+            "Keyword cannot be empty".to_string(),
+        ));
+    }
+    if keyword.contains(char::is_whitespace) || keyword.starts_with('@') {
+        return Err(NavigationError::InvalidKeyword(format!(
+            "Keyword cannot contain spaces or start with @: {keyword}"
+        )));
+    }
+    Ok(keyword)
+}
+
+pub(crate) fn validate_template(template: &str) -> Result<String> {
+    let template = template.trim().to_string();
+    if !template.contains("%s") && !template.contains("%S") {
+        return Err(NavigationError::InvalidTemplate(format!(
+            "Template must contain %s: {template}"
+        )));
+    }
+    match Url::parse(&expand_template(&template, "test")) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(template),
+        _ => Err(NavigationError::InvalidTemplate(format!(
+            "Template must be an http(s) URL: {template}"
+        ))),
+    }
+}
+
+pub struct KeywordManager {
+    db: Database,
+}
+
+impl KeywordManager {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// All keywords, writing the default set on first use
+    pub fn load(&self) -> Result<Vec<SearchKeyword>> {
+        if self.db.get_setting(SEEDED_SETTING)?.is_none() {
+            self.db.transaction(|tx| {
+                let now = Utc::now().to_rfc3339();
+                for (keyword, name, template) in DEFAULT_KEYWORDS {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO search_keywords (keyword, name, template, created_at)
+                         VALUES (?1, ?2, ?3, ?4)",
+                        rusqlite::params![keyword, name, template, now],
+                    )?;
+                }
+                Ok(())
+            })?;
+            self.db.set_setting(SEEDED_SETTING, "true")?;
+        }
+
+        self.list()
+    }
+
+    pub fn list(&self) -> Result<Vec<SearchKeyword>> {
+        Ok(self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT keyword, name, template FROM search_keywords ORDER BY keyword ASC",
+            )?;
+            let keywords = stmt
+                .query_map([], |row| {
+                    Ok(SearchKeyword {
+                        keyword: row.get(0)?,
+                        name: row.get(1)?,
+                        template: row.get(2)?,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(keywords)
+        })?)
+    }
+
+    pub fn add(&self, keyword: &str, name: &str, template: &str) -> Result<SearchKeyword> {
+        let keyword = SearchKeyword {
+            keyword: normalize_keyword(keyword)?,
+            name: name.trim().to_string(),
+            template: validate_template(template)?,
+        };
+
+        let inserted = self.db.with_connection(|conn| {
+            Ok(conn.execute(
+                "INSERT OR IGNORE INTO search_keywords (keyword, name, template, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![
+                    keyword.keyword,
+                    keyword.name,
+                    keyword.template,
+                    Utc::now().to_rfc3339()
+                ],
+            )?)
+        })?;
+        if inserted == 0 {
+            return Err(NavigationError::InvalidKeyword(format!(
+                "Keyword already in use: {}",
+                keyword.keyword
+            )));
+        }
+
+        tracing::info!(keyword = %keyword.keyword, "Added search keyword");
+
+        Ok(keyword)
+    }
+
+    pub fn update(&self, keyword: &str, name: &str, template: &str) -> Result<SearchKeyword> {
+        let keyword = SearchKeyword {
+            keyword: normalize_keyword(keyword)?,
+            name: name.trim().to_string(),
+            template: validate_template(template)?,
+        };
+
+        let updated = self.db.with_connection(|conn| {
+            Ok(conn.execute(
+                "UPDATE search_keywords SET name = ?2, template = ?3 WHERE keyword = ?1",
+                rusqlite::params![keyword.keyword, keyword.name, keyword.template],
+            )?)
+        })?;
+        if updated == 0 {
+            return Err(NavigationError::KeywordNotFound(keyword.keyword));
+        }
+
+        Ok(keyword)
+    }
+
+    pub fn remove(&self, keyword: &str) -> Result<()> {
+        let keyword = normalize_keyword(keyword)?;
+        self.db.with_connection(|conn| {
+            conn.execute("DELETE FROM search_keywords WHERE keyword = ?1", [&keyword])?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+impl Clone for KeywordManager {
+    fn clone(&self) -> Self {
+        Self {
+            db: self.db.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_template() {
+        assert_eq!(
+            expand_template("https://x.test/?q=%s", "a&b c/é"),
+            "https://x.test/?q=a%26b%20c%2F%C3%A9"
+        );
+        assert_eq!(
+            expand_template("https://x.test/%S", "a/b"),
+            "https://x.test/a/b"
+        );
+    }
+
+    #[test]
+    fn test_defaults_and_management() {
+        let manager = KeywordManager::new(Database::open_in_memory().unwrap());
+        let defaults = manager.load().unwrap();
+        assert_eq!(defaults.len(), DEFAULT_KEYWORDS.len());
+
+        manager.remove("!w").unwrap();
+        // Deleted defaults are not re-seeded
+        assert_eq!(manager.load().unwrap().len(), DEFAULT_KEYWORDS.len() - 1);
+
+        let added = manager
+            .add("!RS", "docs.rs", "https://docs.rs/releases/search?query=%s")
+            .unwrap();
+        assert_eq!(added.keyword, "rs");
+        assert!(manager.add("rs", "dup", "https://a.test/?q=%s").is_err());
+        assert!(matches!(
+            manager.add("x", "no query", "https://a.test/"),
+            Err(NavigationError::InvalidTemplate(_))
+        ));
+        assert!(matches!(
+            manager.add("x", "script", "javascript:alert(%s)"),
+            Err(NavigationError::InvalidTemplate(_))
+        ));
+        assert!(manager
+            .add("two words", "x", "https://a.test/?q=%s")
+            .is_err());
+
+        manager
+            .update("rs", "Docs", "https://docs.rs/?q=%s")
+            .unwrap();
+        assert!(manager
+            .update("missing", "x", "https://a.test/?q=%s")
+            .is_err());
+        let rs = manager
+            .list()
+            .unwrap()
+            .into_iter()
+            .find(|k| k.keyword == "rs")
+            .unwrap();
+        assert_eq!(rs.name, "Docs");
+        assert_eq!(rs.homepage().as_deref(), Some("https://docs.rs/"));
+    }
+}
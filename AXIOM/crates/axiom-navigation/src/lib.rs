@@ -10,15 +10,54 @@
 //! - `@tabs` — fuzzy search open tabs
 //! - `@history` — fuzzy search history
 //! - `@sessions` — switch session
+//!
+//! Calculations and unit conversions are answered locally (see `answer`).
+//!
+//! HTTPS-Only mode upgrades `http://` navigations (see `https_only`).
+//!
+//! Internationalized hostnames are displayed as punycode when they could be
+//! mistaken for another name (see `idn`).
+//!
+//! Visits are grouped into topic and time "journeys" (see `journeys`).
+//!
+//! Omnibox suggestions come from a pipeline of pluggable providers (see `suggest`).
 
+mod answer;
+mod calculator;
 mod command;
 mod error;
+mod frecency;
 mod history;
+mod https_only;
+mod idn;
 mod input;
+mod journeys;
+mod keywords;
+mod opensearch;
+mod search_engines;
+mod suggest;
+mod units;
 
+pub use answer::{compute_answer, Answer, AnswerKind};
 pub use command::{Command, CommandType};
 pub use error::NavigationError;
-pub use history::{HistoryEntry, HistoryManager};
+pub use frecency::VisitTransition;
+pub use history::{
+    HistoryEntry, HistoryImportStats, HistoryManager, HistorySort, ImportedHistory, TabVisit,
+    Visit, VisitSource,
+};
+pub use https_only::{upgrade_url, HttpsOnlyException, HttpsOnlyManager};
+pub use idn::{display_host, url_display, IdnRisk, IdnWarning, UrlDisplay};
 pub use input::{InputResolution, InputResolver};
+pub use journeys::{cluster_journeys, Journey, JourneyPage, IDLE_GAP};
+pub use keywords::{KeywordManager, SearchKeyword};
+pub use opensearch::{discover_opensearch_links, parse_opensearch, OpenSearchLink};
+pub use search_engines::{NewSearchEngine, SearchEngine, SearchEngineManager, DEFAULT_ENGINE_ID};
+pub use suggest::{
+    AnswerProvider, BookmarkCandidate, BookmarksProvider, CancellationToken, CommandProvider,
+    HistoryProvider, InlineAutocomplete, OpenTabsProvider, SearchProvider, SuggestQuery,
+    Suggestion, SuggestionKind, SuggestionPipeline, SuggestionProvider, SuggestionResult,
+    TabCandidate, TypedUrlProvider,
+};
 
 pub type Result<T> = std::result::Result<T, NavigationError>;
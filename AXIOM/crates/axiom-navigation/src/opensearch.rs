@@ -0,0 +1,304 @@
+//! OpenSearch discovery and parsing
+//!
+//! Pages advertise a search engine with
+//! `<link rel="search" type="application/opensearchdescription+xml" href="...">`.
+//! The description document is XML; its `text/html` `Url` template (with
+//! `{searchTerms}` and friends) becomes a `%s` template the resolver can use.
+
+use regex::Regex;
+use std::sync::OnceLock;
+use url::Url;
+
+use crate::error::NavigationError;
+use crate::search_engines::NewSearchEngine;
+use crate::Result;
+
+const OPENSEARCH_TYPE: &str = "application/opensearchdescription+xml";
+const SUGGESTIONS_TYPE: &str = "application/x-suggestions+json";
+
+/// An OpenSearch description advertised by a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenSearchLink {
+    pub title: Option<String>,
+    /// Absolute URL of the description document
+    pub href: String,
+}
+
+fn link_tag_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?is)<link\b[^>]*>").unwrap())
+}
+
+fn attribute_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"(?s)([a-zA-Z_:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap()
+    })
+}
+
+/// OpenSearch descriptions linked from an HTML page
+pub fn discover_opensearch_links(html: &str, page_url: &Url) -> Vec<OpenSearchLink> {
+    let mut links = Vec::new();
+
+    for tag in link_tag_regex().find_iter(html) {
+        let mut rel = None;
+        let mut kind = None;
+        let mut href = None;
+        let mut title = None;
+
+        for caps in attribute_regex().captures_iter(tag.as_str()) {
+            let value = caps
+                .get(2)
+                .or_else(|| caps.get(3))
+                .or_else(|| caps.get(4))
+                .map(|m| decode_entities(m.as_str().trim()));
+            match caps[1].to_ascii_lowercase().as_str() {
+                "rel" => rel = value,
+                "type" => kind = value,
+                "href" => href = value,
+                "title" => title = value,
+                _ => {}
+            }
+        }
+
+        let is_search = rel.is_some_and(|rel| {
+            rel.split_ascii_whitespace()
+                .any(|token| token.eq_ignore_ascii_case("search"))
+        });
+        if !is_search || !kind.is_some_and(|k| k.eq_ignore_ascii_case(OPENSEARCH_TYPE)) {
+            continue;
+        }
+
+        if let Some(href) = href.and_then(|href| page_url.join(&href).ok()) {
+            if matches!(href.scheme(), "http" | "https")
+                && !links
+                    .iter()
+                    .any(|l: &OpenSearchLink| l.href == href.as_str())
+            {
+                links.push(OpenSearchLink {
+                    title: title.filter(|t| !t.is_empty()),
+                    href: href.to_string(),
+                });
+            }
+        }
+    }
+
+    links
+}
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Parse an OpenSearch description; relative URLs resolve against `base`
+pub fn parse_opensearch(xml: &str, base: Option<&Url>) -> Result<NewSearchEngine> {
+    let invalid = |reason: &str| NavigationError::InvalidOpenSearch(reason.to_string());
+
+    let doc = roxmltree::Document::parse(xml).map_err(|e| invalid(&e.to_string()))?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "OpenSearchDescription" {
+        return Err(invalid("not an OpenSearchDescription document"));
+    }
+
+    let child_text = |name: &str| {
+        root.children()
+            .find(|n| n.is_element() && n.tag_name().name() == name)
+            .and_then(|n| n.text())
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+    };
+
+    let name = child_text("ShortName")
+        .or_else(|| child_text("LongName"))
+        .ok_or_else(|| invalid("missing ShortName"))?;
+
+    let mut template = None;
+    let mut suggest_url = None;
+    for url in root
+        .children()
+        .filter(|n| n.is_element() && n.tag_name().name() == "Url")
+    {
+        let method = url.attribute("method").unwrap_or("get");
+        let rel = url.attribute("rel").unwrap_or("results");
+        if !method.eq_ignore_ascii_case("get") || !rel.eq_ignore_ascii_case("results") {
+            continue;
+        }
+
+        let Some(converted) = url_template(url, base) else {
+            continue;
+        };
+        match url.attribute("type").unwrap_or_default() {
+            "text/html" if template.is_none() => template = Some(converted),
+            SUGGESTIONS_TYPE if suggest_url.is_none() => suggest_url = Some(converted),
+            _ => {}
+        }
+    }
+
+    let icon_url = root
+        .children()
+        .filter(|n| n.is_element() && n.tag_name().name() == "Image")
+        .filter_map(|n| Some((n.attribute("width"), n.text()?.trim())))
+        .filter(|(_, text)| !text.is_empty())
+        .min_by_key(|(width, _)| if *width == Some("16") { 0 } else { 1 })
+        .and_then(|(_, text)| match base {
+            Some(base) => base.join(text).ok().map(|u| u.to_string()),
+            None => Url::parse(text).ok().map(|u| u.to_string()),
+        });
+
+    Ok(NewSearchEngine {
+        name,
+        template: template.ok_or_else(|| invalid("no text/html GET search URL"))?,
+        suggest_url,
+        icon_url,
+    })
+}
+
+/// Convert a `Url` element (template plus `Param` children) to a `%s` template
+fn url_template(url: roxmltree::Node, base: Option<&Url>) -> Option<String> {
+    let mut template = url.attribute("template")?.trim().to_string();
+
+    let params: Vec<String> = url
+        .children()
+        .filter(|n| n.is_element() && n.tag_name().name() == "Param")
+        .filter_map(|n| {
+            Some(format!(
+                "{}={}",
+                n.attribute("name")?,
+                n.attribute("value")?
+            ))
+        })
+        .collect();
+    if !params.is_empty() {
+        template.push(if template.contains('?') { '&' } else { '?' });
+        template.push_str(&params.join("&"));
+    }
+
+    let template = fill_parameters(&template)?;
+    if !template.contains("%s") {
+        return None;
+    }
+
+    // Placeholders are gone, so the template now parses as a URL
+    let absolute = match base {
+        Some(base) => base.join(&template).ok()?,
+        None => Url::parse(&template).ok()?,
+    };
+    if !matches!(absolute.scheme(), "http" | "https") {
+        return None;
+    }
+    // A `%` not followed by two hex digits survives URL parsing unchanged
+    Some(absolute.to_string())
+}
+
+/// Replace OpenSearch `{parameters}`; `None` if a required one is unknown
+fn fill_parameters(template: &str) -> Option<String> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| Regex::new(r"\{([^{}]+)\}").unwrap());
+
+    let mut unknown_required = false;
+    let filled = re.replace_all(template, |caps: &regex::Captures| {
+        let raw = &caps[1];
+        let optional = raw.ends_with('?');
+        let name = raw.trim_end_matches('?');
+        // Namespaced parameters (`moz:locale`) are never required by us
+        let local = name.rsplit(':').next().unwrap_or(name);
+        match local {
+            "searchTerms" => "%s".to_string(),
+            "inputEncoding" | "outputEncoding" => "UTF-8".to_string(),
+            "language" => "*".to_string(),
+            "startIndex" | "startPage" => "1".to_string(),
+            "count" => "20".to_string(),
+            _ if optional || local != name => String::new(),
+            _ => {
+                unknown_required = true;
+                String::new()
+            }
+        }
+    });
+
+    (!unknown_required).then(|| filled.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_links() {
+        let page = Url::parse("https://developer.mozilla.org/en-US/docs/Web").unwrap();
+        let html = r#"<html><head>
+            <link rel="stylesheet" href="/main.css">
+            <LINK REL="search" TYPE="application/opensearchdescription+xml"
+                  href='/opensearch.xml?a=1&amp;b=2' title="MDN Web Docs">
+            <link rel="alternate search" type="application/opensearchdescription+xml" href="https://example.com/os.xml">
+            <link rel="search" type="text/html" href="/search">
+        </head></html>"#;
+
+        let links = discover_opensearch_links(html, &page);
+        assert_eq!(links.len(), 2);
+        assert_eq!(
+            links[0].href,
+            "https://developer.mozilla.org/opensearch.xml?a=1&b=2"
+        );
+        assert_eq!(links[0].title.as_deref(), Some("MDN Web Docs"));
+        assert_eq!(links[1].href, "https://example.com/os.xml");
+    }
+
+    #[test]
+    fn test_parse_opensearch() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/"
+                       xmlns:moz="http://www.mozilla.org/2006/browser/search/">
+  <ShortName>MDN Web Docs</ShortName>
+  <Description>Search MDN</Description>
+  <InputEncoding>UTF-8</InputEncoding>
+  <Image width="64" height="64">https://developer.mozilla.org/favicon-64.png</Image>
+  <Image width="16" height="16">/favicon.ico</Image>
+  <Url type="application/x-suggestions+json" template="https://developer.mozilla.org/api/suggest?q={searchTerms}"/>
+  <Url type="text/html" method="get" template="/en-US/search?q={searchTerms}&amp;page={startPage?}&amp;l={moz:locale}"/>
+</OpenSearchDescription>"#;
+        let base = Url::parse("https://developer.mozilla.org/opensearch.xml").unwrap();
+
+        let engine = parse_opensearch(xml, Some(&base)).unwrap();
+        assert_eq!(engine.name, "MDN Web Docs");
+        assert_eq!(
+            engine.template,
+            "https://developer.mozilla.org/en-US/search?q=%s&page=1&l="
+        );
+        assert_eq!(
+            engine.suggest_url.as_deref(),
+            Some("https://developer.mozilla.org/api/suggest?q=%s")
+        );
+        assert_eq!(
+            engine.icon_url.as_deref(),
+            Some("https://developer.mozilla.org/favicon.ico")
+        );
+    }
+
+    #[test]
+    fn test_parse_opensearch_params_and_errors() {
+        let xml = r#"<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>Legacy</ShortName>
+  <Url type="text/html" method="POST" template="https://legacy.test/post"/>
+  <Url type="text/html" template="https://legacy.test/find">
+    <Param name="q" value="{searchTerms}"/>
+    <Param name="enc" value="{inputEncoding}"/>
+  </Url>
+</OpenSearchDescription>"#;
+        let engine = parse_opensearch(xml, None).unwrap();
+        assert_eq!(engine.template, "https://legacy.test/find?q=%s&enc=UTF-8");
+
+        assert!(parse_opensearch("<html/>", None).is_err());
+        assert!(parse_opensearch("not xml", None).is_err());
+        let required = r#"<OpenSearchDescription><ShortName>X</ShortName>
+  <Url type="text/html" template="https://x.test/?q={searchTerms}&amp;k={apiKey}"/>
+</OpenSearchDescription>"#;
+        assert!(parse_opensearch(required, None).is_err());
+    }
+}
@@ -0,0 +1,364 @@
+//! Search engines
+//!
+//! The built-in engines live in code; engines the user adds (by hand or
+//! from a page's OpenSearch description) are stored in `search_engines`.
+//! The default engine is referenced by id from the `default_search_engine`
+//! setting.
+
+use chrono::Utc;
+use rusqlite::OptionalExtension;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use axiom_storage::Database;
+
+use crate::error::NavigationError;
+use crate::keywords::{expand_template, validate_template};
+use crate::Result;
+
+const DEFAULT_ENGINE_SETTING: &str = "default_search_engine";
+/// Template of the default engine before engines were selectable by id
+const LEGACY_TEMPLATE_SETTING: &str = "search_engine";
+
+pub const DEFAULT_ENGINE_ID: &str = "duckduckgo";
+
+/// (id, name, template, suggest URL, icon URL)
+const BUILTIN_ENGINES: [(&str, &str, &str, &str, &str); 3] = [
+    (
+        "duckduckgo",
+        "DuckDuckGo",
+        "https://duckduckgo.com/?q=%s",
+        "https://duckduckgo.com/ac/?q=%s&type=list",
+        "https://duckduckgo.com/favicon.ico",
+    ),
+    (
+        "google",
+        "Google",
+        "https://www.google.com/search?q=%s",
+        "https://suggestqueries.google.com/complete/search?client=firefox&q=%s",
+        "https://www.google.com/favicon.ico",
+    ),
+    (
+        "bing",
+        "Bing",
+        "https://www.bing.com/search?q=%s",
+        "https://api.bing.com/osjson.aspx?query=%s",
+        "https://www.bing.com/favicon.ico",
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchEngine {
+    pub id: String,
+    pub name: String,
+    /// Results URL template (%s replaced with query)
+    pub template: String,
+    /// OpenSearch suggestions (JSON) URL template
+    pub suggest_url: Option<String>,
+    pub icon_url: Option<String>,
+    /// Shipped with the browser; cannot be removed
+    pub builtin: bool,
+}
+
+impl SearchEngine {
+    pub fn search_url(&self, query: &str) -> String {
+        expand_template(&self.template, query)
+    }
+
+    pub fn suggestions_url(&self, query: &str) -> Option<String> {
+        self.suggest_url
+            .as_deref()
+            .map(|template| expand_template(template, query))
+    }
+}
+
+/// An engine to be added, e.g. parsed from an OpenSearch description
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewSearchEngine {
+    pub name: String,
+    pub template: String,
+    #[serde(default)]
+    pub suggest_url: Option<String>,
+    #[serde(default)]
+    pub icon_url: Option<String>,
+}
+
+fn builtin_engines() -> impl Iterator<Item = SearchEngine> {
+    BUILTIN_ENGINES
+        .iter()
+        .map(|(id, name, template, suggest, icon)| SearchEngine {
+            id: id.to_string(),
+            name: name.to_string(),
+            template: template.to_string(),
+            suggest_url: Some(suggest.to_string()),
+            icon_url: Some(icon.to_string()),
+            builtin: true,
+        })
+}
+
+fn engine_from_row(row: &rusqlite::Row) -> rusqlite::Result<SearchEngine> {
+    Ok(SearchEngine {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        template: row.get(2)?,
+        suggest_url: row.get(3)?,
+        icon_url: row.get(4)?,
+        builtin: false,
+    })
+}
+
+pub struct SearchEngineManager {
+    db: Database,
+}
+
+impl SearchEngineManager {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Built-in engines first, then added engines by name
+    pub fn list(&self) -> Result<Vec<SearchEngine>> {
+        let custom: Vec<SearchEngine> = self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, name, template, suggest_url, icon_url
+                 FROM search_engines ORDER BY name COLLATE NOCASE ASC",
+            )?;
+            let engines = stmt
+                .query_map([], engine_from_row)?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(engines)
+        })?;
+
+        Ok(builtin_engines().chain(custom).collect())
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<SearchEngine>> {
+        if let Some(engine) = builtin_engines().find(|e| e.id == id) {
+            return Ok(Some(engine));
+        }
+
+        Ok(self.db.with_connection(|conn| {
+            Ok(conn
+                .query_row(
+                    "SELECT id, name, template, suggest_url, icon_url
+                     FROM search_engines WHERE id = ?1",
+                    [id],
+                    engine_from_row,
+                )
+                .optional()?)
+        })?)
+    }
+
+    pub fn find_by_template(&self, template: &str) -> Result<Option<SearchEngine>> {
+        let template = template.trim();
+        Ok(self.list()?.into_iter().find(|e| e.template == template))
+    }
+
+    /// Add an engine; adding a template that is already known returns the existing engine
+    pub fn add(&self, engine: NewSearchEngine) -> Result<SearchEngine> {
+        let template = validate_template(&engine.template)?;
+        if let Some(existing) = self.find_by_template(&template)? {
+            return Ok(existing);
+        }
+
+        let name = engine.name.trim().to_string();
+        if name.is_empty() {
+            return Err(NavigationError::InvalidSearchEngine(
+                "Name cannot be empty".to_string(),
+            ));
+        }
+        let suggest_url = engine
+            .suggest_url
+            .map(|s| validate_template(&s))
+            .transpose()?;
+        let icon_url = engine
+            .icon_url
+            .map(|s| s.trim().to_string())
+            .filter(|s| Url::parse(s).is_ok());
+
+        let added = SearchEngine {
+            id: self.unused_id(&template)?,
+            name,
+            template,
+            suggest_url,
+            icon_url,
+            builtin: false,
+        };
+
+        self.db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO search_engines (id, name, template, suggest_url, icon_url, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![
+                    added.id,
+                    added.name,
+                    added.template,
+                    added.suggest_url,
+                    added.icon_url,
+                    Utc::now().to_rfc3339(),
+                ],
+            )?;
+            Ok(())
+        })?;
+
+        tracing::info!(engine_id = %added.id, "Added search engine");
+
+        Ok(added)
+    }
+
+    /// Remove an added engine; the default falls back to the built-in default
+    pub fn remove(&self, id: &str) -> Result<()> {
+        if builtin_engines().any(|e| e.id == id) {
+            return Err(NavigationError::InvalidSearchEngine(format!(
+                "Built-in engine cannot be removed: {id}"
+            )));
+        }
+
+        let removed = self.db.with_connection(|conn| {
+            Ok(conn.execute("DELETE FROM search_engines WHERE id = ?1", [id])?)
+        })?;
+        if removed == 0 {
+            return Err(NavigationError::SearchEngineNotFound(id.to_string()));
+        }
+
+        if self.db.get_setting(DEFAULT_ENGINE_SETTING)?.as_deref() == Some(id) {
+            self.db
+                .set_setting(DEFAULT_ENGINE_SETTING, DEFAULT_ENGINE_ID)?;
+        }
+        Ok(())
+    }
+
+    /// The default engine, adopting a legacy template setting (or `fallback_template`) on first use
+    pub fn default_engine(&self, fallback_template: &str) -> Result<SearchEngine> {
+        if let Some(id) = self.db.get_setting(DEFAULT_ENGINE_SETTING)? {
+            if let Some(engine) = self.get(&id)? {
+                return Ok(engine);
+            }
+        }
+
+        let template = self
+            .db
+            .get_setting(LEGACY_TEMPLATE_SETTING)?
+            .unwrap_or_else(|| fallback_template.to_string());
+        let engine = match self.find_by_template(&template)? {
+            Some(engine) => engine,
+            None => match self.add(NewSearchEngine {
+                name: "Custom".to_string(),
+                template,
+                suggest_url: None,
+                icon_url: None,
+            }) {
+                Ok(engine) => engine,
+                Err(_) => self.get(DEFAULT_ENGINE_ID)?.ok_or_else(|| {
+                    NavigationError::SearchEngineNotFound(DEFAULT_ENGINE_ID.into())
+                })?,
+            },
+        };
+
+        self.db.set_setting(DEFAULT_ENGINE_SETTING, &engine.id)?;
+        Ok(engine)
+    }
+
+    pub fn set_default(&self, id: &str) -> Result<SearchEngine> {
+        let engine = self
+            .get(id)?
+            .ok_or_else(|| NavigationError::SearchEngineNotFound(id.to_string()))?;
+        self.db.set_setting(DEFAULT_ENGINE_SETTING, &engine.id)?;
+        Ok(engine)
+    }
+
+    /// Readable id from the template host, suffixed if already taken
+    fn unused_id(&self, template: &str) -> Result<String> {
+        let base = Url::parse(&expand_template(template, ""))
+            .ok()
+            .and_then(|u| {
+                u.host_str()
+                    .map(|h| h.trim_start_matches("www.").to_string())
+            })
+            .unwrap_or_else(|| "engine".to_string());
+
+        let mut candidate = base.clone();
+        let mut n = 2;
+        while self.get(&candidate)?.is_some() {
+            candidate = format!("{base}-{n}");
+            n += 1;
+        }
+        Ok(candidate)
+    }
+}
+
+impl Clone for SearchEngineManager {
+    fn clone(&self) -> Self {
+        Self {
+            db: self.db.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_engine(name: &str, template: &str) -> NewSearchEngine {
+        NewSearchEngine {
+            name: name.to_string(),
+            template: template.to_string(),
+            suggest_url: None,
+            icon_url: None,
+        }
+    }
+
+    #[test]
+    fn test_add_list_remove() {
+        let manager = SearchEngineManager::new(Database::open_in_memory().unwrap());
+        assert_eq!(manager.list().unwrap().len(), BUILTIN_ENGINES.len());
+
+        let mdn = manager
+            .add(new_engine(
+                "MDN",
+                "https://developer.mozilla.org/search?q=%s",
+            ))
+            .unwrap();
+        assert_eq!(mdn.id, "developer.mozilla.org");
+        // Same template again is not duplicated
+        let again = manager
+            .add(new_engine(
+                "MDN 2",
+                "https://developer.mozilla.org/search?q=%s",
+            ))
+            .unwrap();
+        assert_eq!(again.id, mdn.id);
+        let other = manager
+            .add(new_engine(
+                "MDN docs",
+                "https://developer.mozilla.org/docs?q=%s",
+            ))
+            .unwrap();
+        assert_eq!(other.id, "developer.mozilla.org-2");
+        assert!(manager.add(new_engine("Bad", "https://x.test/")).is_err());
+
+        manager.set_default(&mdn.id).unwrap();
+        assert_eq!(manager.default_engine("").unwrap().id, mdn.id);
+        manager.remove(&mdn.id).unwrap();
+        assert_eq!(manager.default_engine("").unwrap().id, DEFAULT_ENGINE_ID);
+        assert!(manager.remove("google").is_err());
+    }
+
+    #[test]
+    fn test_default_adopts_legacy_template() {
+        let db = Database::open_in_memory().unwrap();
+        db.set_setting(LEGACY_TEMPLATE_SETTING, "https://www.bing.com/search?q=%s")
+            .unwrap();
+        let manager = SearchEngineManager::new(db.clone());
+        assert_eq!(manager.default_engine("").unwrap().id, "bing");
+
+        let db = Database::open_in_memory().unwrap();
+        db.set_setting(LEGACY_TEMPLATE_SETTING, "https://search.example/?q=%s")
+            .unwrap();
+        let manager = SearchEngineManager::new(db);
+        let engine = manager.default_engine("").unwrap();
+        assert_eq!(engine.id, "search.example");
+        assert_eq!(engine.name, "Custom");
+    }
+}
@@ -0,0 +1,753 @@
+//! Omnibox suggestions
+//!
+//! Each provider turns the address bar input into scored suggestions. The
+//! pipeline runs them in turn, merges the results, keeps the best-scoring
+//! suggestion per URL, caps the list and picks an inline autocomplete
+//! candidate for the address bar. A new keystroke cancels the previous run
+//! through its [`CancellationToken`].
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use url::Url;
+
+use crate::command::Command;
+use crate::error::NavigationError;
+use crate::history::{HistoryManager, HistorySort};
+use crate::input::{InputResolution, InputResolver};
+use crate::Result;
+
+/// Score bases per provider; match quality and frecency are added on top
+const ANSWER_SCORE: i64 = 3000;
+const COMMAND_SCORE: i64 = 2000;
+const TYPED_URL_SCORE: i64 = 1500;
+const SEARCH_SCORE: i64 = 1000;
+const FILE_NAME_DOMAIN_SCORE: i64 = 950;
+const SEARCH_URL_LIKE_SCORE: i64 = 400;
+const OPEN_TAB_SCORE: i64 = 900;
+const BOOKMARK_SCORE: i64 = 800;
+const HISTORY_SCORE: i64 = 500;
+const HISTORY_MAX_FRECENCY_BONUS: i64 = 300;
+
+/// Bonus when the URL (without scheme and `www.`) starts with the input
+const URL_PREFIX_BONUS: i64 = 200;
+/// Bonus when the title starts with the first query term
+const TITLE_PREFIX_BONUS: i64 = 100;
+
+/// History rows fetched per run before term filtering
+const HISTORY_CANDIDATES: usize = 50;
+
+/// Cooperative cancellation flag shared between a caller and a pipeline run
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(NavigationError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionKind {
+    /// Switch to an already open tab
+    OpenTab,
+    History,
+    Bookmark,
+    /// Search the typed text
+    Search,
+    /// An `@` command
+    Command,
+    /// Navigate to exactly what was typed
+    TypedUrl,
+    /// A calculation or unit conversion result
+    Answer,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub kind: SuggestionKind,
+    pub title: String,
+    /// Destination; `None` for commands
+    pub url: Option<String>,
+    /// Text to put in the address bar when chosen instead of navigating
+    pub fill: Option<String>,
+    /// Tab to switch to for [`SuggestionKind::OpenTab`]
+    pub tab_id: Option<String>,
+    pub score: i64,
+}
+
+impl Suggestion {
+    fn new(kind: SuggestionKind, title: String, url: Option<String>, score: i64) -> Self {
+        Self {
+            kind,
+            title,
+            url,
+            fill: None,
+            tab_id: None,
+            score,
+        }
+    }
+}
+
+/// Text to complete the address bar input with, selected after the caret
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InlineAutocomplete {
+    /// Full address bar text; starts with the input (ignoring case)
+    pub completion: String,
+    /// URL to open if the completion is accepted
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SuggestionResult {
+    pub suggestions: Vec<Suggestion>,
+    pub autocomplete: Option<InlineAutocomplete>,
+}
+
+/// Address bar input as seen by providers
+#[derive(Debug, Clone)]
+pub struct SuggestQuery {
+    /// Trimmed input
+    pub input: String,
+    /// Lowercased whitespace-separated terms
+    pub terms: Vec<String>,
+}
+
+impl SuggestQuery {
+    pub fn new(input: &str) -> Self {
+        let input = input.trim().to_string();
+        let terms = input.split_whitespace().map(str::to_lowercase).collect();
+        Self { input, terms }
+    }
+
+    fn is_command(&self) -> bool {
+        self.input.starts_with('@')
+    }
+
+    /// Match bonus if every term occurs in the title or URL, `None` otherwise
+    fn match_bonus(&self, title: &str, url: &str) -> Option<i64> {
+        if self.terms.is_empty() || self.is_command() {
+            return None;
+        }
+
+        let title = title.to_lowercase();
+        let url = url.to_lowercase();
+        if !self
+            .terms
+            .iter()
+            .all(|term| title.contains(term.as_str()) || url.contains(term.as_str()))
+        {
+            return None;
+        }
+
+        let mut bonus = 0;
+        if strip_url_prefix(&url).starts_with(&self.input.to_lowercase()) {
+            bonus += URL_PREFIX_BONUS;
+        }
+        if title.starts_with(self.terms[0].as_str()) {
+            bonus += TITLE_PREFIX_BONUS;
+        }
+        Some(bonus)
+    }
+}
+
+pub trait SuggestionProvider {
+    fn name(&self) -> &'static str;
+
+    /// Suggestions for the query; long-running providers should poll `cancel`
+    fn suggest(&self, query: &SuggestQuery, cancel: &CancellationToken) -> Result<Vec<Suggestion>>;
+}
+
+/// An open tab offered by [`OpenTabsProvider`]
+#[derive(Debug, Clone)]
+pub struct TabCandidate {
+    pub tab_id: String,
+    pub title: String,
+    pub url: String,
+}
+
+pub struct OpenTabsProvider {
+    tabs: Vec<TabCandidate>,
+}
+
+impl OpenTabsProvider {
+    pub fn new(tabs: Vec<TabCandidate>) -> Self {
+        Self { tabs }
+    }
+}
+
+impl SuggestionProvider for OpenTabsProvider {
+    fn name(&self) -> &'static str {
+        "open_tabs"
+    }
+
+    fn suggest(
+        &self,
+        query: &SuggestQuery,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<Suggestion>> {
+        Ok(self
+            .tabs
+            .iter()
+            .filter_map(|tab| {
+                let bonus = query.match_bonus(&tab.title, &tab.url)?;
+                let mut suggestion = Suggestion::new(
+                    SuggestionKind::OpenTab,
+                    tab.title.clone(),
+                    Some(tab.url.clone()),
+                    OPEN_TAB_SCORE + bonus,
+                );
+                suggestion.tab_id = Some(tab.tab_id.clone());
+                Some(suggestion)
+            })
+            .collect())
+    }
+}
+
+/// A bookmark offered by [`BookmarksProvider`]
+#[derive(Debug, Clone)]
+pub struct BookmarkCandidate {
+    pub title: String,
+    pub url: String,
+}
+
+pub struct BookmarksProvider {
+    bookmarks: Vec<BookmarkCandidate>,
+}
+
+impl BookmarksProvider {
+    pub fn new(bookmarks: Vec<BookmarkCandidate>) -> Self {
+        Self { bookmarks }
+    }
+}
+
+impl SuggestionProvider for BookmarksProvider {
+    fn name(&self) -> &'static str {
+        "bookmarks"
+    }
+
+    fn suggest(
+        &self,
+        query: &SuggestQuery,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<Suggestion>> {
+        Ok(self
+            .bookmarks
+            .iter()
+            .filter_map(|bookmark| {
+                let bonus = query.match_bonus(&bookmark.title, &bookmark.url)?;
+                Some(Suggestion::new(
+                    SuggestionKind::Bookmark,
+                    bookmark.title.clone(),
+                    Some(bookmark.url.clone()),
+                    BOOKMARK_SCORE + bonus,
+                ))
+            })
+            .collect())
+    }
+}
+
+/// History entries ranked by frecency
+pub struct HistoryProvider {
+    history: HistoryManager,
+}
+
+impl HistoryProvider {
+    pub fn new(history: HistoryManager) -> Self {
+        Self { history }
+    }
+}
+
+impl SuggestionProvider for HistoryProvider {
+    fn name(&self) -> &'static str {
+        "history"
+    }
+
+    fn suggest(
+        &self,
+        query: &SuggestQuery,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<Suggestion>> {
+        // Narrow in SQL by the most selective (longest) term, then require all terms
+        let Some(longest) = query.terms.iter().max_by_key(|t| t.len()) else {
+            return Ok(Vec::new());
+        };
+        if query.is_command() {
+            return Ok(Vec::new());
+        }
+
+        let entries = self
+            .history
+            .search(longest, HISTORY_CANDIDATES, HistorySort::Frecency)?;
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|entry| {
+                let bonus = query.match_bonus(&entry.title, &entry.url)?;
+                let frecency = entry.frecency.clamp(0, HISTORY_MAX_FRECENCY_BONUS);
+                Some(Suggestion::new(
+                    SuggestionKind::History,
+                    entry.title,
+                    Some(entry.url),
+                    HISTORY_SCORE + frecency + bonus,
+                ))
+            })
+            .collect())
+    }
+}
+
+/// "Search for what you typed" with the resolver's search engine
+pub struct SearchProvider {
+    resolver: InputResolver,
+}
+
+impl SearchProvider {
+    pub fn new(resolver: InputResolver) -> Self {
+        Self { resolver }
+    }
+}
+
+impl SuggestionProvider for SearchProvider {
+    fn name(&self) -> &'static str {
+        "search"
+    }
+
+    fn suggest(
+        &self,
+        query: &SuggestQuery,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<Suggestion>> {
+        if query.input.is_empty() || query.is_command() {
+            return Ok(Vec::new());
+        }
+
+        // Input that navigates still gets a (lower) search entry
+        let (title, url, score) = match self.resolver.resolve(&query.input) {
+            InputResolution::Search {
+                url,
+                query,
+                keyword: Some(keyword),
+            } => {
+                let name = self
+                    .resolver
+                    .keyword(&keyword)
+                    .map(|k| k.name.clone())
+                    .unwrap_or(keyword);
+                (format!("{name}: {query}"), url, SEARCH_SCORE)
+            }
+            InputResolution::Search { url, query, .. } => (query, url, SEARCH_SCORE),
+            _ => (
+                query.input.clone(),
+                self.resolver.search_url(&query.input),
+                SEARCH_URL_LIKE_SCORE,
+            ),
+        };
+
+        Ok(vec![Suggestion::new(
+            SuggestionKind::Search,
+            title,
+            Some(url),
+            score,
+        )])
+    }
+}
+
+/// The result of a calculation or conversion, shown above everything else
+pub struct AnswerProvider {
+    resolver: InputResolver,
+}
+
+impl AnswerProvider {
+    pub fn new(resolver: InputResolver) -> Self {
+        Self { resolver }
+    }
+}
+
+impl SuggestionProvider for AnswerProvider {
+    fn name(&self) -> &'static str {
+        "answer"
+    }
+
+    fn suggest(
+        &self,
+        query: &SuggestQuery,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<Suggestion>> {
+        // Only what the resolver would answer: hosts and keywords win
+        let InputResolution::Answer(answer) = self.resolver.resolve(&query.input) else {
+            return Ok(Vec::new());
+        };
+
+        let mut suggestion = Suggestion::new(
+            SuggestionKind::Answer,
+            format!("{} = {}", answer.query, answer.result),
+            None,
+            ANSWER_SCORE,
+        );
+        suggestion.fill = Some(answer.result);
+        Ok(vec![suggestion])
+    }
+}
+
+/// "Navigate to what you typed" when the input resolves to a URL
+pub struct TypedUrlProvider {
+    resolver: InputResolver,
+}
+
+impl TypedUrlProvider {
+    pub fn new(resolver: InputResolver) -> Self {
+        Self { resolver }
+    }
+}
+
+impl SuggestionProvider for TypedUrlProvider {
+    fn name(&self) -> &'static str {
+        "typed_url"
+    }
+
+    fn suggest(
+        &self,
+        query: &SuggestQuery,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<Suggestion>> {
+        if query.input.is_empty() || query.is_command() {
+            return Ok(Vec::new());
+        }
+
+        match self.resolver.resolve(&query.input) {
+            InputResolution::Navigate(url) => Ok(vec![Suggestion::new(
+                SuggestionKind::TypedUrl,
+                url.clone(),
+                Some(url),
+                TYPED_URL_SCORE,
+            )]),
+            // `docs.rs` searches, but navigating there is the next best guess
+            _ => Ok(self
+                .resolver
+                .file_name_domain_url(&query.input)
+                .map(|url| {
+                    Suggestion::new(
+                        SuggestionKind::TypedUrl,
+                        url.clone(),
+                        Some(url),
+                        FILE_NAME_DOMAIN_SCORE,
+                    )
+                })
+                .into_iter()
+                .collect()),
+        }
+    }
+}
+
+/// `@` commands whose name starts with what was typed
+pub struct CommandProvider;
+
+impl SuggestionProvider for CommandProvider {
+    fn name(&self) -> &'static str {
+        "commands"
+    }
+
+    fn suggest(
+        &self,
+        query: &SuggestQuery,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<Suggestion>> {
+        if !query.is_command() {
+            return Ok(Vec::new());
+        }
+
+        // A complete command with a query is handled by its own UI
+        if query.terms.len() > 1 {
+            if let Some(command) = Command::parse(&query.input) {
+                let mut suggestion = Suggestion::new(
+                    SuggestionKind::Command,
+                    command.command_type.description().to_string(),
+                    None,
+                    COMMAND_SCORE,
+                );
+                suggestion.fill = Some(query.input.clone());
+                return Ok(vec![suggestion]);
+            }
+            return Ok(Vec::new());
+        }
+
+        Ok(Command::completions(&query.input)
+            .into_iter()
+            .map(|command| {
+                let mut suggestion = Suggestion::new(
+                    SuggestionKind::Command,
+                    command.description().to_string(),
+                    None,
+                    COMMAND_SCORE,
+                );
+                suggestion.fill = Some(format!("{} ", command.prefix()));
+                suggestion
+            })
+            .collect())
+    }
+}
+
+/// Providers run in insertion order; earlier providers win score ties
+pub struct SuggestionPipeline {
+    providers: Vec<Box<dyn SuggestionProvider>>,
+    max_results: usize,
+}
+
+impl SuggestionPipeline {
+    pub fn new(max_results: usize) -> Self {
+        Self {
+            providers: Vec::new(),
+            max_results,
+        }
+    }
+
+    pub fn with_provider(mut self, provider: impl SuggestionProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    /// Run every provider; fails with [`NavigationError::Cancelled`] once `cancel` is set
+    pub fn run(&self, input: &str, cancel: &CancellationToken) -> Result<SuggestionResult> {
+        let query = SuggestQuery::new(input);
+        if query.input.is_empty() {
+            return Ok(SuggestionResult::default());
+        }
+
+        let mut merged: Vec<Suggestion> = Vec::new();
+        let mut by_url: HashMap<String, usize> = HashMap::new();
+
+        for provider in &self.providers {
+            cancel.check()?;
+
+            let suggestions = match provider.suggest(&query, cancel) {
+                Ok(suggestions) => suggestions,
+                Err(NavigationError::Cancelled) => return Err(NavigationError::Cancelled),
+                Err(e) => {
+                    tracing::warn!(provider = provider.name(), error = %e, "Suggestion provider failed");
+                    continue;
+                }
+            };
+
+            for suggestion in suggestions {
+                let Some(key) = suggestion.url.as_deref().map(dedup_key) else {
+                    merged.push(suggestion);
+                    continue;
+                };
+                match by_url.get(&key) {
+                    Some(&idx) if merged[idx].score >= suggestion.score => {}
+                    Some(&idx) => merged[idx] = suggestion,
+                    None => {
+                        by_url.insert(key, merged.len());
+                        merged.push(suggestion);
+                    }
+                }
+            }
+        }
+
+        cancel.check()?;
+
+        // Stable sort keeps provider order for equal scores
+        merged.sort_by_key(|s| std::cmp::Reverse(s.score));
+        let autocomplete = inline_autocomplete(&query, &merged);
+        merged.truncate(self.max_results);
+
+        Ok(SuggestionResult {
+            suggestions: merged,
+            autocomplete,
+        })
+    }
+}
+
+/// URL identity for deduplication: fragment dropped, a bare trailing `/` ignored
+fn dedup_key(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut parsed) => {
+            parsed.set_fragment(None);
+            let key = parsed.to_string();
+            key.strip_suffix('/').map(str::to_string).unwrap_or(key)
+        }
+        Err(_) => url.to_string(),
+    }
+}
+
+fn strip_url_prefix(url: &str) -> &str {
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    url.strip_prefix("www.").unwrap_or(url)
+}
+
+/// Complete the input to the host (or, once a `/` is typed, the full URL) of the best match
+fn inline_autocomplete(query: &SuggestQuery, ranked: &[Suggestion]) -> Option<InlineAutocomplete> {
+    let input = query.input.to_lowercase();
+    if query.is_command() || input.contains(char::is_whitespace) || input.contains("://") {
+        return None;
+    }
+    let keep_www = input.starts_with("www.");
+
+    ranked
+        .iter()
+        .filter(|s| {
+            matches!(
+                s.kind,
+                SuggestionKind::OpenTab | SuggestionKind::History | SuggestionKind::Bookmark
+            )
+        })
+        .find_map(|suggestion| {
+            let parsed = Url::parse(suggestion.url.as_deref()?).ok()?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return None;
+            }
+
+            let after_scheme = &parsed[url::Position::BeforeHost..];
+            let shown = if keep_www {
+                after_scheme
+            } else {
+                after_scheme.strip_prefix("www.").unwrap_or(after_scheme)
+            };
+
+            let (completion, url) = if input.contains('/') {
+                (shown.to_string(), parsed.to_string())
+            } else {
+                let host = shown.split('/').next().unwrap_or(shown);
+                let origin = &parsed[..url::Position::BeforePath];
+                (format!("{host}/"), format!("{origin}/"))
+            };
+
+            (completion.len() > input.len() && completion.to_lowercase().starts_with(&input))
+                .then_some(InlineAutocomplete { completion, url })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(Vec<Suggestion>);
+
+    impl SuggestionProvider for Fixed {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn suggest(&self, _: &SuggestQuery, _: &CancellationToken) -> Result<Vec<Suggestion>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn tab(id: &str, title: &str, url: &str) -> TabCandidate {
+        TabCandidate {
+            tab_id: id.to_string(),
+            title: title.to_string(),
+            url: url.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_merge_dedup_and_cap() {
+        let history = Suggestion::new(
+            SuggestionKind::History,
+            "Rust".to_string(),
+            Some("https://www.rust-lang.org/#top".to_string()),
+            700,
+        );
+        let pipeline = SuggestionPipeline::new(3)
+            .with_provider(Fixed(vec![history]))
+            .with_provider(OpenTabsProvider::new(vec![
+                tab(
+                    "1",
+                    "Rust Programming Language",
+                    "https://www.rust-lang.org/",
+                ),
+                tab("2", "Example", "https://example.com/"),
+            ]))
+            .with_provider(SearchProvider::new(InputResolver::new()))
+            .with_provider(TypedUrlProvider::new(InputResolver::new()));
+
+        let result = pipeline.run("rust", &CancellationToken::new()).unwrap();
+        let kinds: Vec<_> = result.suggestions.iter().map(|s| s.kind).collect();
+        // The open tab replaces the lower-scored history row for the same URL
+        assert_eq!(kinds, vec![SuggestionKind::OpenTab, SuggestionKind::Search]);
+        assert_eq!(result.suggestions[0].tab_id.as_deref(), Some("1"));
+
+        let result = pipeline.run("e", &CancellationToken::new()).unwrap();
+        assert!(result.suggestions.len() <= 3);
+
+        // A file name that is also a domain searches first, then offers the site
+        let result = pipeline.run("docs.rs", &CancellationToken::new()).unwrap();
+        let kinds: Vec<_> = result.suggestions.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds[..2],
+            [SuggestionKind::Search, SuggestionKind::TypedUrl]
+        );
+        assert_eq!(
+            result.suggestions[1].url.as_deref(),
+            Some("https://docs.rs")
+        );
+    }
+
+    #[test]
+    fn test_inline_autocomplete() {
+        let pipeline = SuggestionPipeline::new(8).with_provider(BookmarksProvider::new(vec![
+            BookmarkCandidate {
+                title: "GitHub".to_string(),
+                url: "https://www.github.com/rust-lang/rust".to_string(),
+            },
+        ]));
+        let cancel = CancellationToken::new();
+
+        let auto = pipeline.run("git", &cancel).unwrap().autocomplete.unwrap();
+        assert_eq!(auto.completion, "github.com/");
+        assert_eq!(auto.url, "https://www.github.com/");
+
+        let auto = pipeline
+            .run("github.com/r", &cancel)
+            .unwrap()
+            .autocomplete
+            .unwrap();
+        assert_eq!(auto.completion, "github.com/rust-lang/rust");
+
+        assert!(pipeline
+            .run("git hub", &cancel)
+            .unwrap()
+            .autocomplete
+            .is_none());
+    }
+
+    #[test]
+    fn test_commands_and_cancellation() {
+        let pipeline = SuggestionPipeline::new(8)
+            .with_provider(CommandProvider)
+            .with_provider(SearchProvider::new(InputResolver::new()));
+        let cancel = CancellationToken::new();
+
+        let result = pipeline.run("@h", &cancel).unwrap();
+        assert_eq!(result.suggestions.len(), 1);
+        assert_eq!(result.suggestions[0].fill.as_deref(), Some("@history "));
+
+        cancel.cancel();
+        assert!(matches!(
+            pipeline.run("@h", &cancel),
+            Err(NavigationError::Cancelled)
+        ));
+    }
+}
@@ -0,0 +1,408 @@
+//! Unit conversion for address bar answers
+//!
+//! `12 in to cm`, `70 kg in lb`, `100 f in c`, `1.5 gib to mb`, `90 min in h`.
+//! Units convert within one dimension through a base unit (metre, kilogram,
+//! byte, second); temperatures are offset as well as scaled.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Length,
+    Mass,
+    Temperature,
+    DataSize,
+    Time,
+}
+
+struct Unit {
+    /// Display symbol
+    symbol: &'static str,
+    /// Accepted spellings, lowercase
+    names: &'static [&'static str],
+    dimension: Dimension,
+    /// Base units per one of this unit
+    factor: f64,
+    /// Added after scaling (temperatures only; base is kelvin)
+    offset: f64,
+}
+
+const fn unit(
+    symbol: &'static str,
+    names: &'static [&'static str],
+    dimension: Dimension,
+    factor: f64,
+) -> Unit {
+    Unit {
+        symbol,
+        names,
+        dimension,
+        factor,
+        offset: 0.0,
+    }
+}
+
+const DAY: f64 = 86_400.0;
+const YEAR: f64 = 365.2425 * DAY;
+
+const UNITS: &[Unit] = &[
+    // Length (metre)
+    unit(
+        "nm",
+        &["nm", "nanometer", "nanometers", "nanometre", "nanometres"],
+        Dimension::Length,
+        1e-9,
+    ),
+    unit(
+        "µm",
+        &["µm", "um", "micrometer", "micrometers", "micron", "microns"],
+        Dimension::Length,
+        1e-6,
+    ),
+    unit(
+        "mm",
+        &[
+            "mm",
+            "millimeter",
+            "millimeters",
+            "millimetre",
+            "millimetres",
+        ],
+        Dimension::Length,
+        1e-3,
+    ),
+    unit(
+        "cm",
+        &[
+            "cm",
+            "centimeter",
+            "centimeters",
+            "centimetre",
+            "centimetres",
+        ],
+        Dimension::Length,
+        1e-2,
+    ),
+    unit(
+        "m",
+        &["m", "meter", "meters", "metre", "metres"],
+        Dimension::Length,
+        1.0,
+    ),
+    unit(
+        "km",
+        &["km", "kilometer", "kilometers", "kilometre", "kilometres"],
+        Dimension::Length,
+        1e3,
+    ),
+    unit(
+        "in",
+        &["in", "inch", "inches", "\""],
+        Dimension::Length,
+        0.0254,
+    ),
+    unit(
+        "ft",
+        &["ft", "foot", "feet", "'"],
+        Dimension::Length,
+        0.3048,
+    ),
+    unit("yd", &["yd", "yard", "yards"], Dimension::Length, 0.9144),
+    unit("mi", &["mi", "mile", "miles"], Dimension::Length, 1609.344),
+    unit(
+        "nmi",
+        &["nmi", "nautical mile", "nautical miles"],
+        Dimension::Length,
+        1852.0,
+    ),
+    // Mass (kilogram)
+    unit(
+        "mg",
+        &["mg", "milligram", "milligrams"],
+        Dimension::Mass,
+        1e-6,
+    ),
+    unit(
+        "g",
+        &["g", "gram", "grams", "gramme", "grammes"],
+        Dimension::Mass,
+        1e-3,
+    ),
+    unit(
+        "kg",
+        &["kg", "kilo", "kilos", "kilogram", "kilograms"],
+        Dimension::Mass,
+        1.0,
+    ),
+    unit(
+        "t",
+        &["t", "tonne", "tonnes", "metric ton", "metric tons"],
+        Dimension::Mass,
+        1e3,
+    ),
+    unit(
+        "oz",
+        &["oz", "ounce", "ounces"],
+        Dimension::Mass,
+        0.028_349_523_125,
+    ),
+    unit(
+        "lb",
+        &["lb", "lbs", "pound", "pounds"],
+        Dimension::Mass,
+        0.453_592_37,
+    ),
+    unit(
+        "st",
+        &["st", "stone", "stones"],
+        Dimension::Mass,
+        6.350_293_18,
+    ),
+    // Temperature (kelvin)
+    Unit {
+        symbol: "°C",
+        names: &["c", "°c", "celsius", "degc"],
+        dimension: Dimension::Temperature,
+        factor: 1.0,
+        offset: 273.15,
+    },
+    Unit {
+        symbol: "°F",
+        names: &["f", "°f", "fahrenheit", "degf"],
+        dimension: Dimension::Temperature,
+        factor: 5.0 / 9.0,
+        offset: 459.67 * 5.0 / 9.0,
+    },
+    unit(
+        "K",
+        &["k", "°k", "kelvin", "kelvins"],
+        Dimension::Temperature,
+        1.0,
+    ),
+    // Data size (byte); decimal prefixes are powers of 1000, binary of 1024
+    unit("bit", &["bit", "bits"], Dimension::DataSize, 0.125),
+    unit("B", &["b", "byte", "bytes"], Dimension::DataSize, 1.0),
+    unit(
+        "kB",
+        &["kb", "kilobyte", "kilobytes"],
+        Dimension::DataSize,
+        1e3,
+    ),
+    unit(
+        "MB",
+        &["mb", "megabyte", "megabytes"],
+        Dimension::DataSize,
+        1e6,
+    ),
+    unit(
+        "GB",
+        &["gb", "gigabyte", "gigabytes"],
+        Dimension::DataSize,
+        1e9,
+    ),
+    unit(
+        "TB",
+        &["tb", "terabyte", "terabytes"],
+        Dimension::DataSize,
+        1e12,
+    ),
+    unit(
+        "PB",
+        &["pb", "petabyte", "petabytes"],
+        Dimension::DataSize,
+        1e15,
+    ),
+    unit(
+        "KiB",
+        &["kib", "kibibyte", "kibibytes"],
+        Dimension::DataSize,
+        1024.0,
+    ),
+    unit(
+        "MiB",
+        &["mib", "mebibyte", "mebibytes"],
+        Dimension::DataSize,
+        1_048_576.0,
+    ),
+    unit(
+        "GiB",
+        &["gib", "gibibyte", "gibibytes"],
+        Dimension::DataSize,
+        1_073_741_824.0,
+    ),
+    unit(
+        "TiB",
+        &["tib", "tebibyte", "tebibytes"],
+        Dimension::DataSize,
+        1_099_511_627_776.0,
+    ),
+    unit(
+        "kbit",
+        &["kbit", "kilobit", "kilobits"],
+        Dimension::DataSize,
+        125.0,
+    ),
+    unit(
+        "Mbit",
+        &["mbit", "megabit", "megabits"],
+        Dimension::DataSize,
+        125e3,
+    ),
+    unit(
+        "Gbit",
+        &["gbit", "gigabit", "gigabits"],
+        Dimension::DataSize,
+        125e6,
+    ),
+    // Time (second)
+    unit(
+        "ns",
+        &["ns", "nanosecond", "nanoseconds"],
+        Dimension::Time,
+        1e-9,
+    ),
+    unit(
+        "µs",
+        &["µs", "us", "microsecond", "microseconds"],
+        Dimension::Time,
+        1e-6,
+    ),
+    unit(
+        "ms",
+        &["ms", "millisecond", "milliseconds"],
+        Dimension::Time,
+        1e-3,
+    ),
+    unit(
+        "s",
+        &["s", "sec", "secs", "second", "seconds"],
+        Dimension::Time,
+        1.0,
+    ),
+    unit(
+        "min",
+        &["min", "mins", "minute", "minutes"],
+        Dimension::Time,
+        60.0,
+    ),
+    unit(
+        "h",
+        &["h", "hr", "hrs", "hour", "hours"],
+        Dimension::Time,
+        3600.0,
+    ),
+    unit("d", &["d", "day", "days"], Dimension::Time, DAY),
+    unit("wk", &["wk", "week", "weeks"], Dimension::Time, 7.0 * DAY),
+    unit(
+        "mo",
+        &["mo", "month", "months"],
+        Dimension::Time,
+        YEAR / 12.0,
+    ),
+    unit("yr", &["yr", "yrs", "year", "years"], Dimension::Time, YEAR),
+];
+
+/// Words separating the source and target units
+const SEPARATORS: [&str; 6] = ["to", "in", "into", "as", "->", "=>"];
+
+fn find_unit(name: &str) -> Option<&'static Unit> {
+    let name = name.trim().to_lowercase();
+    // `degrees c`, `degrees fahrenheit`
+    let name = name.strip_prefix("degrees ").unwrap_or(&name);
+    UNITS.iter().find(|unit| unit.names.contains(&name))
+}
+
+/// A conversion: the value in the target unit and the units' symbols
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Conversion {
+    pub amount: f64,
+    pub from: &'static str,
+    pub value: f64,
+    pub to: &'static str,
+}
+
+/// Parse and run `<number> <unit> <to|in|as> <unit>`
+pub(crate) fn convert(input: &str) -> Option<Conversion> {
+    let input = input.trim();
+
+    // Split off the leading number (`12in` and `12 in` both work)
+    let number_end = input
+        .char_indices()
+        .find(|(i, c)| {
+            !(c.is_ascii_digit()
+                || *c == '.'
+                || (*i == 0 && matches!(c, '-' | '+'))
+                || *c == ','
+                    && input[..*i]
+                        .chars()
+                        .last()
+                        .is_some_and(|p| p.is_ascii_digit()))
+        })
+        .map(|(i, _)| i)?;
+    let amount: f64 = input[..number_end].replace(',', "").parse().ok()?;
+    let rest = input[number_end..].trim();
+
+    // Try every separator position: `12 in in cm` splits at the second `in`
+    let words: Vec<&str> = rest.split_whitespace().collect();
+    for (idx, word) in words.iter().enumerate().skip(1) {
+        if !SEPARATORS.contains(&word.to_lowercase().as_str()) || idx + 1 >= words.len() {
+            continue;
+        }
+        let (Some(from), Some(to)) = (
+            find_unit(&words[..idx].join(" ")),
+            find_unit(&words[idx + 1..].join(" ")),
+        ) else {
+            continue;
+        };
+        if from.dimension != to.dimension {
+            return None;
+        }
+
+        let base = amount * from.factor + from.offset;
+        let value = (base - to.offset) / to.factor;
+        if !value.is_finite() {
+            return None;
+        }
+        return Some(Conversion {
+            amount,
+            from: from.symbol,
+            value,
+            to: to.symbol,
+        });
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(input: &str) -> Option<(f64, &'static str)> {
+        convert(input).map(|c| ((c.value * 1e6).round() / 1e6, c.to))
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(approx("12 in to cm"), Some((30.48, "cm")));
+        assert_eq!(approx("12 in in cm"), Some((30.48, "cm")));
+        assert_eq!(approx("5 feet in meters"), Some((1.524, "m")));
+        assert_eq!(approx("1 mile to km"), Some((1.609344, "km")));
+        assert_eq!(approx("70 kg in lb"), Some((154.323584, "lb")));
+        assert_eq!(approx("16 oz as lbs"), Some((1.0, "lb")));
+        assert_eq!(approx("100 f in c"), Some((37.777778, "°C")));
+        assert_eq!(approx("-40 °C to °F"), Some((-40.0, "°F")));
+        assert_eq!(approx("0 degrees c to k"), Some((273.15, "K")));
+        assert_eq!(approx("1 gib to mb"), Some((1073.741824, "MB")));
+        assert_eq!(approx("100 mbit to mb"), Some((12.5, "MB")));
+        assert_eq!(approx("90 min in h"), Some((1.5, "h")));
+        assert_eq!(approx("1 year to days"), Some((365.2425, "d")));
+        assert_eq!(approx("1,000 m to km"), Some((1.0, "km")));
+    }
+
+    #[test]
+    fn test_not_conversions() {
+        assert!(convert("12 kg to cm").is_none());
+        assert!(convert("12 apples to oranges").is_none());
+        assert!(convert("how to cook rice").is_none());
+        assert!(convert("12 in").is_none());
+    }
+}
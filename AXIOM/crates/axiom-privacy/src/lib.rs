@@ -13,9 +13,13 @@
 //! - Location: Ask (Per-site)
 //! - Notifications: Deny (Manual)
 //! - WebRTC: Disabled (Global)
+//!
+//! Site boundaries (eTLD+1) come from the Public Suffix List (see `psl`).
 
 mod permissions;
+mod psl;
 mod tracking;
 
 pub use permissions::{Permission, PermissionManager, PermissionState, PermissionType};
+pub use psl::{host_in_domain, PublicSuffixList};
 pub use tracking::{TrackingProtection, TrackingRule};
@@ -130,6 +130,17 @@ impl PermissionManager {
             .remove(&(origin.to_string(), permission_type));
     }
 
+    /// Clear every site permission whose origin matches; returns how many were cleared
+    pub fn clear_site_permissions_where<F>(&mut self, mut matches: F) -> usize
+    where
+        F: FnMut(&str) -> bool,
+    {
+        let before = self.site_permissions.len();
+        self.site_permissions
+            .retain(|(origin, _), _| !matches(origin));
+        before - self.site_permissions.len()
+    }
+
     /// Get all permissions for an origin
     pub fn get_site_permissions(&self, origin: &str) -> Vec<Permission> {
         let mut permissions = Vec::new();
@@ -251,5 +262,9 @@ mod tests {
 
         assert!(manager.is_allowed("https://meet.google.com", PermissionType::Camera));
         assert!(manager.should_prompt("https://other.com", PermissionType::Camera));
+
+        let cleared = manager.clear_site_permissions_where(|origin| origin.contains("google.com"));
+        assert_eq!(cleared, 1);
+        assert!(manager.should_prompt("https://meet.google.com", PermissionType::Camera));
     }
 }
@@ -0,0 +1,242 @@
+//! Public Suffix List
+//!
+//! Decides where a host's registrable part ends: `co.uk`, `github.io` and
+//! `*.kawasaki.jp` are public suffixes, so the site ("eTLD+1") of
+//! `a.b.example.co.uk` is `example.co.uk`. A copy of the list from
+//! <https://publicsuffix.org/list/> is embedded. There is no global list:
+//! whoever needs site boundaries holds the list it uses, so a browser can
+//! install a newer one without affecting anything else in the process.
+//!
+//! Hosts are compared in their ASCII (punycode) form; rules written in Unicode
+//! are converted when the list is parsed.
+
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+const EMBEDDED_LIST: &str = include_str!("../data/public_suffix_list.dat");
+
+#[derive(Debug, Default)]
+pub struct PublicSuffixList {
+    /// `com`, `co.uk`
+    rules: HashSet<String>,
+    /// `kawasaki.jp` for `*.kawasaki.jp`
+    wildcards: HashSet<String>,
+    /// `city.kawasaki.jp` for `!city.kawasaki.jp`
+    exceptions: HashSet<String>,
+}
+
+impl PublicSuffixList {
+    /// Parse the `public_suffix_list.dat` format; unparseable lines are skipped
+    pub fn parse(text: &str) -> Self {
+        let mut list = Self::default();
+
+        for line in text.lines() {
+            // A rule is the first whitespace-delimited token on a line
+            let Some(rule) = line.split_whitespace().next() else {
+                continue;
+            };
+            if rule.starts_with("//") {
+                continue;
+            }
+
+            if let Some(exception) = rule.strip_prefix('!') {
+                if let Some(ascii) = to_ascii(exception) {
+                    list.exceptions.insert(ascii);
+                }
+            } else if let Some(parent) = rule.strip_prefix("*.") {
+                if let Some(ascii) = to_ascii(parent) {
+                    list.wildcards.insert(ascii);
+                }
+            } else if let Some(ascii) = to_ascii(rule) {
+                list.rules.insert(ascii);
+            }
+        }
+
+        list
+    }
+
+    /// The list compiled into the browser
+    pub fn embedded() -> Self {
+        Self::parse(EMBEDDED_LIST)
+    }
+
+    /// The embedded list, parsed once and shared
+    pub fn embedded_shared() -> Arc<Self> {
+        static LIST: OnceLock<Arc<PublicSuffixList>> = OnceLock::new();
+        LIST.get_or_init(|| Arc::new(Self::embedded())).clone()
+    }
+
+    pub fn load_file(path: &Path) -> std::io::Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    /// Number of rules of all kinds
+    pub fn len(&self) -> usize {
+        self.rules.len() + self.wildcards.len() + self.exceptions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Labels in the host's public suffix, and whether a listed rule matched
+    ///
+    /// Unlisted TLDs fall back to the implicit `*` rule (one label, unknown).
+    fn suffix_labels(&self, labels: &[&str]) -> (usize, bool) {
+        for i in 0..labels.len() {
+            let candidate = labels[i..].join(".");
+            if self.exceptions.contains(&candidate) {
+                return (labels.len() - i - 1, true);
+            }
+            if self.rules.contains(&candidate) {
+                return (labels.len() - i, true);
+            }
+            if i + 1 < labels.len() && self.wildcards.contains(&labels[i + 1..].join(".")) {
+                return (labels.len() - i, true);
+            }
+        }
+        (1, false)
+    }
+
+    /// The public suffix of a host (`co.uk` for `www.example.co.uk`)
+    pub fn public_suffix(&self, host: &str) -> Option<String> {
+        let host = normalize_host(host)?;
+        let labels: Vec<&str> = host.split('.').collect();
+        let (count, _) = self.suffix_labels(&labels);
+        Some(labels[labels.len() - count..].join("."))
+    }
+
+    /// True if the host ends in a listed public suffix, not just any last label
+    pub fn has_known_suffix(&self, host: &str) -> bool {
+        let Some(host) = normalize_host(host) else {
+            return false;
+        };
+        let labels: Vec<&str> = host.split('.').collect();
+        self.suffix_labels(&labels).1
+    }
+
+    /// The registrable domain ("eTLD+1"), e.g. `example.co.uk`
+    ///
+    /// `None` for IP addresses, single-label hosts and public suffixes
+    /// themselves.
+    pub fn registrable_domain(&self, host: &str) -> Option<String> {
+        let host = normalize_host(host)?;
+        if host.parse::<IpAddr>().is_ok() {
+            return None;
+        }
+        let labels: Vec<&str> = host.split('.').collect();
+        let (count, _) = self.suffix_labels(&labels);
+        if labels.len() <= count {
+            return None;
+        }
+        Some(labels[labels.len() - count - 1..].join("."))
+    }
+
+    /// The site a host belongs to: its eTLD+1, or the host itself (IPs, `localhost`)
+    pub fn site_for_host(&self, host: &str) -> String {
+        self.registrable_domain(host)
+            .unwrap_or_else(|| host.trim_end_matches('.').to_lowercase())
+    }
+}
+
+/// Lowercase ASCII form without a trailing dot; `None` if not a valid host
+fn normalize_host(host: &str) -> Option<String> {
+    let host = host.trim().trim_end_matches('.');
+    let host = host
+        .strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host);
+    if host.is_empty() || host.split('.').any(str::is_empty) {
+        return None;
+    }
+    to_ascii(host)
+}
+
+fn to_ascii(domain: &str) -> Option<String> {
+    if domain.is_ascii() {
+        return Some(domain.to_ascii_lowercase());
+    }
+    idna::domain_to_ascii(domain).ok()
+}
+
+/// Whether `host` is `domain` or one of its subdomains (case-insensitive)
+pub fn host_in_domain(host: &str, domain: &str) -> bool {
+    let host = host.trim_end_matches('.').to_lowercase();
+    let domain = domain.trim_end_matches('.').to_lowercase();
+    !domain.is_empty()
+        && (host == domain
+            || host
+                .strip_suffix(domain.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registrable_domain() {
+        let list = PublicSuffixList::embedded();
+        assert!(list.len() > 5000);
+
+        assert_eq!(
+            list.registrable_domain("www.example.com").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            list.registrable_domain("a.b.example.co.uk").as_deref(),
+            Some("example.co.uk")
+        );
+        assert_eq!(
+            list.registrable_domain("user.github.io").as_deref(),
+            Some("user.github.io")
+        );
+        assert_eq!(
+            list.public_suffix("example.co.uk").as_deref(),
+            Some("co.uk")
+        );
+        assert!(list.registrable_domain("co.uk").is_none());
+        assert!(list.registrable_domain("127.0.0.1").is_none());
+        assert!(list.registrable_domain("localhost").is_none());
+        assert_eq!(list.site_for_host("LocalHost."), "localhost");
+        assert_eq!(list.site_for_host("mail.example.co.uk"), "example.co.uk");
+
+        // Wildcard and exception rules
+        assert_eq!(
+            list.registrable_domain("a.b.kawasaki.jp").as_deref(),
+            Some("a.b.kawasaki.jp")
+        );
+        assert_eq!(
+            list.registrable_domain("www.city.kawasaki.jp").as_deref(),
+            Some("city.kawasaki.jp")
+        );
+
+        // Unicode hosts and rules compare as punycode
+        assert_eq!(
+            list.registrable_domain("www.пример.рф").as_deref(),
+            Some("xn--e1afmkfd.xn--p1ai")
+        );
+    }
+
+    #[test]
+    fn test_known_suffix() {
+        let list = PublicSuffixList::parse("// comment\ncom\n*.ck\n!www.ck\nphotography\n");
+        assert_eq!(list.len(), 4);
+        assert!(list.has_known_suffix("example.com"));
+        assert!(list.has_known_suffix("studio.photography"));
+        assert!(!list.has_known_suffix("file.txt"));
+        assert_eq!(list.public_suffix("a.b.ck").as_deref(), Some("b.ck"));
+        assert_eq!(list.registrable_domain("www.ck").as_deref(), Some("www.ck"));
+    }
+
+    #[test]
+    fn test_host_in_domain() {
+        assert!(host_in_domain("example.com", "example.com"));
+        assert!(host_in_domain("Mail.Example.com.", "example.com"));
+        assert!(!host_in_domain("notexample.com", "example.com"));
+        assert!(!host_in_domain("example.com", "mail.example.com"));
+        assert!(!host_in_domain("example.com", ""));
+    }
+}
@@ -4,8 +4,11 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::sync::Arc;
 use url::Url;
 
+use crate::psl::PublicSuffixList;
+
 /// Known tracking parameters to strip from URLs
 const TRACKING_PARAMS: &[&str] = &[
     // Google Analytics
@@ -69,6 +72,8 @@ pub struct TrackingProtection {
     strip_params: HashSet<String>,
     /// Whether protection is enabled
     enabled: bool,
+    /// Site boundaries for third-party checks
+    public_suffix_list: Arc<PublicSuffixList>,
 }
 
 impl TrackingProtection {
@@ -106,9 +111,15 @@ impl TrackingProtection {
             allow_domains,
             strip_params,
             enabled: true,
+            public_suffix_list: PublicSuffixList::embedded_shared(),
         }
     }
 
+    /// Use a different public suffix list for third-party checks
+    pub fn set_public_suffix_list(&mut self, list: Arc<PublicSuffixList>) {
+        self.public_suffix_list = list;
+    }
+
     /// Enable or disable protection
     pub fn set_enabled(&mut self, enabled: bool) {
         self.enabled = enabled;
@@ -137,10 +148,11 @@ impl TrackingProtection {
 
     /// Check if a URL should be blocked
     pub fn should_block(&self, url: &str) -> bool {
-        if !self.enabled {
-            return false;
-        }
+        self.enabled && self.matches_blocklist(url)
+    }
 
+    /// Check a URL against the block list, regardless of the enabled flag
+    pub fn matches_blocklist(&self, url: &str) -> bool {
         if self.blocked_domains.is_empty() {
             return false;
         }
@@ -182,6 +194,11 @@ impl TrackingProtection {
             return url.to_string();
         }
 
+        self.strip_tracking_params(url)
+    }
+
+    /// Strip tracking parameters, regardless of the enabled flag
+    pub fn strip_tracking_params(&self, url: &str) -> String {
         match Url::parse(url) {
             Ok(mut parsed) => {
                 let pairs: Vec<(String, String)> = parsed
@@ -208,7 +225,7 @@ impl TrackingProtection {
     }
 
     /// Check if a request is third-party
-    pub fn is_third_party(page_url: &str, request_url: &str) -> bool {
+    pub fn is_third_party(&self, page_url: &str, request_url: &str) -> bool {
         let page = match Url::parse(page_url) {
             Ok(u) => u,
             Err(_) => return false,
@@ -222,21 +239,8 @@ impl TrackingProtection {
         let page_host = page.host_str().unwrap_or("");
         let request_host = request.host_str().unwrap_or("");
 
-        // Extract registrable domain (simplified)
-        fn get_base_domain(host: &str) -> &str {
-            let parts: Vec<&str> = host.split('.').collect();
-            if parts.len() >= 2 {
-                let len = parts.len();
-                // Handle cases like co.uk, com.au (simplified)
-                if parts[len - 1].len() <= 2 && parts.len() >= 3 {
-                    return &host[host.len() - parts[len - 3..].join(".").len()..];
-                }
-                return &host[host.len() - parts[len - 2..].join(".").len()..];
-            }
-            host
-        }
-
-        get_base_domain(page_host) != get_base_domain(request_host)
+        self.public_suffix_list.site_for_host(page_host)
+            != self.public_suffix_list.site_for_host(request_host)
     }
 }
 
@@ -274,14 +278,22 @@ mod tests {
 
     #[test]
     fn test_third_party() {
-        assert!(TrackingProtection::is_third_party(
-            "https://example.com",
-            "https://cdn.other.com/script.js"
-        ));
+        let protection = TrackingProtection::new();
+        assert!(protection.is_third_party("https://example.com", "https://cdn.other.com/script.js"));
 
-        assert!(!TrackingProtection::is_third_party(
-            "https://example.com",
-            "https://cdn.example.com/script.js"
+        assert!(
+            !protection.is_third_party("https://example.com", "https://cdn.example.com/script.js")
+        );
+
+        // Different registrants under the same public suffix
+        assert!(protection.is_third_party(
+            "https://shop.example.co.uk",
+            "https://tracker.co.uk/pixel.gif"
+        ));
+        assert!(!protection.is_third_party(
+            "https://www.example.co.uk",
+            "https://static.example.co.uk/app.js"
         ));
+        assert!(protection.is_third_party("https://alice.github.io", "https://bob.github.io/x.js"));
     }
 }
@@ -0,0 +1,136 @@
+//! Session Archive
+//!
+//! Archived sessions keep their rows in `sessions` and `tabs` untouched so
+//! unarchiving restores them exactly. Their tabs are dropped from memory and
+//! their titles and URLs are copied into `session_archive_index` for search.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use axiom_storage::Database;
+
+use crate::Result;
+
+/// A tab in an archived session matching an archive search
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedTab {
+    pub session_id: String,
+    pub session_name: String,
+    pub tab_id: String,
+    pub title: String,
+    pub url: String,
+    pub archived_at: Option<DateTime<Utc>>,
+}
+
+/// (Re)build the search index rows for a session from its stored tabs
+pub(crate) fn index_session(db: &Database, session_id: &str, session_name: &str) -> Result<usize> {
+    db.transaction(|tx| {
+        tx.execute(
+            "DELETE FROM session_archive_index WHERE session_id = ?1",
+            [session_id],
+        )?;
+
+        let tabs: Vec<(String, String, String)> = {
+            let mut stmt = tx.prepare("SELECT id, title, url FROM tabs WHERE session_id = ?1")?;
+            let rows = stmt
+                .query_map([session_id], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+
+        for (tab_id, title, url) in &tabs {
+            let search_text = format!("{session_name}\n{title}\n{url}").to_lowercase();
+            tx.execute(
+                "INSERT INTO session_archive_index
+                 (session_id, tab_id, session_name, title, url, search_text)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![session_id, tab_id, session_name, title, url, search_text],
+            )?;
+        }
+
+        Ok(tabs.len())
+    })
+    .map_err(Into::into)
+}
+
+pub(crate) fn clear_index(db: &Database, session_id: &str) -> Result<()> {
+    db.with_connection(|conn| {
+        conn.execute(
+            "DELETE FROM session_archive_index WHERE session_id = ?1",
+            [session_id],
+        )?;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+/// Tabs in archived sessions whose session name, title or URL contain every query term
+pub(crate) fn search(db: &Database, query: &str, limit: usize) -> Result<Vec<ArchivedTab>> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("%{}%", escape_like(&term.to_lowercase())))
+        .collect();
+
+    let mut sql = String::from(
+        "SELECT i.session_id, i.session_name, i.tab_id, i.title, i.url, s.archived_at
+         FROM session_archive_index i JOIN sessions s ON s.id = i.session_id
+         WHERE s.archived_at IS NOT NULL",
+    );
+    for _ in &terms {
+        sql.push_str(" AND i.search_text LIKE ? ESCAPE '\\'");
+    }
+    sql.push_str(" ORDER BY s.archived_at DESC, i.title ASC LIMIT ?");
+
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(&sql)?;
+        let mut params: Vec<&dyn rusqlite::ToSql> =
+            terms.iter().map(|t| t as &dyn rusqlite::ToSql).collect();
+        let limit = limit as i64;
+        params.push(&limit);
+
+        let matches = stmt
+            .query_map(params.as_slice(), |row| {
+                let archived_str: Option<String> = row.get(5)?;
+                Ok(ArchivedTab {
+                    session_id: row.get(0)?,
+                    session_name: row.get(1)?,
+                    tab_id: row.get(2)?,
+                    title: row.get(3)?,
+                    url: row.get(4)?,
+                    archived_at: archived_str
+                        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                        .map(|dt| dt.with_timezone(&Utc)),
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(matches)
+    })
+    .map_err(Into::into)
+}
+
+fn escape_like(term: &str) -> String {
+    let mut escaped = String::with_capacity(term.len());
+    for c in term.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("100%_a\\b"), "100\\%\\_a\\\\b");
+        assert_eq!(escape_like("rust"), "rust");
+    }
+}
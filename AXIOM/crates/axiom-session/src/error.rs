@@ -24,4 +24,16 @@ pub enum SessionError {
 
     #[error("Cannot delete the last session")]
     CannotDeleteLastSession,
+
+    #[error("Cannot archive the last session")]
+    CannotArchiveLastSession,
+
+    #[error("Private sessions cannot be archived: {0}")]
+    PrivateSession(String),
+
+    #[error("Session is archived: {0}")]
+    Archived(String),
+
+    #[error("Invalid session import: {0}")]
+    InvalidImport(String),
 }
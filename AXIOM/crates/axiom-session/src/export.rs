@@ -0,0 +1,572 @@
+//! Session export and import
+//!
+//! Sessions can be shared in three formats:
+//! - JSON: full fidelity (tabs, order, titles, groups)
+//! - Markdown: a link list, one heading per group
+//! - OneTab: plain `URL | title` lines, groups separated by blank lines
+//!
+//! Imports only restore web and internal pages (`http`, `https`, `about`,
+//! `axiom`); other entries are skipped and reported.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use axiom_tabs::Tab;
+
+use crate::error::SessionError;
+use crate::session::Session;
+use crate::Result;
+
+/// Current version of the JSON export format
+pub const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionFormat {
+    Json,
+    Markdown,
+    OneTab,
+}
+
+impl SessionFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionFormat::Json => "json",
+            SessionFormat::Markdown => "markdown",
+            SessionFormat::OneTab => "onetab",
+        }
+    }
+}
+
+impl std::fmt::Display for SessionFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for SessionFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(SessionFormat::Json),
+            "markdown" | "md" => Ok(SessionFormat::Markdown),
+            "onetab" => Ok(SessionFormat::OneTab),
+            _ => Err(format!("Unknown session format: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedTab {
+    pub url: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub favicon_url: Option<String>,
+    #[serde(default)]
+    pub scroll_position: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedGroup {
+    /// Group name (`None` for the implicit group of a plain session)
+    #[serde(default)]
+    pub name: Option<String>,
+    pub tabs: Vec<ExportedTab>,
+}
+
+/// An entry left out of an import, with the reason
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedTab {
+    pub url: String,
+    pub reason: String,
+}
+
+/// The session created by an import and the entries it left out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionImport {
+    pub session: Session,
+    pub skipped: Vec<SkippedTab>,
+}
+
+/// Schemes an imported tab may use
+const IMPORTABLE_SCHEMES: &[&str] = &["http", "https", "about", "axiom"];
+
+/// Why `url` cannot be restored into a tab, if it cannot
+pub(crate) fn import_rejection(url: &str) -> Option<String> {
+    let url = url.trim();
+    if url.is_empty() {
+        return Some("Empty URL".to_string());
+    }
+    let scheme = match url.split_once(':') {
+        Some((scheme, _)) if !scheme.is_empty() => scheme.to_ascii_lowercase(),
+        _ => return Some("URL has no scheme".to_string()),
+    };
+    if !IMPORTABLE_SCHEMES.contains(&scheme.as_str()) {
+        return Some(format!("Scheme not allowed: {scheme}"));
+    }
+    if matches!(scheme.as_str(), "http" | "https") {
+        let host = url[scheme.len() + 1..]
+            .strip_prefix("//")
+            .and_then(|rest| rest.split(['/', '?', '#']).next())
+            .unwrap_or("");
+        if host.is_empty() {
+            return Some("URL has no host".to_string());
+        }
+    }
+    None
+}
+
+/// A portable snapshot of a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionExport {
+    pub version: u32,
+    pub name: String,
+    pub exported_at: DateTime<Utc>,
+    pub groups: Vec<ExportedGroup>,
+}
+
+impl SessionExport {
+    /// Build an export from a session and its tabs (already in display order)
+    pub fn from_session(session: &Session, ordered_tabs: &[Tab]) -> Self {
+        // Consecutive tabs of the same group (or of none) form one exported group
+        let mut groups: Vec<ExportedGroup> = Vec::new();
+        for tab in ordered_tabs {
+            let name = session.group_of(&tab.id).map(str::to_string);
+            let exported = ExportedTab {
+                url: tab.url.clone(),
+                title: tab.title.clone(),
+                favicon_url: tab.favicon_url.clone(),
+                scroll_position: tab.scroll_position,
+            };
+            match groups.last_mut() {
+                Some(group) if group.name == name => group.tabs.push(exported),
+                _ => groups.push(ExportedGroup {
+                    name,
+                    tabs: vec![exported],
+                }),
+            }
+        }
+        if groups.is_empty() {
+            groups.push(ExportedGroup {
+                name: None,
+                tabs: Vec::new(),
+            });
+        }
+
+        Self {
+            version: EXPORT_VERSION,
+            name: session.name.clone(),
+            exported_at: Utc::now(),
+            groups,
+        }
+    }
+
+    /// All tabs across groups, in order
+    pub fn tabs(&self) -> impl Iterator<Item = &ExportedTab> {
+        self.groups.iter().flat_map(|g| g.tabs.iter())
+    }
+
+    pub fn tab_count(&self) -> usize {
+        self.groups.iter().map(|g| g.tabs.len()).sum()
+    }
+
+    pub fn render(&self, format: SessionFormat) -> Result<String> {
+        match format {
+            SessionFormat::Json => self.to_json(),
+            SessionFormat::Markdown => Ok(self.to_markdown()),
+            SessionFormat::OneTab => Ok(self.to_onetab()),
+        }
+    }
+
+    pub fn parse(format: SessionFormat, input: &str) -> Result<Self> {
+        let export = match format {
+            SessionFormat::Json => Self::from_json(input)?,
+            SessionFormat::Markdown => Self::from_markdown(input),
+            SessionFormat::OneTab => Self::from_onetab(input),
+        };
+
+        if export.tab_count() == 0 {
+            return Err(SessionError::InvalidImport(
+                "No tabs found in import".to_string(),
+            ));
+        }
+
+        Ok(export)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(input: &str) -> Result<Self> {
+        let export: SessionExport = serde_json::from_str(input)?;
+        if export.version > EXPORT_VERSION {
+            return Err(SessionError::InvalidImport(format!(
+                "Unsupported export version: {}",
+                export.version
+            )));
+        }
+        Ok(export)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# ");
+        out.push_str(&single_line(&self.name));
+        out.push('\n');
+
+        for group in &self.groups {
+            out.push('\n');
+            if let Some(name) = group.name.as_deref() {
+                out.push_str("## ");
+                out.push_str(&single_line(name));
+                out.push_str("\n\n");
+            }
+
+            for tab in &group.tabs {
+                let title = if tab.title.trim().is_empty() {
+                    &tab.url
+                } else {
+                    &tab.title
+                };
+                out.push_str("- [");
+                out.push_str(&escape_markdown_text(&single_line(title)));
+                out.push_str("](");
+                out.push_str(&markdown_destination(&tab.url));
+                out.push_str(")\n");
+            }
+        }
+
+        out
+    }
+
+    pub fn from_markdown(input: &str) -> Self {
+        let mut name = None;
+        let mut groups: Vec<ExportedGroup> = Vec::new();
+        let mut current = ExportedGroup {
+            name: None,
+            tabs: Vec::new(),
+        };
+
+        for line in input.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(heading) = line.strip_prefix("# ") {
+                if name.is_none() {
+                    name = Some(heading.trim().to_string());
+                }
+                continue;
+            }
+
+            if let Some(heading) = line.strip_prefix("## ") {
+                if !current.tabs.is_empty() || current.name.is_some() {
+                    groups.push(std::mem::replace(
+                        &mut current,
+                        ExportedGroup {
+                            name: None,
+                            tabs: Vec::new(),
+                        },
+                    ));
+                }
+                current.name = Some(heading.trim().to_string());
+                continue;
+            }
+
+            let item = line
+                .strip_prefix("- ")
+                .or_else(|| line.strip_prefix("* "))
+                .or_else(|| line.strip_prefix("+ "))
+                .unwrap_or(line)
+                .trim();
+
+            if let Some((title, url)) = parse_markdown_link(item) {
+                current.tabs.push(ExportedTab {
+                    url,
+                    title,
+                    favicon_url: None,
+                    scroll_position: 0,
+                });
+            } else if let Some(url) = parse_bare_url(item) {
+                current.tabs.push(ExportedTab {
+                    url,
+                    title: String::new(),
+                    favicon_url: None,
+                    scroll_position: 0,
+                });
+            }
+        }
+
+        if !current.tabs.is_empty() || current.name.is_some() {
+            groups.push(current);
+        }
+        groups.retain(|g| !g.tabs.is_empty());
+
+        Self {
+            version: EXPORT_VERSION,
+            name: name.unwrap_or_default(),
+            exported_at: Utc::now(),
+            groups,
+        }
+    }
+
+    pub fn to_onetab(&self) -> String {
+        let mut out = String::new();
+        for (i, group) in self.groups.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            for tab in &group.tabs {
+                out.push_str(&tab.url);
+                out.push_str(" | ");
+                out.push_str(&single_line(&tab.title));
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    pub fn from_onetab(input: &str) -> Self {
+        let mut groups: Vec<ExportedGroup> = Vec::new();
+        let mut current: Vec<ExportedTab> = Vec::new();
+
+        for line in input.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                if !current.is_empty() {
+                    groups.push(ExportedGroup {
+                        name: None,
+                        tabs: std::mem::take(&mut current),
+                    });
+                }
+                continue;
+            }
+
+            let (url, title) = match line.split_once(" | ") {
+                Some((url, title)) => (url.trim(), title.trim()),
+                None => (line.trim_end_matches(" |").trim(), ""),
+            };
+
+            if url.is_empty() || url.contains(char::is_whitespace) {
+                continue;
+            }
+
+            current.push(ExportedTab {
+                url: url.to_string(),
+                title: title.to_string(),
+                favicon_url: None,
+                scroll_position: 0,
+            });
+        }
+
+        if !current.is_empty() {
+            groups.push(ExportedGroup {
+                name: None,
+                tabs: current,
+            });
+        }
+
+        Self {
+            version: EXPORT_VERSION,
+            name: String::new(),
+            exported_at: Utc::now(),
+            groups,
+        }
+    }
+}
+
+fn single_line(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn escape_markdown_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        if matches!(ch, '[' | ']' | '\\') {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+fn markdown_destination(url: &str) -> String {
+    if url.contains(|c: char| c.is_whitespace() || c == '(' || c == ')' || c == '<' || c == '>') {
+        format!("<{}>", url.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        url.to_string()
+    }
+}
+
+/// Parse `[title](url)` or `[title](<url>)`, honouring backslash escapes in the title
+fn parse_markdown_link(item: &str) -> Option<(String, String)> {
+    let rest = item.strip_prefix('[')?;
+
+    let mut title = String::new();
+    let mut chars = rest.char_indices();
+    let mut close = None;
+    while let Some((i, ch)) = chars.next() {
+        match ch {
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    title.push(escaped);
+                }
+            }
+            ']' => {
+                close = Some(i);
+                break;
+            }
+            _ => title.push(ch),
+        }
+    }
+
+    let after = rest.get(close? + 1..)?.strip_prefix('(')?;
+    let url = if let Some(angled) = after.strip_prefix('<') {
+        angled.split_once('>')?.0
+    } else {
+        let end = after.rfind(')')?;
+        after[..end].split_whitespace().next()?
+    };
+
+    let url = url.trim();
+    if url.is_empty() {
+        return None;
+    }
+
+    Some((title.trim().to_string(), url.to_string()))
+}
+
+fn parse_bare_url(item: &str) -> Option<String> {
+    let item = item
+        .strip_prefix('<')
+        .and_then(|s| s.strip_suffix('>'))
+        .unwrap_or(item);
+    if item.contains(char::is_whitespace) {
+        return None;
+    }
+    let has_scheme = item
+        .split_once("://")
+        .map(|(scheme, rest)| {
+            !scheme.is_empty()
+                && !rest.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '+')
+        })
+        .unwrap_or(false);
+    (has_scheme || item.starts_with("about:")).then(|| item.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> SessionExport {
+        SessionExport {
+            version: EXPORT_VERSION,
+            name: "Research".to_string(),
+            exported_at: Utc::now(),
+            groups: vec![
+                ExportedGroup {
+                    name: None,
+                    tabs: vec![
+                        ExportedTab {
+                            url: "https://www.rust-lang.org/".to_string(),
+                            title: "Rust [lang]".to_string(),
+                            favicon_url: Some("https://www.rust-lang.org/favicon.ico".to_string()),
+                            scroll_position: 120,
+                        },
+                        ExportedTab {
+                            url: "https://en.wikipedia.org/wiki/Tokio_(software)".to_string(),
+                            title: "Tokio | Wikipedia".to_string(),
+                            favicon_url: None,
+                            scroll_position: 0,
+                        },
+                    ],
+                },
+                ExportedGroup {
+                    name: Some("Reading".to_string()),
+                    tabs: vec![ExportedTab {
+                        url: "https://example.com/a".to_string(),
+                        title: String::new(),
+                        favicon_url: None,
+                        scroll_position: 0,
+                    }],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let export = sample();
+        let json = export.to_json().unwrap();
+        let parsed = SessionExport::parse(SessionFormat::Json, &json).unwrap();
+        assert_eq!(parsed, export);
+    }
+
+    #[test]
+    fn test_markdown_round_trip() {
+        let markdown = sample().to_markdown();
+        let parsed = SessionExport::parse(SessionFormat::Markdown, &markdown).unwrap();
+
+        assert_eq!(parsed.name, "Research");
+        assert_eq!(parsed.groups.len(), 2);
+        assert_eq!(parsed.groups[1].name.as_deref(), Some("Reading"));
+
+        let tabs: Vec<_> = parsed.tabs().collect();
+        assert_eq!(tabs.len(), 3);
+        assert_eq!(tabs[0].title, "Rust [lang]");
+        assert_eq!(
+            tabs[1].url,
+            "https://en.wikipedia.org/wiki/Tokio_(software)"
+        );
+        // Untitled tabs fall back to the URL as link text
+        assert_eq!(tabs[2].title, "https://example.com/a");
+    }
+
+    #[test]
+    fn test_onetab_round_trip() {
+        let onetab = sample().to_onetab();
+        let parsed = SessionExport::parse(SessionFormat::OneTab, &onetab).unwrap();
+
+        assert_eq!(parsed.groups.len(), 2);
+        let tabs: Vec<_> = parsed.tabs().collect();
+        assert_eq!(tabs[1].title, "Tokio | Wikipedia");
+        assert_eq!(tabs[2].url, "https://example.com/a");
+        assert!(tabs[2].title.is_empty());
+    }
+
+    #[test]
+    fn test_import_rejection() {
+        for url in [
+            "https://example.com/",
+            "HTTP://example.com",
+            "about:blank",
+            "axiom://settings",
+        ] {
+            assert_eq!(import_rejection(url), None, "{url}");
+        }
+        for url in [
+            "javascript:alert(1)",
+            "file:///etc/passwd",
+            "data:text/html,<script>alert(1)</script>",
+            "https://",
+            "example.com",
+            "",
+        ] {
+            assert!(import_rejection(url).is_some(), "{url}");
+        }
+    }
+
+    #[test]
+    fn test_empty_import_rejected() {
+        assert!(SessionExport::parse(SessionFormat::OneTab, "\n\n").is_err());
+        assert!(SessionExport::parse(SessionFormat::Markdown, "# Nothing here").is_err());
+        assert!(SessionExport::parse(SessionFormat::Json, "not json").is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! Session Journal
+//!
+//! Crash detection for session state. A "running" marker is written before
+//! the first mutation of a run and replaced on clean shutdown; finding it on
+//! startup means the previous run exited uncleanly. A last-known-good copy of
+//! every session is kept alongside so it can be offered back to the user.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use axiom_storage::Database;
+
+use crate::export::SessionExport;
+use crate::Result;
+
+const JOURNAL_SETTING: &str = "session_journal_state";
+const LAST_KNOWN_GOOD_SETTING: &str = "session_last_known_good";
+
+const STATE_RUNNING: &str = "running";
+const STATE_CLEAN: &str = "clean";
+
+/// What startup found after an unclean exit
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryInfo {
+    /// Tab ids dropped from `tab_order` because the tab was never saved
+    pub dangling_tab_ids: usize,
+    /// Saved tabs that were missing from `tab_order` and got appended
+    pub adopted_tabs: usize,
+    /// When the last-known-good snapshot was taken (last clean shutdown)
+    pub last_known_good_at: Option<DateTime<Utc>>,
+    /// Number of tabs in the last-known-good snapshot
+    pub last_known_good_tabs: usize,
+}
+
+/// Snapshot of all sessions written on clean shutdown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct LastKnownGood {
+    pub saved_at: DateTime<Utc>,
+    pub sessions: Vec<SessionExport>,
+}
+
+impl LastKnownGood {
+    pub fn tab_count(&self) -> usize {
+        self.sessions.iter().map(SessionExport::tab_count).sum()
+    }
+}
+
+/// Mark the start of a run; returns true if the previous run did not shut down cleanly
+pub(crate) fn begin_run(db: &Database) -> Result<bool> {
+    let previous = db.get_setting(JOURNAL_SETTING)?;
+    db.set_setting(JOURNAL_SETTING, STATE_RUNNING)?;
+    Ok(previous.as_deref() == Some(STATE_RUNNING))
+}
+
+/// Record a clean shutdown together with the last-known-good snapshot
+pub(crate) fn end_run(db: &Database, snapshot: &LastKnownGood) -> Result<()> {
+    save_last_known_good(db, snapshot)?;
+    db.set_setting(JOURNAL_SETTING, STATE_CLEAN)?;
+    Ok(())
+}
+
+pub(crate) fn save_last_known_good(db: &Database, snapshot: &LastKnownGood) -> Result<()> {
+    let json = serde_json::to_string(snapshot)?;
+    db.set_setting(LAST_KNOWN_GOOD_SETTING, &json)?;
+    Ok(())
+}
+
+pub(crate) fn load_last_known_good(db: &Database) -> Result<Option<LastKnownGood>> {
+    Ok(db
+        .get_setting(LAST_KNOWN_GOOD_SETTING)?
+        .and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+/// Reconcile a session's `tab_order` with the tab ids actually stored for it
+///
+/// `stored` must be in creation order. Returns the repaired order plus the
+/// number of dangling ids dropped and orphaned tabs adopted.
+pub(crate) fn reconcile_tab_order(
+    tab_order: &[String],
+    stored: &[String],
+) -> (Vec<String>, usize, usize) {
+    let mut repaired: Vec<String> = Vec::with_capacity(stored.len());
+    let mut dangling = 0;
+
+    for id in tab_order {
+        if stored.contains(id) && !repaired.contains(id) {
+            repaired.push(id.clone());
+        } else {
+            dangling += 1;
+        }
+    }
+
+    let mut adopted = 0;
+    for id in stored {
+        if !repaired.contains(id) {
+            repaired.push(id.clone());
+            adopted += 1;
+        }
+    }
+
+    (repaired, dangling, adopted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconcile_tab_order() {
+        let ids = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let (order, dangling, adopted) =
+            reconcile_tab_order(&ids(&["b", "ghost", "a", "b"]), &ids(&["a", "b", "c"]));
+        assert_eq!(order, ids(&["b", "a", "c"]));
+        assert_eq!(dangling, 2);
+        assert_eq!(adopted, 1);
+
+        let (order, dangling, adopted) = reconcile_tab_order(&ids(&["a"]), &ids(&["a"]));
+        assert_eq!(order, ids(&["a"]));
+        assert_eq!((dangling, adopted), (0, 0));
+    }
+
+    #[test]
+    fn test_unclean_run_detected() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(!begin_run(&db).unwrap());
+        // Never ended: the next run sees the running marker
+        assert!(begin_run(&db).unwrap());
+
+        let snapshot = LastKnownGood {
+            saved_at: Utc::now(),
+            sessions: Vec::new(),
+        };
+        end_run(&db, &snapshot).unwrap();
+        assert!(!begin_run(&db).unwrap());
+        assert!(load_last_known_good(&db).unwrap().is_some());
+    }
+}
@@ -7,12 +7,20 @@
 //! - Browser crash must restore last session exactly
 //! - Sessions are local-only (no cross-device sync)
 
+mod archive;
 mod error;
+mod export;
+mod journal;
 mod manager;
 mod session;
 
+pub use archive::ArchivedTab;
 pub use error::SessionError;
+pub use export::{
+    ExportedGroup, ExportedTab, SessionExport, SessionFormat, SessionImport, SkippedTab,
+};
+pub use journal::RecoveryInfo;
 pub use manager::SessionManager;
-pub use session::Session;
+pub use session::{Session, TabGroup};
 
 pub type Result<T> = std::result::Result<T, SessionError>;
@@ -9,12 +9,18 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use axiom_storage::Database;
-use axiom_tabs::{Tab, TabManager};
+use axiom_tabs::{Tab, TabManager, TabState};
 
+use crate::archive::{self, ArchivedTab};
 use crate::error::SessionError;
-use crate::session::Session;
+use crate::export::{import_rejection, SessionExport, SessionFormat, SessionImport, SkippedTab};
+use crate::journal::{self, LastKnownGood, RecoveryInfo};
+use crate::session::{Session, TabGroup};
 use crate::Result;
 
+/// Settings key for how many recently used tabs are restored eagerly
+const EAGER_RESTORE_SETTING: &str = "session_restore_eager_tabs";
+
 pub struct SessionManager {
     /// In-memory session cache
     sessions: Arc<RwLock<HashMap<String, Session>>>,
@@ -24,6 +30,8 @@ pub struct SessionManager {
     db: Database,
     /// Tab manager
     tab_manager: TabManager,
+    /// Pending crash recovery offer (set when the previous run exited uncleanly)
+    recovery: Arc<RwLock<Option<RecoveryInfo>>>,
 }
 
 impl SessionManager {
@@ -35,14 +43,35 @@ impl SessionManager {
             active_session_id: Arc::new(RwLock::new(None)),
             db,
             tab_manager,
+            recovery: Arc::new(RwLock::new(None)),
         }
     }
 
     /// Initialize and load sessions from database
     /// Returns the active session or creates a default one
     pub fn initialize(&self) -> Result<Session> {
-        // Load all sessions
-        let sessions = self.load_all_sessions()?;
+        // Write-ahead marker: stays "running" until a clean shutdown
+        let unclean_shutdown = journal::begin_run(&self.db)?;
+
+        // Load all sessions and repair tab_order against the tabs table
+        self.load_all_sessions()?;
+        let (dangling_tab_ids, adopted_tabs) = self.reconcile_sessions()?;
+        let sessions = self.list_sessions();
+
+        if unclean_shutdown {
+            let last_known_good = journal::load_last_known_good(&self.db)?;
+            tracing::warn!(
+                dangling_tab_ids,
+                adopted_tabs,
+                "Previous run did not shut down cleanly"
+            );
+            *self.recovery.write() = Some(RecoveryInfo {
+                dangling_tab_ids,
+                adopted_tabs,
+                last_known_good_at: last_known_good.as_ref().map(|s| s.saved_at),
+                last_known_good_tabs: last_known_good.as_ref().map_or(0, |s| s.tab_count()),
+            });
+        }
 
         // Find active session or create default
         let active_session = sessions
@@ -61,8 +90,8 @@ impl SessionManager {
         // Set as active
         *self.active_session_id.write() = Some(active_session.id.clone());
 
-        // Load tabs for active session
-        self.tab_manager.load_session_tabs(&active_session.id)?;
+        // Load tabs for active session (non-active tabs stay unloaded)
+        self.restore_session_tabs(&active_session.id)?;
 
         tracing::info!(
             session_id = %active_session.id,
@@ -78,7 +107,9 @@ impl SessionManager {
     fn load_all_sessions(&self) -> Result<Vec<Session>> {
         let sessions = self.db.with_connection(|conn| {
             let mut stmt = conn.prepare(
-                "SELECT id, name, created_at, updated_at, is_active, tab_order FROM sessions",
+                "SELECT id, name, created_at, updated_at, is_active, tab_order, isolated,
+                        archived_at, tab_groups
+                 FROM sessions",
             )?;
 
             let sessions: Vec<Session> = stmt
@@ -97,6 +128,11 @@ impl SessionManager {
                     let updated_at = DateTime::parse_from_rfc3339(&updated_str)
                         .map(|dt| dt.with_timezone(&Utc))
                         .unwrap_or_else(|_| Utc::now());
+                    let archived_str: Option<String> = row.get(7)?;
+                    let archived_at = archived_str
+                        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                        .map(|dt| dt.with_timezone(&Utc));
+                    let tab_groups_json: String = row.get(8)?;
 
                     Ok(Session {
                         id: row.get(0)?,
@@ -105,6 +141,10 @@ impl SessionManager {
                         updated_at,
                         is_active: row.get::<_, i32>(4)? != 0,
                         tab_order,
+                        isolated: row.get::<_, i32>(6)? != 0,
+                        archived_at,
+                        private: false,
+                        tab_groups: serde_json::from_str(&tab_groups_json).unwrap_or_default(),
                     })
                 })?
                 .filter_map(|r| r.ok())
@@ -124,15 +164,170 @@ impl SessionManager {
         Ok(sessions)
     }
 
+    /// Drop dangling tab ids from every session's `tab_order` and adopt orphaned tabs
+    ///
+    /// Returns the total number of dangling ids dropped and tabs adopted.
+    fn reconcile_sessions(&self) -> Result<(usize, usize)> {
+        let stored: Vec<(String, String)> = self.db.with_connection(|conn| {
+            let mut stmt =
+                conn.prepare("SELECT session_id, id FROM tabs ORDER BY created_at ASC")?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(rows)
+        })?;
+
+        let mut stored_by_session: HashMap<&str, Vec<String>> = HashMap::new();
+        for (session_id, tab_id) in &stored {
+            stored_by_session
+                .entry(session_id.as_str())
+                .or_default()
+                .push(tab_id.clone());
+        }
+
+        let mut total_dangling = 0;
+        let mut total_adopted = 0;
+        for mut session in self.list_sessions() {
+            let tab_ids = stored_by_session
+                .get(session.id.as_str())
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let (tab_order, dangling, adopted) =
+                journal::reconcile_tab_order(&session.tab_order, tab_ids);
+            if dangling == 0 && adopted == 0 {
+                continue;
+            }
+
+            tracing::warn!(
+                session_id = %session.id,
+                dangling,
+                adopted,
+                "Reconciled session tab order"
+            );
+            session.tab_order = tab_order;
+            self.save_session(&session)?;
+            total_dangling += dangling;
+            total_adopted += adopted;
+        }
+
+        Ok((total_dangling, total_adopted))
+    }
+
+    /// Crash recovery offer for this run, if the previous run exited uncleanly
+    pub fn recovery_info(&self) -> Option<RecoveryInfo> {
+        self.recovery.read().clone()
+    }
+
+    /// Decline the crash recovery offer
+    pub fn dismiss_recovery(&self) {
+        *self.recovery.write() = None;
+    }
+
+    /// Re-open the last-known-good sessions as new sessions (tabs start `Discarded`)
+    pub fn restore_last_known_good(&self) -> Result<Vec<Session>> {
+        let snapshot = journal::load_last_known_good(&self.db)?;
+        *self.recovery.write() = None;
+
+        let mut restored = Vec::new();
+        for export in snapshot.map(|s| s.sessions).unwrap_or_default() {
+            if export.tab_count() == 0 {
+                continue;
+            }
+            let name = format!("{} (recovered)", export.name);
+            let (session, _) = self.create_session_from_export(&export, Some(name))?;
+            restored.push(session);
+        }
+
+        tracing::info!(count = restored.len(), "Restored last-known-good sessions");
+
+        Ok(restored)
+    }
+
+    /// Drop every URL matching `forget` from the last-known-good snapshot
+    ///
+    /// Tabs showing such a URL are removed (and groups left empty with them);
+    /// other tabs lose a matching favicon URL. Returns how many tabs were
+    /// removed.
+    pub fn forget_in_last_known_good(&self, forget: impl Fn(&str) -> bool) -> Result<usize> {
+        let Some(mut snapshot) = journal::load_last_known_good(&self.db)? else {
+            return Ok(0);
+        };
+
+        let before = snapshot.tab_count();
+        let mut changed = false;
+        for export in &mut snapshot.sessions {
+            for group in &mut export.groups {
+                group.tabs.retain(|tab| !forget(&tab.url));
+                for tab in &mut group.tabs {
+                    if tab.favicon_url.as_deref().is_some_and(&forget) {
+                        tab.favicon_url = None;
+                        changed = true;
+                    }
+                }
+            }
+            export.groups.retain(|group| !group.tabs.is_empty());
+        }
+        let removed = before - snapshot.tab_count();
+
+        if removed > 0 || changed {
+            journal::save_last_known_good(&self.db, &snapshot)?;
+        }
+        Ok(removed)
+    }
+
+    /// Snapshot every session as last-known-good and mark the run as cleanly shut down
+    pub fn record_clean_shutdown(&self) -> Result<()> {
+        let mut sessions = Vec::new();
+        for session in self.list_sessions().into_iter().filter(|s| !s.private) {
+            self.tab_manager.load_session_tabs(&session.id)?;
+            let tabs = self.get_ordered_tabs_for_session(&session.id)?;
+            sessions.push(SessionExport::from_session(&session, &tabs));
+        }
+
+        journal::end_run(
+            &self.db,
+            &LastKnownGood {
+                saved_at: Utc::now(),
+                sessions,
+            },
+        )?;
+
+        tracing::info!("Recorded clean shutdown");
+
+        Ok(())
+    }
+
     /// Save session to database (auto-save on mutation)
+    ///
+    /// Private sessions are only kept in the cache.
     fn save_session(&self, session: &Session) -> Result<()> {
+        if session.private {
+            self.sessions
+                .write()
+                .insert(session.id.clone(), session.clone());
+            return Ok(());
+        }
+
         let tab_order_json = serde_json::to_string(&session.tab_order)?;
+        let tab_groups_json = serde_json::to_string(&session.tab_groups)?;
 
         self.db.with_connection(|conn| {
             conn.execute(
-                "INSERT OR REPLACE INTO sessions
-                 (id, name, created_at, updated_at, is_active, tab_order)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                // Upsert rather than REPLACE: a REPLACE deletes the row first,
+                // which cascades to the session's tabs.
+                "INSERT INTO sessions
+                 (id, name, created_at, updated_at, is_active, tab_order, isolated, archived_at,
+                  tab_groups)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                 ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    updated_at = excluded.updated_at,
+                    is_active = excluded.is_active,
+                    tab_order = excluded.tab_order,
+                    isolated = excluded.isolated,
+                    archived_at = excluded.archived_at,
+                    tab_groups = excluded.tab_groups",
                 rusqlite::params![
                     session.id,
                     session.name,
@@ -140,6 +335,9 @@ impl SessionManager {
                     session.updated_at.to_rfc3339(),
                     session.is_active as i32,
                     tab_order_json,
+                    session.isolated as i32,
+                    session.archived_at.map(|dt| dt.to_rfc3339()),
+                    tab_groups_json,
                 ],
             )?;
             Ok(())
@@ -194,14 +392,27 @@ impl SessionManager {
         Ok(session)
     }
 
-    /// Switch to a different session
-    pub fn switch_session(&self, session_id: &str) -> Result<Session> {
-        // Deactivate current session
-        if let Ok(mut current) = self.active_session() {
-            current.is_active = false;
-            self.save_session(&current)?;
+    /// Create a private session: it, its tabs and its history exist in memory only
+    ///
+    /// Private sessions always get their own storage partition.
+    pub fn create_private_session(&self, name: String) -> Result<Session> {
+        if name.trim().is_empty() {
+            return Err(SessionError::EmptyName);
         }
 
+        let mut session = Session::new(name);
+        session.private = true;
+        session.isolated = true;
+        self.tab_manager.set_session_memory_only(&session.id, true);
+        self.save_session(&session)?;
+
+        tracing::info!(session_id = %session.id, "Created private session");
+
+        Ok(session)
+    }
+
+    /// Switch to a different session
+    pub fn switch_session(&self, session_id: &str) -> Result<Session> {
         // Activate new session
         let mut session = self
             .sessions
@@ -209,13 +420,37 @@ impl SessionManager {
             .get(session_id)
             .cloned()
             .ok_or_else(|| SessionError::NotFound(session_id.to_string()))?;
+        if session.is_archived() {
+            return Err(SessionError::Archived(session.id));
+        }
+
+        // Deactivate current session. Entering a private session leaves the
+        // stored active flag alone, so the next launch reopens the last
+        // persistent session.
+        if let Ok(mut current) = self.active_session() {
+            current.is_active = false;
+            if session.private {
+                self.sessions.write().insert(current.id.clone(), current);
+            } else {
+                self.save_session(&current)?;
+            }
+        }
 
         session.is_active = true;
         self.save_session(&session)?;
+        if !session.private {
+            self.db.with_connection(|conn| {
+                conn.execute(
+                    "UPDATE sessions SET is_active = 0 WHERE id != ?1",
+                    [&session.id],
+                )?;
+                Ok(())
+            })?;
+        }
         *self.active_session_id.write() = Some(session.id.clone());
 
-        // Load tabs for new session
-        self.tab_manager.load_session_tabs(&session.id)?;
+        // Load tabs for new session (non-active tabs stay unloaded)
+        self.restore_session_tabs(&session.id)?;
 
         tracing::info!(
             session_id = %session.id,
@@ -226,9 +461,101 @@ impl SessionManager {
         Ok(session)
     }
 
-    /// Get all sessions
+    /// Get all sessions that are not archived
     pub fn list_sessions(&self) -> Vec<Session> {
-        self.sessions.read().values().cloned().collect()
+        self.sessions
+            .read()
+            .values()
+            .filter(|s| !s.is_archived())
+            .cloned()
+            .collect()
+    }
+
+    /// Rebuild an archived session's search index after its tabs were edited
+    ///
+    /// The session's tabs are dropped from memory again. Returns how many tabs
+    /// were indexed.
+    pub fn reindex_archived_session(&self, session_id: &str) -> Result<usize> {
+        let session = self.get_session(session_id)?;
+        let indexed = archive::index_session(&self.db, &session.id, &session.name)?;
+        self.tab_manager.unload_session_tabs(&session.id);
+        Ok(indexed)
+    }
+
+    /// Archived sessions, most recently archived first
+    pub fn list_archived_sessions(&self) -> Vec<Session> {
+        let mut sessions: Vec<Session> = self
+            .sessions
+            .read()
+            .values()
+            .filter(|s| s.is_archived())
+            .cloned()
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.archived_at));
+        sessions
+    }
+
+    /// Move a session to the archive
+    ///
+    /// The session and its tabs stay in the database unchanged; the tabs are
+    /// dropped from memory and indexed for `search_archive`. Archiving the
+    /// active session switches to another one first.
+    pub fn archive_session(&self, session_id: &str) -> Result<Session> {
+        let mut session = self.get_session(session_id)?;
+        if session.is_archived() {
+            return Ok(session);
+        }
+        if session.private {
+            return Err(SessionError::PrivateSession(session.id));
+        }
+
+        // Private sessions are wiped on exit, so they cannot be the one left behind
+        let fallback = self
+            .list_sessions()
+            .into_iter()
+            .find(|s| s.id != session_id && !s.private)
+            .ok_or(SessionError::CannotArchiveLastSession)?;
+
+        if self.active_session_id.read().as_deref() == Some(session_id) {
+            self.switch_session(&fallback.id)?;
+            session = self.get_session(session_id)?;
+        }
+
+        let indexed = archive::index_session(&self.db, &session.id, &session.name)?;
+        session.is_active = false;
+        session.archived_at = Some(Utc::now());
+        self.save_session(&session)?;
+        self.tab_manager.unload_session_tabs(&session.id);
+
+        tracing::info!(
+            session_id = %session.id,
+            session_name = %session.name,
+            indexed,
+            "Archived session"
+        );
+
+        Ok(session)
+    }
+
+    /// Return an archived session to the session list exactly as it was archived
+    pub fn unarchive_session(&self, session_id: &str) -> Result<Session> {
+        let mut session = self.get_session(session_id)?;
+        if !session.is_archived() {
+            return Ok(session);
+        }
+
+        session.archived_at = None;
+        self.save_session(&session)?;
+        archive::clear_index(&self.db, &session.id)?;
+
+        tracing::info!(session_id = %session.id, "Unarchived session");
+
+        Ok(session)
+    }
+
+    /// Search archived tab titles, URLs and session names
+    pub fn search_archive(&self, query: &str, limit: usize) -> Result<Vec<ArchivedTab>> {
+        archive::search(&self.db, query, limit)
     }
 
     /// Rename a session
@@ -246,14 +573,29 @@ impl SessionManager {
 
         session.rename(name);
         self.save_session(&session)?;
+        if session.is_archived() {
+            archive::index_session(&self.db, &session.id, &session.name)?;
+        }
+
+        Ok(session)
+    }
+
+    /// Toggle per-session storage isolation (applies to webviews created afterwards)
+    pub fn set_session_isolated(&self, session_id: &str, isolated: bool) -> Result<Session> {
+        let mut session = self.get_session(session_id)?;
+        session.isolated = isolated;
+        session.updated_at = Utc::now();
+        self.save_session(&session)?;
+
+        tracing::info!(session_id = %session_id, isolated, "Updated session isolation");
 
         Ok(session)
     }
 
     /// Delete a session (cannot delete the last session)
     pub fn delete_session(&self, session_id: &str) -> Result<()> {
-        let session_count = self.sessions.read().len();
-        if session_count <= 1 {
+        let session = self.get_session(session_id)?;
+        if !session.is_archived() && self.list_sessions().len() <= 1 {
             return Err(SessionError::CannotDeleteLastSession);
         }
 
@@ -263,11 +605,9 @@ impl SessionManager {
             if active_id.as_deref() == Some(session_id) {
                 // Find another session to switch to
                 if let Some(other_session) = self
-                    .sessions
-                    .read()
-                    .values()
+                    .list_sessions()
+                    .into_iter()
                     .find(|s| s.id != session_id)
-                    .cloned()
                 {
                     self.switch_session(&other_session.id)?;
                 }
@@ -275,13 +615,17 @@ impl SessionManager {
         }
 
         // Delete from database (cascades to tabs)
-        self.db.with_connection(|conn| {
-            conn.execute("DELETE FROM sessions WHERE id = ?1", [session_id])?;
-            Ok(())
-        })?;
+        if !session.private {
+            self.db.with_connection(|conn| {
+                conn.execute("DELETE FROM sessions WHERE id = ?1", [session_id])?;
+                Ok(())
+            })?;
+        }
 
         // Remove from cache
         self.sessions.write().remove(session_id);
+        self.tab_manager.unload_session_tabs(session_id);
+        self.tab_manager.set_session_memory_only(session_id, false);
 
         tracing::info!(session_id = %session_id, "Deleted session");
 
@@ -298,6 +642,69 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Number of most recently used tabs (besides the active one) kept loaded on restore
+    pub fn eager_restore_count(&self) -> Result<usize> {
+        Ok(self
+            .db
+            .get_setting(EAGER_RESTORE_SETTING)?
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(0))
+    }
+
+    pub fn set_eager_restore_count(&self, count: usize) -> Result<()> {
+        self.db
+            .set_setting(EAGER_RESTORE_SETTING, &count.to_string())?;
+        Ok(())
+    }
+
+    /// Load a session's tabs for display, keeping only the active tab materialized
+    ///
+    /// The active tab (or the first tab when none is active) and the
+    /// `eager_restore_count` most recently accessed tabs are kept; every other
+    /// tab is marked `Discarded` so no webview is created until it is clicked.
+    pub fn restore_session_tabs(&self, session_id: &str) -> Result<Vec<Tab>> {
+        self.tab_manager.load_session_tabs(session_id)?;
+        let eager_count = self.eager_restore_count()?;
+        let ordered = self.get_ordered_tabs_for_session(session_id)?;
+
+        let active_id = ordered
+            .iter()
+            .find(|t| t.state == TabState::Active)
+            .or_else(|| ordered.first())
+            .map(|t| t.id.clone());
+
+        let mut rest: Vec<&Tab> = ordered
+            .iter()
+            .filter(|t| Some(&t.id) != active_id.as_ref())
+            .collect();
+        rest.sort_by_key(|t| std::cmp::Reverse(t.last_accessed_at));
+
+        let mut changed = Vec::new();
+        for (index, tab) in rest.into_iter().enumerate() {
+            let mut tab = tab.clone();
+            let before = tab.state;
+            if index < eager_count {
+                // Also tabs a smaller eager count discarded on an earlier restore
+                tab.wake()?;
+            } else {
+                tab.discard()?;
+            }
+            if tab.state != before {
+                changed.push(tab);
+            }
+        }
+        self.tab_manager.insert_tabs(&changed)?;
+
+        tracing::info!(
+            session_id = %session_id,
+            tab_count = ordered.len(),
+            discarded = changed.iter().filter(|t| t.state.is_discarded()).count(),
+            "Restored session tabs"
+        );
+
+        self.get_ordered_tabs_for_session(session_id)
+    }
+
     pub fn add_tab_to_session(&self, session_id: &str, tab_id: String) -> Result<Session> {
         let mut session = self.get_session(session_id)?;
         session.add_tab(tab_id);
@@ -338,6 +745,108 @@ impl SessionManager {
         Ok(ordered)
     }
 
+    /// Export a session (tabs in display order) in the given format
+    pub fn export_session(&self, session_id: &str, format: SessionFormat) -> Result<String> {
+        self.tab_manager.load_session_tabs(session_id)?;
+        let session = self.get_session(session_id)?;
+        let tabs = self.get_ordered_tabs_for_session(session_id)?;
+
+        SessionExport::from_session(&session, &tabs).render(format)
+    }
+
+    /// Import a session as a new, inactive session
+    ///
+    /// Imported tabs start `Discarded` so nothing is loaded until a tab is clicked.
+    /// Entries that are not web or internal pages (see [`import_rejection`])
+    /// are left out and listed in the result.
+    pub fn import_session(
+        &self,
+        format: SessionFormat,
+        input: &str,
+        name: Option<String>,
+    ) -> Result<SessionImport> {
+        let export = SessionExport::parse(format, input)?;
+        if export
+            .tabs()
+            .all(|tab| import_rejection(&tab.url).is_some())
+        {
+            return Err(SessionError::InvalidImport(
+                "No importable tabs found in import".to_string(),
+            ));
+        }
+        let (session, skipped) = self.create_session_from_export(&export, name)?;
+
+        tracing::info!(
+            session_id = %session.id,
+            session_name = %session.name,
+            format = %format,
+            tab_count = session.tab_count(),
+            skipped = skipped.len(),
+            "Imported session"
+        );
+
+        Ok(SessionImport { session, skipped })
+    }
+
+    fn create_session_from_export(
+        &self,
+        export: &SessionExport,
+        name: Option<String>,
+    ) -> Result<(Session, Vec<SkippedTab>)> {
+        let name = name
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .or_else(|| Some(export.name.trim().to_string()).filter(|n| !n.is_empty()))
+            .unwrap_or_else(|| "Imported".to_string());
+
+        let mut session = Session::new(name);
+        let mut tabs = Vec::with_capacity(export.tab_count());
+        let mut skipped = Vec::new();
+        for group in &export.groups {
+            let mut tab_ids = Vec::new();
+            for exported in &group.tabs {
+                if let Some(reason) = import_rejection(&exported.url) {
+                    skipped.push(SkippedTab {
+                        url: exported.url.clone(),
+                        reason,
+                    });
+                    continue;
+                }
+                let mut tab =
+                    match Tab::new_discarded(session.id.clone(), exported.url.trim().to_string()) {
+                        Ok(tab) => tab,
+                        Err(e) => {
+                            skipped.push(SkippedTab {
+                                url: exported.url.clone(),
+                                reason: e.to_string(),
+                            });
+                            continue;
+                        }
+                    };
+                tab.title = exported.title.clone();
+                tab.favicon_url = exported.favicon_url.clone();
+                tab.scroll_position = exported.scroll_position;
+                session.tab_order.push(tab.id.clone());
+                tab_ids.push(tab.id.clone());
+                tabs.push(tab);
+            }
+
+            let group_name = group.name.as_deref().map(str::trim).unwrap_or("");
+            if !group_name.is_empty() && !tab_ids.is_empty() {
+                session.tab_groups.push(TabGroup {
+                    name: group_name.to_string(),
+                    tab_ids,
+                });
+            }
+        }
+
+        // Session row first: tabs reference it by foreign key
+        self.save_session(&session)?;
+        self.tab_manager.insert_tabs(&tabs)?;
+
+        Ok((session, skipped))
+    }
+
     /// Create a new tab in the active session
     pub fn create_tab(&self, url: String) -> Result<Tab> {
         let mut session = self.active_session()?;
@@ -393,6 +902,7 @@ impl Clone for SessionManager {
             active_session_id: Arc::clone(&self.active_session_id),
             db: self.db.clone(),
             tab_manager: self.tab_manager.clone(),
+            recovery: Arc::clone(&self.recovery),
         }
     }
 }
@@ -425,4 +935,262 @@ mod tests {
         let default = sessions.iter().find(|s| s.name == "Default").unwrap();
         assert!(!default.is_active);
     }
+
+    #[test]
+    fn test_restore_discards_inactive_tabs() {
+        let db = Database::open_in_memory().unwrap();
+        let manager = SessionManager::new(db);
+        let session = manager.initialize().unwrap();
+
+        let first = manager.create_tab("https://a.example".to_string()).unwrap();
+        let second = manager.create_tab("https://b.example".to_string()).unwrap();
+        let third = manager.create_tab("https://c.example".to_string()).unwrap();
+        manager.tab_manager().blur_tab(&first.id).unwrap();
+        manager.tab_manager().blur_tab(&second.id).unwrap();
+        manager.tab_manager().activate_tab(&second.id).unwrap();
+        manager.tab_manager().blur_tab(&third.id).unwrap();
+
+        let tabs = manager.restore_session_tabs(&session.id).unwrap();
+        assert_eq!(tabs[0].state, TabState::Discarded);
+        assert_eq!(tabs[1].state, TabState::Active);
+        assert_eq!(tabs[2].state, TabState::Discarded);
+
+        // Eager policy keeps the most recently used tab loaded
+        manager.tab_manager().activate_tab(&first.id).unwrap();
+        manager.tab_manager().blur_tab(&first.id).unwrap();
+        manager.set_eager_restore_count(1).unwrap();
+        let tabs = manager.restore_session_tabs(&session.id).unwrap();
+        assert_eq!(tabs[0].state, TabState::Background);
+        assert_eq!(tabs[1].state, TabState::Active);
+        assert_eq!(tabs[2].state, TabState::Discarded);
+    }
+
+    #[test]
+    fn test_raising_eager_count_wakes_discarded_tabs() {
+        let db = Database::open_in_memory().unwrap();
+        let manager = SessionManager::new(db.clone());
+        let session = manager.initialize().unwrap();
+        for url in [
+            "https://a.example",
+            "https://b.example",
+            "https://c.example",
+        ] {
+            manager.create_tab(url.to_string()).unwrap();
+        }
+        let tabs = manager.restore_session_tabs(&session.id).unwrap();
+        assert_eq!(tabs.iter().filter(|t| t.state.is_discarded()).count(), 2);
+
+        // Next launch with every background tab restored eagerly
+        manager.set_eager_restore_count(2).unwrap();
+        let manager = SessionManager::new(db);
+        let session = manager.initialize().unwrap();
+        let tabs = manager.restore_session_tabs(&session.id).unwrap();
+        assert!(tabs.iter().all(|t| !t.state.is_discarded()));
+        assert_eq!(
+            tabs.iter()
+                .filter(|t| t.state == TabState::Background)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_unclean_shutdown_reconciles_and_offers_recovery() {
+        let db = Database::open_in_memory().unwrap();
+        let manager = SessionManager::new(db.clone());
+        manager.initialize().unwrap();
+        manager.create_tab("https://a.example".to_string()).unwrap();
+        manager.record_clean_shutdown().unwrap();
+
+        // Second run: a tab id that was never saved plus a tab missing from the order
+        let manager = SessionManager::new(db.clone());
+        let session = manager.initialize().unwrap();
+        assert!(manager.recovery_info().is_none());
+        let orphan = manager
+            .tab_manager()
+            .create_tab(session.id.clone(), "https://b.example".to_string())
+            .unwrap();
+        manager
+            .add_tab_to_session(&session.id, "never-saved".to_string())
+            .unwrap();
+
+        // Third run after a crash
+        let manager = SessionManager::new(db);
+        let session = manager.initialize().unwrap();
+        let recovery = manager.recovery_info().unwrap();
+        assert_eq!(recovery.dangling_tab_ids, 1);
+        assert_eq!(recovery.adopted_tabs, 1);
+        assert_eq!(recovery.last_known_good_tabs, 1);
+        assert_eq!(session.tab_order.len(), 2);
+        assert_eq!(session.tab_order[1], orphan.id);
+
+        let restored = manager.restore_last_known_good().unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].name, "Default (recovered)");
+        assert!(manager.recovery_info().is_none());
+    }
+
+    #[test]
+    fn test_archive_and_unarchive_session() {
+        let db = Database::open_in_memory().unwrap();
+        let manager = SessionManager::new(db);
+        let default = manager.initialize().unwrap();
+        let work = manager.create_session("Work".to_string()).unwrap();
+        manager.switch_session(&work.id).unwrap();
+        let tab = manager
+            .create_tab("https://tracker.example/issue/42".to_string())
+            .unwrap();
+        manager
+            .tab_manager()
+            .set_tab_title(&tab.id, "Fix 100% CPU".to_string())
+            .unwrap();
+        let before = manager.get_session(&work.id).unwrap();
+
+        // Archiving the active session switches away and unloads its tabs
+        let archived = manager.archive_session(&work.id).unwrap();
+        assert!(archived.is_archived());
+        assert_eq!(manager.active_session().unwrap().id, default.id);
+        assert!(manager.list_sessions().iter().all(|s| s.id != work.id));
+        assert_eq!(manager.list_archived_sessions().len(), 1);
+        assert!(manager.tab_manager().get_session_tabs(&work.id).is_empty());
+        assert!(matches!(
+            manager.switch_session(&work.id),
+            Err(SessionError::Archived(_))
+        ));
+        assert!(matches!(
+            manager.archive_session(&default.id),
+            Err(SessionError::CannotArchiveLastSession)
+        ));
+        // A private session does not count as somewhere to fall back to
+        manager
+            .create_private_session("Private".to_string())
+            .unwrap();
+        assert!(matches!(
+            manager.archive_session(&default.id),
+            Err(SessionError::CannotArchiveLastSession)
+        ));
+
+        let hits = manager.search_archive("work 100%", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].tab_id, tab.id);
+        assert!(manager.search_archive("100% GPU", 10).unwrap().is_empty());
+
+        // Survives a reload and comes back unchanged
+        let manager = SessionManager::new(manager.db.clone());
+        manager.initialize().unwrap();
+        assert_eq!(manager.list_archived_sessions().len(), 1);
+        let restored = manager.unarchive_session(&work.id).unwrap();
+        assert!(!restored.is_archived());
+        assert_eq!(restored.tab_order, before.tab_order);
+        assert!(manager.search_archive("tracker", 10).unwrap().is_empty());
+
+        manager.switch_session(&work.id).unwrap();
+        let tabs = manager.get_ordered_tabs_for_session(&work.id).unwrap();
+        assert_eq!(tabs[0].title, "Fix 100% CPU");
+    }
+
+    #[test]
+    fn test_import_session_discards_tabs() {
+        let db = Database::open_in_memory().unwrap();
+        let manager = SessionManager::new(db);
+        manager.initialize().unwrap();
+
+        let onetab = "https://example.com | Example\nhttps://rust-lang.org | Rust\n";
+        let imported = manager
+            .import_session(SessionFormat::OneTab, onetab, Some("Shared".to_string()))
+            .unwrap()
+            .session;
+        assert_eq!(imported.name, "Shared");
+        assert!(!imported.is_active);
+
+        let tabs = manager.get_ordered_tabs_for_session(&imported.id).unwrap();
+        assert_eq!(tabs.len(), 2);
+        assert!(tabs.iter().all(|t| t.state == TabState::Discarded));
+        assert_eq!(tabs[1].title, "Rust");
+
+        let markdown = manager
+            .export_session(&imported.id, SessionFormat::Markdown)
+            .unwrap();
+        assert!(markdown.starts_with("# Shared"));
+        assert!(markdown.contains("- [Example](https://example.com)"));
+    }
+
+    #[test]
+    fn test_import_skips_unsafe_urls_and_keeps_groups() {
+        let db = Database::open_in_memory().unwrap();
+        let manager = SessionManager::new(db.clone());
+        manager.initialize().unwrap();
+
+        let json = r#"{
+            "version": 1,
+            "name": "Shared",
+            "exported_at": "2024-01-01T00:00:00Z",
+            "groups": [
+                {"name": null, "tabs": [
+                    {"url": "https://example.com/"},
+                    {"url": "javascript:alert(1)"}
+                ]},
+                {"name": "Reading", "tabs": [
+                    {"url": "file:///etc/passwd"},
+                    {"url": "https://rust-lang.org/", "title": "Rust"},
+                    {"url": "about:blank"}
+                ]}
+            ]
+        }"#;
+        let import = manager
+            .import_session(SessionFormat::Json, json, None)
+            .unwrap();
+        let skipped: Vec<&str> = import.skipped.iter().map(|s| s.url.as_str()).collect();
+        assert_eq!(skipped, ["javascript:alert(1)", "file:///etc/passwd"]);
+        assert_eq!(import.session.tab_count(), 3);
+        assert_eq!(import.session.tab_groups.len(), 1);
+        assert_eq!(import.session.tab_groups[0].name, "Reading");
+
+        // Group names survive a restart and are exported again
+        let reloaded = SessionManager::new(db);
+        reloaded.initialize().unwrap();
+        let markdown = reloaded
+            .export_session(&import.session.id, SessionFormat::Markdown)
+            .unwrap();
+        assert!(markdown.contains("## Reading\n\n- [Rust](https://rust-lang.org/)"));
+        assert!(!markdown.contains("javascript:"));
+
+        assert!(manager
+            .import_session(SessionFormat::OneTab, "javascript:alert(1) | x\n", None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_private_session_is_not_persisted() {
+        let db = Database::open_in_memory().unwrap();
+        let manager = SessionManager::new(db.clone());
+        let default = manager.initialize().unwrap();
+
+        let private = manager
+            .create_private_session("Private".to_string())
+            .unwrap();
+        assert!(private.private && private.isolated);
+        manager.switch_session(&private.id).unwrap();
+        let tab = manager
+            .create_tab("https://example.com".to_string())
+            .unwrap();
+        assert_eq!(manager.get_ordered_tabs().unwrap().len(), 1);
+        assert!(matches!(
+            manager.archive_session(&private.id),
+            Err(SessionError::PrivateSession(_))
+        ));
+        manager.record_clean_shutdown().unwrap();
+
+        // Nothing about the private session reached the database, and the
+        // persistent session is still the one a restart reopens
+        let reloaded = SessionManager::new(db);
+        let active = reloaded.initialize().unwrap();
+        assert_eq!(active.id, default.id);
+        assert!(reloaded.get_session(&private.id).is_err());
+        assert!(reloaded.tab_manager().get_tab(&tab.id).is_err());
+
+        manager.delete_session(&private.id).unwrap();
+        assert_eq!(manager.active_session().unwrap().id, default.id);
+        assert!(!manager.tab_manager().is_session_memory_only(&private.id));
+    }
 }
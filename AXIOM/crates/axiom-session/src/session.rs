@@ -18,6 +18,24 @@ pub struct Session {
     pub is_active: bool,
     /// Ordered list of tab IDs (for display order in sidebar)
     pub tab_order: Vec<String>,
+    /// Whether web content gets its own storage partition (cookies, localStorage)
+    #[serde(default)]
+    pub isolated: bool,
+    /// When the session was archived (hidden from the session list, tabs unloaded)
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Private sessions are never written to disk and leave no history
+    #[serde(default)]
+    pub private: bool,
+    /// Named groups of tabs (from imports); tabs in no group are ungrouped
+    #[serde(default)]
+    pub tab_groups: Vec<TabGroup>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TabGroup {
+    pub name: String,
+    pub tab_ids: Vec<String>,
 }
 
 impl Session {
@@ -31,6 +49,10 @@ impl Session {
             updated_at: now,
             is_active: false,
             tab_order: Vec::new(),
+            isolated: false,
+            archived_at: None,
+            private: false,
+            tab_groups: Vec::new(),
         }
     }
 
@@ -52,6 +74,10 @@ impl Session {
     /// Remove a tab ID from the order list
     pub fn remove_tab(&mut self, tab_id: &str) {
         self.tab_order.retain(|id| id != tab_id);
+        for group in &mut self.tab_groups {
+            group.tab_ids.retain(|id| id != tab_id);
+        }
+        self.tab_groups.retain(|group| !group.tab_ids.is_empty());
         self.updated_at = Utc::now();
     }
 
@@ -71,6 +97,18 @@ impl Session {
         self.updated_at = Utc::now();
     }
 
+    /// Name of the group `tab_id` belongs to
+    pub fn group_of(&self, tab_id: &str) -> Option<&str> {
+        self.tab_groups
+            .iter()
+            .find(|group| group.tab_ids.iter().any(|id| id == tab_id))
+            .map(|group| group.name.as_str())
+    }
+
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+
     /// Get the number of tabs
     pub fn tab_count(&self) -> usize {
         self.tab_order.len()
@@ -84,6 +84,42 @@ impl Database {
 
         Ok(())
     }
+
+    /// Get a setting override for one session (callers fall back to the global setting)
+    pub fn get_session_setting(&self, session_id: &str, key: &str) -> Result<Option<String>> {
+        self.with_connection(|conn| {
+            let value = conn
+                .query_row(
+                    "SELECT value FROM session_settings WHERE session_id = ?1 AND key = ?2",
+                    [session_id, key],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value)
+        })
+    }
+
+    pub fn set_session_setting(&self, session_id: &str, key: &str, value: &str) -> Result<()> {
+        let updated_at = Utc::now().to_rfc3339();
+        self.with_connection(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO session_settings (session_id, key, value, updated_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![session_id, key, value, updated_at],
+            )?;
+            Ok(())
+        })
+    }
+
+    pub fn remove_session_setting(&self, session_id: &str, key: &str) -> Result<()> {
+        self.with_connection(|conn| {
+            conn.execute(
+                "DELETE FROM session_settings WHERE session_id = ?1 AND key = ?2",
+                [session_id, key],
+            )?;
+            Ok(())
+        })
+    }
 }
 
 impl Clone for Database {
@@ -3,18 +3,53 @@
 //! Schema per PRD Section 9: tabs, sessions, history, settings, downloads
 
 use crate::Result;
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
 
-const SCHEMA_VERSION: i32 = 1;
+/// Schema steps in order; step `n` (1-based) brings the database to version `n`
+const MIGRATIONS: &[fn(&Connection) -> Result<()>] = &[
+    migrate_v1,
+    migrate_v2,
+    migrate_v3,
+    migrate_v4,
+    migrate_v5,
+    migrate_v6,
+    migrate_v7,
+    migrate_v8,
+    migrate_v9,
+    migrate_v10,
+    migrate_v11,
+    migrate_v12,
+    migrate_v13,
+    migrate_v14,
+    migrate_v15,
+    migrate_v16,
+];
+
+const SCHEMA_VERSION: i32 = MIGRATIONS.len() as i32;
 
 pub fn run_migrations(conn: &Connection) -> Result<()> {
+    migrate_to(conn, SCHEMA_VERSION)
+}
+
+/// Apply the steps after the current version up to `target`
+///
+/// Each step commits together with the version it reaches, so a crash or
+/// error leaves the database at the last complete step and the next start
+/// retries only the step that failed.
+fn migrate_to(conn: &Connection, target: i32) -> Result<()> {
     let current_version = get_schema_version(conn)?;
 
-    if current_version < 1 {
-        migrate_v1(conn)?;
+    for (version, migrate) in (1..=target).zip(MIGRATIONS) {
+        if version <= current_version {
+            continue;
+        }
+        let tx = conn.unchecked_transaction()?;
+        migrate(&tx)?;
+        set_schema_version(&tx, version)?;
+        tx.commit()?;
     }
 
-    set_schema_version(conn, SCHEMA_VERSION)?;
     Ok(())
 }
 
@@ -141,3 +176,518 @@ fn migrate_v1(conn: &Connection) -> Result<()> {
 
     Ok(())
 }
+
+fn migrate_v2(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v2: Per-session storage isolation");
+
+    conn.execute_batch(
+        r#"
+        ALTER TABLE sessions ADD COLUMN isolated INTEGER NOT NULL DEFAULT 0;
+    "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_v3(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v3: Per-session settings overrides");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS session_settings (
+            session_id TEXT NOT NULL,
+            key TEXT NOT NULL,
+            value TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (session_id, key),
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        );
+    "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_v4(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v4: URL routing rules");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS routing_rules (
+            id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            pattern TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            position INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_routing_rules_position ON routing_rules(position);
+    "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_v5(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v5: Session archive");
+
+    // Archived tabs stay in `tabs`; the index only serves archive search
+    conn.execute_batch(
+        r#"
+        ALTER TABLE sessions ADD COLUMN archived_at TEXT;
+
+        CREATE TABLE IF NOT EXISTS session_archive_index (
+            session_id TEXT NOT NULL,
+            tab_id TEXT NOT NULL,
+            session_name TEXT NOT NULL,
+            title TEXT NOT NULL DEFAULT '',
+            url TEXT NOT NULL,
+            search_text TEXT NOT NULL,
+            PRIMARY KEY (session_id, tab_id),
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        );
+    "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_v6(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v6: History frecency");
+
+    // Existing rows get a rough score; scores are recalculated on startup.
+    conn.execute_batch(
+        r#"
+        ALTER TABLE history ADD COLUMN typed_count INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE history ADD COLUMN frecency INTEGER NOT NULL DEFAULT 0;
+
+        UPDATE history SET frecency = visit_count * 100;
+
+        CREATE INDEX IF NOT EXISTS idx_history_frecency ON history(frecency);
+    "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_v7(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v7: Per-visit history log");
+
+    // Existing rows only know their last visit time, logged once per counted
+    // visit up to the 10 samples frecency reads, so scores keep their weight
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS visits (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            history_id INTEGER NOT NULL,
+            visited_at TEXT NOT NULL,
+            transition TEXT NOT NULL DEFAULT 'link',
+            from_visit_id INTEGER,
+            tab_id TEXT,
+            session_id TEXT,
+            FOREIGN KEY (history_id) REFERENCES history(id) ON DELETE CASCADE,
+            FOREIGN KEY (from_visit_id) REFERENCES visits(id) ON DELETE SET NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_visits_history ON visits(history_id, visited_at);
+        CREATE INDEX IF NOT EXISTS idx_visits_visited ON visits(visited_at);
+        CREATE INDEX IF NOT EXISTS idx_visits_from ON visits(from_visit_id);
+
+        WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 10)
+        INSERT INTO visits (history_id, visited_at, transition)
+        SELECT h.id, h.visited_at, 'link' FROM history h
+        JOIN n ON n.i <= MAX(1, MIN(h.visit_count, 10))
+        ORDER BY h.visited_at;
+    "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_v8(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v8: Search keywords");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS search_keywords (
+            keyword TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            template TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+    "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_v9(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v9: Search engines");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS search_engines (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            template TEXT NOT NULL UNIQUE,
+            suggest_url TEXT,
+            icon_url TEXT,
+            created_at TEXT NOT NULL
+        );
+    "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_v10(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v10: HTTPS-Only exceptions");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS https_only_exceptions (
+            host TEXT PRIMARY KEY,
+            created_at TEXT NOT NULL
+        );
+    "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_v11(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v11: Bookmarks table");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS bookmarks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL DEFAULT 'bookmark',
+            parent_id INTEGER,
+            position INTEGER NOT NULL,
+            title TEXT NOT NULL DEFAULT '',
+            url TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL,
+            modified_at TEXT NOT NULL,
+            FOREIGN KEY (parent_id) REFERENCES bookmarks(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_bookmarks_parent ON bookmarks(parent_id, position);
+        CREATE INDEX IF NOT EXISTS idx_bookmarks_url ON bookmarks(url);
+    "#,
+    )?;
+
+    migrate_bookmarks_setting(conn)
+}
+
+/// Move the JSON list under the `bookmarks` setting into the table
+///
+/// Entries were `{title, url, folder}` with `/`-joined folder paths; folders
+/// are created in order of first use and bookmarks keep their list order.
+/// A value that does not parse is kept under `bookmarks_unmigrated`.
+fn migrate_bookmarks_setting(conn: &Connection) -> Result<()> {
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM settings WHERE key = 'bookmarks'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(value) = value else {
+        return Ok(());
+    };
+    let entries: Vec<serde_json::Value> = match serde_json::from_str(&value) {
+        Ok(entries) => entries,
+        Err(e) => {
+            // Keep the list where it can still be recovered rather than losing it
+            tracing::warn!(error = %e, "Bookmarks setting is not a list, left as bookmarks_unmigrated");
+            conn.execute(
+                "UPDATE settings SET key = 'bookmarks_unmigrated' WHERE key = 'bookmarks'",
+                [],
+            )?;
+            return Ok(());
+        }
+    };
+    let now = chrono::Utc::now().to_rfc3339();
+
+    // (parent id, folder name) -> folder id, and next position per parent
+    let mut folders: HashMap<(Option<i64>, String), i64> = HashMap::new();
+    let mut positions: HashMap<Option<i64>, i64> = HashMap::new();
+    let mut insert = |kind: &str, parent_id: Option<i64>, title: &str, url: &str| {
+        let position = positions.entry(parent_id).or_insert(0);
+        conn.execute(
+            "INSERT INTO bookmarks (kind, parent_id, position, title, url, created_at, modified_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+            rusqlite::params![kind, parent_id, *position, title, url, now],
+        )?;
+        *position += 1;
+        Ok::<_, rusqlite::Error>(conn.last_insert_rowid())
+    };
+
+    let mut migrated = 0;
+    for entry in &entries {
+        let Some(url) = entry
+            .get("url")
+            .and_then(|v| v.as_str())
+            .filter(|url| !url.trim().is_empty())
+        else {
+            continue;
+        };
+        let title = entry.get("title").and_then(|v| v.as_str()).unwrap_or(url);
+        let path = entry.get("folder").and_then(|v| v.as_str()).unwrap_or("");
+
+        let mut parent_id = None;
+        for name in path.split('/').map(str::trim).filter(|s| !s.is_empty()) {
+            let key = (parent_id, name.to_string());
+            parent_id = Some(match folders.get(&key) {
+                Some(id) => *id,
+                None => {
+                    let id = insert("folder", parent_id, name, "")?;
+                    folders.insert(key, id);
+                    id
+                }
+            });
+        }
+        insert("bookmark", parent_id, title, url)?;
+        migrated += 1;
+    }
+
+    conn.execute("DELETE FROM settings WHERE key = 'bookmarks'", [])?;
+    tracing::info!(bookmarks = migrated, "Migrated bookmarks from settings");
+    Ok(())
+}
+
+fn migrate_v12(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v12: Bookmark tags, descriptions and smart folders");
+
+    conn.execute_batch(
+        r#"
+        ALTER TABLE bookmarks ADD COLUMN description TEXT NOT NULL DEFAULT '';
+
+        CREATE TABLE IF NOT EXISTS bookmark_tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE
+        );
+
+        CREATE TABLE IF NOT EXISTS bookmark_tag_map (
+            bookmark_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (bookmark_id, tag_id),
+            FOREIGN KEY (bookmark_id) REFERENCES bookmarks(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES bookmark_tags(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_bookmark_tag_map_tag ON bookmark_tag_map(tag_id);
+
+        CREATE TABLE IF NOT EXISTS bookmark_smart_folders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            tag TEXT,
+            domain TEXT,
+            text TEXT,
+            added_within_days INTEGER,
+            created_at TEXT NOT NULL
+        );
+    "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_v13(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v13: Bookmark link checks");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS bookmark_link_checks (
+            bookmark_id INTEGER PRIMARY KEY,
+            status TEXT NOT NULL,
+            detail TEXT,
+            checked_at TEXT NOT NULL,
+            FOREIGN KEY (bookmark_id) REFERENCES bookmarks(id) ON DELETE CASCADE
+        );
+    "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_v14(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v14: Bookmark icons, keywords and file attributes");
+
+    conn.execute_batch(
+        r#"
+        ALTER TABLE bookmarks ADD COLUMN icon TEXT;
+        ALTER TABLE bookmarks ADD COLUMN icon_uri TEXT;
+        ALTER TABLE bookmarks ADD COLUMN shortcut TEXT;
+        ALTER TABLE bookmarks ADD COLUMN attributes TEXT NOT NULL DEFAULT '{}';
+    "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_v15(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v15: Session tab groups");
+
+    conn.execute_batch(
+        r#"
+        ALTER TABLE sessions ADD COLUMN tab_groups TEXT NOT NULL DEFAULT '[]';
+    "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_v16(conn: &Connection) -> Result<()> {
+    tracing::info!("Running migration v16: Consecutive DNS failures of bookmark link checks");
+
+    conn.execute_batch(
+        r#"
+        ALTER TABLE bookmark_link_checks ADD COLUMN dns_failures INTEGER NOT NULL DEFAULT 0;
+        UPDATE bookmark_link_checks SET dns_failures = 1 WHERE status = 'dns';
+    "#,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bookmarks_setting_migration() {
+        let conn = Connection::open_in_memory().unwrap();
+        // A v10 database with the old JSON list
+        migrate_to(&conn, 10).unwrap();
+        conn.execute(
+            "INSERT INTO settings (key, value, updated_at) VALUES ('bookmarks', ?1, '')",
+            [r#"[
+                {"title": "Rust", "url": "https://www.rust-lang.org/", "folder": "Dev/Lang"},
+                {"title": "Example", "url": "https://example.com/"},
+                {"title": "Tokio", "url": "https://tokio.rs/", "folder": "Dev"},
+                {"title": "Crates", "url": "https://crates.io/", "folder": "Dev/Lang"}
+            ]"#],
+        )
+        .unwrap();
+        run_migrations(&conn).unwrap();
+
+        let rows: Vec<(String, Option<i64>, i64, String)> = conn
+            .prepare("SELECT kind, parent_id, position, title FROM bookmarks ORDER BY id")
+            .unwrap()
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        let dev = Some(1);
+        let lang = Some(2);
+        assert_eq!(
+            rows,
+            vec![
+                ("folder".to_string(), None, 0, "Dev".to_string()),
+                ("folder".to_string(), dev, 0, "Lang".to_string()),
+                ("bookmark".to_string(), lang, 0, "Rust".to_string()),
+                ("bookmark".to_string(), None, 1, "Example".to_string()),
+                ("bookmark".to_string(), dev, 1, "Tokio".to_string()),
+                ("bookmark".to_string(), lang, 1, "Crates".to_string()),
+            ]
+        );
+
+        let leftover: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM settings WHERE key = 'bookmarks'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(leftover, 0);
+    }
+
+    #[test]
+    fn test_history_seeds_visit_log() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_to(&conn, 5).unwrap();
+        conn.execute(
+            "INSERT INTO history (url, title, visited_at, visit_count)
+             VALUES ('https://example.com/', 'Example', '2024-01-02T03:04:05Z', 3),
+                    ('https://rust-lang.org/', 'Rust', '2024-01-01T00:00:00Z', 50)",
+            [],
+        )
+        .unwrap();
+        run_migrations(&conn).unwrap();
+
+        let seeded = |url: &str| -> (i64, String) {
+            conn.query_row(
+                "SELECT COUNT(*), MAX(v.visited_at) FROM visits v
+                 JOIN history h ON h.id = v.history_id WHERE h.url = ?1",
+                [url],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            seeded("https://example.com/"),
+            (3, "2024-01-02T03:04:05Z".to_string())
+        );
+        // Capped at the samples frecency reads
+        assert_eq!(seeded("https://rust-lang.org/").0, 10);
+    }
+
+    #[test]
+    fn test_malformed_bookmarks_setting_is_kept() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_to(&conn, 10).unwrap();
+        conn.execute(
+            "INSERT INTO settings (key, value, updated_at) VALUES ('bookmarks', ?1, '')",
+            [r#"[{"title": "Rust", "url": "https://www.rust-lang.org/"#],
+        )
+        .unwrap();
+        run_migrations(&conn).unwrap();
+
+        let kept: String = conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = 'bookmarks_unmigrated'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(kept.contains("rust-lang.org"));
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM bookmarks", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_failed_step_rolls_back() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_to(&conn, 11).unwrap();
+
+        // v12 adds a column and then fails on this table's missing tag_id
+        conn.execute_batch("CREATE TABLE bookmark_tag_map (bookmark_id INTEGER)")
+            .unwrap();
+        assert!(run_migrations(&conn).is_err());
+        assert_eq!(get_schema_version(&conn).unwrap(), 11);
+        let has_description: bool = conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM pragma_table_info('bookmarks')
+                               WHERE name = 'description')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!has_description);
+
+        // Once the cause is gone the same step runs again cleanly
+        conn.execute_batch("DROP TABLE bookmark_tag_map").unwrap();
+        run_migrations(&conn).unwrap();
+        assert_eq!(get_schema_version(&conn).unwrap(), SCHEMA_VERSION);
+    }
+}
@@ -4,7 +4,7 @@
 
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use axiom_storage::Database;
@@ -19,6 +19,8 @@ pub struct TabManager {
     tabs: Arc<RwLock<HashMap<String, Tab>>>,
     /// Database for persistence
     db: Database,
+    /// Sessions whose tabs live in memory only (private sessions)
+    memory_only: Arc<RwLock<HashSet<String>>>,
 }
 
 impl TabManager {
@@ -26,11 +28,29 @@ impl TabManager {
         Self {
             tabs: Arc::new(RwLock::new(HashMap::new())),
             db,
+            memory_only: Arc::new(RwLock::new(HashSet::new())),
         }
     }
 
+    /// Keep a session's tabs out of the database (or stop doing so)
+    pub fn set_session_memory_only(&self, session_id: &str, memory_only: bool) {
+        let mut sessions = self.memory_only.write();
+        if memory_only {
+            sessions.insert(session_id.to_string());
+        } else {
+            sessions.remove(session_id);
+        }
+    }
+
+    pub fn is_session_memory_only(&self, session_id: &str) -> bool {
+        self.memory_only.read().contains(session_id)
+    }
+
     /// Load all tabs for a session from database
     pub fn load_session_tabs(&self, session_id: &str) -> Result<Vec<Tab>> {
+        if self.is_session_memory_only(session_id) {
+            return Ok(self.get_session_tabs(session_id));
+        }
         let tabs: Vec<Tab> = self.db.with_connection(|conn| {
             let mut stmt = conn.prepare(
                 "SELECT id, session_id, url, title, favicon_url, state, scroll_position,
@@ -104,6 +124,28 @@ impl TabManager {
         Ok(tab)
     }
 
+    /// Persist a batch of already-constructed tabs in a single transaction
+    pub fn insert_tabs(&self, tabs: &[Tab]) -> Result<()> {
+        self.db.transaction(|conn| {
+            for tab in tabs {
+                if self.is_session_memory_only(&tab.session_id) {
+                    continue;
+                }
+                write_tab(conn, tab)?;
+            }
+            Ok(())
+        })?;
+
+        let mut cache = self.tabs.write();
+        for tab in tabs {
+            cache.insert(tab.id.clone(), tab.clone());
+        }
+
+        tracing::info!(count = tabs.len(), "Inserted tabs");
+
+        Ok(())
+    }
+
     /// Get a tab by ID
     pub fn get_tab(&self, tab_id: &str) -> Result<Tab> {
         self.tabs
@@ -155,10 +197,17 @@ impl TabManager {
     /// Close a tab (remove from session)
     pub fn close_tab(&self, tab_id: &str) -> Result<()> {
         // Remove from database
-        self.db.with_connection(|conn| {
-            conn.execute("DELETE FROM tabs WHERE id = ?1", [tab_id])?;
-            Ok(())
-        })?;
+        let memory_only = self
+            .tabs
+            .read()
+            .get(tab_id)
+            .is_some_and(|t| self.is_session_memory_only(&t.session_id));
+        if !memory_only {
+            self.db.with_connection(|conn| {
+                conn.execute("DELETE FROM tabs WHERE id = ?1", [tab_id])?;
+                Ok(())
+            })?;
+        }
 
         // Remove from cache
         self.tabs.write().remove(tab_id);
@@ -168,6 +217,14 @@ impl TabManager {
         Ok(())
     }
 
+    /// Drop a session's tabs from memory (they stay in the database)
+    pub fn unload_session_tabs(&self, session_id: &str) -> usize {
+        let mut tabs = self.tabs.write();
+        let before = tabs.len();
+        tabs.retain(|_, t| t.session_id != session_id);
+        before - tabs.len()
+    }
+
     /// Get all tabs in a session
     pub fn get_session_tabs(&self, session_id: &str) -> Vec<Tab> {
         self.tabs
@@ -204,36 +261,42 @@ impl TabManager {
 
     /// Save tab to database
     fn save_tab(&self, tab: &Tab) -> Result<()> {
-        Ok(self.db.with_connection(|conn| {
-            conn.execute(
-                "INSERT OR REPLACE INTO tabs
-                 (id, session_id, url, title, favicon_url, state, scroll_position,
-                  created_at, updated_at, last_accessed_at, snapshot_path)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
-                rusqlite::params![
-                    tab.id,
-                    tab.session_id,
-                    tab.url,
-                    tab.title,
-                    tab.favicon_url,
-                    tab.state.as_str(),
-                    tab.scroll_position,
-                    tab.created_at.to_rfc3339(),
-                    tab.updated_at.to_rfc3339(),
-                    tab.last_accessed_at.to_rfc3339(),
-                    tab.snapshot_path,
-                ],
-            )?;
-            Ok(())
-        })?)
+        if self.is_session_memory_only(&tab.session_id) {
+            return Ok(());
+        }
+        Ok(self.db.with_connection(|conn| write_tab(conn, tab))?)
     }
 }
 
+fn write_tab(conn: &rusqlite::Connection, tab: &Tab) -> axiom_storage::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO tabs
+         (id, session_id, url, title, favicon_url, state, scroll_position,
+          created_at, updated_at, last_accessed_at, snapshot_path)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        rusqlite::params![
+            tab.id,
+            tab.session_id,
+            tab.url,
+            tab.title,
+            tab.favicon_url,
+            tab.state.as_str(),
+            tab.scroll_position,
+            tab.created_at.to_rfc3339(),
+            tab.updated_at.to_rfc3339(),
+            tab.last_accessed_at.to_rfc3339(),
+            tab.snapshot_path,
+        ],
+    )?;
+    Ok(())
+}
+
 impl Clone for TabManager {
     fn clone(&self) -> Self {
         Self {
             tabs: Arc::clone(&self.tabs),
             db: self.db.clone(),
+            memory_only: Arc::clone(&self.memory_only),
         }
     }
 }
@@ -285,4 +348,30 @@ mod tests {
         manager.close_tab(&tab.id).unwrap();
         assert!(manager.get_tab(&tab.id).is_err());
     }
+
+    #[test]
+    fn test_memory_only_session() {
+        let db = Database::open_in_memory().unwrap();
+        let manager = TabManager::new(db.clone());
+        manager.set_session_memory_only("private-1", true);
+
+        // No sessions row exists, so any write would fail the foreign key
+        let tab = manager
+            .create_tab("private-1".to_string(), "https://example.com".to_string())
+            .unwrap();
+        manager
+            .navigate_tab(&tab.id, "https://example.org".to_string())
+            .unwrap();
+        assert_eq!(manager.load_session_tabs("private-1").unwrap().len(), 1);
+
+        let count: i64 = db
+            .with_connection(|conn| {
+                Ok(conn.query_row("SELECT COUNT(*) FROM tabs", [], |row| row.get(0))?)
+            })
+            .unwrap();
+        assert_eq!(count, 0);
+
+        manager.close_tab(&tab.id).unwrap();
+        assert!(manager.get_session_tabs("private-1").is_empty());
+    }
 }
@@ -63,6 +63,15 @@ impl Tab {
         })
     }
 
+    /// Create a tab that starts unloaded (imported or restored tabs)
+    ///
+    /// Only the URL is known; content is loaded on first activation.
+    pub fn new_discarded(session_id: String, url: String) -> Result<Self> {
+        let mut tab = Self::new(session_id, url)?;
+        tab.state = TabState::Discarded;
+        Ok(tab)
+    }
+
     /// Attempt to transition to a new state
     pub fn transition_to(&mut self, new_state: TabState) -> Result<()> {
         if !self.state.can_transition_to(new_state) {
@@ -129,6 +138,17 @@ impl Tab {
         }
     }
 
+    /// Make a tab loadable in the background (restoring a frozen or discarded one)
+    pub fn wake(&mut self) -> Result<()> {
+        match self.state {
+            TabState::Frozen | TabState::Discarded => {
+                self.activate()?;
+                self.blur()
+            }
+            _ => self.blur(),
+        }
+    }
+
     /// Update page title
     pub fn set_title(&mut self, title: String) {
         self.title = title;
@@ -202,6 +222,11 @@ mod tests {
         // Discarded -> Active (restore)
         tab.activate().unwrap();
         assert_eq!(tab.state, TabState::Active);
+
+        // Discarded -> Background, through Active
+        tab.discard().unwrap();
+        tab.wake().unwrap();
+        assert_eq!(tab.state, TabState::Background);
     }
 
     #[test]
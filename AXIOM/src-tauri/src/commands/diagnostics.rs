@@ -1,7 +1,19 @@
+use tauri::{AppHandle, Emitter, State, Window};
+
 use super::tabs::CommandResult;
+use crate::state::AppState;
 
 #[tauri::command]
-pub fn frontend_ready() -> CommandResult<()> {
+pub fn frontend_ready(app: AppHandle, window: Window, state: State<AppState>) -> CommandResult<()> {
     tracing::info!("Frontend ready");
+
+    // Offer crash recovery once, in the main window
+    if window.label() == "main" {
+        if let Ok(Some(recovery)) = state.with_browser(|browser| Ok(browser.recovery_info())) {
+            let ui_label = super::ui_webview_label(window.label());
+            let _ = app.emit_to(ui_label.as_str(), "session-recovery-available", recovery);
+        }
+    }
+
     CommandResult::ok(())
 }
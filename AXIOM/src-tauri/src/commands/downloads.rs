@@ -6,7 +6,7 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
-use tauri::{AppHandle, Emitter, Manager, State};
+use tauri::{AppHandle, Emitter, Manager, State, Window};
 use tauri_plugin_opener::OpenerExt;
 use tokio::io::AsyncWriteExt;
 use tokio::time::Instant;
@@ -89,6 +89,15 @@ impl Default for DownloadRuntime {
     }
 }
 
+impl DownloadRuntime {
+    /// Stop a running transfer, if there is one
+    pub fn cancel(&self, download_id: &str) {
+        if let Some(tx) = self.jobs.read().get(download_id) {
+            let _ = tx.send(DownloadControl::Cancel);
+        }
+    }
+}
+
 fn emit_download_update(app: &AppHandle, download: axiom_core::Download) {
     let _ = app.emit("download-updated", DownloadInfo::from(download));
 }
@@ -121,13 +130,20 @@ pub fn list_downloads(state: State<'_, AppState>) -> CommandResult<Vec<DownloadI
 #[tauri::command]
 pub fn create_download(
     app: AppHandle,
+    window: Window,
     state: State<'_, AppState>,
     url: String,
     file_name: Option<String>,
 ) -> CommandResult<DownloadInfo> {
     let file_name = file_name.unwrap_or_else(|| best_effort_file_name(&url));
+    let session_id = match state.session_id_for_window(window.label()) {
+        Ok(id) => id,
+        Err(e) => return CommandResult::err(e.to_string()),
+    };
 
-    match state.with_browser(|browser| browser.create_download(url, file_name)) {
+    match state
+        .with_browser(|browser| browser.create_download_in_session(&session_id, url, file_name))
+    {
         Ok(download) => {
             emit_download_update(&app, download.clone());
             CommandResult::ok(download.into())
@@ -0,0 +1,296 @@
+//! HTTPS-Only mode commands and the `axiom:` fallback page
+//!
+//! Content webviews hand `http://` navigations to `upgrade_navigation`, which
+//! probes the `https://` URL first. If the secure site answers, the webview
+//! loads it; otherwise it shows an interstitial served from the `axiom`
+//! scheme, from which the user can go back or continue over HTTP. Continuing
+//! adds a per-host exception and then loads the original URL.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tauri::http::{Request, Response};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use super::tabs::CommandResult;
+use super::webview::WebviewManager;
+use crate::state::AppState;
+use axiom_core::HttpsOnlyException;
+
+/// Scheme serving internal pages
+pub const SCHEME: &str = "axiom";
+
+/// An upgrade of the same URL again this soon means the site sent us back to HTTP
+const UPGRADE_LOOP_WINDOW: Duration = Duration::from_secs(10);
+
+/// Per-run secret in "continue over HTTP" links, so pages cannot add
+/// exceptions by linking to the interstitial themselves
+fn continue_token() -> &'static str {
+    static TOKEN: OnceLock<String> = OnceLock::new();
+    TOKEN.get_or_init(|| {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or_default(),
+        );
+        format!("{:016x}", hasher.finish())
+    })
+}
+
+/// Last upgrade per webview label: (https URL, when)
+fn recent_upgrades() -> &'static Mutex<HashMap<String, (String, Instant)>> {
+    static RECENT: OnceLock<Mutex<HashMap<String, (String, Instant)>>> = OnceLock::new();
+    RECENT.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Base URL of internal pages; Windows serves custom schemes over http
+fn page_base() -> &'static str {
+    if cfg!(windows) {
+        "http://axiom.localhost/"
+    } else {
+        "axiom://"
+    }
+}
+
+/// True for URLs served by the `axiom` scheme
+pub(crate) fn is_internal_page(url: &url::Url) -> bool {
+    url.scheme() == SCHEME || (cfg!(windows) && url.host_str() == Some("axiom.localhost"))
+}
+
+fn interstitial_url(http_url: &str, error_kind: &str) -> Option<url::Url> {
+    url::Url::parse_with_params(
+        &format!("{}https-only/", page_base()),
+        &[("url", http_url), ("error", error_kind)],
+    )
+    .ok()
+}
+
+/// Load the `https://` form of a navigation, or the interstitial if it fails
+///
+/// Called from a webview's navigation handler after it cancelled `http_url`.
+pub(crate) fn upgrade_navigation<R: Runtime>(
+    app: AppHandle<R>,
+    webview_label: String,
+    http_url: String,
+    https_url: String,
+) {
+    let looped = {
+        let mut recent = recent_upgrades().lock();
+        let looped = recent
+            .get(&webview_label)
+            .is_some_and(|(url, at)| *url == https_url && at.elapsed() < UPGRADE_LOOP_WINDOW);
+        recent.insert(webview_label.clone(), (https_url.clone(), Instant::now()));
+        looped
+    };
+
+    tauri::async_runtime::spawn(async move {
+        let failure = if looped {
+            Some("redirect_to_http".to_string())
+        } else {
+            let probe = axiom_core::probe_url(&https_url).await;
+            let downgraded = probe
+                .final_url
+                .as_deref()
+                .is_some_and(|final_url| final_url.starts_with("http:"));
+            if !probe.ok {
+                Some(probe.error_kind.unwrap_or_else(|| "unknown".to_string()))
+            } else if downgraded {
+                Some("redirect_to_http".to_string())
+            } else {
+                None
+            }
+        };
+
+        let target = match &failure {
+            None => url::Url::parse(&https_url).ok(),
+            Some(kind) => {
+                tracing::info!(url = %https_url, kind = %kind, "HTTPS-Only upgrade failed");
+                interstitial_url(&http_url, kind)
+            }
+        };
+        let (Some(target), Some(webview)) = (target, app.get_webview(&webview_label)) else {
+            return;
+        };
+        if let Err(e) = webview.navigate(target) {
+            tracing::warn!(label = %webview_label, "HTTPS-Only navigation failed: {}", e);
+        }
+    });
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn failure_reason(error_kind: &str) -> &'static str {
+    match error_kind {
+        "tls" => "its certificate could not be verified",
+        "dns" => "its address could not be found",
+        "timeout" => "it did not respond in time",
+        "connect" => "it refused the secure connection",
+        "redirect_to_http" => "it redirected back to an insecure address",
+        _ => "the secure connection failed",
+    }
+}
+
+fn interstitial_html(http_url: &str, error_kind: &str) -> String {
+    let host = url::Url::parse(http_url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| http_url.to_string());
+    let continue_url = url::Url::parse_with_params(
+        &format!("{}https-only/continue", page_base()),
+        &[("url", http_url), ("token", continue_token())],
+    )
+    .map(|u| u.to_string())
+    .unwrap_or_default();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="color-scheme" content="light dark">
+<title>Secure connection not available</title>
+<style>
+  body {{ font-family: system-ui, sans-serif; max-width: 560px; margin: 15vh auto; padding: 0 24px; line-height: 1.5; }}
+  h1 {{ font-size: 22px; font-weight: 600; }}
+  code {{ word-break: break-all; }}
+  .actions {{ display: flex; gap: 12px; margin-top: 28px; }}
+  button, a.button {{ font: inherit; padding: 8px 16px; border-radius: 6px; border: 1px solid #8884; background: none; color: inherit; cursor: pointer; text-decoration: none; }}
+  button.primary {{ background: #4080ff; border-color: #4080ff; color: #fff; }}
+  .note {{ opacity: 0.7; font-size: 13px; }}
+</style>
+</head>
+<body>
+<h1>Secure connection not available</h1>
+<p>HTTPS-Only mode tried to load <strong>{host}</strong> over HTTPS, but {reason}.</p>
+<p class="note">If you continue, the page loads over HTTP. Anything you see or send there can be read or changed by others on the network. AXIOM will remember this choice for {host}.</p>
+<p class="note"><code>{url}</code></p>
+<div class="actions">
+  <button class="primary" onclick="history.length > 1 ? history.back() : location.replace('about:blank')">Go back</button>
+  <a class="button" href="{continue_url}">Continue to HTTP site</a>
+</div>
+</body>
+</html>"#,
+        host = escape_html(&host),
+        reason = failure_reason(error_kind),
+        url = escape_html(http_url),
+        continue_url = escape_html(&continue_url),
+    )
+}
+
+fn html_response(status: u16, body: String) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "text/html; charset=utf-8")
+        .header("Cache-Control", "no-store")
+        .body(body.into_bytes())
+        .unwrap_or_else(|_| Response::new(Vec::new()))
+}
+
+/// Serve `axiom://https-only/` (the interstitial) and its continue action
+pub(crate) fn handle_request<R: Runtime>(
+    app: &AppHandle<R>,
+    webview_label: &str,
+    request: &Request<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    let Ok(url) = url::Url::parse(&request.uri().to_string()) else {
+        return html_response(400, "Bad request".to_string());
+    };
+    // `axiom://https-only/x` has host `https-only`; `http://axiom.localhost/https-only/x` does not
+    let path = if url.scheme() == SCHEME {
+        format!("{}{}", url.host_str().unwrap_or_default(), url.path())
+    } else {
+        url.path().trim_start_matches('/').to_string()
+    };
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+    let http_url = param("url").filter(|u| u.starts_with("http://"));
+
+    match (path.trim_end_matches('/'), http_url) {
+        ("https-only", Some(http_url)) => {
+            let error_kind = param("error").unwrap_or_default();
+            html_response(200, interstitial_html(&http_url, &error_kind))
+        }
+        ("https-only/continue", Some(http_url))
+            if param("token").as_deref() == Some(continue_token()) =>
+        {
+            // Private sessions keep the exception for themselves, unsaved
+            let tab_id = app
+                .try_state::<WebviewManager>()
+                .and_then(|manager| manager.tab_for_webview(webview_label))
+                .map(|(_, tab_id)| tab_id);
+            let added = app.try_state::<AppState>().map(|state| {
+                state.with_browser(|browser| match &tab_id {
+                    Some(tab_id) => browser.add_https_only_exception_for_tab(tab_id, &http_url),
+                    None => browser.add_https_only_exception(&http_url),
+                })
+            });
+            if let Some(Err(e)) = added {
+                tracing::warn!("Failed to add HTTPS-Only exception: {}", e);
+            }
+            let target = escape_html(&http_url);
+            html_response(
+                200,
+                format!(
+                    r#"<!DOCTYPE html><html><head><meta charset="utf-8"><meta http-equiv="refresh" content="0;url={target}"></head><body></body></html>"#
+                ),
+            )
+        }
+        _ => html_response(404, "Not found".to_string()),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct HttpsOnlySettings {
+    pub enabled: bool,
+    pub exceptions: Vec<HttpsOnlyException>,
+}
+
+#[tauri::command]
+pub fn get_https_only_settings(state: State<AppState>) -> CommandResult<HttpsOnlySettings> {
+    match state.with_browser(|browser| {
+        Ok(HttpsOnlySettings {
+            enabled: browser.get_https_only_enabled()?,
+            exceptions: browser.list_https_only_exceptions()?,
+        })
+    }) {
+        Ok(settings) => CommandResult::ok(settings),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn set_https_only_enabled(state: State<AppState>, enabled: bool) -> CommandResult<()> {
+    match state.with_browser(|browser| browser.set_https_only_enabled(enabled)) {
+        Ok(()) => CommandResult::ok(()),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn remove_https_only_exception(
+    state: State<AppState>,
+    host: String,
+) -> CommandResult<Vec<HttpsOnlyException>> {
+    match state.with_browser(|browser| {
+        browser.remove_https_only_exception(&host)?;
+        browser.list_https_only_exceptions()
+    }) {
+        Ok(exceptions) => CommandResult::ok(exceptions),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
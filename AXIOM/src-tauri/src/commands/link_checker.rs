@@ -0,0 +1,188 @@
+//! Bookmark link checker
+//!
+//! `start_bookmark_link_check` probes bookmarks in the background with the
+//! same HEAD-then-ranged-GET request as error pages (`axiom_core::probe_url`),
+//! a few at a time and at most one request per second to any host. Each
+//! result is reported as a `bookmark-link-checked` event and stored with the
+//! bookmark; DNS failures are stored when the run ends, unless most hosts
+//! failed to resolve and the run is reported as offline in
+//! `bookmark-link-check-finished`.
+
+use futures_util::StreamExt;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::tabs::CommandResult;
+use crate::state::AppState;
+use axiom_core::{HostRateLimiter, LinkCheckRun, LinkProbe, LinkStatus};
+
+/// Probes in flight at once
+const CONCURRENCY: usize = 4;
+
+/// Minimum spacing between requests to the same host
+const HOST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The running check, if any
+#[derive(Default)]
+pub struct LinkCheckRuntime {
+    cancel: Arc<Mutex<Option<Arc<AtomicBool>>>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkCheckProgress {
+    pub bookmark_id: i64,
+    #[serde(flatten)]
+    pub status: LinkStatus,
+    pub checked: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkCheckFinished {
+    pub checked: usize,
+    pub total: usize,
+    pub cancelled: bool,
+    /// Most hosts failed to resolve, so this run's DNS failures were not counted
+    pub offline: bool,
+}
+
+/// Check all bookmarks, or only `ids`; returns how many will be probed
+#[tauri::command]
+pub fn start_bookmark_link_check(
+    app: AppHandle,
+    state: State<AppState>,
+    runtime: State<LinkCheckRuntime>,
+    ids: Option<Vec<i64>>,
+) -> CommandResult<usize> {
+    let bookmarks = match state.with_browser(|browser| browser.bookmarks_to_check(ids.as_deref())) {
+        Ok(bookmarks) => bookmarks,
+        Err(e) => return CommandResult::err(e.to_string()),
+    };
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let mut current = runtime.cancel.lock();
+        if current.is_some() {
+            return CommandResult::err("A link check is already running".to_string());
+        }
+        *current = Some(cancelled.clone());
+    }
+
+    let total = bookmarks.len();
+    let slot = runtime.cancel.clone();
+    tauri::async_runtime::spawn(async move {
+        let limiter = Arc::new(Mutex::new(HostRateLimiter::new(HOST_INTERVAL)));
+        let run = Arc::new(Mutex::new(LinkCheckRun::new()));
+        let checked = Arc::new(AtomicUsize::new(0));
+
+        futures_util::stream::iter(bookmarks)
+            .map(|bookmark| {
+                let app = app.clone();
+                let limiter = limiter.clone();
+                let run = run.clone();
+                let checked = checked.clone();
+                let cancelled = cancelled.clone();
+                async move {
+                    if cancelled.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    let wait = limiter.lock().reserve(&bookmark.url, Instant::now());
+                    tokio::time::sleep(wait).await;
+                    if cancelled.load(Ordering::Relaxed) {
+                        return;
+                    }
+
+                    let probe = LinkProbe::from(axiom_core::probe_url(&bookmark.url).await);
+                    let Some(state) = app.try_state::<AppState>() else {
+                        return;
+                    };
+                    let status = match state.with_browser(|browser| {
+                        browser.record_bookmark_link_check(&mut run.lock(), bookmark.id, &probe)
+                    }) {
+                        Ok(status) => status,
+                        Err(e) => {
+                            tracing::warn!(bookmark = bookmark.id, error = %e, "Link check not saved");
+                            return;
+                        }
+                    };
+
+                    let done = checked.fetch_add(1, Ordering::Relaxed) + 1;
+                    let _ = app.emit(
+                        "bookmark-link-checked",
+                        LinkCheckProgress {
+                            bookmark_id: bookmark.id,
+                            status,
+                            checked: done,
+                            total,
+                        },
+                    );
+                }
+            })
+            .buffer_unordered(CONCURRENCY)
+            .collect::<Vec<()>>()
+            .await;
+
+        let offline = app
+            .try_state::<AppState>()
+            .and_then(|state| {
+                state
+                    .with_browser(|browser| browser.finish_bookmark_link_check(&run.lock()))
+                    .map_err(|e| tracing::warn!(error = %e, "DNS failures not saved"))
+                    .ok()
+            })
+            .unwrap_or(false);
+
+        *slot.lock() = None;
+        let _ = app.emit(
+            "bookmark-link-check-finished",
+            LinkCheckFinished {
+                checked: checked.load(Ordering::Relaxed),
+                total,
+                cancelled: cancelled.load(Ordering::Relaxed),
+                offline,
+            },
+        );
+    });
+
+    CommandResult::ok(total)
+}
+
+/// Stop the running check; results so far are kept
+#[tauri::command]
+pub fn cancel_bookmark_link_check(runtime: State<LinkCheckRuntime>) -> CommandResult<bool> {
+    match runtime.cancel.lock().as_ref() {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::Relaxed);
+            CommandResult::ok(true)
+        }
+        None => CommandResult::ok(false),
+    }
+}
+
+/// Point permanently redirected bookmarks (all, or only `ids`) at their new URL
+#[tauri::command]
+pub fn apply_bookmark_redirects(
+    state: State<AppState>,
+    ids: Option<Vec<i64>>,
+) -> CommandResult<usize> {
+    match state.with_browser(|browser| browser.apply_bookmark_redirects(ids.as_deref())) {
+        Ok(count) => CommandResult::ok(count),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+/// Remove bookmarks (all, or only `ids`) whose last check found them dead
+#[tauri::command]
+pub fn remove_dead_bookmarks(
+    state: State<AppState>,
+    ids: Option<Vec<i64>>,
+) -> CommandResult<usize> {
+    match state.with_browser(|browser| browser.remove_dead_bookmarks(ids.as_deref())) {
+        Ok(count) => CommandResult::ok(count),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
@@ -5,6 +5,8 @@
 
 pub mod diagnostics;
 pub mod downloads;
+pub mod https_only;
+pub mod link_checker;
 pub mod navigation;
 pub mod privacy;
 pub mod reader;
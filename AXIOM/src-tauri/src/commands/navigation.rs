@@ -1,38 +1,57 @@
 //! Navigation and address bar commands
 
 use chrono::{DateTime, FixedOffset, Utc};
+use futures_util::StreamExt;
 use reqwest::redirect::Policy;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State, Window};
 
 use super::tabs::CommandResult;
+use super::webview::WebviewManager;
 use crate::state::AppState;
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", content = "value")]
 pub enum InputResolutionResult {
-    Navigate(String),
-    Search(String),
+    Navigate {
+        url: String,
+        /// Punycode display and homograph warning for the target host
+        display: Option<axiom_core::UrlDisplay>,
+    },
+    Search {
+        url: String,
+        query: String,
+        keyword: Option<String>,
+    },
     Command {
         command_type: String,
         query: Option<String>,
     },
+    Answer(axiom_core::Answer),
 }
 
 impl From<axiom_core::InputResolution> for InputResolutionResult {
     fn from(resolution: axiom_core::InputResolution) -> Self {
         match resolution {
-            axiom_core::InputResolution::Navigate(url) => InputResolutionResult::Navigate(url),
-            axiom_core::InputResolution::Search(url) => InputResolutionResult::Search(url),
+            axiom_core::InputResolution::Navigate(url) => InputResolutionResult::Navigate {
+                display: axiom_core::url_display(&url),
+                url,
+            },
+            axiom_core::InputResolution::Search {
+                url,
+                query,
+                keyword,
+            } => InputResolutionResult::Search {
+                url,
+                query,
+                keyword,
+            },
             axiom_core::InputResolution::Command(cmd) => InputResolutionResult::Command {
-                command_type: match cmd.command_type {
-                    axiom_core::CommandType::Tabs => "tabs".to_string(),
-                    axiom_core::CommandType::History => "history".to_string(),
-                    axiom_core::CommandType::Sessions => "sessions".to_string(),
-                },
+                command_type: cmd.command_type.name().to_string(),
                 query: cmd.query,
             },
+            axiom_core::InputResolution::Answer(answer) => InputResolutionResult::Answer(answer),
         }
     }
 }
@@ -44,15 +63,7 @@ pub struct HistoryEntryInfo {
     pub title: String,
     pub visited_at: String,
     pub visit_count: i32,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ProbeInfo {
-    pub ok: bool,
-    pub status: Option<u16>,
-    pub final_url: Option<String>,
-    pub error_kind: Option<String>,
-    pub error_message: Option<String>,
+    pub frecency: i64,
 }
 
 impl From<axiom_core::HistoryEntry> for HistoryEntryInfo {
@@ -63,54 +74,220 @@ impl From<axiom_core::HistoryEntry> for HistoryEntryInfo {
             title: entry.title,
             visited_at: entry.visited_at.to_rfc3339(),
             visit_count: entry.visit_count,
+            frecency: entry.frecency,
         }
     }
 }
 
 #[tauri::command]
 pub fn resolve_input(
+    window: Window,
     state: State<AppState>,
     input: String,
 ) -> CommandResult<InputResolutionResult> {
-    match state.with_browser(|browser| Ok(browser.resolve_input(&input))) {
+    let session_id = match state.session_id_for_window(window.label()) {
+        Ok(id) => id,
+        Err(e) => return CommandResult::err(e.to_string()),
+    };
+
+    match state.with_browser(|browser| browser.resolve_input_in_session(&session_id, &input)) {
         Ok(resolution) => CommandResult::ok(resolution.into()),
         Err(e) => CommandResult::err(e.to_string()),
     }
 }
 
+#[derive(Debug, Serialize)]
+pub struct CommandMatchesInfo {
+    pub command_type: String,
+    pub query: Option<String>,
+    pub matches: Vec<axiom_core::CommandMatch>,
+}
+
+/// Results for a complete `@command`; `None` if the input is not one
+#[tauri::command]
+pub fn get_command_matches(
+    app: AppHandle,
+    window: Window,
+    state: State<AppState>,
+    input: String,
+    limit: Option<usize>,
+) -> CommandResult<Option<CommandMatchesInfo>> {
+    let Some(command) = axiom_core::Command::parse(&input) else {
+        return CommandResult::ok(None);
+    };
+
+    let session_id = match state.session_id_for_window(window.label()) {
+        Ok(id) => id,
+        Err(e) => return CommandResult::err(e.to_string()),
+    };
+
+    // The session map keeps labels of windows that have since closed
+    let windows: Vec<axiom_core::OpenWindow> = state
+        .open_windows()
+        .into_iter()
+        .filter(|w| app.get_window(&w.label).is_some())
+        .collect();
+
+    match state.with_browser(|browser| {
+        browser.command_matches(&session_id, &command, &windows, limit.unwrap_or(8))
+    }) {
+        Ok(matches) => CommandResult::ok(Some(CommandMatchesInfo {
+            command_type: command.command_type.name().to_string(),
+            query: command.query,
+            matches,
+        })),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+/// Bytes of an OpenSearch description read before giving up
+const OPENSEARCH_XML_LIMIT: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchEngineOffer {
+    pub tab_id: String,
+    pub page_url: String,
+    pub engine: axiom_core::NewSearchEngine,
+}
+
+/// Default number of omnibox suggestions
+const OMNIBOX_SUGGESTION_LIMIT: usize = 8;
+
+/// Ranked suggestions for the address bar; a newer request from the same window cancels this one
+#[tauri::command]
+pub async fn get_omnibox_suggestions(
+    app: AppHandle,
+    window: Window,
+    input: String,
+    limit: Option<usize>,
+) -> CommandResult<axiom_core::SuggestionResult> {
+    let label = window.label().to_string();
+    let limit = limit.unwrap_or(OMNIBOX_SUGGESTION_LIMIT).clamp(1, 50);
+
+    let task = tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let cancel = state.begin_omnibox_request(&label);
+        let session_id = state.session_id_for_window(&label)?;
+        state.with_browser(|browser| {
+            browser.omnibox_suggestions(&session_id, &input, limit, &cancel)
+        })
+    });
+
+    match task.await {
+        Ok(Ok(result)) => CommandResult::ok(result),
+        Ok(Err(e)) => CommandResult::err(e.to_string()),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
 #[tauri::command]
 pub fn search_history(
     state: State<AppState>,
     query: String,
+    sort: Option<axiom_core::HistorySort>,
 ) -> CommandResult<Vec<HistoryEntryInfo>> {
-    match state.with_browser(|browser| browser.search_history(&query)) {
+    let sort = sort.unwrap_or_default();
+    match state.with_browser(|browser| browser.search_history(&query, sort)) {
         Ok(entries) => CommandResult::ok(entries.into_iter().map(HistoryEntryInfo::from).collect()),
         Err(e) => CommandResult::err(e.to_string()),
     }
 }
 
 #[tauri::command]
-pub fn get_recent_history(state: State<AppState>) -> CommandResult<Vec<HistoryEntryInfo>> {
-    match state.with_browser(|browser| browser.recent_history()) {
+pub fn get_recent_history(
+    state: State<AppState>,
+    sort: Option<axiom_core::HistorySort>,
+) -> CommandResult<Vec<HistoryEntryInfo>> {
+    let sort = sort.unwrap_or_default();
+    match state.with_browser(|browser| browser.recent_history(sort)) {
         Ok(entries) => CommandResult::ok(entries.into_iter().map(HistoryEntryInfo::from).collect()),
         Err(e) => CommandResult::err(e.to_string()),
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VisitInfo {
+    pub id: i64,
+    pub url_id: i64,
+    pub url: String,
+    pub title: String,
+    pub visited_at: String,
+    pub transition: String,
+    pub from_visit_id: Option<i64>,
+    pub tab_id: Option<String>,
+    pub session_id: Option<String>,
+}
+
+impl From<axiom_core::Visit> for VisitInfo {
+    fn from(visit: axiom_core::Visit) -> Self {
+        Self {
+            id: visit.id,
+            url_id: visit.url_id,
+            url: visit.url,
+            title: visit.title,
+            visited_at: visit.visited_at.to_rfc3339(),
+            transition: visit.transition.to_string(),
+            from_visit_id: visit.from_visit_id,
+            tab_id: visit.tab_id,
+            session_id: visit.session_id,
+        }
+    }
+}
+
+fn parse_range_bound(value: Option<String>) -> Option<DateTime<Utc>> {
+    value
+        .as_deref()
+        .and_then(|s| DateTime::<FixedOffset>::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+#[tauri::command]
+pub fn get_history_visits(
+    state: State<AppState>,
+    start: Option<String>,
+    end: Option<String>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<VisitInfo>> {
+    let start = parse_range_bound(start);
+    let end = parse_range_bound(end);
+    let limit = limit.unwrap_or(200);
+
+    match state.with_browser(|browser| browser.history_visits(start, end, limit)) {
+        Ok(visits) => CommandResult::ok(visits.into_iter().map(VisitInfo::from).collect()),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+/// History grouped into journeys for the history page, newest first
+#[tauri::command]
+pub fn get_history_journeys(
+    state: State<AppState>,
+    start: Option<String>,
+    end: Option<String>,
+    query: Option<String>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<axiom_core::Journey>> {
+    let start = parse_range_bound(start);
+    let end = parse_range_bound(end);
+    let query = query.filter(|q| !q.trim().is_empty());
+    let limit = limit.unwrap_or(50);
+
+    match state
+        .with_browser(|browser| browser.history_journeys(start, end, query.as_deref(), limit))
+    {
+        Ok(journeys) => CommandResult::ok(journeys),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
 #[tauri::command]
 pub fn clear_history_range(
     state: State<AppState>,
     start: Option<String>,
     end: Option<String>,
 ) -> CommandResult<()> {
-    let start = start
-        .as_deref()
-        .and_then(|s| DateTime::<FixedOffset>::parse_from_rfc3339(s).ok())
-        .map(|dt| dt.with_timezone(&Utc));
-    let end = end
-        .as_deref()
-        .and_then(|s| DateTime::<FixedOffset>::parse_from_rfc3339(s).ok())
-        .map(|dt| dt.with_timezone(&Utc));
+    let start = parse_range_bound(start);
+    let end = parse_range_bound(end);
 
     match state.with_browser(|browser| browser.clear_history_range(start, end)) {
         Ok(()) => CommandResult::ok(()),
@@ -119,122 +296,134 @@ pub fn clear_history_range(
 }
 
 #[tauri::command]
-pub async fn probe_url(url: String) -> CommandResult<ProbeInfo> {
-    let trimmed = url.trim();
-    if trimmed.is_empty() {
-        return CommandResult::ok(ProbeInfo {
-            ok: false,
-            status: None,
-            final_url: None,
-            error_kind: Some("invalid_url".to_string()),
-            error_message: Some("URL is empty".to_string()),
-        });
-    }
-
-    if trimmed.starts_with("about:") || trimmed.starts_with("axiom:") {
-        return CommandResult::ok(ProbeInfo {
-            ok: true,
-            status: None,
-            final_url: Some(trimmed.to_string()),
-            error_kind: None,
-            error_message: None,
-        });
-    }
-
-    let parsed = match url::Url::parse(trimmed) {
-        Ok(u) => u,
-        Err(e) => {
-            return CommandResult::ok(ProbeInfo {
-                ok: false,
-                status: None,
-                final_url: None,
-                error_kind: Some("invalid_url".to_string()),
-                error_message: Some(e.to_string()),
-            });
-        }
-    };
+pub async fn probe_url(url: String) -> CommandResult<axiom_core::ProbeInfo> {
+    CommandResult::ok(axiom_core::probe_url(&url).await)
+}
 
-    if parsed.scheme() != "http" && parsed.scheme() != "https" {
-        return CommandResult::ok(ProbeInfo {
-            ok: true,
-            status: None,
-            final_url: Some(trimmed.to_string()),
-            error_kind: None,
-            error_message: None,
-        });
+/// Reports the page's first OpenSearch `<link>` back through `report_opensearch_link`
+const OPENSEARCH_LINK_SCRIPT: &str = r#"(() => {
+  try {
+    const link = document.querySelector(
+      'link[rel~="search" i][type="application/opensearchdescription+xml" i][href]'
+    );
+    if (link && window.__TAURI_INTERNALS__) {
+      window.__TAURI_INTERNALS__.invoke('report_opensearch_link', { href: link.href });
     }
+  } catch {}
+})();"#;
 
-    let client = match reqwest::Client::builder()
+/// Ask a loaded page for its OpenSearch description link
+///
+/// Runs once per origin per run and never in private sessions. The link is
+/// read from the page the user already has; only the description document
+/// itself is fetched.
+pub(crate) fn request_opensearch_link<R: tauri::Runtime>(
+    state: &AppState,
+    webview: &tauri::Webview<R>,
+    tab_id: &str,
+    page_url: &url::Url,
+) {
+    let private = state
+        .with_browser(|browser| Ok(browser.is_private_tab(tab_id)))
+        .unwrap_or(true);
+    if private || !state.begin_opensearch_check(&page_url.origin().ascii_serialization()) {
+        return;
+    }
+
+    state.expect_opensearch_report(webview.label());
+    let _ = webview.eval(OPENSEARCH_LINK_SCRIPT);
+}
+
+/// A tab's page reporting its OpenSearch link; offers the engine to the UI
+#[tauri::command]
+pub fn report_opensearch_link(
+    app: AppHandle,
+    webview: tauri::Webview,
+    state: State<AppState>,
+    href: String,
+) -> CommandResult<()> {
+    // Only webviews that were just asked may report, once
+    if !state.take_opensearch_report(webview.label()) {
+        return CommandResult::err("Unexpected OpenSearch report".to_string());
+    }
+    let Some((window_label, tab_id)) = app
+        .try_state::<WebviewManager>()
+        .and_then(|manager| manager.tab_for_webview(webview.label()))
+    else {
+        return CommandResult::err("Not a tab webview".to_string());
+    };
+    let (Ok(page_url), Ok(href)) = (webview.url(), url::Url::parse(&href)) else {
+        return CommandResult::err("Invalid OpenSearch link".to_string());
+    };
+    if !matches!(href.scheme(), "http" | "https") {
+        return CommandResult::err(format!("Unsupported OpenSearch link: {}", href));
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let Some(engine) = fetch_opensearch_engine(href).await else {
+            return;
+        };
+
+        let known = app
+            .try_state::<AppState>()
+            .and_then(|state| {
+                state
+                    .with_browser(|browser| browser.has_search_engine(&engine.template))
+                    .ok()
+            })
+            .unwrap_or(true);
+        if known {
+            return;
+        }
+
+        let offer = SearchEngineOffer {
+            tab_id,
+            page_url: page_url.to_string(),
+            engine,
+        };
+        let _ = app.emit_to(
+            super::ui_webview_label(&window_label).as_str(),
+            "search-engine-discovered",
+            offer,
+        );
+    });
+
+    CommandResult::ok(())
+}
+
+async fn fetch_opensearch_engine(href: url::Url) -> Option<axiom_core::NewSearchEngine> {
+    let client = reqwest::Client::builder()
         .redirect(Policy::limited(5))
-        .timeout(Duration::from_secs(6))
+        .timeout(Duration::from_secs(8))
         .build()
-    {
-        Ok(c) => c,
+        .ok()?;
+
+    let xml = fetch_limited(&client, href.clone(), OPENSEARCH_XML_LIMIT).await?;
+    match axiom_core::parse_opensearch(&xml, Some(&href)) {
+        Ok(engine) => Some(engine),
         Err(e) => {
-            return CommandResult::ok(ProbeInfo {
-                ok: false,
-                status: None,
-                final_url: None,
-                error_kind: Some("client".to_string()),
-                error_message: Some(e.to_string()),
-            });
+            tracing::debug!(url = %href, error = %e, "Ignoring OpenSearch description");
+            None
         }
-    };
+    }
+}
 
-    let response = match client.head(parsed.clone()).send().await {
-        Ok(resp) => Ok(resp),
-        Err(_) => {
-            client
-                .get(parsed)
-                .header(reqwest::header::RANGE, "bytes=0-0")
-                .send()
-                .await
-        }
-    };
+/// Body of a successful GET as text, truncated to `limit` bytes
+async fn fetch_limited(client: &reqwest::Client, url: url::Url, limit: usize) -> Option<String> {
+    let resp = client.get(url).send().await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
 
-    match response {
-        Ok(resp) => CommandResult::ok(ProbeInfo {
-            ok: true,
-            status: Some(resp.status().as_u16()),
-            final_url: Some(resp.url().to_string()),
-            error_kind: None,
-            error_message: None,
-        }),
-        Err(e) => {
-            let msg = e.to_string();
-            let mut kind = if e.is_timeout() {
-                "timeout"
-            } else if e.is_connect() {
-                "connect"
-            } else if e.is_request() {
-                "request"
-            } else {
-                "unknown"
-            }
-            .to_string();
-
-            let lowered = msg.to_lowercase();
-            if kind == "connect" {
-                if lowered.contains("dns")
-                    || lowered.contains("resolve")
-                    || lowered.contains("name")
-                {
-                    kind = "dns".to_string();
-                } else if lowered.contains("tls")
-                    || lowered.contains("certificate")
-                    || lowered.contains("handshake")
-                {
-                    kind = "tls".to_string();
-                }
-            }
-
-            CommandResult::ok(ProbeInfo {
-                ok: false,
-                status: None,
-                final_url: None,
-                error_kind: Some(kind),
-                error_message: Some(msg),
-            })
+    let mut body = Vec::new();
+    let mut stream = resp.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        body.extend_from_slice(&chunk.ok()?);
+        if body.len() >= limit {
+            body.truncate(limit);
+            break;
         }
     }
+
+    Some(String::from_utf8_lossy(&body).into_owned())
 }
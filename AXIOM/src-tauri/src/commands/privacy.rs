@@ -5,9 +5,11 @@ use reqwest::redirect::Policy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::time::Duration;
-use tauri::State;
+use tauri::{AppHandle, Manager, State, Window};
 
+use super::downloads::DownloadRuntime;
 use super::tabs::CommandResult;
+use super::webview::WebviewManager;
 use crate::state::AppState;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -90,17 +92,66 @@ pub fn set_permission(
     }
 }
 
+/// Forget a domain and its subdomains, closing its tabs' webviews first
 #[tauri::command]
-pub fn should_block_url(state: State<AppState>, url: String) -> CommandResult<bool> {
-    match state.with_browser(|browser| Ok(browser.should_block_url(&url))) {
+pub fn forget_site(
+    app: AppHandle,
+    state: State<AppState>,
+    domain: String,
+    include_bookmarks: bool,
+) -> CommandResult<axiom_core::ForgetSiteSummary> {
+    let tab_ids: Vec<String> = state
+        .with_browser(|browser| browser.tabs_on_domain(&domain))
+        .map(|tabs| tabs.into_iter().map(|tab| tab.id).collect())
+        .unwrap_or_default();
+    if let Some(manager) = app.try_state::<WebviewManager>() {
+        for tab_id in &tab_ids {
+            for label in manager.unregister_tab_webviews(tab_id) {
+                if let Some(webview) = app.get_webview(&label) {
+                    let _ = webview.close();
+                }
+            }
+        }
+    }
+
+    match state.with_browser(|browser| browser.forget_site(&domain, include_bookmarks)) {
+        Ok(summary) => {
+            if let Some(runtime) = app.try_state::<DownloadRuntime>() {
+                for download_id in &summary.download_ids {
+                    runtime.cancel(download_id);
+                }
+            }
+            CommandResult::ok(summary)
+        }
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn should_block_url(
+    window: Window,
+    state: State<AppState>,
+    url: String,
+) -> CommandResult<bool> {
+    let session_id = match state.session_id_for_window(window.label()) {
+        Ok(id) => id,
+        Err(e) => return CommandResult::err(e.to_string()),
+    };
+
+    match state.with_browser(|browser| browser.should_block_url_in_session(&session_id, &url)) {
         Ok(should_block) => CommandResult::ok(should_block),
         Err(e) => CommandResult::err(e.to_string()),
     }
 }
 
 #[tauri::command]
-pub fn clean_url(state: State<AppState>, url: String) -> CommandResult<String> {
-    match state.with_browser(|browser| Ok(browser.clean_url(&url))) {
+pub fn clean_url(window: Window, state: State<AppState>, url: String) -> CommandResult<String> {
+    let session_id = match state.session_id_for_window(window.label()) {
+        Ok(id) => id,
+        Err(e) => return CommandResult::err(e.to_string()),
+    };
+
+    match state.with_browser(|browser| browser.clean_url_in_session(&session_id, &url)) {
         Ok(cleaned) => CommandResult::ok(cleaned),
         Err(e) => CommandResult::err(e.to_string()),
     }
@@ -1,8 +1,9 @@
 //! Session management commands
 use serde::{Deserialize, Serialize};
-use tauri::{State, Window};
+use tauri::{AppHandle, Emitter, Manager, State, Window};
 
 use super::tabs::CommandResult;
+use super::webview::WebviewManager;
 use crate::state::AppState;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -11,6 +12,9 @@ pub struct SessionInfo {
     pub name: String,
     pub is_active: bool,
     pub tab_count: usize,
+    pub isolated: bool,
+    pub archived: bool,
+    pub private: bool,
 }
 
 impl SessionInfo {
@@ -21,6 +25,9 @@ impl SessionInfo {
             name: session.name,
             is_active,
             tab_count,
+            isolated: session.isolated,
+            archived: session.is_archived(),
+            private: session.private,
         }
     }
 }
@@ -71,26 +78,28 @@ pub fn create_session(state: State<AppState>, name: String) -> CommandResult<Ses
     }
 }
 
+/// Create a private session: no history, no saved tabs, storage wiped on close
+#[tauri::command]
+pub fn create_private_session(state: State<AppState>, name: String) -> CommandResult<SessionInfo> {
+    match state.with_browser(|browser| browser.create_private_session(name)) {
+        Ok(session) => CommandResult::ok(SessionInfo::from_session(session, false)),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
 #[tauri::command]
 pub fn switch_session(
     window: Window,
     state: State<AppState>,
     session_id: String,
 ) -> CommandResult<SessionInfo> {
-    state.set_session_for_window(window.label(), session_id.clone());
+    let session = match state.with_browser(|browser| browser.open_session(&session_id)) {
+        Ok(session) => session,
+        Err(e) => return CommandResult::err(e.to_string()),
+    };
+    state.set_session_for_window(window.label(), session_id);
 
-    match state.with_browser(|browser| {
-        browser
-            .session_manager()
-            .load_tabs_for_session(&session_id)?;
-        browser
-            .session_manager()
-            .get_session(&session_id)
-            .map_err(Into::into)
-    }) {
-        Ok(session) => CommandResult::ok(SessionInfo::from_session(session, true)),
-        Err(e) => CommandResult::err(e.to_string()),
-    }
+    CommandResult::ok(SessionInfo::from_session(session, true))
 }
 
 #[tauri::command]
@@ -110,15 +119,222 @@ pub fn rename_session(
     }
 }
 
+/// Delete a session, closing its tabs' webviews first so their storage can be removed
 #[tauri::command]
-pub fn delete_session(state: State<AppState>, session_id: String) -> CommandResult<()> {
+pub fn delete_session(
+    app: AppHandle,
+    state: State<AppState>,
+    session_id: String,
+) -> CommandResult<()> {
+    let tab_ids = state
+        .with_browser(|browser| Ok(browser.session_manager().get_session(&session_id)?))
+        .map(|session| session.tab_order)
+        .unwrap_or_default();
+    if let Some(manager) = app.try_state::<WebviewManager>() {
+        for tab_id in &tab_ids {
+            for label in manager.unregister_tab_webviews(tab_id) {
+                if let Some(webview) = app.get_webview(&label) {
+                    let _ = webview.close();
+                }
+            }
+        }
+    }
+
+    match state.with_browser(|browser| browser.delete_session(&session_id)) {
+        Ok(()) => CommandResult::ok(()),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn set_session_isolated(
+    state: State<AppState>,
+    session_id: String,
+    isolated: bool,
+) -> CommandResult<SessionInfo> {
+    match state.with_browser(|browser| browser.set_session_isolated(&session_id, isolated)) {
+        Ok(session) => CommandResult::ok(SessionInfo::from_session(session, false)),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn get_session_storage_size(state: State<AppState>, session_id: String) -> CommandResult<u64> {
+    match state.with_browser(|browser| browser.session_storage_size(&session_id)) {
+        Ok(bytes) => CommandResult::ok(bytes),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn restore_previous_session(state: State<AppState>) -> CommandResult<Vec<SessionInfo>> {
+    match state.with_browser(|browser| browser.restore_previous_session()) {
+        Ok(sessions) => CommandResult::ok(
+            sessions
+                .into_iter()
+                .map(|s| SessionInfo::from_session(s, false))
+                .collect(),
+        ),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn dismiss_session_recovery(state: State<AppState>) -> CommandResult<()> {
     match state.with_browser(|browser| {
-        browser
-            .session_manager()
-            .delete_session(&session_id)
-            .map_err(Into::into)
+        browser.dismiss_recovery();
+        Ok(())
     }) {
         Ok(()) => CommandResult::ok(()),
         Err(e) => CommandResult::err(e.to_string()),
     }
 }
+
+#[tauri::command]
+pub fn export_session(
+    state: State<AppState>,
+    session_id: String,
+    format: String,
+) -> CommandResult<String> {
+    let format = match format.parse::<axiom_core::SessionFormat>() {
+        Ok(format) => format,
+        Err(e) => return CommandResult::err(e.to_string()),
+    };
+
+    match state.with_browser(|browser| browser.export_session(&session_id, format)) {
+        Ok(data) => CommandResult::ok(data),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionImportInfo {
+    pub session: SessionInfo,
+    /// Entries left out of the import (disallowed schemes, invalid URLs)
+    pub skipped: Vec<axiom_core::SkippedTab>,
+}
+
+#[tauri::command]
+pub fn import_session(
+    state: State<AppState>,
+    format: String,
+    data: String,
+    name: Option<String>,
+) -> CommandResult<SessionImportInfo> {
+    let format = match format.parse::<axiom_core::SessionFormat>() {
+        Ok(format) => format,
+        Err(e) => return CommandResult::err(e.to_string()),
+    };
+
+    match state.with_browser(|browser| browser.import_session(format, &data, name)) {
+        Ok(import) => CommandResult::ok(SessionImportInfo {
+            session: SessionInfo::from_session(import.session, false),
+            skipped: import.skipped,
+        }),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn list_routing_rules(state: State<AppState>) -> CommandResult<Vec<axiom_core::RoutingRule>> {
+    match state.with_browser(|browser| Ok(browser.list_routing_rules())) {
+        Ok(rules) => CommandResult::ok(rules),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn add_routing_rule(
+    state: State<AppState>,
+    pattern: axiom_core::RoutePattern,
+    session_id: String,
+) -> CommandResult<axiom_core::RoutingRule> {
+    match state.with_browser(|browser| browser.add_routing_rule(pattern, session_id)) {
+        Ok(rule) => CommandResult::ok(rule),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn update_routing_rule(
+    state: State<AppState>,
+    rule_id: String,
+    pattern: axiom_core::RoutePattern,
+    session_id: String,
+    enabled: bool,
+) -> CommandResult<axiom_core::RoutingRule> {
+    match state
+        .with_browser(|browser| browser.update_routing_rule(&rule_id, pattern, session_id, enabled))
+    {
+        Ok(rule) => CommandResult::ok(rule),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn remove_routing_rule(state: State<AppState>, rule_id: String) -> CommandResult<()> {
+    match state.with_browser(|browser| browser.remove_routing_rule(&rule_id)) {
+        Ok(()) => CommandResult::ok(()),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn get_archived_sessions(state: State<AppState>) -> CommandResult<Vec<SessionInfo>> {
+    match state.with_browser(|browser| Ok(browser.list_archived_sessions())) {
+        Ok(sessions) => CommandResult::ok(
+            sessions
+                .into_iter()
+                .map(|s| SessionInfo::from_session(s, false))
+                .collect(),
+        ),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn archive_session(
+    app: AppHandle,
+    state: State<AppState>,
+    session_id: String,
+) -> CommandResult<SessionInfo> {
+    let session = match state.with_browser(|browser| browser.archive_session(&session_id)) {
+        Ok(session) => session,
+        Err(e) => return CommandResult::err(e.to_string()),
+    };
+
+    // Windows showing the archived session move to the active session
+    let fallback = state.with_browser(|browser| {
+        let active = browser.session_manager().active_session()?;
+        browser.restore_session_tabs(&active.id)?;
+        Ok(active.id)
+    });
+    if let Ok(fallback) = fallback {
+        for label in state.reassign_session_windows(&session_id, &fallback) {
+            let _ = app.emit_to(super::ui_webview_label(&label), "session-changed", ());
+        }
+    }
+
+    CommandResult::ok(SessionInfo::from_session(session, false))
+}
+
+#[tauri::command]
+pub fn unarchive_session(state: State<AppState>, session_id: String) -> CommandResult<SessionInfo> {
+    match state.with_browser(|browser| browser.unarchive_session(&session_id)) {
+        Ok(session) => CommandResult::ok(SessionInfo::from_session(session, false)),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn search_archived_sessions(
+    state: State<AppState>,
+    query: String,
+    limit: Option<usize>,
+) -> CommandResult<Vec<axiom_core::ArchivedTab>> {
+    let limit = limit.unwrap_or(20);
+    match state.with_browser(|browser| browser.search_archived_sessions(&query, limit)) {
+        Ok(matches) => CommandResult::ok(matches),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
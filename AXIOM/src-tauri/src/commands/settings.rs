@@ -6,7 +6,10 @@ use tauri::{AppHandle, Manager, State, Theme, Window};
 use super::tabs::CommandResult;
 use super::webview::WebviewManager;
 use crate::state::AppState;
-use axiom_core::Bookmark;
+use axiom_core::{
+    Bookmark, BookmarkFolders, BookmarkTag, ImportReport, ImportSource, SessionSettings,
+    SmartFolder, SmartFolderQuery,
+};
 
 const FORCE_DARK_STYLE_ID: &str = "axiom-force-dark";
 const FORCE_DARK_ENABLE_SCRIPT: &str = r#"
@@ -130,12 +133,15 @@ pub(crate) fn platform_theme_for(ui_theme: &str) -> Option<Theme> {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SettingsInfo {
     pub search_engine: String,
+    pub search_engine_id: String,
     pub theme: Option<String>,
     pub bookmarks_bar_visible: bool,
     pub autofill_enabled: bool,
     pub autofill_name: Option<String>,
     pub autofill_email: Option<String>,
     pub password_save_prompt_enabled: bool,
+    pub restore_eager_tabs: usize,
+    pub download_dir: String,
 }
 
 #[tauri::command]
@@ -143,12 +149,15 @@ pub fn get_settings(state: State<AppState>) -> CommandResult<SettingsInfo> {
     match state.with_browser(|browser| {
         Ok(SettingsInfo {
             search_engine: browser.get_search_engine(),
+            search_engine_id: browser.default_search_engine()?.id,
             theme: browser.get_theme()?,
             bookmarks_bar_visible: browser.get_bookmarks_bar_visible()?,
             autofill_enabled: browser.get_autofill_enabled()?,
             autofill_name: browser.get_autofill_name()?,
             autofill_email: browser.get_autofill_email()?,
             password_save_prompt_enabled: browser.get_password_save_prompt_enabled()?,
+            restore_eager_tabs: browser.get_restore_eager_tab_count()?,
+            download_dir: browser.get_download_dir()?.display().to_string(),
         })
     }) {
         Ok(settings) => CommandResult::ok(settings),
@@ -156,16 +165,81 @@ pub fn get_settings(state: State<AppState>) -> CommandResult<SettingsInfo> {
     }
 }
 
+/// Make the engine with this id the default
 #[tauri::command]
 pub fn set_search_engine(state: State<AppState>, engine: String) -> CommandResult<()> {
-    let template = match engine.to_lowercase().as_str() {
-        "google" => "https://www.google.com/search?q=%s",
-        "bing" => "https://www.bing.com/search?q=%s",
-        "duckduckgo" => "https://duckduckgo.com/?q=%s",
-        _ => return CommandResult::err("Unsupported search engine".to_string()),
-    };
+    match state.with_browser(|browser| browser.set_default_search_engine(&engine)) {
+        Ok(_) => CommandResult::ok(()),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn get_search_engines(state: State<AppState>) -> CommandResult<Vec<axiom_core::SearchEngine>> {
+    match state.with_browser(|browser| browser.list_search_engines()) {
+        Ok(engines) => CommandResult::ok(engines),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn add_search_engine(
+    state: State<AppState>,
+    engine: axiom_core::NewSearchEngine,
+) -> CommandResult<axiom_core::SearchEngine> {
+    match state.with_browser(|browser| browser.add_search_engine(engine)) {
+        Ok(added) => CommandResult::ok(added),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
 
-    match state.with_browser(|browser| browser.set_search_engine(template.to_string())) {
+#[tauri::command]
+pub fn remove_search_engine(state: State<AppState>, engine_id: String) -> CommandResult<()> {
+    match state.with_browser(|browser| browser.remove_search_engine(&engine_id)) {
+        Ok(()) => CommandResult::ok(()),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn get_search_keywords(
+    state: State<AppState>,
+) -> CommandResult<Vec<axiom_core::SearchKeyword>> {
+    match state.with_browser(|browser| Ok(browser.list_search_keywords())) {
+        Ok(keywords) => CommandResult::ok(keywords),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn add_search_keyword(
+    state: State<AppState>,
+    keyword: String,
+    name: String,
+    template: String,
+) -> CommandResult<axiom_core::SearchKeyword> {
+    match state.with_browser(|browser| browser.add_search_keyword(&keyword, &name, &template)) {
+        Ok(added) => CommandResult::ok(added),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn update_search_keyword(
+    state: State<AppState>,
+    keyword: String,
+    name: String,
+    template: String,
+) -> CommandResult<axiom_core::SearchKeyword> {
+    match state.with_browser(|browser| browser.update_search_keyword(&keyword, &name, &template)) {
+        Ok(updated) => CommandResult::ok(updated),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn remove_search_keyword(state: State<AppState>, keyword: String) -> CommandResult<()> {
+    match state.with_browser(|browser| browser.remove_search_keyword(&keyword)) {
         Ok(()) => CommandResult::ok(()),
         Err(e) => CommandResult::err(e.to_string()),
     }
@@ -195,6 +269,35 @@ pub fn set_theme(
     }
 }
 
+#[tauri::command]
+pub fn get_session_settings(
+    state: State<AppState>,
+    session_id: String,
+) -> CommandResult<SessionSettings> {
+    match state.with_browser(|browser| browser.get_session_settings(&session_id)) {
+        Ok(settings) => CommandResult::ok(settings),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn set_session_settings(
+    state: State<AppState>,
+    session_id: String,
+    settings: SessionSettings,
+) -> CommandResult<SessionSettings> {
+    if let Some(theme) = settings.theme.as_deref() {
+        if theme != "light" && theme != "dark" {
+            return CommandResult::err("Unsupported theme".to_string());
+        }
+    }
+
+    match state.with_browser(|browser| browser.set_session_settings(&session_id, settings)) {
+        Ok(settings) => CommandResult::ok(settings),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
 #[tauri::command]
 pub fn get_bookmarks(state: State<AppState>) -> CommandResult<Vec<Bookmark>> {
     match state.with_browser(|browser| browser.get_bookmarks()) {
@@ -208,18 +311,42 @@ pub fn add_bookmark(
     state: State<AppState>,
     title: String,
     url: String,
-    folder: Option<String>,
-) -> CommandResult<Vec<Bookmark>> {
-    match state.with_browser(|browser| browser.add_bookmark(title, url, folder)) {
-        Ok(bookmarks) => CommandResult::ok(bookmarks),
+    parent_id: Option<i64>,
+) -> CommandResult<Bookmark> {
+    match state.with_browser(|browser| browser.add_bookmark(&title, &url, parent_id)) {
+        Ok(bookmark) => CommandResult::ok(bookmark),
         Err(e) => CommandResult::err(e.to_string()),
     }
 }
 
 #[tauri::command]
-pub fn remove_bookmark(state: State<AppState>, url: String) -> CommandResult<Vec<Bookmark>> {
-    match state.with_browser(|browser| browser.remove_bookmark(&url)) {
-        Ok(bookmarks) => CommandResult::ok(bookmarks),
+pub fn add_bookmark_folder(
+    state: State<AppState>,
+    title: String,
+    parent_id: Option<i64>,
+) -> CommandResult<Bookmark> {
+    match state.with_browser(|browser| browser.add_bookmark_folder(&title, parent_id)) {
+        Ok(folder) => CommandResult::ok(folder),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn add_bookmark_separator(
+    state: State<AppState>,
+    parent_id: Option<i64>,
+) -> CommandResult<Bookmark> {
+    match state.with_browser(|browser| browser.add_bookmark_separator(parent_id)) {
+        Ok(separator) => CommandResult::ok(separator),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+/// Remove a bookmark, separator or folder (with its contents)
+#[tauri::command]
+pub fn remove_bookmark(state: State<AppState>, id: i64) -> CommandResult<usize> {
+    match state.with_browser(|browser| browser.remove_bookmark(id)) {
+        Ok(removed) => CommandResult::ok(removed),
         Err(e) => CommandResult::err(e.to_string()),
     }
 }
@@ -227,25 +354,119 @@ pub fn remove_bookmark(state: State<AppState>, url: String) -> CommandResult<Vec
 #[tauri::command]
 pub fn update_bookmark(
     state: State<AppState>,
-    old_url: String,
+    id: i64,
     title: String,
     url: String,
-    folder: Option<String>,
-) -> CommandResult<Vec<Bookmark>> {
-    match state.with_browser(|browser| browser.update_bookmark(&old_url, title, url, folder)) {
-        Ok(bookmarks) => CommandResult::ok(bookmarks),
+    description: String,
+    tags: Option<Vec<String>>,
+) -> CommandResult<Bookmark> {
+    match state.with_browser(|browser| {
+        browser.update_bookmark(id, &title, &url, &description, tags.as_deref())
+    }) {
+        Ok(bookmark) => CommandResult::ok(bookmark),
         Err(e) => CommandResult::err(e.to_string()),
     }
 }
 
 #[tauri::command]
-pub fn get_bookmark_folders(state: State<AppState>) -> CommandResult<Vec<String>> {
+pub fn move_bookmark(
+    state: State<AppState>,
+    id: i64,
+    parent_id: Option<i64>,
+    position: Option<usize>,
+) -> CommandResult<Bookmark> {
+    match state.with_browser(|browser| browser.move_bookmark(id, parent_id, position)) {
+        Ok(bookmark) => CommandResult::ok(bookmark),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn get_bookmark_folders(state: State<AppState>) -> CommandResult<BookmarkFolders> {
     match state.with_browser(|browser| browser.get_bookmark_folders()) {
         Ok(folders) => CommandResult::ok(folders),
         Err(e) => CommandResult::err(e.to_string()),
     }
 }
 
+#[tauri::command]
+pub fn set_bookmark_tags(
+    state: State<AppState>,
+    id: i64,
+    tags: Vec<String>,
+) -> CommandResult<Bookmark> {
+    match state.with_browser(|browser| browser.set_bookmark_tags(id, &tags)) {
+        Ok(bookmark) => CommandResult::ok(bookmark),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn get_bookmark_tags(state: State<AppState>) -> CommandResult<Vec<BookmarkTag>> {
+    match state.with_browser(|browser| browser.get_bookmark_tags()) {
+        Ok(tags) => CommandResult::ok(tags),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+/// Returns how many bookmarks carry the renamed tag
+#[tauri::command]
+pub fn rename_bookmark_tag(
+    state: State<AppState>,
+    from: String,
+    to: String,
+) -> CommandResult<usize> {
+    match state.with_browser(|browser| browser.rename_bookmark_tag(&from, &to)) {
+        Ok(count) => CommandResult::ok(count),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn merge_bookmark_tags(
+    state: State<AppState>,
+    sources: Vec<String>,
+    into: String,
+) -> CommandResult<usize> {
+    match state.with_browser(|browser| browser.merge_bookmark_tags(&sources, &into)) {
+        Ok(count) => CommandResult::ok(count),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn add_smart_folder(
+    state: State<AppState>,
+    title: String,
+    query: SmartFolderQuery,
+) -> CommandResult<SmartFolder> {
+    match state.with_browser(|browser| browser.add_smart_folder(&title, query)) {
+        Ok(folder) => CommandResult::ok(folder),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn update_smart_folder(
+    state: State<AppState>,
+    id: i64,
+    title: String,
+    query: SmartFolderQuery,
+) -> CommandResult<SmartFolder> {
+    match state.with_browser(|browser| browser.update_smart_folder(id, &title, query)) {
+        Ok(folder) => CommandResult::ok(folder),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn remove_smart_folder(state: State<AppState>, id: i64) -> CommandResult<()> {
+    match state.with_browser(|browser| browser.remove_smart_folder(id)) {
+        Ok(()) => CommandResult::ok(()),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
 #[tauri::command]
 pub fn export_bookmarks_html(state: State<AppState>) -> CommandResult<String> {
     match state.with_browser(|browser| browser.export_bookmarks_html()) {
@@ -262,6 +483,34 @@ pub fn import_bookmarks_html(state: State<AppState>, html: String) -> CommandRes
     }
 }
 
+/// Dry run of `import_profile`: counts and conflicts, nothing written
+#[tauri::command]
+pub fn preview_profile_import(
+    state: State<AppState>,
+    source: ImportSource,
+    path: String,
+) -> CommandResult<ImportReport> {
+    let path = std::path::PathBuf::from(path.trim());
+    match state.with_browser(|browser| browser.preview_profile_import(source, &path)) {
+        Ok(report) => CommandResult::ok(report),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+/// Merge bookmarks and history from a Firefox or Chromium profile file
+#[tauri::command]
+pub fn import_profile(
+    state: State<AppState>,
+    source: ImportSource,
+    path: String,
+) -> CommandResult<ImportReport> {
+    let path = std::path::PathBuf::from(path.trim());
+    match state.with_browser(|browser| browser.import_profile(source, &path)) {
+        Ok(report) => CommandResult::ok(report),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
 #[tauri::command]
 pub fn set_bookmarks_bar_visibility(state: State<AppState>, visible: bool) -> CommandResult<()> {
     match state.with_browser(|browser| browser.set_bookmarks_bar_visible(visible)) {
@@ -300,3 +549,29 @@ pub fn set_password_save_prompt_enabled(
         Err(e) => CommandResult::err(e.to_string()),
     }
 }
+
+#[tauri::command]
+pub fn set_restore_eager_tabs(state: State<AppState>, count: usize) -> CommandResult<()> {
+    match state.with_browser(|browser| browser.set_restore_eager_tab_count(count)) {
+        Ok(()) => CommandResult::ok(()),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+/// Choose the global download folder; `None` goes back to the default
+#[tauri::command]
+pub fn set_download_dir(state: State<AppState>, dir: Option<String>) -> CommandResult<()> {
+    match state.with_browser(|browser| browser.set_download_dir(dir.as_deref())) {
+        Ok(()) => CommandResult::ok(()),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+/// Install a newer public suffix list; returns the number of rules
+#[tauri::command]
+pub fn update_public_suffix_list(state: State<AppState>, contents: String) -> CommandResult<usize> {
+    match state.with_browser(|browser| browser.update_public_suffix_list(&contents)) {
+        Ok(rules) => CommandResult::ok(rules),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
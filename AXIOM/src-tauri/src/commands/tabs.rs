@@ -1,6 +1,6 @@
 //! Tab management commands
 use serde::{Deserialize, Serialize};
-use tauri::{State, Window};
+use tauri::{AppHandle, State, Window};
 
 use crate::state::AppState;
 
@@ -8,22 +8,34 @@ use crate::state::AppState;
 pub struct TabInfo {
     pub id: String,
     pub url: String,
+    /// The URL for the address bar, with the host as Unicode only when safe
+    pub display_url: String,
+    /// Set when the host is shown as punycode because it may be a homograph
+    pub idn_warning: Option<axiom_core::IdnWarning>,
     pub title: String,
     pub favicon_url: Option<String>,
     pub state: String,
     pub is_loading: bool,
+    pub session_id: String,
 }
 
 impl From<axiom_core::Tab> for TabInfo {
     fn from(tab: axiom_core::Tab) -> Self {
         let is_loading = tab.is_loading();
+        let (display_url, idn_warning) = match axiom_core::url_display(&tab.url) {
+            Some(display) => (display.display_url, display.warning),
+            None => (tab.url.clone(), None),
+        };
         Self {
             id: tab.id,
             url: tab.url,
+            display_url,
+            idn_warning,
             title: tab.title,
             favicon_url: tab.favicon_url,
             state: tab.state.as_str().to_string(),
             is_loading,
+            session_id: tab.session_id,
         }
     }
 }
@@ -53,13 +65,51 @@ impl<T> CommandResult<T> {
     }
 }
 
+/// Create a tab in the session a routing rule sends `url` to, if any
+///
+/// Returns `None` when no rule applies; the caller then opens the tab in its
+/// own session as usual.
+pub(crate) fn create_routed_tab(
+    app: &AppHandle,
+    state: &AppState,
+    session_id: &str,
+    url: &str,
+) -> Option<CommandResult<TabInfo>> {
+    let target = state
+        .with_browser(|browser| Ok(browser.route_url(session_id, url)))
+        .ok()
+        .flatten()?;
+
+    let tab = match state
+        .with_browser(|browser| browser.create_tab_in_session(&target, url.to_string()))
+    {
+        Ok(tab) => tab,
+        Err(e) => return Some(CommandResult::err(e.to_string())),
+    };
+
+    if let Err(e) = super::windows::open_in_session_window(app, state, &target) {
+        return Some(CommandResult::err(format!("Failed to open window: {e}")));
+    }
+
+    Some(CommandResult::ok(tab.into()))
+}
+
 #[tauri::command]
-pub fn create_tab(window: Window, state: State<AppState>, url: String) -> CommandResult<TabInfo> {
+pub fn create_tab(
+    app: AppHandle,
+    window: Window,
+    state: State<AppState>,
+    url: String,
+) -> CommandResult<TabInfo> {
     let session_id = match state.session_id_for_window(window.label()) {
         Ok(id) => id,
         Err(e) => return CommandResult::err(e.to_string()),
     };
 
+    if let Some(result) = create_routed_tab(&app, &state, &session_id, &url) {
+        return result;
+    }
+
     match state.with_browser(|browser| browser.create_tab_in_session(&session_id, url)) {
         Ok(tab) => CommandResult::ok(tab.into()),
         Err(e) => CommandResult::err(e.to_string()),
@@ -68,6 +118,7 @@ pub fn create_tab(window: Window, state: State<AppState>, url: String) -> Comman
 
 #[tauri::command]
 pub fn create_tab_background(
+    app: AppHandle,
     window: Window,
     state: State<AppState>,
     url: String,
@@ -77,6 +128,10 @@ pub fn create_tab_background(
         Err(e) => return CommandResult::err(e.to_string()),
     };
 
+    if let Some(result) = create_routed_tab(&app, &state, &session_id, &url) {
+        return result;
+    }
+
     match state.with_browser(|browser| browser.create_tab_in_session_background(&session_id, url)) {
         Ok(tab) => CommandResult::ok(tab.into()),
         Err(e) => CommandResult::err(e.to_string()),
@@ -109,6 +164,26 @@ pub fn restore_last_closed_tab(window: Window, state: State<AppState>) -> Comman
     }
 }
 
+/// Reopen a specific closed tab listed by `@closed`
+#[tauri::command]
+pub fn restore_closed_tab(
+    window: Window,
+    state: State<AppState>,
+    closed_id: String,
+) -> CommandResult<TabInfo> {
+    let session_id = match state.session_id_for_window(window.label()) {
+        Ok(id) => id,
+        Err(e) => return CommandResult::err(e.to_string()),
+    };
+
+    match state
+        .with_browser(|browser| browser.restore_closed_tab_in_session(&session_id, &closed_id))
+    {
+        Ok(tab) => CommandResult::ok(tab.into()),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
 #[tauri::command]
 pub fn activate_tab(
     window: Window,
@@ -153,8 +228,14 @@ pub fn get_active_tab(window: Window, state: State<AppState>) -> CommandResult<O
 }
 
 #[tauri::command]
-pub fn navigate_tab(state: State<AppState>, tab_id: String, url: String) -> CommandResult<TabInfo> {
-    match state.with_browser(|browser| browser.navigate_tab(&tab_id, url)) {
+pub fn navigate_tab(
+    state: State<AppState>,
+    tab_id: String,
+    url: String,
+    transition: Option<axiom_core::VisitTransition>,
+) -> CommandResult<TabInfo> {
+    let transition = transition.unwrap_or_default();
+    match state.with_browser(|browser| browser.navigate_tab(&tab_id, url, transition)) {
         Ok(tab) => CommandResult::ok(tab.into()),
         Err(e) => CommandResult::err(e.to_string()),
     }
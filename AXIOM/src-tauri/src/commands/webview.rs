@@ -197,6 +197,28 @@ impl WebviewManager {
             .remove(&Self::key(window_label, tab_id))
     }
 
+    /// Unregister a tab's webviews in every window; returns their labels
+    pub fn unregister_tab_webviews(&self, tab_id: &str) -> Vec<String> {
+        let suffix = format!("::{}", tab_id);
+        let mut webviews = self.webviews.write();
+        let keys: Vec<String> = webviews
+            .keys()
+            .filter(|key| key.ends_with(&suffix))
+            .cloned()
+            .collect();
+        keys.iter().filter_map(|key| webviews.remove(key)).collect()
+    }
+
+    /// Window label and tab id of a registered webview
+    pub fn tab_for_webview(&self, label: &str) -> Option<(String, String)> {
+        self.webviews
+            .read()
+            .iter()
+            .find(|(_, value)| value.as_str() == label)
+            .and_then(|(key, _)| key.split_once("::"))
+            .map(|(window, tab)| (window.to_string(), tab.to_string()))
+    }
+
     pub fn get_all_labels(&self, window_label: &str) -> Vec<String> {
         let prefix = format!("{}::", window_label);
         self.webviews
@@ -329,10 +351,12 @@ pub async fn create_webview(
     let app_handle_for_title = app.clone();
     let tab_id_for_title = tab_id.clone();
     let app_handle_for_download = app.clone();
+    let tab_id_for_download = tab_id.clone();
     let ui_label_for_load = ui_label.clone();
     let ui_label_for_title = ui_label.clone();
-    let _app_handle_for_navigation = app.clone();
-    let _ui_label_for_navigation = ui_label.clone();
+    let app_handle_for_navigation = app.clone();
+    let webview_label_for_navigation = webview_label.clone();
+    let tab_id_for_navigation = tab_id.clone();
     let app_handle_for_new_window = app.clone();
     let ui_label_for_new_window = ui_label.clone();
     let tab_id_for_new_window = tab_id.clone();
@@ -343,16 +367,44 @@ pub async fn create_webview(
         .enable_clipboard_access()
         .initialization_script_for_all_frames(PRIVACY_INIT_SCRIPT);
 
-    if let Some(data_directory) = webview_data_directory(&app, &url) {
+    if let Some(data_directory) = webview_data_directory(&app, &tab_id, &url) {
         webview_builder = webview_builder.data_directory(data_directory);
     }
 
     let webview_builder = webview_builder
         .on_navigation(move |url: &url::Url| {
-            if matches!(url.scheme(), "tauri" | "about" | "axiom") {
+            if matches!(url.scheme(), "tauri" | "about" | "axiom")
+                || super::https_only::is_internal_page(url)
+            {
                 return true;
             }
 
+            // HTTPS-Only: cancel and load the upgraded URL (or the fallback page) instead
+            if url.scheme() == "http" {
+                let upgrade = app_handle_for_navigation
+                    .try_state::<AppState>()
+                    .and_then(|state| {
+                        state
+                            .with_browser(|browser| {
+                                browser.https_only_upgrade_for_tab(
+                                    &tab_id_for_navigation,
+                                    url.as_str(),
+                                )
+                            })
+                            .ok()
+                    })
+                    .flatten();
+                if let Some(https_url) = upgrade {
+                    super::https_only::upgrade_navigation(
+                        app_handle_for_navigation.clone(),
+                        webview_label_for_navigation.clone(),
+                        url.to_string(),
+                        https_url,
+                    );
+                    return false;
+                }
+            }
+
             true
         })
         .on_page_load(move |webview: tauri::webview::Webview<_>, payload: tauri::webview::PageLoadPayload<'_>| {
@@ -367,12 +419,26 @@ pub async fn create_webview(
                     PageLoadEvent::Finished => {
                         let Ok((autofill_enabled, name, email, password_save_enabled, theme)) =
                             state.with_browser(|browser| {
+                                // Session overrides win over the global settings
+                                let (name, email, theme) =
+                                    match browser.session_id_for_tab(&tab_id_for_load) {
+                                        Some(session_id) => {
+                                            let (name, email) =
+                                                browser.effective_autofill_profile(&session_id)?;
+                                            (name, email, browser.effective_theme(&session_id)?)
+                                        }
+                                        None => (
+                                            browser.get_autofill_name()?,
+                                            browser.get_autofill_email()?,
+                                            browser.get_theme()?,
+                                        ),
+                                    };
                                 Ok((
                                     browser.get_autofill_enabled()?,
-                                    browser.get_autofill_name()?,
-                                    browser.get_autofill_email()?,
+                                    name,
+                                    email,
                                     browser.get_password_save_prompt_enabled()?,
-                                    browser.get_theme()?,
+                                    theme,
                                 ))
                             })
                         else {
@@ -386,6 +452,13 @@ pub async fn create_webview(
                             return;
                         }
 
+                        super::navigation::request_opensearch_link(
+                            &state,
+                            &webview,
+                            &tab_id_for_load,
+                            &parsed,
+                        );
+
                         let theme_value = theme.as_deref();
                         let script = match theme_value {
                             Some("dark") => force_dark_script(true),
@@ -488,6 +561,28 @@ pub async fn create_webview(
             let _ = app_handle_for_title.emit_to(ui_label_for_title.as_str(), "tabs-updated", ());
         })
         .on_new_window(move |url: url::Url, _features: tauri::webview::NewWindowFeatures| {
+            // Routing rules may send the popup to another session's window
+            if let Some(state) = app_handle_for_new_window.try_state::<AppState>() {
+                let source_session = state
+                    .with_browser(|browser| {
+                        Ok(browser.session_id_for_tab(&tab_id_for_new_window))
+                    })
+                    .ok()
+                    .flatten();
+                if let Some(session_id) = source_session {
+                    if super::tabs::create_routed_tab(
+                        &app_handle_for_new_window,
+                        &state,
+                        &session_id,
+                        url.as_str(),
+                    )
+                    .is_some()
+                    {
+                        return NewWindowResponse::Deny;
+                    }
+                }
+            }
+
             let _ = app_handle_for_new_window.emit_to(
                 ui_label_for_new_window.as_str(),
                 "new-window-requested",
@@ -507,9 +602,16 @@ pub async fn create_webview(
                     .to_string();
 
                 if let Some(state) = app_handle_for_download.try_state::<AppState>() {
-                    if let Ok(download) = state
-                        .with_browser(|browser| browser.create_download(url.to_string(), file_name))
-                    {
+                    if let Ok(download) = state.with_browser(|browser| {
+                        match browser.session_id_for_tab(&tab_id_for_download) {
+                            Some(session_id) => browser.create_download_in_session(
+                                &session_id,
+                                url.to_string(),
+                                file_name,
+                            ),
+                            None => browser.create_download(url.to_string(), file_name),
+                        }
+                    }) {
                         let _ = app_handle_for_download
                             .emit("download-updated", DownloadInfo::from(download));
                     }
@@ -549,23 +651,11 @@ pub async fn create_webview(
     }
 }
 
-fn webview_data_directory(app: &AppHandle, url: &str) -> Option<std::path::PathBuf> {
-    let base = app.path().app_data_dir().ok()?;
-    let host = url::Url::parse(url)
+fn webview_data_directory(app: &AppHandle, tab_id: &str, url: &str) -> Option<std::path::PathBuf> {
+    let state = app.try_state::<AppState>()?;
+    state
+        .with_browser(|browser| Ok(browser.webview_partition_dir(tab_id, url)))
         .ok()
-        .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
-        .unwrap_or_else(|| "blank".to_string());
-
-    let mut safe = String::with_capacity(host.len());
-    for ch in host.chars() {
-        if ch.is_ascii_alphanumeric() || ch == '.' || ch == '-' {
-            safe.push(ch);
-        } else {
-            safe.push('_');
-        }
-    }
-
-    Some(base.join("webview-partitions").join(safe))
 }
 
 #[tauri::command]
@@ -801,7 +891,10 @@ pub async fn reload_webview(app: AppHandle, window: Window, tab_id: String) -> C
     };
 
     match webview.reload() {
-        Ok(()) => CommandResult::ok(()),
+        Ok(()) => {
+            record_reload(&app, &tab_id);
+            CommandResult::ok(())
+        }
         Err(e) => CommandResult::err(format!("Reload failed: {}", e)),
     }
 }
@@ -828,15 +921,28 @@ pub async fn force_reload_webview(
     };
 
     if webview.eval("location.reload(true)").is_ok() {
+        record_reload(&app, &tab_id);
         return CommandResult::ok(());
     }
 
     match webview.reload() {
-        Ok(()) => CommandResult::ok(()),
+        Ok(()) => {
+            record_reload(&app, &tab_id);
+            CommandResult::ok(())
+        }
         Err(e) => CommandResult::err(format!("Force reload failed: {}", e)),
     }
 }
 
+/// Log a reload in the visit history
+fn record_reload(app: &AppHandle, tab_id: &str) {
+    if let Some(state) = app.try_state::<AppState>() {
+        if let Err(e) = state.with_browser(|browser| browser.record_tab_reload(tab_id)) {
+            tracing::warn!("Failed to record reload for tab {}: {}", tab_id, e);
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn stop_webview_loading(
     app: AppHandle,
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::webview::WebviewBuilder;
 use tauri::window::WindowBuilder;
-use tauri::{AppHandle, Emitter, LogicalPosition, LogicalSize, State, WebviewUrl, Window};
+use tauri::{AppHandle, Emitter, LogicalPosition, LogicalSize, Manager, State, WebviewUrl, Window};
 
 use super::tabs::{CommandResult, TabInfo};
 use crate::state::AppState;
@@ -53,6 +53,18 @@ pub fn toggle_fullscreen(window: Window) -> CommandResult<bool> {
     }
 }
 
+#[tauri::command]
+pub fn focus_window(app: AppHandle, label: String) -> CommandResult<()> {
+    let Some(window) = app.get_window(&label) else {
+        return CommandResult::err(format!("Window not found: {label}"));
+    };
+    let _ = window.unminimize();
+    match window.set_focus() {
+        Ok(()) => CommandResult::ok(()),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
 fn next_window_label() -> String {
     let millis = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -61,6 +73,36 @@ fn next_window_label() -> String {
     format!("window-{millis}")
 }
 
+/// Focus the window showing `session_id`, opening a new one if none is
+///
+/// Used when a routing rule sends a URL to a different session.
+pub(crate) fn open_in_session_window(
+    app: &AppHandle,
+    state: &AppState,
+    session_id: &str,
+) -> Result<String, String> {
+    let existing = state
+        .window_for_session(session_id)
+        .filter(|label| app.get_window(label).is_some());
+
+    let window_label = match existing {
+        Some(label) => label,
+        None => {
+            let label = next_window_label();
+            state.set_session_for_window(&label, session_id.to_string());
+            build_browser_window(app, &label)?;
+            label
+        }
+    };
+
+    if let Some(window) = app.get_window(&window_label) {
+        let _ = window.set_focus();
+    }
+    let _ = app.emit_to(super::ui_webview_label(&window_label), "tabs-updated", ());
+
+    Ok(window_label)
+}
+
 #[tauri::command]
 pub fn create_window(app: AppHandle, state: State<AppState>) -> CommandResult<NewWindowInfo> {
     let window_label = next_window_label();
@@ -9,6 +9,7 @@ mod commands;
 mod state;
 
 use commands::downloads::DownloadRuntime;
+use commands::link_checker::LinkCheckRuntime;
 use commands::webview::WebviewManager;
 use state::AppState;
 use tauri::webview::WebviewBuilder;
@@ -22,9 +23,16 @@ pub fn run() {
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .register_uri_scheme_protocol(commands::https_only::SCHEME, |ctx, request| {
+            commands::https_only::handle_request(ctx.app_handle(), ctx.webview_label(), &request)
+        })
         .setup(|app| {
-            // Initialize browser state
-            let state = AppState::new()?;
+            // Initialize browser state (webview partitions live in the app data dir)
+            let mut config = axiom_core::Config::default();
+            if let Ok(app_data_dir) = app.path().app_data_dir() {
+                config.webview_partitions_dir = app_data_dir.join("webview-partitions");
+            }
+            let state = AppState::new(config)?;
             state.initialize()?;
 
             let initial_theme = state
@@ -40,6 +48,7 @@ pub fn run() {
 
             // Initialize download runtime
             app.manage(DownloadRuntime::default());
+            app.manage(LinkCheckRuntime::default());
 
             let window_label = "main";
 
@@ -70,6 +79,23 @@ pub fn run() {
             )?;
             let _ = ui_webview.show();
 
+            // URLs passed on the command line (external links) honour routing rules
+            let state = app.state::<AppState>();
+            if let Ok(session_id) = state.session_id_for_window(window_label) {
+                for url in std::env::args()
+                    .skip(1)
+                    .filter(|arg| arg.starts_with("http://") || arg.starts_with("https://"))
+                {
+                    if commands::tabs::create_routed_tab(app.handle(), &state, &session_id, &url)
+                        .is_none()
+                    {
+                        let _ = state.with_browser(|browser| {
+                            browser.create_tab_in_session(&session_id, url)
+                        });
+                    }
+                }
+            }
+
             tracing::info!("AXIOM Browser started");
 
             Ok(())
@@ -82,11 +108,13 @@ pub fn run() {
             commands::windows::open_url_in_new_window,
             commands::windows::detach_tab_to_new_window,
             commands::windows::toggle_fullscreen,
+            commands::windows::focus_window,
             // Tab commands
             commands::tabs::create_tab,
             commands::tabs::create_tab_background,
             commands::tabs::close_tab,
             commands::tabs::restore_last_closed_tab,
+            commands::tabs::restore_closed_tab,
             commands::tabs::activate_tab,
             commands::tabs::get_tabs,
             commands::tabs::get_active_tab,
@@ -100,36 +128,88 @@ pub fn run() {
             commands::sessions::get_sessions,
             commands::sessions::get_active_session,
             commands::sessions::create_session,
+            commands::sessions::create_private_session,
             commands::sessions::switch_session,
             commands::sessions::rename_session,
             commands::sessions::delete_session,
+            commands::sessions::set_session_isolated,
+            commands::sessions::get_session_storage_size,
+            commands::sessions::export_session,
+            commands::sessions::import_session,
+            commands::sessions::restore_previous_session,
+            commands::sessions::dismiss_session_recovery,
+            commands::sessions::get_archived_sessions,
+            commands::sessions::archive_session,
+            commands::sessions::unarchive_session,
+            commands::sessions::search_archived_sessions,
+            commands::sessions::list_routing_rules,
+            commands::sessions::add_routing_rule,
+            commands::sessions::update_routing_rule,
+            commands::sessions::remove_routing_rule,
             // Navigation commands
             commands::navigation::resolve_input,
+            commands::navigation::get_command_matches,
+            commands::navigation::get_omnibox_suggestions,
             commands::navigation::probe_url,
+            commands::navigation::report_opensearch_link,
             commands::navigation::search_history,
             commands::navigation::get_recent_history,
+            commands::navigation::get_history_visits,
+            commands::navigation::get_history_journeys,
             commands::navigation::clear_history_range,
             // Privacy commands
             commands::privacy::check_permission,
             commands::privacy::set_permission,
+            commands::privacy::forget_site,
             commands::privacy::should_block_url,
             commands::privacy::clean_url,
             commands::privacy::refresh_filter_lists,
             // Settings commands
             commands::settings::get_settings,
             commands::settings::set_search_engine,
+            commands::settings::get_search_engines,
+            commands::settings::add_search_engine,
+            commands::settings::remove_search_engine,
+            commands::settings::get_search_keywords,
+            commands::settings::add_search_keyword,
+            commands::settings::update_search_keyword,
+            commands::settings::remove_search_keyword,
             commands::settings::set_theme,
+            commands::settings::get_session_settings,
+            commands::settings::set_session_settings,
             commands::settings::get_bookmarks,
             commands::settings::add_bookmark,
+            commands::settings::add_bookmark_folder,
+            commands::settings::add_bookmark_separator,
             commands::settings::remove_bookmark,
             commands::settings::update_bookmark,
+            commands::settings::move_bookmark,
             commands::settings::get_bookmark_folders,
+            commands::settings::set_bookmark_tags,
+            commands::settings::get_bookmark_tags,
+            commands::settings::rename_bookmark_tag,
+            commands::settings::merge_bookmark_tags,
+            commands::settings::add_smart_folder,
+            commands::settings::update_smart_folder,
+            commands::settings::remove_smart_folder,
+            commands::link_checker::start_bookmark_link_check,
+            commands::link_checker::cancel_bookmark_link_check,
+            commands::link_checker::apply_bookmark_redirects,
+            commands::link_checker::remove_dead_bookmarks,
             commands::settings::export_bookmarks_html,
             commands::settings::import_bookmarks_html,
+            commands::settings::preview_profile_import,
+            commands::settings::import_profile,
             commands::settings::set_bookmarks_bar_visibility,
             commands::settings::set_autofill_enabled,
             commands::settings::set_autofill_profile,
             commands::settings::set_password_save_prompt_enabled,
+            commands::settings::set_restore_eager_tabs,
+            commands::settings::set_download_dir,
+            commands::settings::update_public_suffix_list,
+            commands::https_only::get_https_only_settings,
+            commands::https_only::set_https_only_enabled,
+            commands::https_only::remove_https_only_exception,
             // Webview commands
             commands::webview::create_webview,
             commands::webview::navigate_webview,
@@ -154,6 +234,16 @@ pub fn run() {
             // Reader mode
             commands::reader::extract_reader,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running AXIOM browser");
+        .build(tauri::generate_context!())
+        .expect("error while building AXIOM browser")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Clears the crash journal marker; skipped if the process dies
+                if let Some(state) = app.try_state::<AppState>() {
+                    if let Err(e) = state.with_browser(|browser| browser.shutdown()) {
+                        tracing::error!("Failed to record clean shutdown: {}", e);
+                    }
+                }
+            }
+        });
 }
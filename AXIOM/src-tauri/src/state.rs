@@ -1,23 +1,31 @@
 //! Application state management
-use axiom_core::{Browser, Config, Result};
+use axiom_core::{Browser, CancellationToken, Config, Result};
 use parking_lot::RwLock;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 /// Thread-safe application state wrapper
 pub struct AppState {
     browser: Arc<RwLock<Option<Browser>>>,
     window_sessions: Arc<RwLock<HashMap<String, String>>>,
+    /// In-flight omnibox suggestion request per window
+    omnibox_requests: Arc<RwLock<HashMap<String, CancellationToken>>>,
+    /// Origins already checked for an OpenSearch description this run
+    opensearch_checked: Arc<RwLock<HashSet<String>>>,
+    /// Webviews asked to report their page's OpenSearch link, by label
+    opensearch_reports: Arc<RwLock<HashSet<String>>>,
 }
 
 impl AppState {
-    pub fn new() -> Result<Self> {
-        let config = Config::default();
+    pub fn new(config: Config) -> Result<Self> {
         let browser = Browser::new(config)?;
 
         Ok(Self {
             browser: Arc::new(RwLock::new(Some(browser))),
             window_sessions: Arc::new(RwLock::new(HashMap::new())),
+            omnibox_requests: Arc::new(RwLock::new(HashMap::new())),
+            opensearch_checked: Arc::new(RwLock::new(HashSet::new())),
+            opensearch_reports: Arc::new(RwLock::new(HashSet::new())),
         })
     }
 
@@ -61,4 +69,71 @@ impl AppState {
             .write()
             .insert(window_label.to_string(), session_id);
     }
+
+    /// Label of a window currently showing the given session
+    pub fn window_for_session(&self, session_id: &str) -> Option<String> {
+        self.window_sessions
+            .read()
+            .iter()
+            .find(|(_, id)| id.as_str() == session_id)
+            .map(|(label, _)| label.clone())
+    }
+
+    /// Every window label with the session it shows, sorted by label
+    pub fn open_windows(&self) -> Vec<axiom_core::OpenWindow> {
+        let mut windows: Vec<axiom_core::OpenWindow> = self
+            .window_sessions
+            .read()
+            .iter()
+            .map(|(label, session_id)| axiom_core::OpenWindow {
+                label: label.clone(),
+                session_id: session_id.clone(),
+            })
+            .collect();
+        windows.sort_by(|a, b| a.label.cmp(&b.label));
+        windows
+    }
+
+    /// Point every window showing `from` at `to`; returns the affected window labels
+    pub fn reassign_session_windows(&self, from: &str, to: &str) -> Vec<String> {
+        let mut window_sessions = self.window_sessions.write();
+        let mut labels = Vec::new();
+        for (label, session_id) in window_sessions.iter_mut() {
+            if session_id.as_str() == from {
+                *session_id = to.to_string();
+                labels.push(label.clone());
+            }
+        }
+        labels
+    }
+
+    /// Cancel the window's previous omnibox request and register a new one
+    pub fn begin_omnibox_request(&self, window_label: &str) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Some(previous) = self
+            .omnibox_requests
+            .write()
+            .insert(window_label.to_string(), token.clone())
+        {
+            previous.cancel();
+        }
+        token
+    }
+
+    /// True the first time an origin is seen; later calls return false
+    pub fn begin_opensearch_check(&self, origin: &str) -> bool {
+        self.opensearch_checked.write().insert(origin.to_string())
+    }
+
+    /// Accept one OpenSearch report from this webview
+    pub fn expect_opensearch_report(&self, webview_label: &str) {
+        self.opensearch_reports
+            .write()
+            .insert(webview_label.to_string());
+    }
+
+    /// True if a report from this webview was expected; consumes the expectation
+    pub fn take_opensearch_report(&self, webview_label: &str) -> bool {
+        self.opensearch_reports.write().remove(webview_label)
+    }
 }
@@ -52,10 +52,17 @@ const TAB_IDLE_DISCARD_MS = 30 * 60 * 1000;
 const POPUP_AUTO_OPEN_COOLDOWN_MS = 1200;
 let lastPopupAutoOpenAt = 0;
 let bookmarksBarVisible = true;
-let selectedBookmarkUrl = null;
+let selectedBookmarkId = null;
+let bookmarkEditorKind = 'bookmark';
+let bookmarkFolders = { folders: [], smart_folders: [] };
+let selectedSmartFolderId = null;
+let bookmarkTags = [];
+let selectedBookmarkTags = new Set();
+let bookmarkLinkCheckRunning = false;
 let draggingTabId = null;
 let draggingDidDrop = false;
 let uiOverlayDepth = 0;
+let omniboxRequestSeq = 0;
 
 async function invokeCommand(command, args) {
   const result = await invoke(command, args);
@@ -101,12 +108,6 @@ async function endUiOverlay() {
   }
 }
 
-const SEARCH_ENGINES = {
-  google: 'https://www.google.com/search?q=%s',
-  bing: 'https://www.bing.com/search?q=%s',
-  duckduckgo: 'https://duckduckgo.com/?q=%s',
-};
-
 const RELOAD_ICON_SVG = `
   <svg width="16" height="16" viewBox="0 0 16 16" fill="none">
     <path d="M13.5 8a5.5 5.5 0 11-1.5-3.8" stroke="currentColor" stroke-width="1.5" stroke-linecap="round"/>
@@ -147,9 +148,11 @@ const elements = {
   sessionList: null,
   newSessionName: null,
   createSessionBtn: null,
+  createPrivateSessionBtn: null,
   historyModal: null,
   historyList: null,
   historySearch: null,
+  historySort: null,
   historyClearRange: null,
   historyClearStart: null,
   historyClearEnd: null,
@@ -157,18 +160,48 @@ const elements = {
   settingsModal: null,
   searchEngineSelect: null,
   manageBookmarksBtn: null,
+  pslUpdateFile: null,
+  profileImportSource: null,
+  profileImportPath: null,
+  profileImportPreviewBtn: null,
+  profileImportBtn: null,
+  profileImportReport: null,
+  forgetSiteDomain: null,
+  forgetSiteBookmarks: null,
+  forgetSiteBtn: null,
+  forgetSiteReport: null,
   bookmarksModal: null,
   bookmarksSearch: null,
   bookmarksFolderFilter: null,
   bookmarksNewBtn: null,
+  bookmarksNewFolderBtn: null,
+  bookmarksNewSeparatorBtn: null,
+  bookmarksNewSmartBtn: null,
   bookmarksImportFile: null,
   bookmarksExportBtn: null,
+  bookmarksCheckLinksBtn: null,
+  bookmarksApplyRedirectsBtn: null,
+  bookmarksRemoveDeadBtn: null,
+  bookmarksLinkCheckStatus: null,
   bookmarksManagerList: null,
   bookmarksEditor: null,
   bookmarkEditTitle: null,
   bookmarkEditUrl: null,
   bookmarkEditFolder: null,
   bookmarkDeleteBtn: null,
+  bookmarkMoveUpBtn: null,
+  bookmarkMoveDownBtn: null,
+  bookmarkEditTags: null,
+  bookmarkEditDescription: null,
+  bookmarkSmartFields: null,
+  smartFolderTag: null,
+  smartFolderDomain: null,
+  smartFolderText: null,
+  smartFolderDays: null,
+  bookmarksTags: null,
+  bookmarksTagList: null,
+  bookmarksTagTarget: null,
+  bookmarksTagApplyBtn: null,
   bookmarksBar: null,
   bookmarksList: null,
   addBookmarkBtn: null,
@@ -177,6 +210,10 @@ const elements = {
   autofillName: null,
   autofillEmail: null,
   passwordSaveToggle: null,
+  httpsOnlyToggle: null,
+  httpsOnlyExceptions: null,
+  restoreEagerSelect: null,
+  downloadDirInput: null,
   emptyState: null,
   webviewContainer: null,
   tabPlaceholder: null,
@@ -229,9 +266,11 @@ window.addEventListener('DOMContentLoaded', async () => {
   elements.sessionList = document.getElementById('session-list');
   elements.newSessionName = document.getElementById('new-session-name');        
   elements.createSessionBtn = document.getElementById('create-session-btn');
+  elements.createPrivateSessionBtn = document.getElementById('create-private-session-btn');
   elements.historyModal = document.getElementById('history-modal');
   elements.historyList = document.getElementById('history-list');
   elements.historySearch = document.getElementById('history-search');
+  elements.historySort = document.getElementById('history-sort');
   elements.historyClearRange = document.getElementById('history-clear-range');
   elements.historyClearStart = document.getElementById('history-clear-start');
   elements.historyClearEnd = document.getElementById('history-clear-end');
@@ -239,18 +278,48 @@ window.addEventListener('DOMContentLoaded', async () => {
   elements.settingsModal = document.getElementById('settings-modal');
   elements.searchEngineSelect = document.getElementById('search-engine-select');
   elements.manageBookmarksBtn = document.getElementById('manage-bookmarks-btn');
+  elements.pslUpdateFile = document.getElementById('psl-update-file');
+  elements.profileImportSource = document.getElementById('profile-import-source');
+  elements.profileImportPath = document.getElementById('profile-import-path');
+  elements.profileImportPreviewBtn = document.getElementById('profile-import-preview-btn');
+  elements.profileImportBtn = document.getElementById('profile-import-btn');
+  elements.profileImportReport = document.getElementById('profile-import-report');
+  elements.forgetSiteDomain = document.getElementById('forget-site-domain');
+  elements.forgetSiteBookmarks = document.getElementById('forget-site-bookmarks');
+  elements.forgetSiteBtn = document.getElementById('forget-site-btn');
+  elements.forgetSiteReport = document.getElementById('forget-site-report');
   elements.bookmarksModal = document.getElementById('bookmarks-modal');
   elements.bookmarksSearch = document.getElementById('bookmarks-search');
   elements.bookmarksFolderFilter = document.getElementById('bookmarks-folder-filter');
   elements.bookmarksNewBtn = document.getElementById('bookmarks-new-btn');
+  elements.bookmarksNewFolderBtn = document.getElementById('bookmarks-new-folder-btn');
+  elements.bookmarksNewSeparatorBtn = document.getElementById('bookmarks-new-separator-btn');
+  elements.bookmarksNewSmartBtn = document.getElementById('bookmarks-new-smart-btn');
   elements.bookmarksImportFile = document.getElementById('bookmarks-import-file');
   elements.bookmarksExportBtn = document.getElementById('bookmarks-export-btn');
+  elements.bookmarksCheckLinksBtn = document.getElementById('bookmarks-check-links-btn');
+  elements.bookmarksApplyRedirectsBtn = document.getElementById('bookmarks-apply-redirects-btn');
+  elements.bookmarksRemoveDeadBtn = document.getElementById('bookmarks-remove-dead-btn');
+  elements.bookmarksLinkCheckStatus = document.getElementById('bookmarks-link-check-status');
   elements.bookmarksManagerList = document.getElementById('bookmarks-manager-list');
   elements.bookmarksEditor = document.getElementById('bookmarks-editor');
   elements.bookmarkEditTitle = document.getElementById('bookmark-edit-title');
   elements.bookmarkEditUrl = document.getElementById('bookmark-edit-url');
   elements.bookmarkEditFolder = document.getElementById('bookmark-edit-folder');
   elements.bookmarkDeleteBtn = document.getElementById('bookmark-delete-btn');
+  elements.bookmarkMoveUpBtn = document.getElementById('bookmark-move-up-btn');
+  elements.bookmarkMoveDownBtn = document.getElementById('bookmark-move-down-btn');
+  elements.bookmarkEditTags = document.getElementById('bookmark-edit-tags');
+  elements.bookmarkEditDescription = document.getElementById('bookmark-edit-description');
+  elements.bookmarkSmartFields = document.getElementById('bookmark-smart-fields');
+  elements.smartFolderTag = document.getElementById('smart-folder-tag');
+  elements.smartFolderDomain = document.getElementById('smart-folder-domain');
+  elements.smartFolderText = document.getElementById('smart-folder-text');
+  elements.smartFolderDays = document.getElementById('smart-folder-days');
+  elements.bookmarksTags = document.getElementById('bookmarks-tags');
+  elements.bookmarksTagList = document.getElementById('bookmarks-tag-list');
+  elements.bookmarksTagTarget = document.getElementById('bookmarks-tag-target');
+  elements.bookmarksTagApplyBtn = document.getElementById('bookmarks-tag-apply-btn');
   elements.bookmarksBar = document.getElementById('bookmarks-bar');
   elements.bookmarksList = document.getElementById('bookmarks-list');
   elements.addBookmarkBtn = document.getElementById('add-bookmark-btn');
@@ -259,6 +328,10 @@ window.addEventListener('DOMContentLoaded', async () => {
   elements.autofillName = document.getElementById('autofill-name');
   elements.autofillEmail = document.getElementById('autofill-email');
   elements.passwordSaveToggle = document.getElementById('password-save-toggle');
+  elements.httpsOnlyToggle = document.getElementById('https-only-toggle');
+  elements.httpsOnlyExceptions = document.getElementById('https-only-exceptions');
+  elements.restoreEagerSelect = document.getElementById('restore-eager-select');
+  elements.downloadDirInput = document.getElementById('download-dir-input');
   elements.emptyState = document.getElementById('empty-state');
   elements.webviewContainer = document.getElementById('webview-container');
   elements.tabPlaceholder = document.getElementById('tab-placeholder');
@@ -275,18 +348,16 @@ window.addEventListener('DOMContentLoaded', async () => {
   elements.readerWidthIncreaseBtn = document.getElementById('reader-width-increase');
   elements.readerCloseBtn = document.getElementById('reader-close-btn');
 
-  try {
-    await invoke('frontend_ready');
-  } catch (error) {
-    showFatalError('Failed to initialize IPC with Rust core.', String(error));
-    return;
-  }
-
   const listen = window?.__TAURI__?.event?.listen;
   if (typeof listen === 'function') {
     try {
+      await listen('session-recovery-available', (event) => handleSessionRecovery(event.payload));
       await listen('tabs-updated', () => refreshTabs());
+      await listen('session-changed', () => reloadCurrentSession());
+      await listen('search-engine-discovered', (event) => handleSearchEngineDiscovered(event.payload));
       await listen('download-updated', (event) => handleDownloadUpdated(event.payload));
+      await listen('bookmark-link-checked', (event) => handleBookmarkLinkChecked(event.payload));
+      await listen('bookmark-link-check-finished', (event) => handleBookmarkLinkCheckFinished(event.payload));
       await listen('new-window-requested', (event) => handleNewWindowRequested(event.payload));
       await listen('navigation-blocked', (event) => {
         const url = typeof event.payload === 'string' ? event.payload : String(event.payload || '');
@@ -298,6 +369,14 @@ window.addEventListener('DOMContentLoaded', async () => {
     }
   }
 
+  // Listeners are registered first so startup events (crash recovery) are not missed
+  try {
+    await invoke('frontend_ready');
+  } catch (error) {
+    showFatalError('Failed to initialize IPC with Rust core.', String(error));
+    return;
+  }
+
   // Setup event listeners
   setupEventListeners();
 
@@ -326,11 +405,26 @@ async function loadInitialState() {
 
     // Load tabs
     await refreshTabs();
+    await preloadRestoredTabs();
   } catch (error) {
     console.error('Failed to load initial state:', error);
   }
 }
 
+// Restored sessions keep most tabs discarded; webviews are created when a
+// discarded tab is activated. Tabs the eager restore policy kept in the
+// background are loaded up front.
+async function preloadRestoredTabs() {
+  for (const tab of currentTabs) {
+    if (!tab || !tab.id || tab.id === activeTabId || tab.state !== 'background') continue;
+    try {
+      await ensureWebview(tab);
+    } catch (error) {
+      console.warn('Failed to preload restored tab:', error);
+    }
+  }
+}
+
 // ============================================
 // Event Listeners
 // ============================================
@@ -376,6 +470,7 @@ function setupEventListeners() {
   elements.sessionModal.querySelector('.modal-backdrop').addEventListener('click', closeSessionModal);
   elements.sessionModal.querySelector('.modal-close').addEventListener('click', closeSessionModal);
   elements.createSessionBtn.addEventListener('click', createNewSession);
+  elements.createPrivateSessionBtn?.addEventListener('click', createPrivateSession);
   elements.newSessionName.addEventListener('keydown', (e) => {
     if (e.key === 'Enter') createNewSession();
   });
@@ -385,6 +480,9 @@ function setupEventListeners() {
     elements.historyModal.querySelector('.modal-backdrop').addEventListener('click', closeHistoryModal);
     elements.historyModal.querySelector('.modal-close').addEventListener('click', closeHistoryModal);
     elements.historySearch.addEventListener('input', handleHistorySearchInput);
+    elements.historySort?.addEventListener('change', () =>
+      loadHistoryList(elements.historySearch.value.trim())
+    );
     elements.historyClearRange.addEventListener('change', handleHistoryClearRangeChange);
     elements.clearHistoryBtn.addEventListener('click', clearHistoryFromUI);
   }
@@ -412,21 +510,61 @@ function setupEventListeners() {
   if (elements.passwordSaveToggle) {
     elements.passwordSaveToggle.addEventListener('change', handlePasswordSaveToggle);
   }
+  if (elements.restoreEagerSelect) {
+    elements.restoreEagerSelect.addEventListener('change', handleRestoreEagerChange);
+  }
+  if (elements.downloadDirInput) {
+    elements.downloadDirInput.addEventListener('change', handleDownloadDirChange);
+  }
+  if (elements.httpsOnlyToggle) {
+    elements.httpsOnlyToggle.addEventListener('change', handleHttpsOnlyToggle);
+  }
   elements.addBookmarkBtn.addEventListener('click', addBookmarkFromActiveTab);
   if (elements.manageBookmarksBtn) {
     elements.manageBookmarksBtn.addEventListener('click', openBookmarksModal);
   }
 
+  if (elements.pslUpdateFile) {
+    elements.pslUpdateFile.addEventListener('change', updatePublicSuffixListFromFile);
+  }
+
+  if (elements.profileImportPreviewBtn) {
+    const resetImport = () => {
+      elements.profileImportBtn.disabled = true;
+      elements.profileImportReport.textContent = '';
+    };
+    elements.profileImportSource.addEventListener('change', resetImport);
+    elements.profileImportPath.addEventListener('input', resetImport);
+    elements.profileImportPreviewBtn.addEventListener('click', () => runProfileImport(true));
+    elements.profileImportBtn.addEventListener('click', () => runProfileImport(false));
+  }
+
+  if (elements.forgetSiteBtn) {
+    elements.forgetSiteBtn.addEventListener('click', forgetSite);
+    elements.forgetSiteDomain.addEventListener('input', () => {
+      elements.forgetSiteReport.textContent = '';
+    });
+  }
+
   if (elements.bookmarksModal) {
     elements.bookmarksModal.querySelector('.modal-backdrop').addEventListener('click', closeBookmarksModal);
     elements.bookmarksModal.querySelector('.modal-close').addEventListener('click', closeBookmarksModal);
     elements.bookmarksSearch.addEventListener('input', renderBookmarksManager);
-    elements.bookmarksFolderFilter.addEventListener('change', renderBookmarksManager);
+    elements.bookmarksFolderFilter.addEventListener('change', handleBookmarksFolderFilterChange);
     elements.bookmarksNewBtn.addEventListener('click', startNewBookmark);
+    elements.bookmarksNewFolderBtn.addEventListener('click', startNewBookmarkFolder);
+    elements.bookmarksNewSeparatorBtn.addEventListener('click', addBookmarkSeparator);
+    elements.bookmarksNewSmartBtn.addEventListener('click', startNewSmartFolder);
+    elements.bookmarksTags.addEventListener('submit', applyBookmarkTagEdit);
     elements.bookmarksExportBtn.addEventListener('click', exportBookmarksHtml);
+    elements.bookmarksCheckLinksBtn.addEventListener('click', toggleBookmarkLinkCheck);
+    elements.bookmarksApplyRedirectsBtn.addEventListener('click', applyBookmarkRedirects);
+    elements.bookmarksRemoveDeadBtn.addEventListener('click', removeDeadBookmarks);
     elements.bookmarksImportFile.addEventListener('change', importBookmarksFromFile);
     elements.bookmarksEditor.addEventListener('submit', saveBookmarkEdits);
     elements.bookmarkDeleteBtn.addEventListener('click', deleteSelectedBookmark);
+    elements.bookmarkMoveUpBtn.addEventListener('click', () => moveSelectedBookmark(-1));
+    elements.bookmarkMoveDownBtn.addEventListener('click', () => moveSelectedBookmark(1));
   }
 
   // Keyboard shortcuts
@@ -594,16 +732,17 @@ async function loadSettings() {
     const result = await invoke('get_settings');
     if (result.success) {
       const {
-        search_engine: template,
+        search_engine_id: engineId,
         theme,
         bookmarks_bar_visible: barVisible,
         autofill_enabled: autofillEnabled,
         autofill_name: autofillName,
         autofill_email: autofillEmail,
         password_save_prompt_enabled: passwordSaveEnabled,
+        restore_eager_tabs: restoreEagerTabs,
+        download_dir: downloadDir,
       } = result.data;
-      const engineId = getEngineIdFromTemplate(template);
-      elements.searchEngineSelect.value = engineId;
+      await loadSearchEngines(engineId);
 
       const initialTheme = theme || getSystemTheme();
       applyTheme(initialTheme);
@@ -622,6 +761,13 @@ async function loadSettings() {
       if (elements.passwordSaveToggle) {
         elements.passwordSaveToggle.checked = typeof passwordSaveEnabled === 'boolean' ? passwordSaveEnabled : false;
       }
+      if (elements.restoreEagerSelect) {
+        elements.restoreEagerSelect.value = String(Number.isInteger(restoreEagerTabs) ? restoreEagerTabs : 0);
+      }
+      if (elements.downloadDirInput) {
+        elements.downloadDirInput.value = typeof downloadDir === 'string' ? downloadDir : '';
+      }
+      await loadHttpsOnlySettings();
       await loadBookmarks();
       return;
     }
@@ -645,6 +791,12 @@ async function loadSettings() {
   if (elements.passwordSaveToggle) {
     elements.passwordSaveToggle.checked = false;
   }
+  if (elements.restoreEagerSelect) {
+    elements.restoreEagerSelect.value = '0';
+  }
+  if (elements.downloadDirInput) {
+    elements.downloadDirInput.value = '';
+  }
   await loadBookmarks();
 }
 
@@ -705,9 +857,56 @@ async function toggleTheme() {
   }
 }
 
-function getEngineIdFromTemplate(template) {
-  const entry = Object.entries(SEARCH_ENGINES).find(([, value]) => value === template);
-  return entry ? entry[0] : 'duckduckgo';
+async function loadSearchEngines(selectedId = elements.searchEngineSelect.value) {
+  try {
+    const result = await invoke('get_search_engines');
+    if (!result.success || !Array.isArray(result.data)) return;
+
+    elements.searchEngineSelect.innerHTML = '';
+    for (const engine of result.data) {
+      const option = document.createElement('option');
+      option.value = engine.id;
+      option.textContent = engine.name;
+      elements.searchEngineSelect.appendChild(option);
+    }
+    elements.searchEngineSelect.value = selectedId || 'duckduckgo';
+  } catch (error) {
+    console.error('Failed to load search engines:', error);
+  }
+}
+
+function handleSearchEngineDiscovered(payload) {
+  const engine = payload?.engine;
+  if (!engine || payload.tab_id !== activeTabId) return;
+
+  let host = '';
+  try {
+    host = new URL(payload.page_url).host;
+  } catch {
+    host = '';
+  }
+
+  showToast({
+    title: 'Add search engine?',
+    message: host ? `${engine.name} (${host})` : engine.name,
+    timeout: 10000,
+    actions: [
+      {
+        label: 'Add',
+        kind: 'primary',
+        onClick: async () => {
+          const result = await invoke('add_search_engine', { engine });
+          if (!result.success) {
+            showToast({ title: 'Could not add search engine', message: result.error || '' });
+            return;
+          }
+          await loadSearchEngines();
+          showToast({ title: 'Search engine added', message: result.data.name, timeout: 2500 });
+        },
+      },
+      { label: 'Not now' },
+    ],
+  });
 }
 
 async function handleSearchEngineChange() {
@@ -753,10 +952,81 @@ async function handlePasswordSaveToggle() {
   }
 }
 
+async function loadHttpsOnlySettings() {
+  if (!elements.httpsOnlyToggle) return;
+  try {
+    const { enabled, exceptions } = await invokeCommand('get_https_only_settings');
+    elements.httpsOnlyToggle.checked = Boolean(enabled);
+    renderHttpsOnlyExceptions(exceptions || []);
+  } catch (error) {
+    console.error('Failed to load HTTPS-Only settings:', error);
+  }
+}
+
+function renderHttpsOnlyExceptions(exceptions) {
+  const list = elements.httpsOnlyExceptions;
+  if (!list) return;
+  list.innerHTML = '';
+  for (const exception of exceptions) {
+    const item = document.createElement('li');
+    const host = document.createElement('span');
+    host.textContent = `${exception.host} loads over HTTP`;
+    const remove = document.createElement('button');
+    remove.type = 'button';
+    remove.className = 'btn-secondary';
+    remove.textContent = 'Remove';
+    remove.addEventListener('click', async () => {
+      try {
+        renderHttpsOnlyExceptions(
+          await invokeCommand('remove_https_only_exception', { host: exception.host }),
+        );
+      } catch (error) {
+        console.error('Failed to remove HTTPS-Only exception:', error);
+      }
+    });
+    item.append(host, remove);
+    list.appendChild(item);
+  }
+}
+
+async function handleHttpsOnlyToggle() {
+  const enabled = Boolean(elements.httpsOnlyToggle.checked);
+  try {
+    await invokeCommand('set_https_only_enabled', { enabled });
+  } catch (error) {
+    console.error('Failed to persist HTTPS-Only setting:', error);
+  }
+}
+
+async function handleRestoreEagerChange() {
+  if (!elements.restoreEagerSelect) return;
+  const count = Number.parseInt(elements.restoreEagerSelect.value, 10) || 0;
+  try {
+    await invoke('set_restore_eager_tabs', { count });
+  } catch (error) {
+    console.error('Failed to persist session restore setting:', error);
+  }
+}
+
+async function handleDownloadDirChange() {
+  if (!elements.downloadDirInput) return;
+  const dir = elements.downloadDirInput.value.trim();
+  try {
+    const result = await invoke('set_download_dir', { dir: dir || null });
+    if (result && result.success === false) {
+      showToast({ title: 'Download folder not changed', message: result.error, timeout: 4000 });
+    }
+  } catch (error) {
+    console.error('Failed to persist download folder:', error);
+  }
+}
+
 async function openSettingsModal() {
   await beginUiOverlay();
   elements.settingsModal.classList.remove('hidden');
   elements.searchEngineSelect.focus();
+  // Exceptions are added from the HTTPS-Only interstitial, outside this modal
+  await loadHttpsOnlySettings();
 }
 
 function closeSettingsModal() {
@@ -1001,7 +1271,8 @@ function renderBookmarks() {
 
   elements.bookmarksList.innerHTML = '';
 
-  if (currentBookmarks.length === 0) {
+  const bookmarks = currentBookmarks.filter((b) => b.kind === 'bookmark');
+  if (bookmarks.length === 0) {
     const empty = document.createElement('div');
     empty.className = 'bookmarks-empty';
     empty.textContent = 'No bookmarks yet';
@@ -1009,8 +1280,8 @@ function renderBookmarks() {
     return;
   }
 
-    currentBookmarks.forEach((bookmark) => {
-      const item = document.createElement('button');
+  bookmarks.forEach((bookmark) => {
+    const item = document.createElement('button');
     item.className = 'bookmark-item';
     item.type = 'button';
     item.dataset.url = bookmark.url;
@@ -1020,19 +1291,19 @@ function renderBookmarks() {
     title.className = 'bookmark-title';
     title.textContent = bookmark.title || bookmark.url;
 
-      item.appendChild(title);
-      item.addEventListener('click', (e) => {
-        openUrlWithDisposition(bookmark.url, dispositionFromPointerEvent(e));
-      });
-      item.addEventListener('auxclick', (e) => {
-        if (e.button !== 1) return;
-        e.preventDefault();
-        openUrlWithDisposition(bookmark.url, 'new_background_tab');
-      });
-
-      elements.bookmarksList.appendChild(item);
+    item.appendChild(title);
+    item.addEventListener('click', (e) => {
+      openUrlWithDisposition(bookmark.url, dispositionFromPointerEvent(e), 'bookmark');
     });
-  }
+    item.addEventListener('auxclick', (e) => {
+      if (e.button !== 1) return;
+      e.preventDefault();
+      openUrlWithDisposition(bookmark.url, 'new_background_tab', 'bookmark');
+    });
+
+    elements.bookmarksList.appendChild(item);
+  });
+}
 
 async function openBookmarksModal() {
   if (!elements.bookmarksModal) return;
@@ -1041,13 +1312,12 @@ async function openBookmarksModal() {
   closeSettingsModal();
   elements.bookmarksModal.classList.remove('hidden');
   elements.bookmarksSearch.value = '';
-  selectedBookmarkUrl = null;
+  selectedBookmarkId = null;
 
   try {
     await loadBookmarks();
     await refreshBookmarksFolderFilter();
     startNewBookmark();
-    renderBookmarksManager();
   } catch (error) {
     console.error('Failed to open bookmarks manager:', error);
   } finally {
@@ -1058,35 +1328,143 @@ async function openBookmarksModal() {
 function closeBookmarksModal() {
   if (!elements.bookmarksModal) return;
   elements.bookmarksModal.classList.add('hidden');
-  selectedBookmarkUrl = null;
+  selectedBookmarkId = null;
   endUiOverlay();
 }
 
 async function refreshBookmarksFolderFilter() {
   if (!elements.bookmarksFolderFilter) return;
 
-  let folders = [];
   try {
-    folders = await invokeCommand('get_bookmark_folders');
+    bookmarkFolders = await invokeCommand('get_bookmark_folders');
+  } catch (error) {
+    bookmarkFolders = { folders: [], smart_folders: [] };
+  }
+  await refreshBookmarkTags();
+
+  // `options` are [value, label] pairs or [groupLabel, [[value, label], ...]]
+  const fillSelect = (select, options) => {
+    const currentValue = select.value;
+    const values = [];
+    select.innerHTML = '';
+    const addOption = (parent, value, label) => {
+      const opt = document.createElement('option');
+      opt.value = value;
+      opt.textContent = label;
+      parent.appendChild(opt);
+      values.push(value);
+    };
+    options.forEach(([value, label]) => {
+      if (!Array.isArray(label)) {
+        addOption(select, value, label);
+        return;
+      }
+      if (!label.length) return;
+      const group = document.createElement('optgroup');
+      group.label = value;
+      label.forEach(([v, l]) => addOption(group, v, l));
+      select.appendChild(group);
+    });
+    if (values.includes(currentValue)) {
+      select.value = currentValue;
+    }
+  };
+
+  const folderOptions = bookmarkFolders.folders.map((folder) => [String(folder.id), folder.path]);
+  fillSelect(elements.bookmarksFolderFilter, [
+    ['all', 'All'],
+    ['__root__', 'No folder'],
+    ['Folders', folderOptions],
+    ['Smart folders', bookmarkFolders.smart_folders.map((f) => [`smart:${f.id}`, `${f.title} (${f.bookmark_ids.length})`])],
+    ['Tags', bookmarkTags.map((t) => [`tag:${t.name}`, `#${t.name} (${t.count})`])],
+    ['Link check', [
+      ['links:problems', 'Problems'],
+      ['links:dead', 'Dead'],
+      ['links:redirected', 'Moved'],
+    ]],
+  ]);
+  fillSelect(elements.bookmarkEditFolder, [['', 'No folder'], ...folderOptions]);
+}
+
+async function refreshBookmarkTags() {
+  try {
+    bookmarkTags = await invokeCommand('get_bookmark_tags');
   } catch (error) {
-    folders = [];
+    bookmarkTags = [];
   }
+  const names = new Set(bookmarkTags.map((t) => t.name));
+  selectedBookmarkTags = new Set([...selectedBookmarkTags].filter((name) => names.has(name)));
+  renderBookmarkTags();
+}
 
-  const currentValue = elements.bookmarksFolderFilter.value || 'all';
-  elements.bookmarksFolderFilter.innerHTML = '';
+function renderBookmarkTags() {
+  if (!elements.bookmarksTagList) return;
 
-  const addOption = (value, label) => {
-    const opt = document.createElement('option');
-    opt.value = value;
-    opt.textContent = label;
-    elements.bookmarksFolderFilter.appendChild(opt);
-  };
+  elements.bookmarksTagList.innerHTML = '';
+  if (!bookmarkTags.length) {
+    const empty = document.createElement('div');
+    empty.className = 'bookmarks-empty';
+    empty.textContent = 'No tags';
+    elements.bookmarksTagList.appendChild(empty);
+  }
+
+  bookmarkTags.forEach((tag) => {
+    const chip = document.createElement('button');
+    chip.type = 'button';
+    chip.className = 'bookmarks-tag';
+    chip.textContent = `#${tag.name} ${tag.count}`;
+    chip.classList.toggle('active', selectedBookmarkTags.has(tag.name));
+    chip.addEventListener('click', () => {
+      if (selectedBookmarkTags.has(tag.name)) {
+        selectedBookmarkTags.delete(tag.name);
+      } else {
+        selectedBookmarkTags.add(tag.name);
+      }
+      renderBookmarkTags();
+    });
+    elements.bookmarksTagList.appendChild(chip);
+  });
+
+  // One selected tag is renamed, several are merged into the new name
+  const selected = selectedBookmarkTags.size;
+  elements.bookmarksTagApplyBtn.textContent = selected > 1 ? `Merge ${selected}` : 'Rename';
+  elements.bookmarksTagApplyBtn.disabled = selected === 0;
+}
+
+async function applyBookmarkTagEdit(e) {
+  e.preventDefault();
+
+  const into = (elements.bookmarksTagTarget.value || '').trim();
+  const sources = [...selectedBookmarkTags];
+  if (!into || !sources.length) return;
 
-  addOption('all', 'All');
-  addOption('__root__', 'No folder');
-  folders.forEach((folder) => addOption(folder, folder));
+  try {
+    if (sources.length === 1) {
+      await invokeCommand('rename_bookmark_tag', { from: sources[0], to: into });
+    } else {
+      await invokeCommand('merge_bookmark_tags', { sources, into });
+    }
+    selectedBookmarkTags = new Set();
+    elements.bookmarksTagTarget.value = '';
+    await loadBookmarks();
+    await refreshBookmarksFolderFilter();
+    renderBookmarksManager();
+  } catch (error) {
+    showToast({ title: 'Could not update tags', message: String(error) });
+  }
+}
 
-  elements.bookmarksFolderFilter.value = currentValue;
+// Ids of `bookmark`'s ancestor folders, nearest first
+function bookmarkAncestorIds(bookmark) {
+  const byId = new Map(currentBookmarks.map((b) => [b.id, b]));
+  const ids = [];
+  let parentId = bookmark.parent_id;
+  while (parentId != null && !ids.includes(parentId)) {
+    ids.push(parentId);
+    const parent = byId.get(parentId);
+    parentId = parent ? parent.parent_id : null;
+  }
+  return ids;
 }
 
 function renderBookmarksManager() {
@@ -1094,18 +1472,28 @@ function renderBookmarksManager() {
 
   const query = (elements.bookmarksSearch.value || '').trim().toLowerCase();
   const folderFilter = elements.bookmarksFolderFilter.value || 'all';
+  // Smart folders and tags list matching bookmarks without their tree
+  const flat = Boolean(query) || /^(smart|tag|links):/.test(folderFilter);
+  const smartFolder = folderFilter.startsWith('smart:')
+    ? bookmarkFolders.smart_folders.find((f) => `smart:${f.id}` === folderFilter)
+    : null;
 
   const filtered = currentBookmarks.filter((b) => {
-    const title = (b.title || '').toLowerCase();
-    const url = (b.url || '').toLowerCase();
-    const folder = (b.folder || '').toString();
-
-    const matchesQuery = !query || title.includes(query) || url.includes(query) || folder.toLowerCase().includes(query);
-    if (!matchesQuery) return false;
+    if (query) {
+      if (b.kind === 'separator') return false;
+      const fields = [b.title, b.url, b.folder, b.description, ...(b.tags || [])];
+      if (!fields.some((field) => (field || '').toLowerCase().includes(query))) return false;
+    }
 
     if (folderFilter === 'all') return true;
-    if (folderFilter === '__root__') return !b.folder;
-    return b.folder === folderFilter || (b.folder && b.folder.startsWith(`${folderFilter}/`));
+    if (folderFilter === '__root__') return b.parent_id == null;
+    if (folderFilter.startsWith('smart:')) return Boolean(smartFolder) && smartFolder.bookmark_ids.includes(b.id);
+    if (folderFilter.startsWith('links:')) return matchesLinkCheckFilter(b, folderFilter.slice(6));
+    if (folderFilter.startsWith('tag:')) {
+      const name = folderFilter.slice(4).toLowerCase();
+      return (b.tags || []).some((tag) => tag.toLowerCase() === name);
+    }
+    return bookmarkAncestorIds(b).includes(Number(folderFilter));
   });
 
   elements.bookmarksManagerList.innerHTML = '';
@@ -1119,95 +1507,273 @@ function renderBookmarksManager() {
   }
 
   filtered.forEach((bookmark) => {
+    // Indent by nesting depth, unless results are shown flat
+    const depth = flat ? 0 : bookmarkAncestorIds(bookmark).length;
+    const indent = `calc(var(--space-4) + ${depth * 16}px)`;
+
+    if (bookmark.kind === 'separator') {
+      const item = document.createElement('div');
+      item.className = 'bookmarks-manager-separator';
+      item.style.paddingLeft = indent;
+      if (bookmark.id === selectedBookmarkId) {
+        item.classList.add('active');
+      }
+      item.appendChild(document.createElement('hr'));
+      item.addEventListener('click', () => selectBookmarkForEditing(bookmark.id));
+      elements.bookmarksManagerList.appendChild(item);
+      return;
+    }
+
     const item = document.createElement('button');
     item.type = 'button';
     item.className = 'bookmarks-manager-item';
-    item.dataset.url = bookmark.url;
-    if (selectedBookmarkUrl && bookmark.url === selectedBookmarkUrl) {
+    item.style.paddingLeft = indent;
+    item.dataset.id = bookmark.id;
+    if (bookmark.kind === 'folder') {
+      item.classList.add('bookmarks-manager-item-folder');
+    }
+    if (bookmark.id === selectedBookmarkId) {
       item.classList.add('active');
     }
+    if (bookmark.description) {
+      item.title = bookmark.description;
+    }
 
     const text = document.createElement('div');
     text.className = 'bookmarks-manager-item-text';
 
     const title = document.createElement('div');
     title.className = 'bookmarks-manager-item-title';
-    title.textContent = bookmark.title || bookmark.url;
-
-    const url = document.createElement('div');
-    url.className = 'bookmarks-manager-item-url';
-    url.textContent = bookmark.folder ? `${bookmark.folder} · ${bookmark.url}` : bookmark.url;
-
+    title.textContent = bookmark.kind === 'folder' ? `📁 ${bookmark.title}` : bookmark.title || bookmark.url;
     text.appendChild(title);
-    text.appendChild(url);
+
+    if (bookmark.kind === 'bookmark') {
+      const url = document.createElement('div');
+      url.className = 'bookmarks-manager-item-url';
+      url.textContent = flat && bookmark.folder ? `${bookmark.folder} · ${bookmark.url}` : bookmark.url;
+      const linkStatus = describeLinkStatus(bookmark.link_check);
+      if (linkStatus) {
+        const badge = document.createElement('span');
+        badge.className = 'bookmarks-link-status';
+        badge.classList.toggle('redirected', bookmark.link_check.status === 'redirected');
+        badge.textContent = linkStatus;
+        badge.title = `Checked ${new Date(bookmark.link_check.checked_at).toLocaleString()}`;
+        url.appendChild(badge);
+      }
+      if (bookmark.tags && bookmark.tags.length) {
+        const tags = document.createElement('span');
+        tags.className = 'bookmarks-manager-item-tags';
+        tags.textContent = ` ${bookmark.tags.map((tag) => `#${tag}`).join(' ')}`;
+        url.appendChild(tags);
+      }
+      text.appendChild(url);
+    }
     item.appendChild(text);
 
     item.addEventListener('click', () => {
-      selectBookmarkForEditing(bookmark.url);
-    });
-    item.addEventListener('dblclick', () => {
-      openUrlWithDisposition(bookmark.url, 'current_tab');
-      closeBookmarksModal();
-    });
-    item.addEventListener('auxclick', (e) => {
-      if (e.button !== 1) return;
-      e.preventDefault();
-      openUrlWithDisposition(bookmark.url, 'new_background_tab');
-      closeBookmarksModal();
+      selectBookmarkForEditing(bookmark.id);
     });
+    if (bookmark.kind === 'bookmark') {
+      item.addEventListener('dblclick', () => {
+        openUrlWithDisposition(bookmark.url, 'current_tab', 'bookmark');
+        closeBookmarksModal();
+      });
+      item.addEventListener('auxclick', (e) => {
+        if (e.button !== 1) return;
+        e.preventDefault();
+        openUrlWithDisposition(bookmark.url, 'new_background_tab', 'bookmark');
+        closeBookmarksModal();
+      });
+    }
 
     elements.bookmarksManagerList.appendChild(item);
   });
 }
 
-function selectBookmarkForEditing(url) {
-  const bookmark = currentBookmarks.find((b) => b.url === url);
-  if (!bookmark) return;
-  selectedBookmarkUrl = bookmark.url;
-  elements.bookmarkEditTitle.value = bookmark.title || '';
-  elements.bookmarkEditUrl.value = bookmark.url || '';
-  elements.bookmarkEditFolder.value = bookmark.folder || '';
+// Fill the editor for `kind` ('bookmark', 'folder', 'separator' or 'smart') from `item`
+function setBookmarkEditor(kind, item = {}) {
+  bookmarkEditorKind = kind;
+  const isSmart = kind === 'smart';
+  const isBookmark = kind === 'bookmark';
+  const query = item.query || {};
+
+  elements.bookmarkEditTitle.value = item.title || '';
+  elements.bookmarkEditUrl.value = item.url || '';
+  elements.bookmarkEditFolder.value = item.parent_id == null ? '' : String(item.parent_id);
+  elements.bookmarkEditTags.value = (item.tags || []).join(', ');
+  elements.bookmarkEditDescription.value = item.description || '';
+  elements.smartFolderTag.value = query.tag || '';
+  elements.smartFolderDomain.value = query.domain || '';
+  elements.smartFolderText.value = query.text || '';
+  elements.smartFolderDays.value = query.added_within_days || '';
+
+  elements.bookmarkEditTitle.disabled = kind === 'separator';
+  elements.bookmarkEditTitle.placeholder = kind === 'bookmark' ? 'Title' : 'Folder name';
+  elements.bookmarkEditUrl.classList.toggle('hidden', !isBookmark);
+  elements.bookmarkEditTags.classList.toggle('hidden', !isBookmark);
+  elements.bookmarkEditDescription.classList.toggle('hidden', !isBookmark);
+  elements.bookmarkEditFolder.classList.toggle('hidden', isSmart);
+  elements.bookmarkSmartFields.classList.toggle('hidden', !isSmart);
+
+  const selected = isSmart ? selectedSmartFolderId != null : selectedBookmarkId != null;
+  elements.bookmarkDeleteBtn.disabled = !selected;
+  elements.bookmarkMoveUpBtn.disabled = !selected || isSmart;
+  elements.bookmarkMoveDownBtn.disabled = !selected || isSmart;
   renderBookmarksManager();
 }
 
+function selectBookmarkForEditing(id) {
+  const bookmark = currentBookmarks.find((b) => b.id === id);
+  if (!bookmark) return;
+  selectedBookmarkId = bookmark.id;
+  selectedSmartFolderId = null;
+  setBookmarkEditor(bookmark.kind, bookmark);
+}
+
+function selectSmartFolderForEditing(id) {
+  const folder = bookmarkFolders.smart_folders.find((f) => f.id === id);
+  if (!folder) return;
+  selectedBookmarkId = null;
+  selectedSmartFolderId = folder.id;
+  setBookmarkEditor('smart', folder);
+}
+
 function startNewBookmark() {
-  selectedBookmarkUrl = null;
-  elements.bookmarkEditTitle.value = '';
-  elements.bookmarkEditUrl.value = '';
-  elements.bookmarkEditFolder.value = '';
+  selectedBookmarkId = null;
+  selectedSmartFolderId = null;
+  setBookmarkEditor('bookmark');
+}
+
+function startNewBookmarkFolder() {
+  selectedBookmarkId = null;
+  selectedSmartFolderId = null;
+  setBookmarkEditor('folder');
+  elements.bookmarkEditTitle.focus();
+}
+
+function startNewSmartFolder() {
+  selectedBookmarkId = null;
+  selectedSmartFolderId = null;
+  setBookmarkEditor('smart');
+  elements.bookmarkEditTitle.focus();
+}
+
+function handleBookmarksFolderFilterChange() {
+  const value = elements.bookmarksFolderFilter.value || '';
+  if (value.startsWith('smart:')) {
+    selectSmartFolderForEditing(Number(value.slice(6)));
+    return;
+  }
   renderBookmarksManager();
 }
 
+async function addBookmarkSeparator() {
+  const folderValue = elements.bookmarkEditFolder.value;
+  const parentId = folderValue ? Number(folderValue) : null;
+
+  try {
+    const separator = await invokeCommand('add_bookmark_separator', { parentId });
+    await loadBookmarks();
+    selectBookmarkForEditing(separator.id);
+  } catch (error) {
+    console.error('Failed to add separator:', error);
+  }
+}
+
+async function saveSmartFolder(title) {
+  const days = parseInt(elements.smartFolderDays.value, 10);
+  const query = {
+    tag: elements.smartFolderTag.value.trim() || null,
+    domain: elements.smartFolderDomain.value.trim() || null,
+    text: elements.smartFolderText.value.trim() || null,
+    added_within_days: Number.isFinite(days) && days > 0 ? days : null,
+  };
+
+  const folder = selectedSmartFolderId == null
+    ? await invokeCommand('add_smart_folder', { title, query })
+    : await invokeCommand('update_smart_folder', { id: selectedSmartFolderId, title, query });
+
+  await refreshBookmarksFolderFilter();
+  elements.bookmarksFolderFilter.value = `smart:${folder.id}`;
+  selectSmartFolderForEditing(folder.id);
+}
+
 async function saveBookmarkEdits(e) {
   e.preventDefault();
 
   const title = (elements.bookmarkEditTitle.value || '').trim();
   const url = (elements.bookmarkEditUrl.value || '').trim();
-  const folderRaw = (elements.bookmarkEditFolder.value || '').trim();
-  const folder = folderRaw ? folderRaw : null;
+  const folderValue = elements.bookmarkEditFolder.value;
+  const parentId = folderValue ? Number(folderValue) : null;
+  const description = (elements.bookmarkEditDescription.value || '').trim();
+  const tags = (elements.bookmarkEditTags.value || '').split(',').map((tag) => tag.trim()).filter(Boolean);
 
   try {
-    const bookmarks = selectedBookmarkUrl
-      ? await invokeCommand('update_bookmark', { oldUrl: selectedBookmarkUrl, title, url, folder })
-      : await invokeCommand('add_bookmark', { title, url, folder });
+    if (bookmarkEditorKind === 'smart') {
+      await saveSmartFolder(title);
+      return;
+    }
 
-    currentBookmarks = Array.isArray(bookmarks) ? bookmarks : [];
-    renderBookmarks();
+    let saved;
+    if (selectedBookmarkId == null) {
+      saved = bookmarkEditorKind === 'folder'
+        ? await invokeCommand('add_bookmark_folder', { title, parentId })
+        : await invokeCommand('add_bookmark', { title, url, parentId });
+      if (saved.kind === 'bookmark' && (description || tags.length)) {
+        saved = await invokeCommand('update_bookmark', { id: saved.id, title, url, description, tags });
+      }
+    } else {
+      const current = currentBookmarks.find((b) => b.id === selectedBookmarkId);
+      saved = current && current.kind === 'separator'
+        ? current
+        : await invokeCommand('update_bookmark', {
+          id: selectedBookmarkId,
+          title,
+          url,
+          description,
+          tags: current && current.kind === 'bookmark' ? tags : null,
+        });
+      if (current && (current.parent_id ?? null) !== parentId) {
+        saved = await invokeCommand('move_bookmark', { id: selectedBookmarkId, parentId, position: null });
+      }
+    }
+
+    await loadBookmarks();
     await refreshBookmarksFolderFilter();
-    selectedBookmarkUrl = url;
-    renderBookmarksManager();
+    selectBookmarkForEditing(saved.id);
   } catch (error) {
     console.error('Failed to save bookmark:', error);
   }
 }
 
-async function deleteSelectedBookmark() {
-  if (!selectedBookmarkUrl) return;
+// Move the selected item one place up or down among its siblings
+async function moveSelectedBookmark(delta) {
+  const bookmark = currentBookmarks.find((b) => b.id === selectedBookmarkId);
+  if (!bookmark) return;
+
+  const position = bookmark.position + delta;
+  if (position < 0) return;
 
   try {
-    const bookmarks = await invokeCommand('remove_bookmark', { url: selectedBookmarkUrl });
-    currentBookmarks = Array.isArray(bookmarks) ? bookmarks : [];
-    renderBookmarks();
+    await invokeCommand('move_bookmark', { id: bookmark.id, parentId: bookmark.parent_id ?? null, position });
+    await loadBookmarks();
+    renderBookmarksManager();
+  } catch (error) {
+    console.error('Failed to move bookmark:', error);
+  }
+}
+
+async function deleteSelectedBookmark() {
+  try {
+    if (bookmarkEditorKind === 'smart') {
+      if (selectedSmartFolderId == null) return;
+      await invokeCommand('remove_smart_folder', { id: selectedSmartFolderId });
+      elements.bookmarksFolderFilter.value = 'all';
+    } else {
+      if (selectedBookmarkId == null) return;
+      await invokeCommand('remove_bookmark', { id: selectedBookmarkId });
+      await loadBookmarks();
+    }
     await refreshBookmarksFolderFilter();
     startNewBookmark();
   } catch (error) {
@@ -1215,6 +1781,122 @@ async function deleteSelectedBookmark() {
   }
 }
 
+// Short label for a bookmark's last link check; empty when it was fine or never checked
+function describeLinkStatus(check) {
+  if (!check) return '';
+  switch (check.status) {
+    case 'ok':
+      return '';
+    case 'redirected':
+      return `Moved to ${check.to}`;
+    case 'not_found':
+      return 'Not found';
+    case 'http_error':
+      return `HTTP ${check.code}`;
+    case 'dns':
+      return check.dns_failures > 1 ? `Host not found (${check.dns_failures} checks in a row)` : 'Host not found';
+    case 'tls':
+      return 'TLS error';
+    case 'timeout':
+      return 'Timed out';
+    default:
+      return `Unreachable (${check.kind || 'unknown'})`;
+  }
+}
+
+function matchesLinkCheckFilter(bookmark, filter) {
+  const check = bookmark.link_check;
+  if (!check) return false;
+  switch (filter) {
+    case 'dead':
+      return Boolean(check.dead);
+    case 'redirected':
+      return check.status === 'redirected';
+    default:
+      return check.status !== 'ok';
+  }
+}
+
+async function toggleBookmarkLinkCheck() {
+  try {
+    if (bookmarkLinkCheckRunning) {
+      await invokeCommand('cancel_bookmark_link_check');
+      return;
+    }
+    const total = await invokeCommand('start_bookmark_link_check', { ids: null });
+    bookmarkLinkCheckRunning = total > 0;
+    elements.bookmarksCheckLinksBtn.textContent = bookmarkLinkCheckRunning ? 'Stop' : 'Check links';
+    elements.bookmarksLinkCheckStatus.textContent = total > 0 ? `Checking 0 of ${total}…` : 'Nothing to check';
+  } catch (error) {
+    elements.bookmarksLinkCheckStatus.textContent = error.message || String(error);
+  }
+}
+
+function handleBookmarkLinkChecked(progress) {
+  const { bookmark_id: id, checked, total, ...status } = progress;
+  const bookmark = currentBookmarks.find((b) => b.id === id);
+  if (bookmark) {
+    bookmark.link_check = { ...status, checked_at: new Date().toISOString() };
+  }
+  if (elements.bookmarksLinkCheckStatus) {
+    elements.bookmarksLinkCheckStatus.textContent = `Checking ${checked} of ${total}…`;
+  }
+  if (elements.bookmarksModal && !elements.bookmarksModal.classList.contains('hidden')) {
+    renderBookmarksManager();
+  }
+}
+
+async function handleBookmarkLinkCheckFinished(result) {
+  bookmarkLinkCheckRunning = false;
+  if (!elements.bookmarksCheckLinksBtn) return;
+
+  elements.bookmarksCheckLinksBtn.textContent = 'Check links';
+  await loadBookmarks();
+  const problems = currentBookmarks.filter((b) => matchesLinkCheckFilter(b, 'problems')).length;
+  const done = result.cancelled ? `Stopped after ${result.checked} of ${result.total}` : `Checked ${result.checked}`;
+  const offline = result.offline ? ' (most hosts could not be found; you may be offline, so lookups were not counted)' : '';
+  elements.bookmarksLinkCheckStatus.textContent = `${done}; ${problems} with problems${offline}`;
+  renderBookmarksManager();
+}
+
+async function applyBookmarkRedirects() {
+  try {
+    const count = await invokeCommand('apply_bookmark_redirects', { ids: null });
+    elements.bookmarksLinkCheckStatus.textContent = `Updated ${count} moved bookmark${count === 1 ? '' : 's'}`;
+    await loadBookmarks();
+    renderBookmarksManager();
+  } catch (error) {
+    console.error('Failed to apply redirects:', error);
+  }
+}
+
+async function removeDeadBookmarks() {
+  const dead = currentBookmarks.filter((b) => matchesLinkCheckFilter(b, 'dead')).map((b) => b.id);
+  if (!dead.length) {
+    elements.bookmarksLinkCheckStatus.textContent = 'No dead bookmarks';
+    return;
+  }
+  // Only the bookmarks the user confirmed are removed, even if a check finished since
+  const confirmed = (elements.bookmarksRemoveDeadBtn.dataset.armed || '').split(',').filter(Boolean).map(Number);
+  if (!confirmed.length) {
+    elements.bookmarksRemoveDeadBtn.dataset.armed = dead.join(',');
+    elements.bookmarksRemoveDeadBtn.textContent = `Click again to remove ${dead.length}`;
+    return;
+  }
+  delete elements.bookmarksRemoveDeadBtn.dataset.armed;
+  elements.bookmarksRemoveDeadBtn.textContent = 'Remove dead';
+
+  try {
+    const count = await invokeCommand('remove_dead_bookmarks', { ids: confirmed });
+    elements.bookmarksLinkCheckStatus.textContent = `Removed ${count} dead bookmark${count === 1 ? '' : 's'}`;
+    await loadBookmarks();
+    await refreshBookmarksFolderFilter();
+    renderBookmarksManager();
+  } catch (error) {
+    console.error('Failed to remove dead bookmarks:', error);
+  }
+}
+
 async function exportBookmarksHtml() {
   try {
     const html = await invokeCommand('export_bookmarks_html');
@@ -1250,6 +1932,142 @@ async function importBookmarksFromFile() {
   }
 }
 
+// Preview first (dry run), then import the same file
+async function runProfileImport(dryRun) {
+  const source = elements.profileImportSource.value;
+  const path = elements.profileImportPath.value.trim();
+  if (!path) return;
+
+  elements.profileImportPreviewBtn.disabled = true;
+  elements.profileImportBtn.disabled = true;
+  try {
+    const report = await invokeCommand(dryRun ? 'preview_profile_import' : 'import_profile', {
+      source,
+      path,
+    });
+    renderProfileImportReport(report);
+    elements.profileImportBtn.disabled = !dryRun;
+    if (!dryRun) {
+      await loadBookmarks();
+      showToast({ title: 'Import finished', message: describeProfileImport(report) });
+    }
+  } catch (error) {
+    console.error('Profile import failed:', error);
+    elements.profileImportReport.textContent = error.message || String(error);
+  } finally {
+    elements.profileImportPreviewBtn.disabled = false;
+  }
+}
+
+function describeProfileImport(report) {
+  const parts = [];
+  if (report.bookmarks_found > 0) {
+    parts.push(`${report.bookmarks_added} of ${report.bookmarks_found} bookmarks new`);
+  }
+  if (report.history_urls_found > 0) {
+    const visits = report.dry_run ? report.visits_found : report.visits_added;
+    parts.push(
+      `${report.history_urls_added} new and ${report.history_urls_merged} known history pages, ${visits} visits`,
+    );
+  }
+  return parts.join(' · ') || 'Nothing to import';
+}
+
+function renderProfileImportReport(report) {
+  const container = elements.profileImportReport;
+  container.innerHTML = '';
+
+  const summary = document.createElement('p');
+  summary.textContent = describeProfileImport(report);
+  container.appendChild(summary);
+
+  const conflicts = report.bookmark_conflicts || [];
+  if (conflicts.length === 0) return;
+
+  const heading = document.createElement('p');
+  heading.textContent = `${conflicts.length} bookmark${conflicts.length === 1 ? '' : 's'} already saved differently (yours are kept):`;
+  const list = document.createElement('ul');
+  list.className = 'settings-list';
+  for (const conflict of conflicts.slice(0, 50)) {
+    const item = document.createElement('li');
+    const existingFolder = conflict.existing_folder ? ` in ${conflict.existing_folder}` : '';
+    const importedFolder = conflict.imported_folder ? ` in ${conflict.imported_folder}` : '';
+    item.textContent = `${conflict.existing_title}${existingFolder} ← ${conflict.imported_title}${importedFolder}`;
+    item.title = conflict.url;
+    list.appendChild(item);
+  }
+  container.append(heading, list);
+}
+
+// Two clicks: the first arms the button, the second forgets the site
+async function forgetSite() {
+  const domain = elements.forgetSiteDomain.value.trim();
+  if (!domain) return;
+
+  if (elements.forgetSiteBtn.dataset.armed !== domain) {
+    elements.forgetSiteBtn.dataset.armed = domain;
+    elements.forgetSiteBtn.textContent = 'Click again to forget';
+    return;
+  }
+  delete elements.forgetSiteBtn.dataset.armed;
+  elements.forgetSiteBtn.textContent = 'Forget';
+
+  elements.forgetSiteBtn.disabled = true;
+  try {
+    const summary = await invokeCommand('forget_site', {
+      domain,
+      includeBookmarks: elements.forgetSiteBookmarks.checked,
+    });
+    elements.forgetSiteReport.textContent = describeForgetSite(summary);
+    elements.forgetSiteDomain.value = '';
+    await refreshTabs();
+    if (summary.bookmarks > 0) {
+      await loadBookmarks();
+    }
+    if (summary.download_ids.length > 0) {
+      await refreshDownloads();
+    }
+  } catch (error) {
+    console.error('Failed to forget site:', error);
+    elements.forgetSiteReport.textContent = error.message || String(error);
+  } finally {
+    elements.forgetSiteBtn.disabled = false;
+  }
+}
+
+function describeForgetSite(summary) {
+  const counts = [
+    [summary.history_entries, 'history page'],
+    [summary.bookmarks, 'bookmark'],
+    [summary.permissions, 'permission'],
+    [summary.download_ids.length, 'download'],
+    [summary.closed_tab_ids.length + summary.recently_closed_tabs, 'tab'],
+    [summary.partitions, 'storage folder'],
+  ];
+  const parts = counts
+    .filter(([count]) => count > 0)
+    .map(([count, noun]) => `${count} ${noun}${count === 1 ? '' : 's'}`);
+  return parts.length > 0
+    ? `Forgot ${summary.domain}: ${parts.join(', ')}`
+    : `Nothing was stored for ${summary.domain}`;
+}
+
+async function updatePublicSuffixListFromFile() {
+  const file = elements.pslUpdateFile.files && elements.pslUpdateFile.files[0];
+  if (!file) return;
+
+  try {
+    const contents = await file.text();
+    const rules = await invokeCommand('update_public_suffix_list', { contents });
+    showToast({ title: 'Public suffix list updated', message: `${rules} rules loaded` });
+  } catch (error) {
+    console.error('Failed to update public suffix list:', error);
+    showToast({ title: 'Public suffix list not updated', message: error.message || String(error) });
+  } finally {
+    elements.pslUpdateFile.value = '';
+  }
+}
+
 function getActiveTab() {
   return currentTabs.find((tab) => tab.id === activeTabId) || null;
 }     
@@ -1268,11 +2086,15 @@ async function addBookmarkFromActiveTab() {
   const title = activeTab.title || activeTab.url;
   const url = activeTab.url;
 
+  if (currentBookmarks.some((b) => b.kind === 'bookmark' && b.url === url)) {
+    showToast({ title: 'Already bookmarked', message: title });
+    return;
+  }
+
   try {
-    const result = await invoke('add_bookmark', { title, url });
+    const result = await invoke('add_bookmark', { title, url, parentId: null });
     if (result.success) {
-      currentBookmarks = result.data;
-      renderBookmarks();
+      await loadBookmarks();
     }
   } catch (error) {
     console.error('Failed to add bookmark:', error);
@@ -1284,7 +2106,8 @@ async function openBookmark(url) {
   await openUrlWithDisposition(url, 'current_tab');
 }
 
-async function openUrlWithDisposition(url, disposition) {
+// `transition` records how the visit started (link/typed/bookmark) for history ranking
+async function openUrlWithDisposition(url, disposition, transition = 'link') {
   if (!url) return;
 
   switch (disposition) {
@@ -1298,7 +2121,7 @@ async function openUrlWithDisposition(url, disposition) {
       await openUrlInNewTab(url, false);
       return;
     default:
-      await navigateToUrl(url);
+      await navigateToUrl(url, transition);
   }
 }
 
@@ -1310,6 +2133,17 @@ async function openUrlInNewWindow(url) {
   }
 }
 
+// A routing rule opened the tab in another session's window
+function openedInOtherSession(tab) {
+  if (!tab || !currentSession || tab.session_id === currentSession.id) return false;
+  showToast({
+    title: 'Opened in another session',
+    message: tab.url,
+    actions: [{ label: 'Dismiss', kind: 'secondary' }],
+  });
+  return true;
+}
+
 async function openUrlInNewTab(url, background) {
   const command = background ? 'create_tab_background' : 'create_tab';
 
@@ -1318,6 +2152,7 @@ async function openUrlInNewTab(url, background) {
     if (!result || !result.success || !result.data) return;
 
     const tab = result.data;
+    if (openedInOtherSession(tab)) return;
     if (!background) {
       activeTabId = tab.id;
       await ensureActiveWebview(tab);
@@ -1350,7 +2185,7 @@ function hostForUrl(url) {
   }
 }
 
-async function navigateToUrl(url) {
+async function navigateToUrl(url, transition = 'link') {
   try {
     const cleanedUrl = await invokeCommand('clean_url', { url });
 
@@ -1404,7 +2239,7 @@ async function navigateToUrl(url) {
           console.warn('Failed to re-partition webview storage:', error);
         }
       }
-      await invokeCommand('navigate_tab', { tabId: activeTabId, url: targetUrl });
+      await invokeCommand('navigate_tab', { tabId: activeTabId, url: targetUrl, transition });
       await ensureWebview({ id: activeTabId, url: targetUrl });
       await invokeCommand('navigate_webview', { tabId: activeTabId, url: targetUrl });
       await refreshTabs();
@@ -1414,6 +2249,7 @@ async function navigateToUrl(url) {
     const tabResult = await invoke('create_tab', { url: targetUrl });
     if (tabResult.success) {
       const tab = tabResult.data;
+      if (openedInOtherSession(tab)) return;
       activeTabId = tab.id;
       await ensureActiveWebview(tab);
       await refreshTabs();
@@ -1424,7 +2260,7 @@ async function navigateToUrl(url) {
       title: 'Navigation failed',
       message: error?.message || String(error || 'Failed to navigate'),
       actions: [
-        { label: 'Retry', kind: 'primary', onClick: () => navigateToUrl(url) },
+        { label: 'Retry', kind: 'primary', onClick: () => navigateToUrl(url, transition) },
         { label: 'Dismiss', kind: 'secondary' },
       ],
       timeout: 0,
@@ -1444,7 +2280,7 @@ async function refreshTabs() {
     if (activeResult.success && activeResult.data) {
       activeTab = activeResult.data;
       activeTabId = activeTab.id;
-      updateAddressBar(activeTab.url);
+      updateAddressBar(activeTab.display_url || activeTab.url, activeTab.idn_warning);
     } else {
       activeTabId = null;
       updateAddressBar('');
@@ -1465,7 +2301,7 @@ async function refreshTabs() {
       if (fallback.success && fallback.data) {
         activeTab = fallback.data;
         activeTabId = activeTab.id;
-        updateAddressBar(activeTab.url);
+        updateAddressBar(activeTab.display_url || activeTab.url, activeTab.idn_warning);
       }
     }
 
@@ -1959,12 +2795,50 @@ async function syncWebviewBounds() {
 // Address Bar
 // ============================================
 
-function updateAddressBar(url) {
+function updateAddressBar(url, idnWarning = null) {
   if (url && url !== 'about:blank') {
     elements.addressBar.value = url;
   } else {
     elements.addressBar.value = '';
   }
+  updateIdnWarning(idnWarning);
+}
+
+function describeIdnWarning(warning) {
+  if (warning.lookalike_of) {
+    return `${warning.unicode_host} looks like ${warning.lookalike_of}`;
+  }
+  if (warning.risk === 'mixed_script') {
+    return `${warning.unicode_host} mixes letters from different alphabets`;
+  }
+  if (warning.risk === 'whole_script_confusable') {
+    return `${warning.unicode_host} uses letters that imitate Latin ones`;
+  }
+  return `${warning.unicode_host} contains look-alike characters`;
+}
+
+function updateIdnWarning(warning) {
+  elements.addressBar.classList.toggle('idn-warning', !!warning);
+  elements.addressBar.title = warning ? `Shown as punycode: ${describeIdnWarning(warning)}` : '';
+}
+
+function warnAboutIdn(warning) {
+  if (!warning) return;
+  const actions = [];
+  if (warning.lookalike_of) {
+    actions.push({
+      label: `Go to ${warning.lookalike_of}`,
+      kind: 'primary',
+      onClick: () => openUrlWithDisposition(`https://${warning.lookalike_of}/`, 'current_tab', 'typed'),
+    });
+  }
+  actions.push({ label: 'Dismiss' });
+  showToast({
+    title: warning.lookalike_of ? `Did you mean ${warning.lookalike_of}?` : 'Unusual address',
+    message: describeIdnWarning(warning),
+    actions,
+    timeout: 12000,
+  });
 }
 
 async function handleAddressBarKeydown(e) {
@@ -1982,10 +2856,11 @@ async function handleAddressBarKeydown(e) {
   }
 }
 
-async function handleAddressBarInput() {
+async function handleAddressBarInput(e) {
   const input = elements.addressBar.value.trim();
 
   if (input.length < 2) {
+    omniboxRequestSeq++;
     elements.addressSuggestions.classList.add('hidden');
     return;
   }
@@ -1994,7 +2869,10 @@ async function handleAddressBarInput() {
   if (input.startsWith('@')) {
     await showCommandSuggestions(input);
   } else {
-    await showHistorySuggestions(input);
+    // Don't re-complete what the user just deleted
+    const deleting = String(e?.inputType || '').startsWith('delete');
+    const caretAtEnd = elements.addressBar.selectionStart === elements.addressBar.value.length;
+    await showOmniboxSuggestions(elements.addressBar.value, !deleting && caretAtEnd);
   }
 }
 
@@ -2020,10 +2898,15 @@ async function navigateToInput(disposition = 'current_tab') {
 
     const resolution = result.data;
 
-    if (resolution.type === 'Navigate' || resolution.type === 'Search') {
-      await openUrlWithDisposition(resolution.value, disposition);
+    if (resolution.type === 'Navigate') {
+      await openUrlWithDisposition(resolution.value.url, disposition, 'typed');
+      warnAboutIdn(resolution.value.display?.warning);
+    } else if (resolution.type === 'Search') {
+      await openUrlWithDisposition(resolution.value.url, disposition, 'typed');
     } else if (resolution.type === 'Command') {
       await handleCommand(resolution.value);
+    } else if (resolution.type === 'Answer') {
+      await acceptAnswer(resolution.value.result);
     }
 
     elements.addressSuggestions.classList.add('hidden');
@@ -2033,189 +2916,323 @@ async function navigateToInput(disposition = 'current_tab') {
 }
 
 async function showCommandSuggestions(input) {
-  const trimmed = input.trim();
-  const [prefix, ...rest] = trimmed.split(/\s+/);
-  const query = rest.join(' ').trim();
-  const cmd = (prefix || '').toLowerCase();
+  // `@h` alone is still being typed: offer completions (`@history`, ...)
+  if (!/^@\S+\s/.test(input.trimStart())) {
+    await showOmniboxSuggestions(input);
+    return;
+  }
 
-  if (cmd === '@tabs') {
-    const q = query.toLowerCase();
-    const matches = (Array.isArray(currentTabs) ? currentTabs : [])
-      .filter((tab) => {
-        if (!q) return true;
-        const title = (tab.title || '').toLowerCase();
-        const url = (tab.url || '').toLowerCase();
-        return title.includes(q) || url.includes(q);
-      })
-      .slice(0, 8);
+  const seq = ++omniboxRequestSeq;
+  let info = null;
+  try {
+    const result = await invoke('get_command_matches', { input });
+    if (seq !== omniboxRequestSeq) return;
+    info = result.success ? result.data : null;
+  } catch (error) {
+    console.error('Failed to load command matches:', error);
+  }
 
-    if (!matches.length) {
-      elements.addressSuggestions.classList.add('hidden');
-      return;
-    }
+  if (!info) {
+    await showOmniboxSuggestions(input);
+    return;
+  }
 
-    elements.addressSuggestions.innerHTML = matches
-      .map(
-        (tab) => `
-        <div class="suggestion-item" data-tab-id="${tab.id}">
-          <div class="suggestion-icon">▣</div>
+  if (info.command_type === 'sessions') {
+    await showSessionCommandSuggestions(info.query || '');
+    return;
+  }
+
+  renderCommandMatches(info.command_type, info.matches);
+}
+
+const COMMAND_ICONS = {
+  tabs: '▣',
+  history: '↺',
+  bookmarks: '★',
+  downloads: '↓',
+  closed: '↶',
+  settings: '⚙',
+  windows: '⧉',
+};
+
+function renderCommandMatches(commandType, matches) {
+  if (!Array.isArray(matches) || !matches.length) {
+    elements.addressSuggestions.classList.add('hidden');
+    return;
+  }
+
+  const icon = COMMAND_ICONS[commandType] || '@';
+  elements.addressSuggestions.innerHTML = matches
+    .map(
+      (match, index) => `
+        <div class="suggestion-item" data-index="${index}">
+          <div class="suggestion-icon">${icon}</div>
           <div class="suggestion-text">
-            <div class="suggestion-title">${escapeHtml(tab.title || tab.url || 'Tab')}</div>
-            <div class="suggestion-url">${escapeHtml(tab.url || '')}</div>
+            <div class="suggestion-title">${escapeHtml(match.title || '')}</div>
+            <div class="suggestion-url">${escapeHtml(match.detail || '')}</div>
           </div>
         </div>
       `
-      )
-      .join('');
+    )
+    .join('');
 
-    elements.addressSuggestions.classList.remove('hidden');
+  elements.addressSuggestions.classList.remove('hidden');
 
-    elements.addressSuggestions.querySelectorAll('.suggestion-item').forEach((el) => {
-      el.addEventListener('click', () => {
-        const tabId = el.dataset.tabId;
-        if (tabId) activateTab(tabId);
-        elements.addressSuggestions.classList.add('hidden');
-      });
+  elements.addressSuggestions.querySelectorAll('.suggestion-item').forEach((el) => {
+    const match = matches[Number(el.dataset.index)];
+    el.addEventListener('click', (e) => {
+      e.preventDefault();
+      activateCommandMatch(commandType, match, dispositionFromPointerEvent(e));
     });
+  });
+}
 
-    return;
-  }
+/** Element focused when `@settings` jumps to a setting key */
+const SETTING_ELEMENT_IDS = {
+  search_engine: 'search-engine-select',
+  bookmarks_bar: 'bookmarks-bar-toggle',
+  autofill: 'autofill-toggle',
+  autofill_name: 'autofill-name',
+  autofill_email: 'autofill-email',
+  password_save_prompt: 'password-save-toggle',
+  https_only: 'https-only-toggle',
+  download_dir: 'download-dir-input',
+  restore_eager_tabs: 'restore-eager-select',
+};
 
-  if (cmd === '@sessions') {
-    try {
-      const result = await invoke('get_sessions');
-      if (!result.success) {
-        elements.addressSuggestions.classList.add('hidden');
-        return;
+async function activateCommandMatch(commandType, match, disposition = 'current_tab') {
+  elements.addressSuggestions.classList.add('hidden');
+  const { type, value } = match?.target || {};
+
+  switch (type) {
+    case 'tab':
+      await activateTab(value);
+      break;
+    case 'url':
+      await openUrlWithDisposition(value, disposition, commandType === 'bookmarks' ? 'bookmark' : 'link');
+      break;
+    case 'session':
+      await switchSession(value);
+      break;
+    case 'download': {
+      await openDownloadsModal();
+      const row = elements.downloadsList.querySelector(`[data-download-id="${CSS.escape(value)}"]`);
+      if (row) {
+        row.scrollIntoView({ block: 'nearest' });
+        row.classList.add('highlighted');
+      }
+      break;
+    }
+    case 'closed_tab': {
+      const result = await invoke('restore_closed_tab', { closedId: value });
+      if (result.success && result.data) {
+        activeTabId = result.data.id;
+        await ensureActiveWebview(result.data);
+        await refreshTabs();
+      }
+      break;
+    }
+    case 'setting':
+      if (value === 'theme') {
+        await toggleTheme();
+        break;
       }
+      await openSettingsModal();
+      document.getElementById(SETTING_ELEMENT_IDS[value] || '')?.focus();
+      break;
+    case 'window':
+      await invoke('focus_window', { label: value });
+      break;
+    default:
+      break;
+  }
+}
 
-      const q = query.toLowerCase();
-      const sessions = Array.isArray(result.data) ? result.data : [];
-      const matches = sessions
-        .filter((session) => !q || String(session.name || '').toLowerCase().includes(q))
-        .slice(0, 8);
+async function showSessionCommandSuggestions(query) {
+  try {
+    const result = await invoke('get_sessions');
+    if (!result.success) {
+      elements.addressSuggestions.classList.add('hidden');
+      return;
+    }
 
-      if (!matches.length) {
-        elements.addressSuggestions.classList.add('hidden');
-        return;
-      }
+    const q = query.toLowerCase();
+    const sessions = Array.isArray(result.data) ? result.data : [];
+    const matches = sessions
+      .filter((session) => !q || String(session.name || '').toLowerCase().includes(q))
+      .slice(0, 8);
+
+    // Archived sessions are only surfaced when searching
+    let archivedMatches = [];
+    if (q) {
+      const archived = await invoke('search_archived_sessions', { query, limit: 8 });
+      archivedMatches = archived.success && Array.isArray(archived.data) ? archived.data : [];
+    }
 
-      elements.addressSuggestions.innerHTML = matches
+    if (!matches.length && !archivedMatches.length) {
+      elements.addressSuggestions.classList.add('hidden');
+      return;
+    }
+
+    elements.addressSuggestions.innerHTML =
+      matches
         .map(
           (session) => `
-          <div class="suggestion-item" data-session-id="${session.id}">
-            <div class="suggestion-icon">@</div>
-            <div class="suggestion-text">
-              <div class="suggestion-title">${escapeHtml(session.name || 'Session')}</div>
-              <div class="suggestion-url">${Number(session.tab_count || 0)} tabs</div>
-            </div>
+        <div class="suggestion-item" data-session-id="${session.id}">
+          <div class="suggestion-icon">@</div>
+          <div class="suggestion-text">
+            <div class="suggestion-title">${escapeHtml(session.name || 'Session')}</div>
+            <div class="suggestion-url">${Number(session.tab_count || 0)} tabs</div>
           </div>
-        `
+        </div>
+      `
+        )
+        .join('') +
+      archivedMatches
+        .map(
+          (hit) => `
+        <div class="suggestion-item" data-session-id="${hit.session_id}" data-tab-id="${hit.tab_id}" data-archived="true">
+          <div class="suggestion-icon">&#8617;</div>
+          <div class="suggestion-text">
+            <div class="suggestion-title">${escapeHtml(hit.title || hit.url)}</div>
+            <div class="suggestion-url">Archived: ${escapeHtml(hit.session_name)} · ${escapeHtml(hit.url)}</div>
+          </div>
+        </div>
+      `
         )
         .join('');
 
-      elements.addressSuggestions.classList.remove('hidden');
-      elements.addressSuggestions.querySelectorAll('.suggestion-item').forEach((el) => {
-        el.addEventListener('click', async () => {
-          const sessionId = el.dataset.sessionId;
-          if (sessionId) await switchSession(sessionId);
-          elements.addressSuggestions.classList.add('hidden');
-        });
+    elements.addressSuggestions.classList.remove('hidden');
+    elements.addressSuggestions.querySelectorAll('.suggestion-item').forEach((el) => {
+      el.addEventListener('click', async () => {
+        const sessionId = el.dataset.sessionId;
+        if (sessionId && el.dataset.archived) {
+          await unarchiveSession(sessionId, true, el.dataset.tabId);
+        } else if (sessionId) {
+          await switchSession(sessionId);
+        }
+        elements.addressSuggestions.classList.add('hidden');
       });
-    } catch (error) {
-      console.error('Failed to load sessions:', error);
-      elements.addressSuggestions.classList.add('hidden');
-    }
+    });
+  } catch (error) {
+    console.error('Failed to load sessions:', error);
+    elements.addressSuggestions.classList.add('hidden');
+  }
+}
 
-    return;
+const OMNIBOX_ICONS = {
+  answer: '=',
+  open_tab: '▣',
+  bookmark: '★',
+  command: '@',
+  typed_url: '→',
+  search: `
+    <svg width="16" height="16" viewBox="0 0 16 16" fill="none">
+      <circle cx="7" cy="7" r="4.5" stroke="currentColor" stroke-width="1.5"/>
+      <path d="M10.5 10.5L14 14" stroke="currentColor" stroke-width="1.5" stroke-linecap="round"/>
+    </svg>`,
+  history: `
+    <svg width="16" height="16" viewBox="0 0 16 16" fill="none">
+      <circle cx="8" cy="8" r="6" stroke="currentColor" stroke-width="1.5"/>
+      <path d="M8 4v4l2 2" stroke="currentColor" stroke-width="1.5" stroke-linecap="round"/>
+    </svg>`,
+};
+
+function omniboxSubtitle(suggestion) {
+  switch (suggestion.kind) {
+    case 'open_tab':
+      return `Switch to tab · ${suggestion.url}`;
+    case 'search':
+      return 'Search';
+    case 'command':
+      return suggestion.fill || '';
+    case 'answer':
+      return 'Press Enter to copy';
+    default:
+      return suggestion.url || '';
   }
+}
 
-  if (cmd === '@history') {
-    const label = query ? `Search history for "${query}"` : 'Open history';
-    elements.addressSuggestions.innerHTML = `
-      <div class="suggestion-item" data-action="open-history">
-        <div class="suggestion-icon">@</div>
-        <div class="suggestion-text">
-          <div class="suggestion-title">${escapeHtml(label)}</div>
-          <div class="suggestion-url">History</div>
-        </div>
-      </div>
-    `;
+function omniboxTransition(kind) {
+  if (kind === 'typed_url' || kind === 'search') return 'typed';
+  if (kind === 'bookmark') return 'bookmark';
+  return 'link';
+}
 
-    elements.addressSuggestions.classList.remove('hidden');
-    const item = elements.addressSuggestions.querySelector('.suggestion-item');
-    if (item) {
-      item.addEventListener('click', () => {
-        openHistoryModal(query || '');
-        elements.addressSuggestions.classList.add('hidden');
-      });
-    }
-    return;
+/** Put a calculator/conversion result in the address bar and copy it */
+async function acceptAnswer(result) {
+  if (!result) return;
+  elements.addressBar.value = result;
+  elements.addressBar.select();
+  try {
+    await navigator.clipboard.writeText(result);
+    showToast({ title: 'Copied', message: result, timeout: 2000 });
+  } catch (error) {
+    console.error('Failed to copy answer:', error);
   }
+}
 
-  const commands = [
-    { prefix: '@tabs', description: 'Search open tabs' },
-    { prefix: '@history', description: 'Search history' },
-    { prefix: '@sessions', description: 'Switch session' },
-  ];
+function applyInlineAutocomplete(input, autocomplete) {
+  // Only complete if nothing was typed while the request was in flight
+  if (elements.addressBar.value !== input) return;
+  const completion = String(autocomplete?.completion || '');
+  if (!completion.toLowerCase().startsWith(input.toLowerCase())) return;
 
-  const filtered = commands.filter((cmd) =>
-    cmd.prefix.toLowerCase().includes((prefix || input).toLowerCase())
-  );
+  elements.addressBar.value = input + completion.slice(input.length);
+  elements.addressBar.setSelectionRange(input.length, elements.addressBar.value.length);
+}
 
-  if (filtered.length === 0) {
-    elements.addressSuggestions.classList.add('hidden');
+async function activateOmniboxSuggestion(suggestion, disposition) {
+  elements.addressSuggestions.classList.add('hidden');
+
+  if (suggestion.kind === 'open_tab' && suggestion.tab_id && disposition === 'current_tab') {
+    await activateTab(suggestion.tab_id);
     return;
   }
 
-  elements.addressSuggestions.innerHTML = filtered
-    .map(
-      (cmd) => `
-      <div class="suggestion-item" data-command="${cmd.prefix}">
-        <div class="suggestion-icon">@</div>
-        <div class="suggestion-text">
-          <div class="suggestion-title">${cmd.prefix}</div>
-          <div class="suggestion-url">${cmd.description}</div>
-        </div>
-      </div>
-    `
-    )
-    .join('');
+  if (suggestion.kind === 'answer') {
+    await acceptAnswer(suggestion.fill || '');
+    return;
+  }
 
-  elements.addressSuggestions.classList.remove('hidden');
+  if (suggestion.kind === 'command') {
+    elements.addressBar.value = suggestion.fill || '';
+    elements.addressBar.focus();
+    await handleAddressBarInput();
+    return;
+  }
 
-  // Add click handlers
-  elements.addressSuggestions.querySelectorAll('.suggestion-item').forEach((el) => {
-    el.addEventListener('click', () => {
-      elements.addressBar.value = el.dataset.command + ' ';
-      elements.addressBar.focus();
-      elements.addressSuggestions.classList.add('hidden');
-    });
-  });
+  if (suggestion.url) {
+    await openUrlWithDisposition(suggestion.url, disposition, omniboxTransition(suggestion.kind));
+  }
 }
 
-async function showHistorySuggestions(query) {
+async function showOmniboxSuggestions(input, allowAutocomplete = false) {
+  const seq = ++omniboxRequestSeq;
+
   try {
-    const result = await invoke('search_history', { query });
-    if (!result.success || result.data.length === 0) {
+    const result = await invoke('get_omnibox_suggestions', { input });
+    // A newer keystroke superseded (and cancelled) this request
+    if (seq !== omniboxRequestSeq) return;
+
+    const suggestions = result.success ? result.data.suggestions : [];
+    if (!suggestions.length) {
       elements.addressSuggestions.classList.add('hidden');
       return;
     }
 
-    elements.addressSuggestions.innerHTML = result.data
-      .slice(0, 8)
+    if (allowAutocomplete && result.data.autocomplete) {
+      applyInlineAutocomplete(input, result.data.autocomplete);
+    }
+
+    elements.addressSuggestions.innerHTML = suggestions
       .map(
-        (entry) => `
-        <div class="suggestion-item" data-url="${entry.url}">
-          <div class="suggestion-icon">
-            <svg width="16" height="16" viewBox="0 0 16 16" fill="none">
-              <circle cx="8" cy="8" r="6" stroke="currentColor" stroke-width="1.5"/>
-              <path d="M8 4v4l2 2" stroke="currentColor" stroke-width="1.5" stroke-linecap="round"/>
-            </svg>
-          </div>
+        (suggestion, index) => `
+        <div class="suggestion-item" data-index="${index}">
+          <div class="suggestion-icon">${OMNIBOX_ICONS[suggestion.kind] || ''}</div>
           <div class="suggestion-text">
-            <div class="suggestion-title">${escapeHtml(entry.title || entry.url)}</div>
-            <div class="suggestion-url">${escapeHtml(entry.url)}</div>
+            <div class="suggestion-title">${escapeHtml(suggestion.title || suggestion.url || '')}</div>
+            <div class="suggestion-url">${escapeHtml(omniboxSubtitle(suggestion))}</div>
           </div>
         </div>
       `
@@ -2224,51 +3241,36 @@ async function showHistorySuggestions(query) {
 
     elements.addressSuggestions.classList.remove('hidden');
 
-      // Add click handlers
-      elements.addressSuggestions.querySelectorAll('.suggestion-item').forEach((el) => {
-        el.addEventListener('click', (e) => {
-          e.preventDefault();
-          openUrlWithDisposition(el.dataset.url, dispositionFromPointerEvent(e));
-          elements.addressSuggestions.classList.add('hidden');
-        });
-        el.addEventListener('auxclick', (e) => {
-          if (e.button !== 1) return;
-          e.preventDefault();
-          openUrlWithDisposition(el.dataset.url, 'new_background_tab');
-          elements.addressSuggestions.classList.add('hidden');
-        });
+    elements.addressSuggestions.querySelectorAll('.suggestion-item').forEach((el) => {
+      const suggestion = suggestions[Number(el.dataset.index)];
+      el.addEventListener('click', (e) => {
+        e.preventDefault();
+        activateOmniboxSuggestion(suggestion, dispositionFromPointerEvent(e));
+      });
+      el.addEventListener('auxclick', (e) => {
+        if (e.button !== 1) return;
+        e.preventDefault();
+        activateOmniboxSuggestion(suggestion, 'new_background_tab');
       });
+    });
   } catch (error) {
-    console.error('Failed to search history:', error);
+    console.error('Failed to load suggestions:', error);
   }
 }
 
+/** Panels opened by a command entered without a query */
+const COMMAND_PANELS = {
+  history: () => openHistoryModal(''),
+  sessions: () => openSessionModal(),
+  bookmarks: () => openBookmarksModal(),
+  downloads: () => openDownloadsModal(),
+  settings: () => openSettingsModal(),
+};
+
 async function handleCommand(command) {
   const commandType = String(command?.command_type || '').toLowerCase();
   const query = String(command?.query || '').trim();
 
-  if (commandType === 'tabs') {
-    const q = query.toLowerCase();
-    const matches = (Array.isArray(currentTabs) ? currentTabs : []).filter((tab) => {
-      if (!q) return false;
-      const title = (tab.title || '').toLowerCase();
-      const url = (tab.url || '').toLowerCase();
-      return title.includes(q) || url.includes(q);
-    });
-
-    if (!matches.length) {
-      showToast({
-        title: 'No matching tabs',
-        message: query ? `@tabs ${query}` : 'Type @tabs <query>',
-        timeout: 2500,
-      });
-      return;
-    }
-
-    activateTab(matches[0].id);
-    return;
-  }
-
   if (commandType === 'history') {
     openHistoryModal(query);
     return;
@@ -2276,7 +3278,26 @@ async function handleCommand(command) {
 
   if (commandType === 'sessions') {
     openSessionModal();
+    return;
   }
+
+  if (!query && COMMAND_PANELS[commandType]) {
+    await COMMAND_PANELS[commandType]();
+    return;
+  }
+
+  const result = await invoke('get_command_matches', { input: `@${commandType} ${query}`, limit: 1 });
+  const match = result.success ? result.data?.matches?.[0] : null;
+  if (!match) {
+    showToast({
+      title: 'No matches',
+      message: query ? `@${commandType} ${query}` : `Type @${commandType} <query>`,
+      timeout: 2500,
+    });
+    return;
+  }
+
+  await activateCommandMatch(commandType, match);
 }
 
 // ============================================
@@ -2286,6 +3307,7 @@ async function handleCommand(command) {
 function updateSessionDisplay() {
   if (currentSession) {
     elements.sessionName.textContent = currentSession.name;
+    elements.sessionName.classList.toggle('private', !!currentSession.private);
   }
 }
 
@@ -2293,38 +3315,162 @@ async function openSessionModal() {
   await beginUiOverlay();
   elements.sessionModal.classList.remove('hidden');
   try {
-    const result = await invoke('get_sessions');
-    if (result.success) {
-      renderSessionList(result.data);
-    }
+    await reloadSessionList();
     elements.newSessionName.focus();
   } catch (error) {
     console.error('Failed to load sessions:', error);
   }
 }
 
+async function reloadSessionList() {
+  const [sessions, archived] = await Promise.all([
+    invoke('get_sessions'),
+    invoke('get_archived_sessions'),
+  ]);
+  if (sessions.success) {
+    renderSessionList(sessions.data, archived.success ? archived.data : []);
+  }
+}
+
 function closeSessionModal() {
   elements.sessionModal.classList.add('hidden');
   elements.newSessionName.value = '';
   endUiOverlay();
 }
 
-function renderSessionList(sessions) {
+function renderSessionList(sessions, archived = []) {
+  const archivedHtml = archived.length
+    ? `<div class="session-list-heading">Archived</div>` +
+      archived
+        .map(
+          (session) => `
+      <div class="session-item archived" data-session-id="${session.id}">
+        <span class="session-item-name">${escapeHtml(session.name)}</span>
+        <span class="session-item-count">${session.tab_count} tabs</span>
+        <button type="button" class="session-item-action session-item-unarchive">Restore</button>
+      </div>
+    `
+        )
+        .join('')
+    : '';
+
   elements.sessionList.innerHTML = sessions
     .map(
       (session) => `
       <div class="session-item ${session.is_active ? 'active' : ''}" data-session-id="${session.id}">
         <span class="session-item-name">${escapeHtml(session.name)}</span>
         <span class="session-item-count">${session.tab_count} tabs</span>
+        ${
+          session.private
+            ? `<span class="session-item-private" title="Nothing from this session is saved">Private</span>
+        <button type="button" class="session-item-action session-item-close" title="Close this session and delete its site data">Close</button>`
+            : `<button
+          type="button"
+          class="session-item-isolation ${session.isolated ? 'isolated' : ''}"
+          title="${session.isolated ? 'Cookies and site data are private to this session' : 'Cookies and site data are shared with other sessions'}"
+        >${session.isolated ? 'Isolated' : 'Shared'}</button>
+        <button type="button" class="session-item-action session-item-archive" title="Hide this session and unload its tabs">Archive</button>`
+        }
       </div>
     `
     )
-    .join('');
+    .join('') + archivedHtml;
 
   // Add click handlers
-  elements.sessionList.querySelectorAll('.session-item').forEach((el) => {
+  elements.sessionList.querySelectorAll('.session-item:not(.archived)').forEach((el) => {
     el.addEventListener('click', () => switchSession(el.dataset.sessionId));
   });
+  elements.sessionList.querySelectorAll('.session-item.archived').forEach((el) => {
+    el.addEventListener('click', () => unarchiveSession(el.dataset.sessionId, true));
+  });
+  elements.sessionList.querySelectorAll('.session-item-archive').forEach((el) => {
+    el.addEventListener('click', (e) => {
+      e.stopPropagation();
+      const item = el.closest('.session-item');
+      if (item) archiveSession(item.dataset.sessionId);
+    });
+  });
+  elements.sessionList.querySelectorAll('.session-item-close').forEach((el) => {
+    el.addEventListener('click', (e) => {
+      e.stopPropagation();
+      const item = el.closest('.session-item');
+      if (item) closePrivateSession(item.dataset.sessionId, sessions);
+    });
+  });
+  elements.sessionList.querySelectorAll('.session-item-unarchive').forEach((el) => {
+    el.addEventListener('click', (e) => {
+      e.stopPropagation();
+      const item = el.closest('.session-item');
+      if (item) unarchiveSession(item.dataset.sessionId, false);
+    });
+  });
+  elements.sessionList.querySelectorAll('.session-item-isolation').forEach((el) => {
+    el.addEventListener('click', (e) => {
+      e.stopPropagation();
+      const item = el.closest('.session-item');
+      if (item) toggleSessionIsolation(item.dataset.sessionId, !el.classList.contains('isolated'));
+    });
+  });
+}
+
+async function toggleSessionIsolation(sessionId, isolated) {
+  try {
+    const result = await invoke('set_session_isolated', { sessionId, isolated });
+    if (!result.success) return;
+
+    await reloadSessionList();
+    showToast({
+      title: isolated ? 'Session isolated' : 'Session storage shared',
+      message: 'Applies to tabs opened or reloaded from now on.',
+      timeout: 4000,
+    });
+  } catch (error) {
+    console.error('Failed to update session isolation:', error);
+  }
+}
+
+async function archiveSession(sessionId) {
+  try {
+    const result = await invoke('archive_session', { sessionId });
+    if (!result.success) {
+      showToast({ title: 'Could not archive session', message: result.error || '', timeout: 4000 });
+      return;
+    }
+    await reloadSessionList();
+  } catch (error) {
+    console.error('Failed to archive session:', error);
+  }
+}
+
+// Bring an archived session back; optionally switch to it (and focus a tab)
+async function unarchiveSession(sessionId, open, tabId = null) {
+  try {
+    const result = await invoke('unarchive_session', { sessionId });
+    if (!result.success) return;
+
+    if (open) {
+      await switchSession(sessionId);
+      if (tabId) await activateTab(tabId);
+    } else {
+      await reloadSessionList();
+    }
+  } catch (error) {
+    console.error('Failed to unarchive session:', error);
+  }
+}
+
+// The window's session was archived elsewhere; follow the backend's choice
+async function reloadCurrentSession() {
+  try {
+    const result = await invoke('get_active_session');
+    if (!result.success) return;
+    currentSession = result.data;
+    updateSessionDisplay();
+    await refreshTabs();
+    await preloadRestoredTabs();
+  } catch (error) {
+    console.error('Failed to reload session:', error);
+  }
 }
 
 async function createNewSession() {
@@ -2342,6 +3488,71 @@ async function createNewSession() {
   }
 }
 
+async function createPrivateSession() {
+  const name = elements.newSessionName.value.trim() || 'Private';
+
+  try {
+    const result = await invoke('create_private_session', { name });
+    if (result.success) {
+      elements.newSessionName.value = '';
+      await switchSession(result.data.id);
+    }
+  } catch (error) {
+    console.error('Failed to create private session:', error);
+  }
+}
+
+// Closing a private session deletes it; leave it first if this window shows it
+async function closePrivateSession(sessionId, sessions) {
+  try {
+    if (currentSession?.id === sessionId) {
+      const fallback = sessions.find((s) => s.id !== sessionId && !s.private);
+      if (!fallback) return;
+      await switchSession(fallback.id);
+    }
+
+    const result = await invoke('delete_session', { sessionId });
+    if (!result.success) {
+      showToast({ title: 'Could not close session', message: result.error || '', timeout: 4000 });
+      return;
+    }
+    if (!elements.sessionModal.classList.contains('hidden')) {
+      await reloadSessionList();
+    }
+  } catch (error) {
+    console.error('Failed to close private session:', error);
+  }
+}
+
+function handleSessionRecovery(recovery) {
+  if (!recovery) return;
+
+  const tabCount = Number(recovery.last_known_good_tabs) || 0;
+  if (tabCount === 0) {
+    invoke('dismiss_session_recovery').catch(() => {});
+    return;
+  }
+
+  showToast({
+    title: 'AXIOM did not shut down correctly',
+    message: `Restore previous session? (${tabCount} tab${tabCount === 1 ? '' : 's'})`,
+    actions: [
+      {
+        label: 'Restore',
+        kind: 'primary',
+        onClick: async () => {
+          const result = await invoke('restore_previous_session');
+          if (result && result.success && Array.isArray(result.data) && result.data.length > 0) {
+            await switchSession(result.data[0].id);
+          }
+        },
+      },
+      { label: 'Dismiss', kind: 'secondary', onClick: () => invoke('dismiss_session_recovery') },
+    ],
+    timeout: 0,
+  });
+}
+
   async function switchSession(sessionId) {
   try {
     const result = await invoke('switch_session', { sessionId });   
@@ -2349,6 +3560,7 @@ async function createNewSession() {
       currentSession = result.data;
       updateSessionDisplay();
       await refreshTabs();
+      await preloadRestoredTabs();
       closeSessionModal();
     }
   } catch (error) {
@@ -2543,6 +3755,7 @@ function renderDownloadsList() {
 function createDownloadElement(download) {
   const row = document.createElement('div');
   row.className = 'download-item';
+  row.dataset.downloadId = download.id;
 
   const icon = document.createElement('div');
   icon.className = 'download-item-icon';
@@ -2809,9 +4022,16 @@ async function clearHistoryFromUI() {
 
 async function loadHistoryList(query) {
   try {
+    const sort = elements.historySort?.value || 'recency';
+    if (sort === 'journeys') {
+      const journeys = await invoke('get_history_journeys', { query: query || null, limit: 50 });
+      if (journeys && journeys.success) renderHistoryJourneys(journeys.data || []);
+      return;
+    }
+
     const result = query
-      ? await invoke('search_history', { query })
-      : await invoke('get_recent_history');
+      ? await invoke('search_history', { query, sort })
+      : await invoke('get_recent_history', { sort });
 
     if (!result || !result.success) return;
 
@@ -2834,47 +4054,101 @@ function renderHistoryList(entries) {
   }
 
   entries.forEach((entry) => {
-    const item = document.createElement('button');
-    item.type = 'button';
-    item.className = 'history-item';
-    item.dataset.url = entry.url;
-
-    const text = document.createElement('div');
-    text.className = 'history-item-text';
+    elements.historyList.appendChild(createHistoryItem(entry));
+  });
+}
 
-    const title = document.createElement('div');
-    title.className = 'history-item-title';
-    title.textContent = entry.title || entry.url;
+// Journeys: related visits grouped under a label and time range
+function renderHistoryJourneys(journeys) {
+  elements.historyList.innerHTML = '';
 
-    const url = document.createElement('div');
-    url.className = 'history-item-url';
-    url.textContent = entry.url;
+  if (!journeys.length) {
+    const empty = document.createElement('div');
+    empty.className = 'bookmarks-empty';
+    empty.textContent = 'No journeys';
+    elements.historyList.appendChild(empty);
+    return;
+  }
 
-    text.appendChild(title);
-    text.appendChild(url);
+  journeys.forEach((journey) => {
+    const group = document.createElement('section');
+    group.className = 'history-journey';
 
-    const meta = document.createElement('div');
+    const header = document.createElement('div');
+    header.className = 'history-journey-header';
+    const label = document.createElement('span');
+    label.className = 'history-journey-label';
+    label.textContent = journey.label;
+    const meta = document.createElement('span');
     meta.className = 'history-item-meta';
-    const when = entry.visited_at ? formatRelativeTime(entry.visited_at) : '';
-    const count = typeof entry.visit_count === 'number' ? entry.visit_count : 0;
-    meta.textContent = [when, count ? `${count}×` : ''].filter(Boolean).join(' · ');
+    const start = new Date(journey.started_at);
+    const end = new Date(journey.ended_at);
+    const time = (dt) => dt.toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' });
+    meta.textContent = [
+      `${start.toLocaleDateString()} ${time(start)}–${time(end)}`,
+      (journey.sites || []).slice(0, 3).join(', '),
+    ]
+      .filter(Boolean)
+      .join(' · ');
+    header.append(label, meta);
+    group.appendChild(header);
+
+    journey.pages.forEach((page) => {
+      group.appendChild(
+        createHistoryItem({
+          url: page.url,
+          title: page.title,
+          visited_at: page.last_visited_at,
+          visit_count: page.visit_count,
+        }),
+      );
+    });
 
-    item.appendChild(text);
-    item.appendChild(meta);
+    elements.historyList.appendChild(group);
+  });
+}
 
-    item.addEventListener('click', (e) => {
-      openUrlWithDisposition(entry.url, dispositionFromPointerEvent(e));
-      closeHistoryModal();
-    });
-    item.addEventListener('auxclick', (e) => {
-      if (e.button !== 1) return;
-      e.preventDefault();
-      openUrlWithDisposition(entry.url, 'new_background_tab');
-      closeHistoryModal();
-    });
+function createHistoryItem(entry) {
+  const item = document.createElement('button');
+  item.type = 'button';
+  item.className = 'history-item';
+  item.dataset.url = entry.url;
+
+  const text = document.createElement('div');
+  text.className = 'history-item-text';
 
-    elements.historyList.appendChild(item);
+  const title = document.createElement('div');
+  title.className = 'history-item-title';
+  title.textContent = entry.title || entry.url;
+
+  const url = document.createElement('div');
+  url.className = 'history-item-url';
+  url.textContent = entry.url;
+
+  text.appendChild(title);
+  text.appendChild(url);
+
+  const meta = document.createElement('div');
+  meta.className = 'history-item-meta';
+  const when = entry.visited_at ? formatRelativeTime(entry.visited_at) : '';
+  const count = typeof entry.visit_count === 'number' ? entry.visit_count : 0;
+  meta.textContent = [when, count ? `${count}×` : ''].filter(Boolean).join(' · ');
+
+  item.appendChild(text);
+  item.appendChild(meta);
+
+  item.addEventListener('click', (e) => {
+    openUrlWithDisposition(entry.url, dispositionFromPointerEvent(e));
+    closeHistoryModal();
   });
+  item.addEventListener('auxclick', (e) => {
+    if (e.button !== 1) return;
+    e.preventDefault();
+    openUrlWithDisposition(entry.url, 'new_background_tab');
+    closeHistoryModal();
+  });
+
+  return item;
 }
 
 function formatRelativeTime(iso) {